@@ -0,0 +1,230 @@
+use std::fs::File;
+use std::io::{BufReader, Read, Seek};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use flate2::read::GzDecoder;
+
+use crate::logging;
+use crate::scan::{self, ScanOptions, Scanned};
+use crate::types::DependencyRecord;
+
+/// `.zip` / `.tar.gz`（`.tgz`） / `.tar`を展開せずに走査し、含まれるマニフェストを解析します。
+/// Source列にはアーカイブ内のパスを使います。
+pub fn collect_archive(path: &Path, options: &ScanOptions) -> Result<Vec<DependencyRecord>> {
+    scan_archive(path, options).map(|scanned| scanned.records)
+}
+
+/// `collect_archive`と同様に解析し、アーカイブ内のファイルごとの形式と件数も返します。
+pub fn scan_archive(path: &Path, options: &ScanOptions) -> Result<Scanned> {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_ascii_lowercase())
+        .unwrap_or_default();
+    let file = File::open(path)
+        .with_context(|| format!("アーカイブを開けませんでした: {}", path.display()))?;
+    let reader = BufReader::new(file);
+    let scanned = if name.ends_with(".zip") {
+        collect_zip(reader, options)
+    } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+        collect_tar(GzDecoder::new(reader), options)
+    } else if name.ends_with(".tar") {
+        collect_tar(reader, options)
+    } else {
+        bail!(
+            "未対応のアーカイブ形式です（.zip / .tar.gz / .tgz / .tar）: {}",
+            path.display()
+        );
+    };
+    scanned.with_context(|| format!("アーカイブの読み込みに失敗: {}", path.display()))
+}
+
+fn collect_zip<R: Read + Seek>(reader: R, options: &ScanOptions) -> Result<Scanned> {
+    let mut archive = zip::ZipArchive::new(reader)?;
+    let mut scanned = Scanned::default();
+    for index in 0..archive.len() {
+        let mut entry = archive.by_index(index)?;
+        if !entry.is_file() {
+            continue;
+        }
+        let Some(entry_path) = entry.enclosed_name() else {
+            continue;
+        };
+        scanned.extend(parse_entry(&entry_path, &mut entry, options));
+    }
+    Ok(scanned)
+}
+
+fn collect_tar<R: Read>(reader: R, options: &ScanOptions) -> Result<Scanned> {
+    let mut archive = tar::Archive::new(reader);
+    let mut scanned = Scanned::default();
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let entry_path = entry.path()?.into_owned();
+        scanned.extend(parse_entry(&entry_path, &mut entry, options));
+    }
+    Ok(scanned)
+}
+
+fn parse_entry(entry_path: &Path, reader: &mut dyn Read, options: &ScanOptions) -> Scanned {
+    let entry_path: PathBuf = entry_path
+        .components()
+        .filter(|component| component.as_os_str() != ".")
+        .collect();
+    let Some(kind) = options.manifest_kind(&entry_path, &entry_path) else {
+        return Scanned::default();
+    };
+    if scan::is_excluded_entry(&entry_path, options)
+        || !scan::is_included_entry(&entry_path, options)
+    {
+        return Scanned::default();
+    }
+    // 壊れたエントリが1つあっても、アーカイブ内の他のファイルの結果は失わないようにする
+    let mut content = String::new();
+    let records = reader
+        .read_to_string(&mut content)
+        .with_context(|| {
+            format!(
+                "アーカイブ内のファイルを読み込めません: {}",
+                entry_path.display()
+            )
+        })
+        .and_then(|_| scan::parse_manifest_entry(&entry_path, &content, options));
+    match records {
+        Ok(records) => Scanned::single(entry_path, kind, records.unwrap_or_default()),
+        Err(err) => {
+            logging::warn(format!("{err:#}（このファイルはスキップします）"));
+            Scanned::single(entry_path, kind, Vec::new())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Write};
+
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+    use glob::Pattern;
+
+    use super::*;
+
+    const FILES: &[(&str, &str)] = &[
+        ("project/backend/requirements.txt", "flask==3.0.0\n"),
+        (
+            "project/frontend/package-lock.json",
+            r#"{"packages": {"": {}, "node_modules/react": {"version": "18.2.0", "license": "MIT"}}}"#,
+        ),
+        (
+            "project/frontend/node_modules/x/requirements.txt",
+            "ignored==1.0\n",
+        ),
+        ("project/dist/requirements.txt", "excluded==1.0\n"),
+        ("project/README.md", "# readme\n"),
+    ];
+
+    fn options() -> ScanOptions {
+        ScanOptions {
+            exclude: vec![Pattern::new("dist").unwrap()],
+            ..Default::default()
+        }
+    }
+
+    fn summary(records: &[DependencyRecord]) -> Vec<(String, String)> {
+        let mut summary: Vec<(String, String)> = records
+            .iter()
+            .map(|r| (r.name.clone(), r.source.display().to_string()))
+            .collect();
+        summary.sort();
+        summary
+    }
+
+    fn expected() -> Vec<(String, String)> {
+        vec![
+            (
+                "flask".to_string(),
+                "project/backend/requirements.txt".to_string(),
+            ),
+            (
+                "react".to_string(),
+                "project/frontend/package-lock.json".to_string(),
+            ),
+        ]
+    }
+
+    #[test]
+    fn zip_entries_are_parsed_in_memory() {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, content) in FILES {
+            writer
+                .start_file(*name, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(content.as_bytes()).unwrap();
+        }
+        let bytes = writer.finish().unwrap().into_inner();
+
+        let records = collect_zip(Cursor::new(bytes), &options()).unwrap().records;
+        assert_eq!(summary(&records), expected());
+    }
+
+    #[test]
+    fn broken_zip_entries_are_skipped() {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let broken: &[(&str, &[u8])] = &[
+            ("project/broken/package-lock.json", b"{\"packages\": ["),
+            ("project/latin1/requirements.txt", b"caf\xe9==1.0\n"),
+        ];
+        let entries = FILES
+            .iter()
+            .map(|(name, content)| (*name, content.as_bytes()))
+            .chain(broken.iter().copied());
+        for (name, content) in entries {
+            writer
+                .start_file(name, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(content).unwrap();
+        }
+        let bytes = writer.finish().unwrap().into_inner();
+
+        let scanned = collect_zip(Cursor::new(bytes), &options()).unwrap();
+        assert_eq!(summary(&scanned.records), expected());
+        let skipped: Vec<(String, usize)> = scanned
+            .files
+            .iter()
+            .filter(|file| {
+                file.path.starts_with("project/broken") || file.path.starts_with("project/latin1")
+            })
+            .map(|file| (file.path.display().to_string(), file.count))
+            .collect();
+        assert_eq!(
+            skipped,
+            vec![
+                ("project/broken/package-lock.json".to_string(), 0),
+                ("project/latin1/requirements.txt".to_string(), 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn tar_gz_entries_are_parsed_in_memory() {
+        let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+        for (name, content) in FILES {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, format!("./{name}"), content.as_bytes())
+                .unwrap();
+        }
+        let bytes = builder.into_inner().unwrap().finish().unwrap();
+
+        let records = collect_tar(GzDecoder::new(Cursor::new(bytes)), &options())
+            .unwrap()
+            .records;
+        assert_eq!(summary(&records), expected());
+    }
+}
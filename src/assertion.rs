@@ -0,0 +1,183 @@
+use std::str::FromStr;
+
+use crate::scan::normalize_pypi_name;
+use crate::spdx::normalize_spdx;
+use crate::types::DependencyRecord;
+
+#[derive(Debug, Clone)]
+pub struct LicenseAssertion {
+    pub package: String,
+    pub license: String,
+}
+
+impl FromStr for LicenseAssertion {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (package, license) = value
+            .split_once('=')
+            .ok_or_else(|| format!("PACKAGE=LICENSE 形式で指定してください: {value}"))?;
+        let package = package.trim();
+        let license = license.trim();
+        if package.is_empty() || license.is_empty() {
+            return Err(format!(
+                "パッケージ名とライセンスの両方を指定してください: {value}"
+            ));
+        }
+        Ok(Self {
+            package: package.to_string(),
+            license: license.to_string(),
+        })
+    }
+}
+
+/// `pip:requests`のように`マネージャ:`を前置すると、そのマネージャの依存だけを対象にします。
+/// 前置が無い場合は全マネージャから名前で探します。名前はpipならPEP 503の正規化、それ以外は大文字小文字を無視して比較します。
+/// ライセンスはSPDX IDに正規化して比較するため、`Apache Software License`は`Apache-2.0`と一致します。
+pub fn check_assertions(
+    records: &[DependencyRecord],
+    assertions: &[LicenseAssertion],
+) -> Vec<String> {
+    let mut failures = Vec::new();
+    for assertion in assertions {
+        let matched: Vec<&DependencyRecord> = records
+            .iter()
+            .filter(|record| package_matches(record, &assertion.package))
+            .collect();
+
+        if matched.is_empty() {
+            failures.push(format!(
+                "{}: 依存関係に見つかりません (期待: {})",
+                assertion.package, assertion.license
+            ));
+            continue;
+        }
+
+        for record in matched {
+            if !same_license(&record.license, &assertion.license) {
+                failures.push(format!(
+                    "{}({} {}): 期待 {} / 実際 {}",
+                    record.name,
+                    record.manager,
+                    record.version.as_deref().unwrap_or("-"),
+                    assertion.license,
+                    record.license
+                ));
+            }
+        }
+    }
+    failures
+}
+
+fn package_matches(record: &DependencyRecord, package: &str) -> bool {
+    // `group:artifact`形式のMaven座標と区別するため、前置がマネージャ名と一致する場合だけ絞り込む
+    if let Some((manager, name)) = package.split_once(':')
+        && record.manager.eq_ignore_ascii_case(manager)
+    {
+        return name_matches(record, name);
+    }
+    name_matches(record, package)
+}
+
+fn name_matches(record: &DependencyRecord, name: &str) -> bool {
+    if record.manager.eq_ignore_ascii_case("pip") {
+        normalize_pypi_name(&record.name) == normalize_pypi_name(name)
+    } else {
+        record.name.eq_ignore_ascii_case(name)
+    }
+}
+
+fn same_license(actual: &str, expected: &str) -> bool {
+    let canonical =
+        |license: &str| normalize_spdx(license).unwrap_or_else(|| license.trim().to_string());
+    canonical(actual).eq_ignore_ascii_case(&canonical(expected))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(manager: &str, name: &str, license: &str) -> DependencyRecord {
+        DependencyRecord {
+            manager: manager.to_string(),
+            name: name.to_string(),
+            license: license.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn assertions(specs: &[&str]) -> Vec<LicenseAssertion> {
+        specs.iter().map(|spec| spec.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn manager_prefix_restricts_matching_to_that_manager() {
+        let records = [
+            record("pip", "requests", "Apache-2.0"),
+            record("npm", "requests", "MIT"),
+        ];
+        assert!(check_assertions(&records, &assertions(&["pip:requests=Apache-2.0"])).is_empty());
+        assert!(check_assertions(&records, &assertions(&["NPM:Requests=mit"])).is_empty());
+        assert_eq!(
+            check_assertions(&records, &assertions(&["requests=Apache-2.0"])),
+            ["requests(npm -): 期待 Apache-2.0 / 実際 MIT"]
+        );
+        assert_eq!(
+            check_assertions(&records, &assertions(&["gem:requests=MIT"])),
+            ["gem:requests: 依存関係に見つかりません (期待: MIT)"]
+        );
+    }
+
+    #[test]
+    fn names_containing_colons_are_matched_as_a_whole() {
+        let records = [record("maven", "junit:junit", "EPL-1.0")];
+        assert!(check_assertions(&records, &assertions(&["junit:junit=EPL-1.0"])).is_empty());
+        assert!(check_assertions(&records, &assertions(&["maven:junit:junit=EPL-1.0"])).is_empty());
+        assert!("requests".parse::<LicenseAssertion>().is_err());
+        assert!("requests= ".parse::<LicenseAssertion>().is_err());
+    }
+
+    #[test]
+    fn licenses_are_compared_after_spdx_normalization() {
+        let records = [
+            record("pip", "requests", "Apache Software License"),
+            record("npm", "left-pad", "Apache 2.0"),
+            record("npm", "react", "mit"),
+        ];
+        assert!(
+            check_assertions(
+                &records,
+                &assertions(&["requests=Apache-2.0", "left-pad=Apache-2.0", "React=MIT"])
+            )
+            .is_empty()
+        );
+
+        let failures = check_assertions(&records, &assertions(&["requests=MIT"]));
+        assert_eq!(
+            failures,
+            ["requests(pip -): 期待 MIT / 実際 Apache Software License"]
+        );
+    }
+
+    #[test]
+    fn pip_names_use_pep_503_normalization() {
+        let records = [
+            record("pip", "flask-sqlalchemy", "BSD-3-Clause"),
+            record("npm", "flask_sqlalchemy", "MIT"),
+        ];
+        assert!(
+            check_assertions(
+                &records,
+                &assertions(&[
+                    "pip:Flask_SQLAlchemy=BSD-3-Clause",
+                    "pip:flask.sqlalchemy=BSD-3-Clause"
+                ])
+            )
+            .is_empty()
+        );
+        assert_eq!(
+            check_assertions(&records, &assertions(&["npm:flask-sqlalchemy=MIT"])),
+            ["npm:flask-sqlalchemy: 依存関係に見つかりません (期待: MIT)"]
+        );
+    }
+}
@@ -1,10 +1,15 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
+use std::io;
 use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
+use crate::logging;
+use crate::scan::package_key;
 use crate::types::PackageMetadata;
 
 #[derive(Debug)]
@@ -12,57 +17,191 @@ pub struct LicenseCache {
     path: PathBuf,
     data: CacheData,
     dirty: bool,
+    ttl: Option<Duration>,
+    /// これより前（UNIX時刻）に取得したエントリは使わずに再取得します（`--since`）。
+    since: Option<u64>,
+    refresh_not_found: bool,
+    stats: CacheStats,
 }
 
+/// ライセンス取得時にキャッシュで解決できたパッケージ数（`--timing`で表示）。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: usize,
+    pub misses: usize,
+}
+
+const CACHE_VERSION: u8 = 2;
+/// `MIGRATIONS[n]`はバージョン`n + 1`のキャッシュを`n + 2`の形式に変換します。
+/// 形式を変更して`CACHE_VERSION`を上げるときは、ここに変換処理を追加します。
+const MIGRATIONS: [fn(Value) -> Value; CACHE_VERSION as usize - 1] = [migrate_v1_to_v2];
+/// レジストリに見つからなかったパッケージを再確認するまでの期間。
+const NOT_FOUND_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
 #[derive(Debug, Serialize, Deserialize)]
 struct CacheData {
     version: u8,
-    entries: HashMap<String, PackageMetadata>,
+    entries: HashMap<String, CacheEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheEntry {
+    #[serde(flatten)]
+    metadata: PackageMetadata,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    fetched_at: Option<u64>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    not_found: bool,
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
 }
 
 impl Default for CacheData {
     fn default() -> Self {
         Self {
-            version: 1,
+            version: CACHE_VERSION,
             entries: HashMap::new(),
         }
     }
 }
 
 impl LicenseCache {
-    pub fn load() -> Result<Self> {
-        let path = default_cache_path();
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        let path = path.map_or_else(default_cache_path, Path::to_path_buf);
         if let Some(parent) = path.parent() {
             fs::create_dir_all(parent).with_context(|| {
                 format!("キャッシュディレクトリの作成に失敗: {}", parent.display())
             })?;
         }
 
-        let data = if path.exists() {
+        let (data, dirty) = if path.exists() {
             let content = fs::read_to_string(&path).with_context(|| {
                 format!("キャッシュファイルの読み込みに失敗: {}", path.display())
             })?;
-            serde_json::from_str(&content)
-                .with_context(|| format!("キャッシュファイルの解析に失敗: {}", path.display()))?
+            parse_cache_data(&content, &path)?
         } else {
-            CacheData::default()
+            (CacheData::default(), false)
         };
 
         Ok(Self {
             path,
             data,
-            dirty: false,
+            dirty,
+            ttl: None,
+            since: None,
+            refresh_not_found: false,
+            stats: CacheStats::default(),
         })
     }
 
+    pub fn clear(path: Option<&Path>) -> Result<PathBuf> {
+        let path = path.map_or_else(default_cache_path, Path::to_path_buf);
+        match fs::remove_file(&path) {
+            Ok(()) => Ok(path),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(path),
+            Err(err) => Err(err)
+                .with_context(|| format!("キャッシュファイルの削除に失敗: {}", path.display())),
+        }
+    }
+
+    pub fn with_ttl(mut self, ttl: Option<Duration>) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// `since`（UNIX時刻）より前に取得したエントリ、取得日時の無いエントリを期限切れとして扱います。
+    pub fn with_since(mut self, since: Option<u64>) -> Self {
+        self.since = since;
+        self
+    }
+
+    /// trueの場合、「見つからなかった」記録を無視してレジストリに再確認します。
+    pub fn with_refresh_not_found(mut self, refresh: bool) -> Self {
+        self.refresh_not_found = refresh;
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.entries.is_empty()
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    pub(crate) fn record_lookup(&mut self, hit: bool) {
+        if hit {
+            self.stats.hits += 1;
+        } else {
+            self.stats.misses += 1;
+        }
+    }
+
     pub fn get(&self, manager: &str, name: &str) -> Option<PackageMetadata> {
-        let key = cache_key(manager, name);
-        self.data.entries.get(&key).cloned()
+        let entry = self.data.entries.get(&cache_key(manager, name))?;
+        if entry.not_found
+            || self.ttl.is_some_and(|ttl| is_expired(entry, ttl))
+            || self.fetched_before_since(entry)
+        {
+            return None;
+        }
+        Some(entry.metadata.clone())
+    }
+
+    /// 直近のレジストリ問い合わせで見つからなかったパッケージならtrue。
+    pub fn is_known_not_found(&self, manager: &str, name: &str) -> bool {
+        if self.refresh_not_found {
+            return false;
+        }
+        let ttl = self.ttl.map_or(NOT_FOUND_TTL, |ttl| ttl.min(NOT_FOUND_TTL));
+        self.data
+            .entries
+            .get(&cache_key(manager, name))
+            .is_some_and(|entry| {
+                entry.not_found && !is_expired(entry, ttl) && !self.fetched_before_since(entry)
+            })
+    }
+
+    fn fetched_before_since(&self, entry: &CacheEntry) -> bool {
+        self.since
+            .is_some_and(|since| entry.fetched_at.is_none_or(|fetched_at| fetched_at < since))
     }
 
     pub fn insert(&mut self, manager: &str, name: &str, metadata: PackageMetadata) {
-        let key = cache_key(manager, name);
-        self.data.entries.insert(key, metadata);
+        self.insert_entry(manager, name, metadata, false);
+    }
+
+    pub fn insert_not_found(&mut self, manager: &str, name: &str) {
+        let metadata = PackageMetadata {
+            license: None,
+            homepage: None,
+            latest_version: None,
+            description: None,
+            provenance: None,
+            deprecations: BTreeMap::new(),
+        };
+        self.insert_entry(manager, name, metadata, true);
+    }
+
+    fn insert_entry(
+        &mut self,
+        manager: &str,
+        name: &str,
+        metadata: PackageMetadata,
+        not_found: bool,
+    ) {
+        let entry = CacheEntry {
+            metadata,
+            fetched_at: Some(now_secs()),
+            not_found,
+        };
+        self.data.entries.insert(cache_key(manager, name), entry);
         self.dirty = true;
     }
 
@@ -84,18 +223,252 @@ impl LicenseCache {
     }
 }
 
-fn cache_key(manager: &str, name: &str) -> String {
+/// 古い形式は現在の形式に変換し、知らないバージョン（新しいバイナリが書いたものなど）は
+/// 警告を出して破棄します。2つ目の値は書き戻しが必要かどうかです。
+fn parse_cache_data(content: &str, path: &Path) -> Result<(CacheData, bool)> {
+    let value: Value = serde_json::from_str(content)
+        .with_context(|| format!("キャッシュファイルの解析に失敗: {}", path.display()))?;
+    let version = value
+        .get("version")
+        .and_then(Value::as_u64)
+        .and_then(|v| u8::try_from(v).ok())
+        .filter(|v| (1..=CACHE_VERSION).contains(v));
+    let Some(version) = version else {
+        logging::warn(format!(
+            "キャッシュファイルの形式（version: {}）に対応していないため破棄します: {}",
+            value.get("version").unwrap_or(&Value::Null),
+            path.display()
+        ));
+        return Ok((CacheData::default(), true));
+    };
+
+    let value = MIGRATIONS[usize::from(version) - 1..]
+        .iter()
+        .fold(value, |value, migrate| migrate(value));
+    let mut data: CacheData = serde_json::from_value(value)
+        .with_context(|| format!("キャッシュファイルの解析に失敗: {}", path.display()))?;
+    data.version = CACHE_VERSION;
+    Ok((data, version < CACHE_VERSION))
+}
+
+/// v2では`fetched_at`・`not_found`を追加しただけなので、既定値のまま読み込めます。
+fn migrate_v1_to_v2(value: Value) -> Value {
+    value
+}
+
+pub(crate) fn cache_key(manager: &str, name: &str) -> String {
     format!(
         "{}::{}",
         manager.to_ascii_lowercase(),
-        name.to_ascii_lowercase()
+        package_key(manager, name)
     )
 }
 
-fn default_cache_path() -> PathBuf {
+fn is_expired(entry: &CacheEntry, ttl: Duration) -> bool {
+    entry
+        .fetched_at
+        .is_none_or(|fetched_at| now_secs().saturating_sub(fetched_at) > ttl.as_secs())
+}
+
+/// `--since`の日付（`2026-10-01`、UTCの0時）をUNIX時刻に変換します。
+pub fn parse_since(value: &str) -> Result<u64, String> {
+    let invalid = || format!("YYYY-MM-DD 形式の日付で指定してください: {value}");
+    let parts: Vec<&str> = value.trim().split('-').collect();
+    let [year, month, day] = parts.as_slice() else {
+        return Err(invalid());
+    };
+    let (Ok(year), Ok(month), Ok(day)) = (
+        year.parse::<i64>(),
+        month.parse::<u32>(),
+        day.parse::<u32>(),
+    ) else {
+        return Err(invalid());
+    };
+    let leap = (year % 4 == 0 && year % 100 != 0) || year % 400 == 0;
+    let days_in_month = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return Err(invalid()),
+    };
+    if year < 1970 || !(1..=days_in_month).contains(&day) {
+        return Err(invalid());
+    }
+    // 1970-01-01からの日数（Howard Hinnantのdays_from_civil）
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let year_of_era = y - era * 400;
+    let month_index = i64::from((month + 9) % 12);
+    let day_of_year = (153 * month_index + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+    u64::try_from(days * 86_400).map_err(|_| invalid())
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+pub(crate) fn default_cache_path() -> PathBuf {
     if let Some(dir) = dirs::cache_dir() {
         dir.join("license-scout").join("license-cache.json")
     } else {
         Path::new(".license-scout-cache.json").to_path_buf()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache_from(json: &str) -> LicenseCache {
+        LicenseCache {
+            path: PathBuf::new(),
+            data: serde_json::from_str(json).unwrap(),
+            dirty: false,
+            ttl: None,
+            since: None,
+            refresh_not_found: false,
+            stats: CacheStats::default(),
+        }
+    }
+
+    #[test]
+    fn v1_entries_without_timestamps_expire_only_with_ttl() {
+        let v1 = r#"{"version":1,"entries":{"npm::react":{"license":"MIT","homepage":null}}}"#;
+        let cache = cache_from(v1);
+        assert_eq!(
+            cache.get("npm", "React").and_then(|m| m.license).as_deref(),
+            Some("MIT")
+        );
+
+        let cache = cache.with_ttl(Some(Duration::from_secs(86_400)));
+        assert!(cache.get("npm", "react").is_none());
+    }
+
+    #[test]
+    fn load_migrates_old_versions_and_discards_unknown_ones() {
+        let path = Path::new("license-cache.json");
+        let v1 = r#"{"version":1,"entries":{"npm::react":{"license":"MIT","homepage":null}}}"#;
+        let (data, dirty) = parse_cache_data(v1, path).unwrap();
+        assert_eq!(data.version, CACHE_VERSION);
+        assert!(data.entries.contains_key("npm::react"));
+        assert!(dirty);
+
+        let current = r#"{"version":2,"entries":{}}"#;
+        assert!(!parse_cache_data(current, path).unwrap().1);
+
+        for unknown in [
+            r#"{"version":99,"entries":{"npm::react":{"license":"MIT"}},"extra":true}"#,
+            r#"{"entries":{}}"#,
+        ] {
+            let (data, dirty) = parse_cache_data(unknown, path).unwrap();
+            assert!(data.entries.is_empty());
+            assert_eq!(data.version, CACHE_VERSION);
+            assert!(dirty);
+        }
+        assert!(parse_cache_data("not json", path).is_err());
+    }
+
+    #[test]
+    fn ttl_expires_old_entries() {
+        let mut cache =
+            cache_from(r#"{"version":2,"entries":{}}"#).with_ttl(Some(Duration::from_secs(86_400)));
+        let metadata = PackageMetadata {
+            license: Some("MIT".to_string()),
+            homepage: None,
+            latest_version: None,
+            description: None,
+            provenance: None,
+            deprecations: BTreeMap::new(),
+        };
+        cache.insert("pip", "requests", metadata);
+        assert!(cache.get("pip", "requests").is_some());
+
+        if let Some(entry) = cache.data.entries.get_mut("pip::requests") {
+            entry.fetched_at = Some(now_secs() - 2 * 86_400);
+        }
+        assert!(cache.get("pip", "requests").is_none());
+    }
+
+    #[test]
+    fn since_refetches_entries_fetched_before_the_date() {
+        assert_eq!(parse_since("1970-01-01"), Ok(0));
+        assert_eq!(parse_since("2024-03-01"), Ok(1_709_251_200));
+        assert!(parse_since("2023-02-29").is_err());
+        assert!(parse_since("2024/03/01").is_err());
+
+        let since = parse_since("2024-03-01").unwrap();
+        let mut cache = cache_from(r#"{"version":2,"entries":{}}"#).with_since(Some(since));
+        let metadata = PackageMetadata {
+            license: Some("MIT".to_string()),
+            homepage: None,
+            latest_version: None,
+            description: None,
+            provenance: None,
+            deprecations: BTreeMap::new(),
+        };
+        cache.insert("pip", "requests", metadata.clone());
+        cache.insert("pip", "flask", metadata);
+        cache.insert_not_found("pip", "internal-lib");
+        for key in ["pip::flask", "pip::internal-lib"] {
+            if let Some(entry) = cache.data.entries.get_mut(key) {
+                entry.fetched_at = Some(since - 1);
+            }
+        }
+        assert!(cache.get("pip", "requests").is_some());
+        assert!(cache.get("pip", "flask").is_none());
+        assert!(!cache.is_known_not_found("pip", "internal-lib"));
+    }
+
+    #[test]
+    fn not_found_entries_expire_sooner_and_can_be_refreshed() {
+        let mut cache = cache_from(r#"{"version":2,"entries":{}}"#);
+        cache.insert_not_found("pip", "internal-lib");
+        assert!(cache.is_known_not_found("pip", "internal-lib"));
+        assert!(cache.get("pip", "internal-lib").is_none());
+        assert!(!cache.is_known_not_found("pip", "requests"));
+
+        if let Some(entry) = cache.data.entries.get_mut("pip::internal-lib") {
+            entry.fetched_at = Some(now_secs() - 2 * 86_400);
+        }
+        assert!(!cache.is_known_not_found("pip", "internal-lib"));
+
+        cache.insert_not_found("pip", "internal-lib");
+        let cache = cache.with_refresh_not_found(true);
+        assert!(!cache.is_known_not_found("pip", "internal-lib"));
+    }
+
+    #[test]
+    fn pip_keys_use_pep_503_names() {
+        let mut cache = cache_from(r#"{"version":2,"entries":{}}"#);
+        cache.insert(
+            "pip",
+            "Flask_SQLAlchemy",
+            PackageMetadata {
+                license: Some("BSD-3-Clause".to_string()),
+                homepage: None,
+                latest_version: None,
+                description: None,
+                provenance: None,
+                deprecations: BTreeMap::new(),
+            },
+        );
+        assert!(cache.get("pip", "flask-sqlalchemy").is_some());
+        assert!(cache.get("npm", "flask-sqlalchemy").is_none());
+    }
+
+    #[test]
+    fn clear_removes_file_and_tolerates_missing() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("clear.json");
+        fs::write(&path, "{}").unwrap();
+        assert_eq!(LicenseCache::clear(Some(&path)).unwrap(), path);
+        assert!(!path.exists());
+        assert!(LicenseCache::clear(Some(&path)).is_ok());
+    }
+}
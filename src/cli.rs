@@ -1,6 +1,19 @@
 use std::path::PathBuf;
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
+use clap_complete::Shell;
+use glob::Pattern;
+
+use license_scout::assertion::LicenseAssertion;
+use license_scout::cache::parse_since;
+use license_scout::compat::ProjectLicense;
+use license_scout::policy::{MinVersionRule, SeverityRule};
+use license_scout::scan::{FormatMapping, ManifestKind};
+
+use crate::output::{DiffFormat, GroupBy, OutputFormat, SearchField, SortField};
+use crate::progress::ProgressFormat;
+
+pub const DEFAULT_CONCURRENCY: usize = 8;
 
 #[derive(Parser, Debug)]
 #[command(
@@ -10,27 +23,541 @@ use clap::Parser;
     long_about = None
 )]
 pub struct Cli {
-    /// 解析対象ディレクトリ。複数指定可。省略時はカレントディレクトリ。
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// 設定ファイルのパス。省略時はカレントディレクトリのlicense-scout.tomlがあれば読み込みます。
+    #[arg(long = "config", value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// 解析対象ディレクトリ、またはマニフェスト・ロックファイル（そのファイルのみ解析）。複数指定可。省略時はカレントディレクトリ。`services/*/`のようなglobパターンと先頭の`~`も展開します。
     #[arg(short, long = "path", value_name = "PATH")]
     pub paths: Vec<PathBuf>,
 
+    /// 走査から除外するディレクトリ・ファイルのパターン（glob）。node_modulesなどの既定の除外に追加されます。複数指定可。
+    #[arg(long = "exclude", value_name = "GLOB")]
+    pub exclude: Vec<Pattern>,
+
+    /// 指定すると、名前または相対パスがパターン（glob）に一致するディレクトリ配下のファイルだけを走査します。除外が優先されます。複数指定可。
+    #[arg(long = "include", value_name = "GLOB")]
+    pub include: Vec<Pattern>,
+
+    /// ファイル名（または相対パス）がglobに一致するファイルを指定の形式（requirements / package-lockなど）として解析します。組み込みのファイル名より優先されます（例: prod-requirements.in=requirements）。複数指定可。
+    #[arg(long = "map", value_name = "GLOB=FORMAT")]
+    pub map: Vec<FormatMapping>,
+
+    /// 解析対象ディレクトリ直下の.gitignoreに記載されたパスを走査しません。
+    #[arg(long = "respect-gitignore")]
+    pub respect_gitignore: bool,
+
+    /// シンボリックリンク先のディレクトリも走査します。リンクが循環していても、同じディレクトリは1回だけ走査します。
+    #[arg(long = "follow-symlinks")]
+    pub follow_symlinks: bool,
+
+    /// 解析対象になるマニフェスト・ロックファイルと検出した形式を一覧表示して終了します（内容の解析やライセンスの取得は行いません）。
+    #[arg(long = "list-files", conflicts_with_all = ["watch", "tui"])]
+    pub list_files: bool,
+
+    /// 解析対象のマニフェスト・ロックファイルごとに抽出できた依存の件数を表示して終了します。0件のファイル（未対応の形式や解析の失敗）の確認に使います。
+    #[arg(
+        long = "manifest-report",
+        conflicts_with_all = ["watch", "tui", "list_files", "stream_scan"]
+    )]
+    pub manifest_report: bool,
+
+    /// 依存の総数・マネージャ別件数・Unknownの件数だけを表示します（ライセンスの取得や表の描画は行いません）。
+    #[arg(
+        long = "stat",
+        conflicts_with_all = ["fetch_licenses", "flag_outdated", "check_homepages", "warm_cache", "tui", "summary", "top_licenses", "obligations"]
+    )]
+    pub stat: bool,
+
+    /// 解析対象ディレクトリから何階層下まで走査するか（直下のファイルが1）。
+    #[arg(long = "max-depth", value_name = "N", default_value_t = 64)]
+    pub max_depth: usize,
+
+    /// チェックアウトせずにgitの指定リビジョン上のロックファイルを解析します（例: main:frontend/package-lock.json）。複数指定可。
+    #[arg(long = "git-show", value_name = "REF:PATH")]
+    pub git_show: Vec<String>,
+
+    /// リモートのgitリポジトリを一時ディレクトリにshallow cloneして走査し、終了後に削除します。Source列はリポジトリ内のパスになります。
+    #[arg(long = "git", value_name = "URL", conflicts_with = "watch")]
+    pub git: Option<String>,
+
+    /// --gitでクローンするブランチ・タグ。省略時はデフォルトブランチです。
+    #[arg(long = "ref", value_name = "REF", requires = "git")]
+    pub git_ref: Option<String>,
+
+    /// プロジェクトのアーカイブ（.zip / .tar.gz / .tgz / .tar）を展開せずに走査します。Source列はアーカイブ内のパスになります。複数指定可。
+    #[arg(long = "archive", value_name = "FILE", conflicts_with = "watch")]
+    pub archive: Vec<PathBuf>,
+
+    /// 標準入力からマニフェストを読み込み、指定の形式（package-lock / requirementsなど）として解析します。パスを指定しない場合はディレクトリを走査しません。
+    #[arg(
+        long = "stdin",
+        value_name = "FORMAT",
+        value_parser = parse_manifest_format,
+        conflicts_with = "watch"
+    )]
+    pub stdin: Option<ManifestKind>,
+
+    /// 各依存のホームページにHEADリクエストを送り、リンク切れを報告します（ネットワークアクセスあり）。--concurrency・--rate-limit・--request-timeoutに従って並列に確認し、リンク切れのURLはテーブルで赤く表示します。
+    #[arg(long = "check-homepages", visible_alias = "verify-homepages")]
+    pub check_homepages: bool,
+
+    /// 標準出力に出すレポートの形式（table / json / csv / markdown）。table以外では状況メッセージを標準エラーに出します。
+    #[arg(
+        long = "format",
+        value_name = "FORMAT",
+        value_enum,
+        default_value = "table"
+    )]
+    pub format: OutputFormat,
+
     /// JSON出力を書き出すファイルパス。
     #[arg(long = "json-output", value_name = "FILE")]
     pub json_output: Option<PathBuf>,
 
+    /// 1行に1件ずつJSONを書き出すファイルパス（JSON Lines / ndjson）。`-`で標準出力。
+    #[arg(long = "ndjson", value_name = "FILE")]
+    pub ndjson: Option<PathBuf>,
+
+    /// CSVを書き出すファイルパス。`-`で標準出力。
+    #[arg(long = "csv", value_name = "FILE")]
+    pub csv: Option<PathBuf>,
+
+    /// CycloneDX 1.5形式のSBOM(JSON)を書き出すファイルパス。
+    #[arg(long = "cyclonedx", value_name = "FILE")]
+    pub cyclonedx: Option<PathBuf>,
+
+    /// GitHub形式のMarkdownテーブルを書き出すファイルパス。`-`で標準出力。
+    #[arg(long = "markdown", value_name = "FILE")]
+    pub markdown: Option<PathBuf>,
+
+    /// 各依存のライセンス本文を同梱のSPDXテキストから特定し、特定できない依存（Unknown・複合ライセンスなど）を手動確認が必要なものとして報告します。
+    #[arg(long = "fetch-license-texts")]
+    pub fetch_license_texts: bool,
+
+    /// ライセンス本文をまとめた帰属表示ファイル（THIRD_PARTY_LICENSES.txtなど）を書き出すパス。--fetch-license-textsを含みます。
+    #[arg(long = "notices", value_name = "FILE")]
+    pub notices: Option<PathBuf>,
+
+    /// ポリシー違反（--deny / --allow-only / --deny-unknown）をJSON配列で書き出すファイルパス。違反がなければ空の配列を書き出します。
+    #[arg(long = "policy-report", value_name = "JSON")]
+    pub policy_report: Option<PathBuf>,
+
+    /// 解析したマニフェストごとの形式（parser）と抽出できた依存の件数をJSONで書き出すファイルパス。
+    #[arg(long = "scanned-files", value_name = "FILE")]
+    pub scanned_files: Option<PathBuf>,
+
+    /// 以前に保存したJSONレポート（--json-outputなど）と比較し、追加・削除・ライセンス変更された依存を表示します。
+    #[arg(long = "baseline", value_name = "JSON")]
+    pub baseline: Option<PathBuf>,
+
+    /// --baselineとの差分の表示形式。textは`+`/`-`/`~`付きの1行1件（色付き）、jsonは標準出力にレポートの代わりに差分のJSONを出力します。
+    #[arg(
+        long = "diff-format",
+        value_name = "FORMAT",
+        value_enum,
+        default_value_t = DiffFormat::Text,
+        requires = "baseline"
+    )]
+    pub diff_format: DiffFormat,
+
+    /// --baselineとの差分をJSON（--diff-format jsonと同じ内容）で書き出すファイルパス。`-`で標準出力。
+    #[arg(long = "diff-json", value_name = "FILE", requires = "baseline")]
+    pub diff_json: Option<PathBuf>,
+
     /// JSONを標準出力へ出す場合は指定してください。
     #[arg(long = "print-json")]
     pub print_json: bool,
 
-    /// PyPI / npm Registryからライセンス情報を取得してUnknownを補完します。
+    /// JSONを1件ずつ逐次書き出します。巨大なスキャンでのメモリ使用量を抑えます。
+    #[arg(long = "stream")]
+    pub stream: bool,
+
+    /// マニフェストを解析するたびに依存を並べ替えずに書き出します（テーブルは1行1件の簡易表示、JSONはJSON Lines）。レジストリからの取得やポリシーの判定は行いません。
+    #[arg(
+        long = "stream-scan",
+        conflicts_with_all = ["watch", "tui", "list_files", "stat", "explain", "stream", "fetch_licenses", "flag_outdated", "warm_cache", "check_homepages", "baseline", "deny", "allow_only", "deny_unknown", "project_license", "git", "git_show", "archive", "stdin"]
+    )]
+    pub stream_scan: bool,
+
+    /// JSONを改行・インデントなしの1行で出力します（標準出力・--json-outputの両方に適用）。
+    #[arg(long = "json-compact")]
+    pub json_compact: bool,
+
+    /// JSON出力を配列ではなく、生成日時（`generated_at`）・ツールのバージョン・解析したパスと`dependencies`を持つオブジェクトにします。
+    #[arg(long = "with-metadata")]
+    pub with_metadata: bool,
+
+    /// node_modulesやvendorディレクトリ内のLICENSE/COPYINGファイルからUnknownのライセンスを推定します。
+    #[arg(long = "local-licenses")]
+    pub local_licenses: bool,
+
+    /// PyPI / npm Registry / crates.io / RubyGems / Maven Centralからライセンス情報を取得してUnknownを補完します。
     #[arg(long = "fetch-licenses")]
     pub fetch_licenses: bool,
 
+    /// ライセンスが判明済みの依存もレジストリに問い合わせ、ロックファイルの値と異なれば両方を表示して一覧で警告します（例: ロックファイルはISC、npmはMIT）。
+    #[arg(long = "reconcile", requires = "fetch_licenses")]
+    pub reconcile: bool,
+
+    /// レジストリ上の最新バージョンも取得し、固定バージョンより新しいものがあればLatest列に表示します。
+    #[arg(long = "flag-outdated")]
+    pub flag_outdated: bool,
+
+    /// レジストリへのリクエストが一時的に失敗した場合（通信エラー・429・5xx）の再試行回数。
+    #[arg(long = "max-retries", value_name = "N", default_value_t = 2)]
+    pub max_retries: u32,
+
+    /// レジストリへの1リクエストあたりのタイムアウト秒数。
+    #[arg(
+        long = "request-timeout",
+        value_name = "SECS",
+        default_value_t = 10,
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub request_timeout: u64,
+
+    /// レジストリやホームページへのリクエストに使うUser-Agent（例: "acme-audit/1.0 (oss@example.com)"）。省略時は`license-scout/<バージョン>`です。
+    #[arg(long = "user-agent", value_name = "STRING")]
+    pub user_agent: Option<String>,
+
+    /// ライセンス取得全体の制限時間（秒）。過ぎた時点で未取得のパッケージは補完せず警告を出します。
+    #[arg(long = "deadline", value_name = "SECS")]
+    pub deadline: Option<u64>,
+
+    /// PyPI互換リポジトリのベースURL（`<URL>/pypi/<name>/json`を参照）。社内ミラー向け。
+    #[arg(long = "pypi-url", value_name = "URL")]
+    pub pypi_url: Option<String>,
+
+    /// npmレジストリのベースURL。社内ミラー向け。
+    #[arg(long = "npm-registry", value_name = "URL")]
+    pub npm_registry: Option<String>,
+
+    /// レジストリへの1秒あたりの最大リクエスト数（全体での上限）。0で制限しません。
+    #[arg(
+        long = "rate-limit",
+        value_name = "REQS_PER_SEC",
+        default_value_t = 10.0
+    )]
+    pub rate_limit: f64,
+
+    /// ネットワークに一切アクセスせず、ローカルのキャッシュのみからライセンス情報を補完します。
+    #[arg(long = "offline", conflicts_with = "check_homepages")]
+    pub offline: bool,
+
+    /// ライセンスキャッシュのファイルパス。省略時はユーザーのキャッシュディレクトリ。
+    #[arg(long = "cache-path", value_name = "FILE")]
+    pub cache_path: Option<PathBuf>,
+
+    /// パッケージごとのライセンス・ホームページの手動修正を記述したファイル（TOML / JSON）。キーは`manager:name`または`manager:name@version`。取得結果より優先されます。
+    #[arg(long = "overrides", value_name = "FILE")]
+    pub overrides: Option<PathBuf>,
+
+    /// パッケージ名のパターン（`manager:name`、名前はglob可）とライセンスの対応表（TOML / JSON）。ライセンス取得の前に、ライセンスが不明な依存へ適用します。推定した依存はレジストリに問い合わせません。
+    #[arg(long = "guess-licenses", value_name = "FILE")]
+    pub guess_licenses: Option<PathBuf>,
+
+    /// レポートから除外するパッケージを`manager:name`形式（名前はglob可）で1行ずつ記述したファイル。省略時は作業ディレクトリの`.license-scout-ignore`があれば使います。
+    #[arg(long = "ignore-file", value_name = "FILE")]
+    pub ignore_file: Option<PathBuf>,
+
+    /// マニフェストの解析結果のキャッシュ（ライセンスキャッシュと同じディレクトリのscan-cache.json）を使わずに、すべてのファイルを解析し直します。
+    #[arg(long = "no-scan-cache")]
+    pub no_scan_cache: bool,
+
+    /// ライセンスキャッシュと走査キャッシュを削除して終了します。
+    #[arg(long = "clear-cache")]
+    pub clear_cache: bool,
+
+    /// キャッシュにないパッケージの情報をレジストリから取得してキャッシュに保存し、レポートを出さずに終了します。オフライン実行の事前準備向け。
+    #[arg(long = "warm-cache", conflicts_with_all = ["offline", "watch"])]
+    pub warm_cache: bool,
+
+    /// レジストリに見つからなかったと記録済みのパッケージも再確認します（記録は通常1日で失効）。
+    #[arg(long = "refresh")]
+    pub refresh: bool,
+
+    /// キャッシュの有効期間（日数）。これより古いエントリは再取得します。
+    #[arg(long = "cache-ttl", value_name = "DAYS")]
+    pub cache_ttl: Option<u64>,
+
+    /// 指定日（YYYY-MM-DD、UTC）より前に取得したキャッシュのエントリは使わずに再取得し、それ以降のエントリはそのまま使います。
+    #[arg(long = "since", value_name = "DATE", value_parser = parse_since)]
+    pub since: Option<u64>,
+
+    /// 名前がパターン（glob）に一致するパッケージをライセンス取得の対象外にします。複数指定可。
+    #[arg(long = "fetch-skip", value_name = "PATTERN")]
+    pub fetch_skip: Vec<Pattern>,
+
+    /// ライセンス取得時の同時リクエスト数（既定: 8）。
+    #[arg(long = "concurrency", value_name = "N")]
+    pub concurrency: Option<usize>,
+
+    /// レジストリのホストごとに保持するアイドル接続数の上限（既定: 上限なし）。0で接続を再利用しません。
+    #[arg(long = "pool-size", value_name = "N")]
+    pub pool_size: Option<usize>,
+
+    /// 指定パッケージのライセンスが期待値と一致するか検証します（例: requests=Apache-2.0）。`pip:requests=Apache-2.0`のようにマネージャを前置すると、そのマネージャの依存だけを対象にします。複数指定可。
+    #[arg(long = "assert-license", value_name = "PACKAGE=LICENSE")]
+    pub assert_license: Vec<LicenseAssertion>,
+
+    /// 使用を禁止するライセンス（SPDX識別子）。該当する依存があれば終了コード3で終了します。複数指定可。
+    #[arg(long = "deny", value_name = "SPDX")]
+    pub deny: Vec<String>,
+
+    /// 許可するライセンス（SPDX識別子）。これ以外のライセンスがあれば終了コード3で終了します。複数指定可。
+    #[arg(long = "allow-only", value_name = "SPDX")]
+    pub allow_only: Vec<String>,
+
+    /// ライセンスまたは区分（permissive / weak-copyleft / strong-copyleft / unknown）に重大度（forbidden / warn / review）を割り当てます（例: GPL-3.0=forbidden）。forbiddenのみ終了コード3になります。複数指定可。
+    #[arg(long = "severity", value_name = "LICENSE=SEVERITY")]
+    pub severity: Vec<SeverityRule>,
+
+    /// パッケージの最低バージョンを指定します（例: npm:lodash=4.17.21）。ロックファイルで固定されたバージョンがこれより古ければ終了コード3になります。複数指定可。
+    #[arg(long = "min-version", value_name = "MANAGER:NAME=VERSION")]
+    pub min_version: Vec<MinVersionRule>,
+
+    /// ライセンスがUnknownの依存もポリシー違反として扱います。
+    #[arg(long = "deny-unknown")]
+    pub deny_unknown: bool,
+
+    /// 確認済みの依存を`manager/name@version 理由`形式で1行ずつ記述したファイル。一致する依存はポリシー違反ではなく承認済みの例外として理由とともに表示されます。バージョンが変わると再び違反になります。
+    #[arg(long = "exceptions-file", value_name = "FILE")]
+    pub exceptions_file: Option<PathBuf>,
+
+    /// ライセンスがUnknownの依存の割合（%）がこの値を超えた場合に終了コード3で終了します（例: 10）。
+    #[arg(long = "fail-on-unknown", value_name = "PERCENT", value_parser = parse_percent)]
+    pub fail_on_unknown: Option<f64>,
+
+    /// レジストリで非推奨（npmの`deprecated`）・取り下げ済み（PyPIのyank）とされた依存があれば一覧を表示して終了コード3で終了します。--fetch-licensesなどでレジストリから取得した依存が対象で、非推奨かどうかを確認していないバージョンはキャッシュがあっても取得し直します。
+    #[arg(long = "fail-on-deprecated")]
+    pub fail_on_deprecated: bool,
+
+    /// プロジェクト自身のライセンス（SPDX ID）。依存ライセンスとの互換性を判定し、テーブル・MarkdownにCompat列を追加します（例: MIT）。
+    #[arg(long = "project-license", value_name = "SPDX")]
+    pub project_license: Option<ProjectLicense>,
+
+    /// --project-licenseと互換性の無いライセンスの依存があれば一覧を表示して終了コード3で終了します。
+    #[arg(long = "fail-on-incompatible", requires = "project_license")]
+    pub fail_on_incompatible: bool,
+
+    /// ライセンスがSPDX ID・SPDX式として認識できない依存（表記揺れや独自のライセンス名など）を警告として一覧表示します。Unknownは対象外です。
+    #[arg(long = "validate-spdx")]
+    pub validate_spdx: bool,
+
     /// テーブルとJSON出力を指定文字列でフィルタします（名前・マネージャ・ライセンス・ソースが対象）。
     #[arg(long = "search", value_name = "QUERY")]
     pub search: Option<String>,
 
+    /// --searchのクエリを正規表現（大文字小文字無視）として扱います。
+    #[arg(long = "search-regex", requires = "search")]
+    pub search_regex: bool,
+
+    /// --searchの対象を1つの項目に限定します。
+    #[arg(
+        long = "search-field",
+        value_name = "FIELD",
+        value_enum,
+        requires = "search"
+    )]
+    pub search_field: Option<SearchField>,
+
+    /// 指定したマネージャ（例: npm）の依存のみ表示します。大文字小文字は区別しません。複数指定可。
+    #[arg(long = "manager", value_name = "NAME")]
+    pub manager: Vec<String>,
+
+    /// 指定したライセンス（例: GPL-3.0）の依存のみ表示します。完全一致で、大文字小文字は区別しません。複数指定可。
+    #[arg(long = "license", value_name = "SPDX")]
+    pub license: Vec<String>,
+
+    /// テーブル出力を色付けしません。環境変数NO_COLORが設定されている場合や、標準出力が端末でない場合も同様です。
+    #[arg(long = "no-color")]
+    pub no_color: bool,
+
+    /// テーブルを指定した項目（license / manager / name）ごとにグループ化して表示します。
+    #[arg(long = "group-by", value_name = "FIELD", value_enum)]
+    pub group_by: Option<GroupBy>,
+
+    /// 指定した項目（manager / name / version / license / source）で並べ替えます。省略時はマネージャ→名前→バージョン→ソースの順。
+    #[arg(long = "sort-by", value_name = "FIELD", value_enum)]
+    pub sort_by: Option<SortField>,
+
+    /// 並び順を逆にします。バージョンのない依存は常に末尾に表示します。
+    #[arg(long = "reverse")]
+    pub reverse: bool,
+
+    /// 直接依存（マニフェストに直接記載された依存）のみ表示します。
+    #[arg(long = "direct-only")]
+    pub direct_only: bool,
+
+    /// テーブル出力に直接依存かどうかを示すDirect列を追加します。
+    #[arg(long = "show-direct")]
+    pub show_direct: bool,
+
+    /// 開発用依存（npmのdevDependencies、Pipfileのdevelop、poetryのdevカテゴリなど）を除外します。
+    #[arg(long = "no-dev")]
+    pub no_dev: bool,
+
+    /// npmの任意依存（package-lock.jsonで`optional`・`devOptional`の依存）を除外します。
+    #[arg(long = "no-optional")]
+    pub no_optional: bool,
+
+    /// npmのpeer依存（package-lock.jsonで`peer`の依存）を除外します。
+    #[arg(long = "no-peer")]
+    pub no_peer: bool,
+
+    /// 解析対象のプロジェクト自身（package-lock.jsonのルートパッケージ、npmワークスペースや`file:`で参照するローカルパッケージ）も依存として一覧に含めます。
+    #[arg(long = "include-self")]
+    pub include_self: bool,
+
+    /// 指定したパッケージについて、ロックファイルの解析結果・キャッシュ・レジストリの値と正規化・上書きなどの処理過程と最終的な値を、通常の出力の代わりに表示します。名前の大文字小文字と`-`・`_`・`.`の違いは無視します。
+    #[arg(
+        long = "explain",
+        value_name = "NAME",
+        conflicts_with_all = ["watch", "stat", "warm_cache", "tui"]
+    )]
+    pub explain: Option<String>,
+
+    /// テーブルのHomepage列（とリンク切れ一覧のURL）の最大表示幅（桁数）。0なら省略しません。
+    #[arg(long = "url-width", value_name = "N", default_value_t = 60)]
+    pub url_width: usize,
+
+    /// テーブルの幅（桁数）。省略時は端末の幅に合わせ、パイプ出力などでは制限しません。狭い場合はHomepage列、さらにSource列を省きます。
+    #[arg(long = "width", value_name = "COLS", value_parser = clap::value_parser!(u16).range(20..))]
+    pub width: Option<u16>,
+
+    /// テーブル出力に開発用依存かどうかを示すDev列を追加します。
+    #[arg(long = "show-dev")]
+    pub show_dev: bool,
+
+    /// テーブル出力にパッケージの説明（PyPIのsummary・npmのdescription）を示すDescription列を追加します。--fetch-licensesで取得した依存のみ表示されます。
+    #[arg(long = "show-description")]
+    pub show_description: bool,
+
+    /// ライセンスの取得元（lockfile / pypi-classifier / pypi-license / npm-registry / license-file / override / guessedなど）を示すProvenance列を追加し、JSONにも`provenance`を出力します。
+    #[arg(long = "show-provenance")]
+    pub show_provenance: bool,
+
+    /// 解析・ライセンス取得・出力の各フェーズの所要時間と、ライセンス取得時のキャッシュのヒット・ミス件数を標準エラー出力に表示します。
+    #[arg(long = "timing")]
+    pub timing: bool,
+
+    /// JSON・CSVなどに出力するソースファイルのパスを、カレントディレクトリからの相対パスに変換せず絶対パスのまま出力します。
+    #[arg(long = "absolute-paths")]
+    pub absolute_paths: bool,
+
     /// テーブル出力時にSource列を非表示にします。
     #[arg(long = "hide-source")]
     pub hide_source: bool,
+
+    /// 同じ名前のパッケージが複数のマネージャに存在する場合に警告します。
+    #[arg(long = "warn-name-collisions")]
+    pub warn_name_collisions: bool,
+
+    /// 同じパッケージ・バージョンなのにソースによってライセンス（Unknown以外）が異なる場合に警告します。
+    #[arg(long = "check-conflicts")]
+    pub check_conflicts: bool,
+
+    /// 同じパッケージが複数のバージョンで使われている場合に、バージョンごとの検出元ファイルとともに警告します。
+    #[arg(long = "version-conflicts")]
+    pub version_conflicts: bool,
+
+    /// パッケージごとに利用しているプロジェクト（マニフェストのあるディレクトリ）を集約して表示します。
+    #[arg(long = "by-project")]
+    pub by_project: bool,
+
+    /// 依存をソースファイル（ロックファイル・マニフェスト）ごとにツリー表示します。
+    #[arg(long = "tree", conflicts_with = "by_project")]
+    pub tree: bool,
+
+    /// ソースファイル（ロックファイル・マニフェスト）ごとに依存件数とライセンス別件数を表示します。
+    #[arg(long = "by-source", conflicts_with_all = ["by_project", "tree"])]
+    pub by_source: bool,
+
+    /// 依存の一覧を全画面の対話型ブラウザで表示します（/で検索、sで並べ替え、l・mでライセンス・マネージャの絞り込み、qで終了）。
+    #[arg(long = "tui", conflicts_with_all = ["watch", "by_project", "tree", "by_source"])]
+    pub tui: bool,
+
+    /// テーブル出力の後に、依存総数・マネージャ別件数・ライセンス別件数のサマリーを表示します。
+    #[arg(long = "summary")]
+    pub summary: bool,
+
+    /// 依存の一覧の代わりに、件数の多いライセンスの上位N件（件数と割合）だけを表示します。
+    #[arg(
+        long = "top-licenses",
+        value_name = "N",
+        value_parser = clap::value_parser!(u64).range(1..),
+        conflicts_with_all = ["by_project", "tree", "by_source", "tui", "summary"]
+    )]
+    pub top_licenses: Option<u64>,
+
+    /// 依存の一覧の代わりに、ライセンスごとの主な義務（著作権表示・ソース開示など）と、義務ごとに該当する依存を表示します。不明・複合ライセンスは要確認として挙げます。
+    #[arg(
+        long = "obligations",
+        conflicts_with_all = ["by_project", "tree", "by_source", "tui", "summary", "top_licenses"]
+    )]
+    pub obligations: bool,
+
+    /// 最初のスキャンで見つかったマニフェスト・ロックファイルを監視し、変更されるたびに再スキャンして表示し直します。
+    #[arg(long = "watch")]
+    pub watch: bool,
+
+    /// 進捗表示・状況メッセージ・警告・末尾の集計行を抑制し、レポートとエラーのみ出力します。
+    #[arg(short, long = "quiet", conflicts_with = "verbose")]
+    pub quiet: bool,
+
+    /// パッケージごとの取得結果（キャッシュ利用・取得・未登録）を標準エラーに出力します。
+    #[arg(short, long = "verbose")]
+    pub verbose: bool,
+
+    /// 進捗の表示形式（human / json）。jsonでは`{"phase":"enrich","done":12,"total":300}`形式のイベントを1行ずつ標準エラーに出力します。
+    #[arg(
+        long = "progress-format",
+        value_name = "FORMAT",
+        value_enum,
+        default_value = "human"
+    )]
+    pub progress_format: ProgressFormat,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// シェルの補完スクリプトを標準出力に書き出します（例: `license-scout completions bash > /etc/bash_completion.d/license-scout`）。
+    Completions {
+        #[arg(value_enum)]
+        shell: Shell,
+    },
+    /// 走査せずに1つのパッケージのライセンス情報をレジストリ（とキャッシュ）から取得して表示します（例: `license-scout query npm lodash@4.17.21`、`license-scout query pip requests==2.32.0`）。
+    Query {
+        /// マネージャ（pip / npm / cargo / gem / maven）
+        manager: String,
+        /// パッケージ名。バージョンはpipなら`==`、それ以外は`@`で指定し、省略すると最新版
+        #[arg(value_name = "PACKAGE")]
+        package: String,
+    },
+}
+
+fn parse_manifest_format(value: &str) -> Result<ManifestKind, String> {
+    ManifestKind::from_format(value).ok_or_else(|| {
+        format!(
+            "未対応の形式です: {value}（{}）",
+            ManifestKind::format_names()
+        )
+    })
+}
+
+fn parse_percent(value: &str) -> Result<f64, String> {
+    let percent: f64 = value
+        .trim()
+        .trim_end_matches('%')
+        .parse()
+        .map_err(|_| format!("数値で指定してください: {value}"))?;
+    if (0.0..=100.0).contains(&percent) {
+        Ok(percent)
+    } else {
+        Err(format!("0〜100の範囲で指定してください: {value}"))
+    }
 }
@@ -0,0 +1,249 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::spdx::{Category, LicenseExpr, license_category, normalize_spdx};
+use crate::types::DependencyRecord;
+
+/// プロジェクトのライセンスから見た依存ライセンスの互換性（`--project-license`）。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Compatibility {
+    Compatible,
+    /// 利用形態（リンク方法・変更の有無など）次第のため要確認
+    Review,
+    Incompatible,
+}
+
+impl Compatibility {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Compatible => "compatible",
+            Self::Review => "review",
+            Self::Incompatible => "incompatible",
+        }
+    }
+}
+
+impl fmt::Display for Compatibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+/// プロジェクト自身のライセンス（正規化済みのSPDX ID）。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectLicense(String);
+
+impl FromStr for ProjectLicense {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        if LicenseExpr::parse(value).is_some_and(|expr| expr.is_compound()) {
+            return Err(format!(
+                "ライセンス式ではなく単一のSPDX IDを指定してください: {value}"
+            ));
+        }
+        normalize_spdx(value)
+            .map(Self)
+            .ok_or_else(|| format!("SPDX IDとして認識できないライセンスです: {value}"))
+    }
+}
+
+/// 左のライセンスで頒布する成果物に組み込めない依存ライセンス（`-only`表記に揃えたもの）。
+const INCOMPATIBLE: &[(&str, &[&str])] = &[
+    (
+        "GPL-2.0-only",
+        &[
+            "AGPL-3.0-only",
+            "Apache-2.0",
+            "CC-BY-SA-4.0",
+            "CDDL-1.0",
+            "CDDL-1.1",
+            "EPL-1.0",
+            "EPL-2.0",
+            "GPL-3.0-only",
+            "LGPL-3.0-only",
+            "MPL-1.1",
+            "MS-PL",
+        ],
+    ),
+    (
+        "GPL-3.0-only",
+        &[
+            "CDDL-1.0",
+            "CDDL-1.1",
+            "EPL-1.0",
+            "GPL-2.0-only",
+            "MPL-1.1",
+            "MS-PL",
+        ],
+    ),
+    (
+        "AGPL-3.0-only",
+        &[
+            "CDDL-1.0",
+            "CDDL-1.1",
+            "EPL-1.0",
+            "GPL-2.0-only",
+            "MPL-1.1",
+            "MS-PL",
+        ],
+    ),
+];
+
+impl ProjectLicense {
+    pub fn id(&self) -> &str {
+        &self.0
+    }
+
+    /// 依存のライセンス（式を含む）との互換性。ORは最も良い選択肢、ANDは最も悪い構成要素で判定し、
+    /// 不明なライセンスは`Review`になります。
+    pub fn check(&self, license: &str) -> Compatibility {
+        match LicenseExpr::parse(license) {
+            Some(expr) => self.expr_compatibility(&expr),
+            None => self.term_compatibility(license),
+        }
+    }
+
+    pub fn annotate(&self, records: &mut [DependencyRecord]) {
+        for record in records.iter_mut() {
+            record.compatibility = Some(if record.has_unknown_license() {
+                Compatibility::Review
+            } else {
+                self.check(&record.license)
+            });
+        }
+    }
+
+    fn expr_compatibility(&self, expr: &LicenseExpr) -> Compatibility {
+        match expr {
+            LicenseExpr::License(term) => self.term_compatibility(term),
+            LicenseExpr::And(operands) => operands
+                .iter()
+                .map(|operand| self.expr_compatibility(operand))
+                .max()
+                .unwrap_or(Compatibility::Review),
+            LicenseExpr::Or(operands) => operands
+                .iter()
+                .map(|operand| self.expr_compatibility(operand))
+                .min()
+                .unwrap_or(Compatibility::Review),
+        }
+    }
+
+    /// `-or-later`はどの版を選んでもよいため、プロジェクト・依存とも最も都合の良い版で判定します。
+    fn term_compatibility(&self, term: &str) -> Compatibility {
+        let Some(dependency) = normalize_spdx(term) else {
+            return Compatibility::Review;
+        };
+        license_versions(&self.0)
+            .iter()
+            .flat_map(|project| {
+                license_versions(&dependency)
+                    .into_iter()
+                    .map(move |dependency| pair_compatibility(project, &dependency))
+            })
+            .min()
+            .unwrap_or(Compatibility::Review)
+    }
+}
+
+/// `GPL-2.0-or-later`を`GPL-2.0-only`・`GPL-3.0-only`のように選択可能な版へ展開します。
+fn license_versions(id: &str) -> Vec<String> {
+    const FAMILIES: &[(&str, &[&str])] = &[
+        ("GPL-2.0", &["GPL-2.0-only", "GPL-3.0-only"]),
+        ("GPL-3.0", &["GPL-3.0-only"]),
+        ("AGPL-3.0", &["AGPL-3.0-only"]),
+        ("LGPL-2.0", &["LGPL-2.0-only", "LGPL-3.0-only"]),
+        ("LGPL-2.1", &["LGPL-2.1-only", "LGPL-3.0-only"]),
+        ("LGPL-3.0", &["LGPL-3.0-only"]),
+    ];
+    let (base, or_later) = match id.strip_suffix("-or-later") {
+        Some(base) => (base, true),
+        None => (id.strip_suffix("-only").unwrap_or(id), false),
+    };
+    match FAMILIES.iter().find(|(family, _)| *family == base) {
+        Some((_, versions)) if or_later => versions.iter().map(|v| v.to_string()).collect(),
+        Some((family, _)) => vec![format!("{family}-only")],
+        None => vec![id.to_string()],
+    }
+}
+
+fn pair_compatibility(project: &str, dependency: &str) -> Compatibility {
+    if project == dependency {
+        return Compatibility::Compatible;
+    }
+    if INCOMPATIBLE
+        .iter()
+        .any(|(left, rights)| *left == project && rights.contains(&dependency))
+    {
+        return Compatibility::Incompatible;
+    }
+    match (license_category(project), license_category(dependency)) {
+        (_, Category::Unknown) => Compatibility::Review,
+        // AGPL-3.0はGPL-3.0の成果物との結合を明示的に認めています
+        (Category::StrongCopyleft, Category::StrongCopyleft)
+            if project == "AGPL-3.0-only" && dependency == "GPL-3.0-only" =>
+        {
+            Compatibility::Compatible
+        }
+        (Category::StrongCopyleft, Category::StrongCopyleft) => Compatibility::Review,
+        (Category::StrongCopyleft, _) => Compatibility::Compatible,
+        (_, Category::StrongCopyleft) => Compatibility::Incompatible,
+        (_, Category::WeakCopyleft) => Compatibility::Review,
+        _ => Compatibility::Compatible,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project(license: &str) -> ProjectLicense {
+        license.parse().unwrap()
+    }
+
+    #[test]
+    fn permissive_project_rejects_strong_copyleft() {
+        let mit = project("mit");
+        assert_eq!(mit.id(), "MIT");
+        assert_eq!(mit.check("Apache-2.0"), Compatibility::Compatible);
+        assert_eq!(mit.check("GPL-3.0-only"), Compatibility::Incompatible);
+        assert_eq!(mit.check("GPL-2.0-or-later"), Compatibility::Incompatible);
+        assert_eq!(mit.check("MPL-2.0"), Compatibility::Review);
+        assert_eq!(mit.check("MIT OR GPL-3.0"), Compatibility::Compatible);
+        assert_eq!(mit.check("MIT AND GPL-3.0"), Compatibility::Incompatible);
+        assert_eq!(mit.check("Some Custom License"), Compatibility::Review);
+    }
+
+    #[test]
+    fn gpl_versions_follow_or_later() {
+        let gpl2 = project("GPL-2.0-only");
+        assert_eq!(gpl2.check("Apache-2.0"), Compatibility::Incompatible);
+        assert_eq!(gpl2.check("GPL-3.0-or-later"), Compatibility::Incompatible);
+        assert_eq!(gpl2.check("GPL-2.0-or-later"), Compatibility::Compatible);
+        assert_eq!(gpl2.check("LGPL-2.1-or-later"), Compatibility::Compatible);
+
+        let gpl3 = project("GPL-3.0-or-later");
+        assert_eq!(gpl3.check("Apache-2.0"), Compatibility::Compatible);
+        assert_eq!(gpl3.check("GPL-2.0-only"), Compatibility::Incompatible);
+        assert_eq!(gpl3.check("GPL-2.0-or-later"), Compatibility::Compatible);
+        assert_eq!(gpl3.check("AGPL-3.0-only"), Compatibility::Review);
+        assert_eq!(
+            project("AGPL-3.0").check("GPL-3.0"),
+            Compatibility::Compatible
+        );
+
+        let gpl2_or_later = project("GPL-2.0-or-later");
+        assert_eq!(gpl2_or_later.check("Apache-2.0"), Compatibility::Compatible);
+        assert_eq!(gpl2_or_later.check("EPL-1.0"), Compatibility::Incompatible);
+    }
+
+    #[test]
+    fn rejects_expressions_and_unknown_project_licenses() {
+        assert!("MIT OR Apache-2.0".parse::<ProjectLicense>().is_err());
+        assert!("Some Custom License".parse::<ProjectLicense>().is_err());
+    }
+}
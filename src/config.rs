@@ -0,0 +1,196 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, anyhow};
+use glob::Pattern;
+use license_scout::policy::{MinVersionRule, SeverityRule};
+use serde::Deserialize;
+
+use crate::cli::Cli;
+
+pub const DEFAULT_CONFIG_FILE: &str = "license-scout.toml";
+
+/// `license-scout.toml`で指定できる既定値。コマンドラインの指定が優先されます。
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Config {
+    pub paths: Vec<PathBuf>,
+    pub exclude: Vec<String>,
+    pub include: Vec<String>,
+    pub deny: Vec<String>,
+    pub allow_only: Vec<String>,
+    pub concurrency: Option<usize>,
+    pub cache_path: Option<PathBuf>,
+    /// ライセンスまたは区分ごとの重大度（例: `"GPL-3.0" = "forbidden"`）。
+    pub severity: BTreeMap<String, String>,
+    /// パッケージごとの最低バージョン（例: `"npm:lodash" = "4.17.21"`）。
+    pub min_version: BTreeMap<String, String>,
+    /// ファイル名のglobと解析に使う形式（例: `"prod-requirements.in" = "requirements"`）。
+    pub map: BTreeMap<String, String>,
+}
+
+impl Config {
+    /// `--config`で指定されたファイル、なければカレントディレクトリの`license-scout.toml`を読み込みます。
+    /// 既定のファイルが存在しない場合は`None`を返します。
+    pub fn discover(explicit: Option<&Path>, cwd: &Path) -> Result<Option<(PathBuf, Config)>> {
+        let path = match explicit {
+            Some(path) => cwd.join(path),
+            None => cwd.join(DEFAULT_CONFIG_FILE),
+        };
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == ErrorKind::NotFound && explicit.is_none() => {
+                return Ok(None);
+            }
+            Err(err) => {
+                return Err(err).with_context(|| {
+                    format!("設定ファイルを読み込めませんでした: {}", path.display())
+                });
+            }
+        };
+        let config = Self::parse(&text)
+            .with_context(|| format!("設定ファイルの形式が不正です: {}", path.display()))?;
+        Ok(Some((path, config)))
+    }
+
+    fn parse(text: &str) -> Result<Self> {
+        Ok(toml::from_str(text)?)
+    }
+
+    /// コマンドラインで指定されなかった項目を設定ファイルの値で補います。
+    /// 相対パスは設定ファイルのあるディレクトリを基準に解決します。
+    pub fn apply(self, cli: &mut Cli, base_dir: &Path) -> Result<()> {
+        if cli.paths.is_empty() {
+            cli.paths = self.paths.iter().map(|p| base_dir.join(p)).collect();
+        }
+        if cli.exclude.is_empty() {
+            cli.exclude = self
+                .exclude
+                .iter()
+                .map(|glob| {
+                    Pattern::new(glob)
+                        .with_context(|| format!("excludeのパターンが不正です: {glob}"))
+                })
+                .collect::<Result<_>>()?;
+        }
+        if cli.include.is_empty() {
+            cli.include = self
+                .include
+                .iter()
+                .map(|glob| {
+                    Pattern::new(glob)
+                        .with_context(|| format!("includeのパターンが不正です: {glob}"))
+                })
+                .collect::<Result<_>>()?;
+        }
+        if cli.map.is_empty() {
+            cli.map = self
+                .map
+                .iter()
+                .map(|(glob, format)| {
+                    format!("{glob}={format}")
+                        .parse()
+                        .map_err(|err| anyhow!("map: {err}"))
+                })
+                .collect::<Result<_>>()?;
+        }
+        if cli.deny.is_empty() {
+            cli.deny = self.deny;
+        }
+        if cli.allow_only.is_empty() {
+            cli.allow_only = self.allow_only;
+        }
+        if cli.severity.is_empty() {
+            cli.severity = self
+                .severity
+                .iter()
+                .map(|(target, severity)| {
+                    SeverityRule::new(target, severity).map_err(|err| anyhow!("severity: {err}"))
+                })
+                .collect::<Result<_>>()?;
+        }
+        if cli.min_version.is_empty() {
+            cli.min_version = self
+                .min_version
+                .iter()
+                .map(|(package, version)| {
+                    MinVersionRule::new(package, version)
+                        .map_err(|err| anyhow!("min-version: {err}"))
+                })
+                .collect::<Result<_>>()?;
+        }
+        if cli.concurrency.is_none() {
+            cli.concurrency = self.concurrency;
+        }
+        if cli.cache_path.is_none() {
+            cli.cache_path = self.cache_path.map(|p| base_dir.join(p));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[test]
+    fn parses_kebab_case_keys() {
+        let config = Config::parse(
+            r#"
+paths = ["backend", "frontend"]
+exclude = ["dist"]
+deny = ["GPL-3.0"]
+allow-only = ["MIT", "Apache-2.0"]
+concurrency = 4
+cache-path = ".cache/licenses.json"
+
+[severity]
+"GPL-3.0" = "forbidden"
+weak-copyleft = "review"
+
+[min-version]
+"npm:lodash" = "4.17.21"
+
+[map]
+"prod-requirements.in" = "requirements"
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.paths,
+            vec![PathBuf::from("backend"), PathBuf::from("frontend")]
+        );
+        assert_eq!(config.allow_only, vec!["MIT", "Apache-2.0"]);
+        assert_eq!(config.concurrency, Some(4));
+        assert_eq!(config.severity["weak-copyleft"], "review");
+        assert_eq!(config.min_version["npm:lodash"], "4.17.21");
+        assert_eq!(config.map["prod-requirements.in"], "requirements");
+        assert!(Config::parse("unknown = 1").is_err());
+    }
+
+    #[test]
+    fn cli_values_take_precedence() {
+        let config = Config {
+            paths: vec![PathBuf::from("backend")],
+            exclude: vec!["dist".to_string()],
+            include: vec!["services/*".to_string()],
+            deny: vec!["GPL-3.0".to_string()],
+            concurrency: Some(4),
+            cache_path: Some(PathBuf::from("cache.json")),
+            ..Config::default()
+        };
+        let mut cli =
+            Cli::parse_from(["license-scout", "--deny", "AGPL-3.0", "--concurrency", "2"]);
+        config.apply(&mut cli, Path::new("/repo")).unwrap();
+
+        assert_eq!(cli.paths, vec![PathBuf::from("/repo/backend")]);
+        assert_eq!(cli.exclude, vec![Pattern::new("dist").unwrap()]);
+        assert_eq!(cli.include, vec![Pattern::new("services/*").unwrap()]);
+        assert_eq!(cli.deny, vec!["AGPL-3.0"]);
+        assert_eq!(cli.concurrency, Some(2));
+        assert_eq!(cli.cache_path, Some(PathBuf::from("/repo/cache.json")));
+    }
+}
@@ -0,0 +1,224 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::types::DependencyRecord;
+
+/// ベースラインと現在のスキャン結果の差分。`(manager, name)`単位で比較します。
+#[derive(Debug, Default, Serialize)]
+pub struct DependencyDiff {
+    pub added: Vec<DiffEntry>,
+    pub removed: Vec<DiffEntry>,
+    pub changed: Vec<DiffChange>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DiffEntry {
+    pub manager: String,
+    pub name: String,
+    pub version: String,
+    pub license: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DiffChange {
+    pub manager: String,
+    pub name: String,
+    pub old_version: String,
+    pub new_version: String,
+    pub old_license: String,
+    pub new_license: String,
+}
+
+impl DiffChange {
+    pub fn license_changed(&self) -> bool {
+        self.old_license != self.new_license
+    }
+}
+
+impl DependencyDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// `--print-json`や`--json-output`で保存したJSONレポートを読み込みます。
+pub fn load_baseline(path: &Path) -> Result<Vec<DependencyRecord>> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("ベースラインの読み込みに失敗: {}", path.display()))?;
+    let report: BaselineReport = serde_json::from_str(&content)
+        .with_context(|| format!("ベースラインのJSON解析に失敗: {}", path.display()))?;
+    Ok(match report {
+        BaselineReport::Records(records)
+        | BaselineReport::Wrapped {
+            dependencies: records,
+        } => records,
+    })
+}
+
+/// 配列のレポートと、`--with-metadata`で`dependencies`に包んだレポートの両方を読み込みます。
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum BaselineReport {
+    Records(Vec<DependencyRecord>),
+    Wrapped { dependencies: Vec<DependencyRecord> },
+}
+
+pub fn diff_records(baseline: &[DependencyRecord], current: &[DependencyRecord]) -> DependencyDiff {
+    let before = summarize(baseline);
+    let after = summarize(current);
+    let mut diff = DependencyDiff::default();
+
+    for ((manager, name), new) in &after {
+        match before.get(&(manager.clone(), name.clone())) {
+            None => diff.added.push(new.entry(manager, name)),
+            Some(old) if old != new => diff.changed.push(DiffChange {
+                manager: manager.clone(),
+                name: name.clone(),
+                old_version: old.version(),
+                new_version: new.version(),
+                old_license: old.license(),
+                new_license: new.license(),
+            }),
+            Some(_) => {}
+        }
+    }
+    for ((manager, name), old) in &before {
+        if !after.contains_key(&(manager.clone(), name.clone())) {
+            diff.removed.push(old.entry(manager, name));
+        }
+    }
+    diff
+}
+
+/// 同じパッケージが複数のソース・バージョンで現れる場合はまとめて比較する。
+#[derive(Debug, Default, PartialEq, Eq)]
+struct PackageSummary {
+    versions: BTreeSet<String>,
+    licenses: BTreeSet<String>,
+}
+
+impl PackageSummary {
+    fn version(&self) -> String {
+        if self.versions.is_empty() {
+            "-".to_string()
+        } else {
+            self.versions.iter().cloned().collect::<Vec<_>>().join(", ")
+        }
+    }
+
+    fn license(&self) -> String {
+        self.licenses.iter().cloned().collect::<Vec<_>>().join(", ")
+    }
+
+    fn entry(&self, manager: &str, name: &str) -> DiffEntry {
+        DiffEntry {
+            manager: manager.to_string(),
+            name: name.to_string(),
+            version: self.version(),
+            license: self.license(),
+        }
+    }
+}
+
+fn summarize(records: &[DependencyRecord]) -> BTreeMap<(String, String), PackageSummary> {
+    let mut summaries: BTreeMap<(String, String), PackageSummary> = BTreeMap::new();
+    for record in records {
+        let summary = summaries
+            .entry((record.manager.clone(), record.name.clone()))
+            .or_default();
+        if let Some(version) = &record.version {
+            summary.versions.insert(version.clone());
+        }
+        let license = if record.has_unknown_license() {
+            "Unknown".to_string()
+        } else {
+            record.license.clone()
+        };
+        summary.licenses.insert(license);
+    }
+    summaries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(name: &str, version: &str, license: &str) -> DependencyRecord {
+        DependencyRecord {
+            manager: "npm".to_string(),
+            name: name.to_string(),
+            version: Some(version.to_string()),
+            license: license.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn baseline_accepts_wrapped_reports() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("baseline.json");
+        fs::write(
+            &path,
+            r#"{"generated_at": "2024-01-02T03:04:05Z", "dependencies": [{"manager": "npm", "name": "react"}]}"#,
+        )
+        .unwrap();
+        let records = load_baseline(&path).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].name, "react");
+    }
+
+    #[test]
+    fn reports_added_removed_and_changed_packages() {
+        let baseline = vec![
+            record("react", "18.2.0", "MIT"),
+            record("left-pad", "1.3.0", "WTFPL"),
+            record("lodash", "4.17.20", "MIT"),
+            record("stable", "1.0.0", "ISC"),
+        ];
+        let current = vec![
+            record("react", "18.3.0", "MIT"),
+            record("lodash", "4.17.21", "GPL-3.0"),
+            record("stable", "1.0.0", "ISC"),
+            record("zod", "3.22.0", "MIT"),
+        ];
+
+        let diff = diff_records(&baseline, &current);
+        assert_eq!(
+            diff.added
+                .iter()
+                .map(|e| e.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["zod"]
+        );
+        assert_eq!(
+            diff.removed
+                .iter()
+                .map(|e| e.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["left-pad"]
+        );
+        let changed: Vec<(&str, bool)> = diff
+            .changed
+            .iter()
+            .map(|c| (c.name.as_str(), c.license_changed()))
+            .collect();
+        assert_eq!(changed, vec![("lodash", true), ("react", false)]);
+        assert_eq!(diff.changed[0].old_license, "MIT");
+        assert_eq!(diff.changed[0].new_license, "GPL-3.0");
+    }
+
+    #[test]
+    fn baseline_json_round_trips() {
+        let records = vec![record("react", "18.2.0", "MIT")];
+        let json = serde_json::to_string(&records).unwrap();
+        let loaded: Vec<DependencyRecord> = serde_json::from_str(&json).unwrap();
+        assert!(diff_records(&loaded, &records).is_empty());
+        let minimal: Vec<DependencyRecord> =
+            serde_json::from_str(r#"[{"manager":"npm","name":"react","license":"MIT"}]"#).unwrap();
+        assert_eq!(minimal[0].version, None);
+    }
+}
@@ -0,0 +1,149 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result, bail};
+
+use crate::scan::{self, ManifestKind, ScanOptions, Scanned};
+
+/// `REF:path`のファイルを`git show`で取り出して解析します。
+/// `-`で始まる指定はgitのオプションとして解釈されるおそれがあるため受け付けません。
+pub fn scan_git_object(spec: &str, repo_dir: &Path) -> Result<Scanned> {
+    let Some((git_ref, file_path)) = spec.split_once(':') else {
+        bail!("REF:path 形式で指定してください: {spec}");
+    };
+    if git_ref.is_empty() || file_path.is_empty() {
+        bail!("REF:path 形式で指定してください: {spec}");
+    }
+    if git_ref.starts_with('-') {
+        bail!("REFを`-`で始めることはできません: {spec}");
+    }
+
+    let Some(kind) = ManifestKind::from_path(Path::new(file_path)) else {
+        bail!("未対応のファイル形式です: {file_path}");
+    };
+
+    let output = Command::new("git")
+        .args(["show", "--end-of-options"])
+        .arg(spec)
+        .current_dir(repo_dir)
+        .output()
+        .context("gitコマンドの実行に失敗しました")?;
+    if !output.status.success() {
+        bail!(
+            "git showに失敗しました({spec}): {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    let content = String::from_utf8(output.stdout)
+        .with_context(|| format!("{spec}の内容がUTF-8ではありません"))?;
+    let source = PathBuf::from(spec);
+    let records = scan::parse_manifest(kind, &content, &source)
+        .with_context(|| format!("{}の解析に失敗: {spec}", kind.label()))?;
+    Ok(Scanned::single(source, kind, records))
+}
+
+/// リモートのgitリポジトリを一時ディレクトリにshallow cloneして走査し、終了後に削除します。
+/// `git_ref`にはブランチ名・タグ名を指定できます。Source列と解析したファイルのパスはクローン先からの相対パスになります。
+pub fn scan_remote(url: &str, git_ref: Option<&str>, options: &ScanOptions) -> Result<Scanned> {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.subsec_nanos());
+    let clone = TempDir(
+        std::env::temp_dir().join(format!("license-scout-git-{}-{nanos}", std::process::id())),
+    );
+
+    let mut command = Command::new("git");
+    command.args(["clone", "--quiet", "--depth", "1"]);
+    if let Some(git_ref) = git_ref {
+        command.arg("--branch").arg(git_ref);
+    }
+    let output = command
+        .arg("--")
+        .arg(url)
+        .arg(&clone.0)
+        .output()
+        .context("gitコマンドの実行に失敗しました")?;
+    if !output.status.success() {
+        bail!(
+            "git cloneに失敗しました({url}): {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    // 一時ディレクトリの解析結果を走査キャッシュに残さない
+    let options = ScanOptions {
+        scan_cache: None,
+        ..options.clone()
+    };
+    let mut scanned = scan::scan_records(&clone.0, &options)?;
+    for record in &mut scanned.records {
+        if let Ok(relative) = record.source.strip_prefix(&clone.0) {
+            record.source = relative.to_path_buf();
+        }
+    }
+    for file in &mut scanned.files {
+        if let Ok(relative) = file.path.strip_prefix(&clone.0) {
+            file.path = relative.to_path_buf();
+        }
+    }
+    Ok(scanned)
+}
+
+/// 破棄時に削除する一時ディレクトリ。
+struct TempDir(PathBuf);
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .args(["-c", "commit.gpgsign=false"])
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {args:?}");
+    }
+
+    #[test]
+    fn reads_manifests_from_a_ref_and_rejects_option_like_specs() {
+        let repo = TempDir(
+            std::env::temp_dir().join(format!("license-scout-git-show-{}", std::process::id())),
+        );
+        fs::create_dir_all(&repo.0).unwrap();
+        git(&repo.0, &["init", "--quiet"]);
+        fs::write(repo.0.join("requirements.txt"), "requests==2.31.0\n").unwrap();
+        git(&repo.0, &["add", "requirements.txt"]);
+        git(&repo.0, &["commit", "--quiet", "-m", "init"]);
+        fs::write(repo.0.join("requirements.txt"), "flask==3.0.0\n").unwrap();
+
+        let records = scan_git_object("HEAD:requirements.txt", &repo.0)
+            .unwrap()
+            .records;
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].name, "requests");
+        assert_eq!(records[0].version.as_deref(), Some("2.31.0"));
+        assert_eq!(records[0].source, PathBuf::from("HEAD:requirements.txt"));
+
+        let target = repo.0.join("injected");
+        let spec = format!("--output={}:requirements.txt", target.display());
+        let err = scan_git_object(&spec, &repo.0).unwrap_err();
+        assert!(err.to_string().contains("`-`で始める"), "{err}");
+        assert!(!target.exists());
+
+        assert!(scan_git_object("HEAD:README.md", &repo.0).is_err());
+        assert!(scan_git_object("HEAD", &repo.0).is_err());
+    }
+}
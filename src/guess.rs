@@ -0,0 +1,136 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::{Result, bail};
+use glob::Pattern;
+use serde::Deserialize;
+
+use crate::ignore::{matches_package_pattern, parse_package_pattern};
+use crate::logging;
+use crate::overrides::load_json_or_toml;
+use crate::types::{DependencyRecord, Provenance};
+
+/// パッケージ名のパターンからライセンスを推定する対応表（`--guess-licenses`）。
+/// キーは`manager:name`（名前はglob可）、値はライセンスです。
+#[derive(Debug, Default, Deserialize)]
+#[serde(try_from = "BTreeMap<String, String>")]
+pub struct LicenseGuesses {
+    entries: Vec<Guess>,
+}
+
+#[derive(Debug)]
+struct Guess {
+    key: String,
+    manager: String,
+    pattern: Pattern,
+    license: String,
+}
+
+impl LicenseGuesses {
+    /// 拡張子が`.json`ならJSON、それ以外はTOMLとして読み込みます。
+    pub fn load(path: &Path) -> Result<Self> {
+        load_json_or_toml(path, "ライセンス推定ファイル")
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// ライセンスが不明な依存にだけ推定結果を設定し、設定した件数を返します。
+    /// 複数のパターンに一致する場合は、より長い（具体的な）パターンを優先します。
+    pub fn apply(&self, records: &mut [DependencyRecord]) -> usize {
+        let mut applied = 0;
+        for record in records.iter_mut().filter(|r| r.has_unknown_license()) {
+            let Some(guess) = self
+                .entries
+                .iter()
+                .filter(|g| matches_package_pattern(&g.manager, &g.pattern, record))
+                .max_by_key(|g| g.pattern.as_str().len())
+            else {
+                continue;
+            };
+            record.license = guess.license.clone();
+            record.provenance = Some(Provenance::Guessed);
+            logging::verbose(format!(
+                "ライセンスを推定: {}({}) -> {}（{}）",
+                record.name, record.manager, guess.license, guess.key
+            ));
+            applied += 1;
+        }
+        applied
+    }
+}
+
+impl TryFrom<BTreeMap<String, String>> for LicenseGuesses {
+    type Error = anyhow::Error;
+
+    fn try_from(values: BTreeMap<String, String>) -> Result<Self> {
+        let entries = values
+            .into_iter()
+            .map(|(key, license)| {
+                let license = license.trim().to_string();
+                if license.is_empty() {
+                    bail!("ライセンスを指定してください: {key}");
+                }
+                let (manager, pattern) = parse_package_pattern(&key)?;
+                Ok(Guess {
+                    key,
+                    manager,
+                    pattern,
+                    license,
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { entries })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(manager: &str, name: &str, license: &str) -> DependencyRecord {
+        DependencyRecord {
+            manager: manager.to_string(),
+            name: name.to_string(),
+            license: license.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn guesses_only_fill_unknown_licenses() {
+        let guesses = toml::from_str::<LicenseGuesses>(
+            r#"
+"npm:@mycompany/*" = "MIT"
+"npm:@mycompany/legacy-*" = "Proprietary"
+"pip:Internal_*" = "Apache-2.0"
+"#,
+        )
+        .unwrap();
+        assert_eq!(guesses.len(), 3);
+
+        let mut records = vec![
+            record("npm", "@mycompany/ui", "Unknown"),
+            record("npm", "@mycompany/legacy-auth", ""),
+            record("npm", "@mycompany/core", "ISC"),
+            record("pip", "internal-tool", "Unknown"),
+            record("npm", "react", "Unknown"),
+        ];
+        assert_eq!(guesses.apply(&mut records), 3);
+        let licenses: Vec<_> = records.iter().map(|r| r.license.as_str()).collect();
+        assert_eq!(
+            licenses,
+            ["MIT", "Proprietary", "ISC", "Apache-2.0", "Unknown"]
+        );
+        assert_eq!(records[0].provenance, Some(Provenance::Guessed));
+        assert_eq!(records[2].provenance, None);
+
+        assert!(serde_json::from_str::<LicenseGuesses>(r#"{"react": "MIT"}"#).is_err());
+        assert!(toml::from_str::<LicenseGuesses>(r#""npm:react" = " ""#).is_err());
+    }
+}
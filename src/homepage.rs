@@ -0,0 +1,412 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, mpsc};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use indicatif::ProgressBar;
+use reqwest::StatusCode;
+use reqwest::blocking::Client;
+use serde::{Deserialize, Serialize};
+
+use crate::cache;
+use crate::logging;
+use crate::metadata::{self, RateLimiter};
+use crate::types::DependencyRecord;
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+const HOMEPAGE_CACHE_FILE: &str = "homepage-cache.json";
+const HOMEPAGE_CACHE_VERSION: u8 = 1;
+/// 確認結果を再利用する期間。リンク切れは直ることもあるため、これを過ぎたURLは確認し直します。
+const HOMEPAGE_CACHE_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+#[derive(Debug)]
+pub struct BrokenHomepage {
+    pub url: String,
+    pub reason: String,
+    pub packages: Vec<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct HomepageCheckOptions {
+    pub concurrency: usize,
+    /// 1秒あたりの最大リクエスト数（全ワーカー合計）。0以下なら制限しません。
+    pub rate_limit: f64,
+    /// 1リクエストあたりのタイムアウト。`None`なら10秒です。
+    pub request_timeout: Option<Duration>,
+    /// 送信するUser-Agent。`None`なら`metadata::DEFAULT_USER_AGENT`です。
+    pub user_agent: Option<String>,
+}
+
+/// URLごとの`--check-homepages`の確認結果。ライセンスキャッシュと同じディレクトリの
+/// `homepage-cache.json`に保存し、`HOMEPAGE_CACHE_TTL`以内に確認したURLにはリクエストを送りません。
+#[derive(Debug)]
+pub struct HomepageCache {
+    path: PathBuf,
+    data: HomepageCacheData,
+    dirty: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct HomepageCacheData {
+    version: u8,
+    entries: HashMap<String, HomepageCacheEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct HomepageCacheEntry {
+    /// リンク切れの理由。到達できた場合は`None`です。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    broken: Option<String>,
+    checked_at: u64,
+}
+
+impl Default for HomepageCacheData {
+    fn default() -> Self {
+        Self {
+            version: HOMEPAGE_CACHE_VERSION,
+            entries: HashMap::new(),
+        }
+    }
+}
+
+impl HomepageCache {
+    /// ライセンスキャッシュと同じディレクトリの`homepage-cache.json`を読み込みます。
+    pub fn load_near(license_cache_path: Option<&Path>) -> Result<Self> {
+        Self::load(&homepage_cache_path(license_cache_path))
+    }
+
+    /// 壊れたファイルや形式の異なるファイルは、空のキャッシュとして扱います。
+    pub fn load(path: &Path) -> Result<Self> {
+        let data = match fs::read_to_string(path) {
+            Ok(content) => serde_json::from_str(&content)
+                .ok()
+                .filter(|data: &HomepageCacheData| data.version == HOMEPAGE_CACHE_VERSION)
+                .unwrap_or_default(),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => HomepageCacheData::default(),
+            Err(err) => {
+                return Err(err).with_context(|| {
+                    format!(
+                        "ホームページキャッシュファイルの読み込みに失敗: {}",
+                        path.display()
+                    )
+                });
+            }
+        };
+        Ok(Self {
+            path: path.to_path_buf(),
+            data,
+            dirty: false,
+        })
+    }
+
+    pub fn clear(license_cache_path: Option<&Path>) -> Result<PathBuf> {
+        let path = homepage_cache_path(license_cache_path);
+        match fs::remove_file(&path) {
+            Ok(()) => Ok(path),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(path),
+            Err(err) => Err(err).with_context(|| {
+                format!(
+                    "ホームページキャッシュファイルの削除に失敗: {}",
+                    path.display()
+                )
+            }),
+        }
+    }
+
+    /// 期限内に確認したURLなら、その結果（リンク切れの理由、到達できた場合は`None`）を返します。
+    pub fn get(&self, url: &str) -> Option<Option<String>> {
+        let entry = self.data.entries.get(url)?;
+        (now_secs().saturating_sub(entry.checked_at) <= HOMEPAGE_CACHE_TTL.as_secs())
+            .then(|| entry.broken.clone())
+    }
+
+    pub fn insert(&mut self, url: &str, broken: Option<String>) {
+        let entry = HomepageCacheEntry {
+            broken,
+            checked_at: now_secs(),
+        };
+        self.data.entries.insert(url.to_string(), entry);
+        self.dirty = true;
+    }
+
+    /// 期限切れのエントリを取り除いて書き出します。
+    pub fn save(&mut self) -> Result<()> {
+        if !self.dirty {
+            return Ok(());
+        }
+        let now = now_secs();
+        self.data.entries.retain(|_, entry| {
+            now.saturating_sub(entry.checked_at) <= HOMEPAGE_CACHE_TTL.as_secs()
+        });
+        if let Some(parent) = self.path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            fs::create_dir_all(parent).with_context(|| {
+                format!("キャッシュディレクトリの作成に失敗: {}", parent.display())
+            })?;
+        }
+        let json = serde_json::to_string_pretty(&self.data)
+            .context("ホームページキャッシュのJSON化に失敗しました")?;
+        fs::write(&self.path, json).with_context(|| {
+            format!(
+                "ホームページキャッシュファイルの書き込みに失敗: {}",
+                self.path.display()
+            )
+        })?;
+        self.dirty = false;
+        Ok(())
+    }
+}
+
+fn homepage_cache_path(license_cache_path: Option<&Path>) -> PathBuf {
+    license_cache_path
+        .map_or_else(cache::default_cache_path, Path::to_path_buf)
+        .with_file_name(HOMEPAGE_CACHE_FILE)
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+/// キャッシュに期限内の結果があるURLはそれを使い、残りだけにリクエストを送ります。
+pub fn check_homepages(
+    records: &[DependencyRecord],
+    progress: Option<&ProgressBar>,
+    cache: &mut HomepageCache,
+    options: &HomepageCheckOptions,
+) -> Result<Vec<BrokenHomepage>> {
+    let mut packages_by_url: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    for record in records {
+        if let Some(url) = record.homepage.as_deref() {
+            let label = format!("{}({})", record.name, record.manager);
+            let packages = packages_by_url.entry(url).or_default();
+            if !packages.contains(&label) {
+                packages.push(label);
+            }
+        }
+    }
+
+    let mut broken = Vec::new();
+    packages_by_url.retain(|url, packages| match cache.get(url) {
+        Some(reason) => {
+            if let Some(reason) = reason {
+                broken.push(BrokenHomepage {
+                    url: url.to_string(),
+                    reason,
+                    packages: std::mem::take(packages),
+                });
+            }
+            false
+        }
+        None => true,
+    });
+    if packages_by_url.is_empty() {
+        broken.sort_by(|a, b| a.url.cmp(&b.url));
+        return Ok(broken);
+    }
+
+    let client = Client::builder()
+        .user_agent(
+            options
+                .user_agent
+                .as_deref()
+                .unwrap_or(metadata::DEFAULT_USER_AGENT),
+        )
+        .timeout(options.request_timeout.unwrap_or(DEFAULT_TIMEOUT))
+        .build()
+        .context("HTTPクライアントの初期化に失敗しました")?;
+    let rate_limiter = RateLimiter::new(options.rate_limit);
+
+    let total = packages_by_url.len();
+    let workers = options.concurrency.clamp(1, total);
+    let queue = Mutex::new(packages_by_url.into_iter());
+    let (sender, receiver) = mpsc::channel();
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            let sender = sender.clone();
+            let (queue, client, rate_limiter) = (&queue, &client, &rate_limiter);
+            scope.spawn(move || {
+                loop {
+                    let next = queue.lock().ok().and_then(|mut urls| urls.next());
+                    let Some((url, packages)) = next else {
+                        break;
+                    };
+                    if let Some(limiter) = rate_limiter {
+                        limiter.acquire();
+                    }
+                    let reason = probe_homepage(client, url);
+                    if sender.send((url, packages, reason)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(sender);
+
+        for (done, (url, packages, reason)) in receiver.into_iter().enumerate() {
+            if let Some(pb) = progress {
+                pb.set_message(format!("ホームページを確認中... ({}/{total})", done + 1));
+            }
+            logging::progress("homepage", done + 1, total);
+            cache.insert(url, reason.clone());
+            if let Some(reason) = reason {
+                broken.push(BrokenHomepage {
+                    url: url.to_string(),
+                    reason,
+                    packages,
+                });
+            }
+        }
+    });
+
+    broken.sort_by(|a, b| a.url.cmp(&b.url));
+    Ok(broken)
+}
+
+/// ホームページを持つ依存に到達できたかどうか（`homepage_reachable`）を記録します。
+pub fn mark_reachability(records: &mut [DependencyRecord], broken: &[BrokenHomepage]) {
+    let broken: HashSet<&str> = broken.iter().map(|entry| entry.url.as_str()).collect();
+    for record in records.iter_mut() {
+        record.homepage_reachable = record.homepage.as_deref().map(|url| !broken.contains(url));
+    }
+}
+
+fn probe_homepage(client: &Client, url: &str) -> Option<String> {
+    let status = match client.head(url).send() {
+        Ok(response) => response.status(),
+        Err(err) => return Some(format!("接続失敗: {err}")),
+    };
+
+    let status = if status == StatusCode::METHOD_NOT_ALLOWED {
+        match client.get(url).send() {
+            Ok(response) => response.status(),
+            Err(err) => return Some(format!("接続失敗: {err}")),
+        }
+    } else {
+        status
+    };
+
+    (status.is_client_error() || status.is_server_error()).then(|| status.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::Arc;
+
+    use super::*;
+
+    #[test]
+    fn unreachable_homepages_are_reported_and_marked() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let user_agents_log = Arc::new(Mutex::new(Vec::new()));
+        let log = Arc::clone(&user_agents_log);
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut line = String::new();
+                while reader.read_line(&mut line).unwrap() > 2 {
+                    if let Some((name, value)) = line.split_once(':')
+                        && name.eq_ignore_ascii_case("user-agent")
+                    {
+                        log.lock().unwrap().push(value.trim().to_string());
+                    }
+                    line.clear();
+                }
+                let status = if request_line.contains("/gone ") {
+                    "404 Not Found"
+                } else {
+                    "200 OK"
+                };
+                write!(
+                    stream,
+                    "HTTP/1.1 {status}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                )
+                .unwrap();
+            }
+        });
+
+        let record = |name: &str, homepage: Option<String>| DependencyRecord {
+            manager: "npm".to_string(),
+            name: name.to_string(),
+            homepage,
+            ..Default::default()
+        };
+        let mut records = vec![
+            record("alive", Some(format!("{base}/alive"))),
+            record("dead", Some(format!("{base}/gone"))),
+            record("also-dead", Some(format!("{base}/gone"))),
+            record("none", None),
+        ];
+        let options = HomepageCheckOptions {
+            concurrency: 4,
+            user_agent: Some("acme-audit/1.0 (oss@example.com)".to_string()),
+            ..Default::default()
+        };
+
+        let dir = tempfile::tempdir().unwrap();
+        let cache_path = dir.path().join(HOMEPAGE_CACHE_FILE);
+        let mut cache = HomepageCache::load(&cache_path).unwrap();
+        let broken = check_homepages(&records, None, &mut cache, &options).unwrap();
+        assert_eq!(broken.len(), 1);
+        assert_eq!(broken[0].url, format!("{base}/gone"));
+        assert_eq!(broken[0].reason, "404 Not Found");
+        assert_eq!(broken[0].packages, vec!["dead(npm)", "also-dead(npm)"]);
+        let user_agents = user_agents_log.lock().unwrap();
+        assert!(!user_agents.is_empty());
+        assert!(
+            user_agents
+                .iter()
+                .all(|ua| ua == "acme-audit/1.0 (oss@example.com)")
+        );
+
+        let requests = user_agents.len();
+        drop(user_agents);
+
+        // 2回目はキャッシュの結果を使い、リクエストを送らない
+        cache.save().unwrap();
+        let mut cache = HomepageCache::load(&cache_path).unwrap();
+        let cached = check_homepages(&records, None, &mut cache, &options).unwrap();
+        assert_eq!(user_agents_log.lock().unwrap().len(), requests);
+        assert_eq!(cached.len(), 1);
+        assert_eq!(cached[0].reason, "404 Not Found");
+        assert_eq!(cached[0].packages, vec!["dead(npm)", "also-dead(npm)"]);
+
+        mark_reachability(&mut records, &broken);
+        let reachable: Vec<Option<bool>> = records.iter().map(|r| r.homepage_reachable).collect();
+        assert_eq!(reachable, vec![Some(true), Some(false), Some(false), None]);
+    }
+
+    #[test]
+    fn cached_results_expire_after_the_ttl() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut cache = HomepageCache::load(&dir.path().join(HOMEPAGE_CACHE_FILE)).unwrap();
+        cache.insert("https://example.com", None);
+        cache.insert(
+            "https://example.com/gone",
+            Some("404 Not Found".to_string()),
+        );
+        assert_eq!(cache.get("https://example.com"), Some(None));
+        assert_eq!(
+            cache.get("https://example.com/gone"),
+            Some(Some("404 Not Found".to_string()))
+        );
+
+        if let Some(entry) = cache.data.entries.get_mut("https://example.com") {
+            entry.checked_at = now_secs() - HOMEPAGE_CACHE_TTL.as_secs() - 1;
+        }
+        assert_eq!(cache.get("https://example.com"), None);
+    }
+}
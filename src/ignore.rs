@@ -0,0 +1,132 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+use glob::{MatchOptions, Pattern};
+
+use crate::scan::package_key;
+use crate::types::DependencyRecord;
+
+/// 作業ディレクトリで自動的に読み込む除外設定ファイル名。
+pub const DEFAULT_IGNORE_FILE: &str = ".license-scout-ignore";
+
+/// レポートから除外するパッケージの一覧（1行に1つの`manager:name`パターン）。
+#[derive(Debug, Default)]
+pub struct IgnoreList {
+    entries: Vec<(String, Pattern)>,
+}
+
+impl IgnoreList {
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path).with_context(|| {
+            format!("除外設定ファイルを読み込めませんでした: {}", path.display())
+        })?;
+        Self::parse(&text).with_context(|| format!("{}の解析に失敗しました", path.display()))
+    }
+
+    /// `#`以降はコメント、空行は無視します。名前には`*`などのglobを使えます。
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut entries = Vec::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            entries
+                .push(parse_package_pattern(line).with_context(|| format!("{}行目", index + 1))?);
+        }
+        Ok(Self { entries })
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn matches(&self, record: &DependencyRecord) -> bool {
+        self.entries
+            .iter()
+            .any(|(manager, pattern)| matches_package_pattern(manager, pattern, record))
+    }
+
+    /// 一致する依存を取り除き、取り除いた件数を返します。
+    pub fn apply(&self, records: &mut Vec<DependencyRecord>) -> usize {
+        let before = records.len();
+        records.retain(|record| !self.matches(record));
+        before - records.len()
+    }
+}
+
+/// `manager:name`形式のパターンを解析します。名前はpipなら正規化、それ以外は小文字にそろえます。
+pub(crate) fn parse_package_pattern(text: &str) -> Result<(String, Pattern)> {
+    let Some((manager, name)) = text.split_once(':') else {
+        bail!("manager:name 形式で指定してください: {text}");
+    };
+    let (manager, name) = (manager.trim(), name.trim());
+    if manager.is_empty() || name.is_empty() {
+        bail!("manager:name 形式で指定してください: {text}");
+    }
+    let manager = manager.to_ascii_lowercase();
+    let name = package_key(&manager, name);
+    let pattern = Pattern::new(&name).with_context(|| format!("パターンが不正です: {text}"))?;
+    Ok((manager, pattern))
+}
+
+pub(crate) fn matches_package_pattern(
+    manager: &str,
+    pattern: &Pattern,
+    record: &DependencyRecord,
+) -> bool {
+    if !manager.eq_ignore_ascii_case(&record.manager) {
+        return false;
+    }
+    let name = package_key(&record.manager, &record.name);
+    let options = MatchOptions {
+        case_sensitive: true,
+        require_literal_separator: false,
+        require_literal_leading_dot: false,
+    };
+    pattern.matches_with(&name, options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(manager: &str, name: &str) -> DependencyRecord {
+        DependencyRecord {
+            manager: manager.to_string(),
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn patterns_match_manager_and_glob_name() {
+        let ignore = IgnoreList::parse(
+            "# 社内パッケージ\nnpm:@mycompany/*\n\npip:Internal_Tool  # 自社製\n",
+        )
+        .unwrap();
+        assert_eq!(ignore.len(), 2);
+
+        let mut records = vec![
+            record("npm", "@mycompany/ui"),
+            record("npm", "@mycompany/core/sub"),
+            record("npm", "react"),
+            record("pip", "internal-tool"),
+            record("cargo", "@mycompany/ui"),
+        ];
+        assert_eq!(ignore.apply(&mut records), 3);
+        let names: Vec<_> = records
+            .iter()
+            .map(|r| format!("{}:{}", r.manager, r.name))
+            .collect();
+        assert_eq!(names, ["npm:react", "cargo:@mycompany/ui"]);
+
+        assert!(IgnoreList::parse("requests").is_err());
+        assert!(IgnoreList::parse("npm:").is_err());
+    }
+}
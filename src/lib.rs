@@ -0,0 +1,90 @@
+pub mod archive;
+pub mod assertion;
+pub mod cache;
+pub mod compat;
+pub mod diff;
+pub mod git;
+pub mod guess;
+pub mod homepage;
+pub mod ignore;
+pub mod logging;
+pub mod metadata;
+pub mod notices;
+pub mod overrides;
+pub mod policy;
+pub mod scan;
+pub mod scan_cache;
+pub mod spdx;
+pub mod types;
+pub mod version;
+
+use std::path::Path;
+use std::thread;
+
+use anyhow::{Result, anyhow};
+
+use crate::cache::LicenseCache;
+
+pub use crate::metadata::{EnrichOptions, FetchError, enrich_metadata};
+pub use crate::scan::{ScanOptions, Scanned, collect_records, scan_records};
+pub use crate::types::{DependencyRecord, PackageMetadata, Provenance};
+
+pub fn scan_paths<P: AsRef<Path>>(
+    paths: &[P],
+    options: &ScanOptions,
+) -> Result<Vec<DependencyRecord>> {
+    let mut records = scan_paths_with_files(paths, options)?.records;
+    sort_records(&mut records);
+    Ok(records)
+}
+
+/// `scan_paths`と同様に各パスを並行して走査し、解析したファイルごとの件数も返します（並べ替えはしません）。
+pub fn scan_paths_with_files<P: AsRef<Path>>(
+    paths: &[P],
+    options: &ScanOptions,
+) -> Result<Scanned> {
+    let results: Vec<Result<Scanned>> = thread::scope(|scope| {
+        let handles: Vec<_> = paths
+            .iter()
+            .map(|path| {
+                let path = path.as_ref();
+                scope.spawn(move || scan_records(path, options))
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|_| Err(anyhow!("走査スレッドが異常終了しました")))
+            })
+            .collect()
+    });
+
+    let mut scanned = Scanned::default();
+    for result in results {
+        scanned.extend(result?);
+    }
+    Ok(scanned)
+}
+
+pub fn enrich(
+    mut records: Vec<DependencyRecord>,
+    options: &EnrichOptions,
+    cache_path: Option<&Path>,
+) -> Result<(Vec<DependencyRecord>, Vec<FetchError>)> {
+    let mut cache = LicenseCache::load(cache_path)?;
+    let failures = enrich_metadata(&mut records, None, &mut cache, options)?;
+    cache.save()?;
+    Ok((records, failures))
+}
+
+pub fn sort_records(records: &mut [DependencyRecord]) {
+    records.sort_by(|a, b| {
+        a.manager
+            .cmp(&b.manager)
+            .then(a.name.cmp(&b.name))
+            .then(a.version.cmp(&b.version))
+            .then(a.source.cmp(&b.source))
+    });
+}
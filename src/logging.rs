@@ -0,0 +1,135 @@
+use std::fmt::Display;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+
+use serde_json::Value;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    Quiet,
+    Normal,
+    Verbose,
+}
+
+static VERBOSITY: AtomicU8 = AtomicU8::new(Verbosity::Normal as u8);
+static STATUS_TO_STDERR: AtomicBool = AtomicBool::new(false);
+static PROGRESS_EVENTS: AtomicBool = AtomicBool::new(false);
+static EXPLAIN: Mutex<Option<Explanation>> = Mutex::new(None);
+
+/// `--explain`で追跡するパッケージと、その処理過程の記録。
+struct Explanation {
+    target: String,
+    steps: Vec<String>,
+}
+
+pub fn set_verbosity(verbosity: Verbosity) {
+    VERBOSITY.store(verbosity as u8, Ordering::Relaxed);
+}
+
+pub fn verbosity() -> Verbosity {
+    match VERBOSITY.load(Ordering::Relaxed) {
+        0 => Verbosity::Quiet,
+        1 => Verbosity::Normal,
+        _ => Verbosity::Verbose,
+    }
+}
+
+pub fn set_status_to_stderr(enabled: bool) {
+    STATUS_TO_STDERR.store(enabled, Ordering::Relaxed);
+}
+
+/// 進捗をJSONイベントとして標準エラー出力に書き出すかどうかを設定します。
+pub fn set_progress_events(enabled: bool) {
+    PROGRESS_EVENTS.store(enabled, Ordering::Relaxed);
+}
+
+pub fn progress_events_enabled() -> bool {
+    PROGRESS_EVENTS.load(Ordering::Relaxed)
+}
+
+/// `{"phase":"enrich","done":12,"total":300}`の形式で進捗を1行出力します。
+pub fn progress(phase: &str, done: usize, total: usize) {
+    if progress_events_enabled() {
+        eprintln!(
+            r#"{{"phase":{},"done":{done},"total":{total}}}"#,
+            Value::from(phase)
+        );
+    }
+}
+
+pub fn status(message: impl Display) {
+    if verbosity() == Verbosity::Quiet {
+        return;
+    }
+    if STATUS_TO_STDERR.load(Ordering::Relaxed) {
+        eprintln!("{message}");
+    } else {
+        println!("{message}");
+    }
+}
+
+pub fn warn(message: impl Display) {
+    if verbosity() > Verbosity::Quiet {
+        eprintln!("警告: {message}");
+    }
+}
+
+pub fn verbose(message: impl Display) {
+    if verbosity() == Verbosity::Verbose {
+        eprintln!("{message}");
+    }
+}
+
+/// `--explain`で処理過程を記録するパッケージ名を設定します。
+pub fn set_explain_target(name: Option<&str>) {
+    if let Ok(mut explain) = EXPLAIN.lock() {
+        *explain = name.map(|name| Explanation {
+            target: explain_key(name),
+            steps: Vec::new(),
+        });
+    }
+}
+
+/// `--explain`の対象なら`true`。大文字小文字と`-`・`_`・`.`の違いは無視します。
+pub fn explaining(name: &str) -> bool {
+    EXPLAIN
+        .lock()
+        .ok()
+        .and_then(|explain| {
+            explain
+                .as_ref()
+                .map(|explain| explain.target == explain_key(name))
+        })
+        .unwrap_or(false)
+}
+
+/// `--explain`の対象パッケージの処理を1行記録します。`message`は対象の場合だけ呼び出します。
+pub fn explain(name: &str, message: impl FnOnce() -> String) {
+    if !explaining(name) {
+        return;
+    }
+    let message = message();
+    if let Ok(mut explain) = EXPLAIN.lock()
+        && let Some(explain) = explain.as_mut()
+    {
+        explain.steps.push(message);
+    }
+}
+
+/// これまでに記録した処理過程を取り出します。
+pub fn take_explanation() -> Vec<String> {
+    EXPLAIN
+        .lock()
+        .ok()
+        .and_then(|mut explain| {
+            explain
+                .as_mut()
+                .map(|explain| std::mem::take(&mut explain.steps))
+        })
+        .unwrap_or_default()
+}
+
+/// `--explain`の名前を比較するためのキー。小文字にし、`_`と`.`を`-`にそろえます。
+fn explain_key(name: &str) -> String {
+    name.trim().to_ascii_lowercase().replace(['_', '.'], "-")
+}
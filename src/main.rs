@@ -1,111 +1,1259 @@
-mod cache;
 mod cli;
-mod metadata;
+mod config;
 mod output;
 mod progress;
-mod scan;
-mod types;
+mod tui;
+mod watch;
 
-use std::path::PathBuf;
+use std::io::{self, IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use anyhow::Result;
-use clap::Parser;
+use anyhow::{Context, Result, bail};
+use clap::{CommandFactory, Parser};
+use regex::{Regex, RegexBuilder};
 
-use crate::cache::LicenseCache;
-use crate::cli::Cli;
-use crate::types::DependencyRecord;
+use license_scout::cache::LicenseCache;
+use license_scout::guess::LicenseGuesses;
+use license_scout::homepage::{HomepageCache, HomepageCheckOptions};
+use license_scout::ignore::{DEFAULT_IGNORE_FILE, IgnoreList};
+use license_scout::logging::{self, Verbosity};
+use license_scout::overrides::Overrides;
+use license_scout::policy::{Policy, PolicyException};
+use license_scout::scan::ManifestKind;
+use license_scout::scan_cache::ScanCache;
+use license_scout::{
+    DependencyRecord, EnrichOptions, ScanOptions, Scanned, archive, assertion, diff, git, homepage,
+    metadata, scan,
+};
 
-fn main() -> Result<()> {
+use crate::cli::{Cli, Command, DEFAULT_CONCURRENCY};
+use crate::config::Config;
+use crate::output::{
+    ByProjectWriter, BySourceWriter, CsvWriter, CycloneDxWriter, DiffFormat, JsonWriter,
+    MarkdownWriter, NdjsonWriter, ObligationsWriter, OutputFormat, PhaseTimings, ReportFile,
+    ReportMetadata, ReportWriter, ScanCounts, SearchField, SourcePaths, TableOptions, TableWriter,
+    TopLicensesWriter, TreeWriter,
+};
+use crate::progress::ProgressFormat;
+
+/// 依存関係が1件も見つからなかった。
+const EXIT_NO_DEPENDENCIES: u8 = 2;
+/// ポリシー違反・ライセンス検証の失敗・Unknownの割合の超過。
+const EXIT_POLICY_VIOLATION: u8 = 3;
+/// 一部のパッケージでレジストリからの取得に失敗した。
+const EXIT_FETCH_FAILURE: u8 = 4;
+
+/// 標準入力から読み込んだ依存のSource列に表示する名前。
+const STDIN_SOURCE: &str = "<stdin>";
+
+/// 1回分のスキャン結果。
+struct Report {
+    exit_code: ExitCode,
+    /// 依存を検出したマニフェスト・ロックファイル（--watchの監視対象）。
+    sources: Vec<PathBuf>,
+}
+
+fn main() -> Result<ExitCode> {
     let cwd = std::env::current_dir()?;
-    let cli = Cli::parse();
-    let search_paths = resolve_search_paths(&cli.paths, &cwd);
+    // clapは引数の誤りを終了コード2で報告するため、EXIT_NO_DEPENDENCIESと区別できるよう1に揃える
+    let mut cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        Err(err) => {
+            let _ = err.print();
+            return Ok(if err.use_stderr() {
+                ExitCode::FAILURE
+            } else {
+                ExitCode::SUCCESS
+            });
+        }
+    };
+    if let Some(Command::Completions { shell }) = cli.command {
+        let mut command = Cli::command();
+        let name = command.get_name().to_string();
+        // generateは書き込みに失敗するとpanicするため、一度バッファに書き出す
+        let mut script = Vec::new();
+        clap_complete::generate(shell, &mut command, name, &mut script);
+        io::stdout()
+            .write_all(&script)
+            .context("補完スクリプトの出力に失敗しました")?;
+        return Ok(ExitCode::SUCCESS);
+    }
+    logging::set_verbosity(if cli.quiet {
+        Verbosity::Quiet
+    } else if cli.verbose {
+        Verbosity::Verbose
+    } else {
+        Verbosity::Normal
+    });
+    logging::set_status_to_stderr(
+        cli.format != OutputFormat::Table || cli.diff_format == DiffFormat::Json,
+    );
+    logging::set_progress_events(cli.progress_format == ProgressFormat::Json);
+    let no_color_env = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    output::set_color_enabled(!cli.no_color && !no_color_env && io::stdout().is_terminal());
+    if let Some((path, config)) = Config::discover(cli.config.as_deref(), &cwd)? {
+        logging::verbose(format!("設定ファイル: {}", path.display()));
+        let base_dir = path.parent().unwrap_or(&cwd).to_path_buf();
+        config.apply(&mut cli, &base_dir)?;
+    }
+    if cli.clear_cache {
+        let path = LicenseCache::clear(cli.cache_path.as_deref())?;
+        logging::status(format!("キャッシュを削除しました: {}", path.display()));
+        let path = ScanCache::clear(cli.cache_path.as_deref())?;
+        logging::status(format!("走査キャッシュを削除しました: {}", path.display()));
+        let path = HomepageCache::clear(cli.cache_path.as_deref())?;
+        logging::status(format!(
+            "ホームページキャッシュを削除しました: {}",
+            path.display()
+        ));
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if cli.list_files {
+        return list_files(&cli, &cwd);
+    }
+    if cli.stream_scan {
+        return stream_scan(&cli, &cwd);
+    }
+    if cli.manifest_report {
+        return manifest_report(&cli, &cwd);
+    }
+
+    if let Some(Command::Query { manager, package }) = &cli.command {
+        return query(&cli, manager, package);
+    }
+
+    if cli.watch {
+        watch::watch(|| run(&cli, &cwd).map(|report| report.sources))?;
+        return Ok(ExitCode::SUCCESS);
+    }
+    Ok(run(&cli, &cwd)?.exit_code)
+}
+
+fn walk_options(cli: &Cli) -> ScanOptions {
+    ScanOptions {
+        exclude: cli.exclude.clone(),
+        include: cli.include.clone(),
+        respect_gitignore: cli.respect_gitignore,
+        follow_symlinks: cli.follow_symlinks,
+        max_depth: Some(cli.max_depth),
+        format_map: cli.map.clone(),
+        ..Default::default()
+    }
+}
+
+/// 1回の走査結果から書き出すレポートファイル。
+fn report_files(cli: &Cli) -> Vec<(ReportFile, &Path)> {
+    [
+        (ReportFile::Json, cli.json_output.as_deref()),
+        (ReportFile::Ndjson, cli.ndjson.as_deref()),
+        (ReportFile::Csv, cli.csv.as_deref()),
+        (ReportFile::Markdown, cli.markdown.as_deref()),
+        (ReportFile::CycloneDx, cli.cyclonedx.as_deref()),
+    ]
+    .into_iter()
+    .filter_map(|(kind, path)| Some((kind, path?)))
+    .collect()
+}
+
+/// `--explain`の対象について、`apply`でライセンス・ホームページが変わった場合に記録します。
+fn explain_changes<T>(
+    step: &str,
+    records: &mut [DependencyRecord],
+    apply: impl FnOnce(&mut [DependencyRecord]) -> T,
+) -> T {
+    let before: Vec<Option<(String, Option<String>)>> = records
+        .iter()
+        .map(|record| {
+            logging::explaining(&record.name)
+                .then(|| (record.license.clone(), record.homepage.clone()))
+        })
+        .collect();
+    let result = apply(records);
+    for (record, before) in records.iter().zip(before) {
+        let Some((license, homepage)) = before else {
+            continue;
+        };
+        if record.license != license {
+            logging::explain(&record.name, || {
+                format!(
+                    "[{}] {step}: ライセンス {license} → {}",
+                    record.manager, record.license
+                )
+            });
+        }
+        if record.homepage != homepage {
+            logging::explain(&record.name, || {
+                format!(
+                    "[{}] {step}: ホームページ {} → {}",
+                    record.manager,
+                    homepage.as_deref().unwrap_or("-"),
+                    record.homepage.as_deref().unwrap_or("-")
+                )
+            });
+        }
+    }
+    result
+}
+
+fn enrich_options(cli: &Cli) -> EnrichOptions {
+    EnrichOptions {
+        skip_patterns: cli.fetch_skip.clone(),
+        flag_outdated: cli.flag_outdated,
+        concurrency: cli.concurrency.unwrap_or(DEFAULT_CONCURRENCY),
+        offline: cli.offline,
+        max_retries: cli.max_retries,
+        pypi_url: cli.pypi_url.clone(),
+        npm_registry: cli.npm_registry.clone(),
+        rate_limit: cli.rate_limit,
+        pool_size: cli.pool_size,
+        request_timeout: Some(Duration::from_secs(cli.request_timeout)),
+        deadline: cli.deadline.map(Duration::from_secs),
+        user_agent: cli.user_agent.clone(),
+        reconcile: cli.reconcile,
+        check_deprecations: cli.fail_on_deprecated,
+    }
+}
+
+fn load_license_cache(cli: &Cli) -> Result<LicenseCache> {
+    let cache_ttl = cli
+        .cache_ttl
+        .map(|days| Duration::from_secs(days.saturating_mul(86_400)));
+    Ok(LicenseCache::load(cli.cache_path.as_deref())?
+        .with_ttl(cache_ttl)
+        .with_since(cli.since)
+        .with_refresh_not_found(cli.refresh))
+}
+
+/// `query`サブコマンド: 走査せずに1つのパッケージの情報を取得して表示します。
+fn query(cli: &Cli, manager: &str, package: &str) -> Result<ExitCode> {
+    let mut records = vec![metadata::query_record(manager, package)?];
+    let mut cache = load_license_cache(cli)?;
+    let failures = metadata::enrich_metadata(&mut records, None, &mut cache, &enrich_options(cli))?;
+    cache.save()?;
+    for failure in &failures {
+        logging::warn(format!("ライセンス取得に失敗しました: {failure}"));
+    }
+
+    let cwd = Path::new("");
+    let options = TableOptions {
+        hide_source: true,
+        show_latest: cli.flag_outdated,
+        show_description: true,
+        show_provenance: true,
+        url_width: cli.url_width,
+        hyperlinks: !cli.no_color && output::terminal_supports_hyperlinks(),
+        width: cli.width.or_else(output::terminal_width),
+        ..Default::default()
+    };
+    let paths = SourcePaths {
+        cwd,
+        search_paths: &[],
+        home_dir: None,
+    };
+    match cli.format {
+        OutputFormat::Table => output::print_report(&TableWriter { paths, options }, &records)?,
+        OutputFormat::Json => output::print_report(
+            &JsonWriter {
+                stream: false,
+                metadata: None,
+                compact: cli.json_compact,
+            },
+            &records,
+        )?,
+        OutputFormat::Csv => output::print_report(&CsvWriter, &records)?,
+        OutputFormat::Markdown => {
+            output::print_report(&MarkdownWriter { paths, options }, &records)?
+        }
+    }
+    Ok(if failures.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(EXIT_FETCH_FAILURE)
+    })
+}
+
+/// `--list-files`: 解析対象になるマニフェスト・ロックファイルと形式を表示します（内容は解析しません）。
+fn list_files(cli: &Cli, cwd: &Path) -> Result<ExitCode> {
+    let options = walk_options(cli);
+    let mut found = 0;
+    for root in resolve_search_paths(&cli.paths, cwd)? {
+        for (path, kind) in scan::manifest_files(&root, &options)? {
+            let display = path.strip_prefix(cwd).unwrap_or(&path);
+            println!("{:<14} {}", kind.format_name(), display.display());
+            found += 1;
+        }
+    }
+    if found == 0 {
+        logging::status("解析対象のファイルは見つかりませんでした。");
+        return Ok(ExitCode::from(EXIT_NO_DEPENDENCIES));
+    }
+    logging::status(format!("> {found}件のファイル"));
+    Ok(ExitCode::SUCCESS)
+}
+
+/// `--manifest-report`: 走査で見つけたファイルごとに、実際に抽出できた依存の件数を表示します。
+fn manifest_report(cli: &Cli, cwd: &Path) -> Result<ExitCode> {
+    let options = walk_options(cli);
+    let mut files = Vec::new();
+    for root in resolve_search_paths(&cli.paths, cwd)? {
+        let mut scanned = scan::scan_records(&root, &options)?;
+        if !cli.include_self {
+            for record in scanned.records.iter().filter(|record| record.is_self) {
+                if let Some(file) = scanned
+                    .files
+                    .iter_mut()
+                    .find(|file| file.path == record.source)
+                {
+                    file.count -= 1;
+                }
+            }
+        }
+        files.extend(scanned.files);
+    }
+    if files.is_empty() {
+        logging::status("解析対象のファイルは見つかりませんでした。");
+        return Ok(ExitCode::from(EXIT_NO_DEPENDENCIES));
+    }
+    print!("{}", output::render_manifest_report(&files, cwd));
+    Ok(ExitCode::SUCCESS)
+}
 
-    let mut records = progress::with_spinner("依存関係を解析中...", |spinner| {
-        let mut acc = Vec::<DependencyRecord>::new();
-        for dir in &search_paths {
-            spinner.set_message(format!("解析中: {}", dir.display()));
-            acc.extend(scan::collect_records(dir)?);
+/// `--stream-scan`: マニフェストを解析するたびに依存を書き出します。並べ替えやレジストリからの取得、
+/// ポリシーの判定は行わず、`--search`・`--no-dev`などの絞り込みだけを適用します。
+fn stream_scan(cli: &Cli, cwd: &Path) -> Result<ExitCode> {
+    if cli.format == OutputFormat::Markdown {
+        bail!("--stream-scanはMarkdown形式に対応していません");
+    }
+    let search = cli
+        .search
+        .as_deref()
+        .map(|query| SearchQuery::new(query, cli.search_regex, cli.search_field))
+        .transpose()?;
+    let keep = |record: &DependencyRecord| {
+        (cli.include_self || !record.is_self) && matches_filters(cli, search.as_ref(), record)
+    };
+    let options = walk_options(cli);
+    let mut stdout = io::stdout().lock();
+    let mut written = 0;
+    let mut write_error = None;
+    for root in resolve_search_paths(&cli.paths, cwd)? {
+        scan::stream_records(&root, &options, |Scanned { mut records, .. }| {
+            if write_error.is_some() {
+                return;
+            }
+            records.retain(|record| keep(record));
+            if records.is_empty() {
+                return;
+            }
+            if !cli.absolute_paths {
+                output::relativize_sources(&mut records, cwd);
+            }
+            match output::write_streamed(cli.format, &records, written == 0, &mut stdout) {
+                Ok(()) => written += records.len(),
+                Err(err) => write_error = Some(err),
+            }
+        })?;
+        if let Some(err) = write_error {
+            return Err(err);
+        }
+    }
+    if written == 0 {
+        logging::status("依存関係は見つかりませんでした。");
+        return Ok(ExitCode::from(EXIT_NO_DEPENDENCIES));
+    }
+    logging::status(format!("> {written}件の依存"));
+    Ok(ExitCode::SUCCESS)
+}
+
+fn run(cli: &Cli, cwd: &Path) -> Result<Report> {
+    let started = Instant::now();
+    output::check_report_files(
+        &report_files(cli),
+        (cli.format != OutputFormat::Table).then_some("--format"),
+    )?;
+    logging::set_explain_target(cli.explain.as_deref());
+    let search = cli
+        .search
+        .as_deref()
+        .map(|query| SearchQuery::new(query, cli.search_regex, cli.search_field))
+        .transpose()?;
+    let search_paths = if cli.paths.is_empty()
+        && (!cli.git_show.is_empty()
+            || !cli.archive.is_empty()
+            || cli.git.is_some()
+            || cli.stdin.is_some())
+    {
+        Vec::new()
+    } else {
+        resolve_search_paths(&cli.paths, cwd)?
+    };
+    let mut baseline = cli
+        .baseline
+        .as_deref()
+        .map(|path| diff::load_baseline(&cwd.join(path)))
+        .transpose()?;
+    // 以前のバージョンで保存したベースラインは絶対パスのソースを含むため、今回の出力に揃える
+    if !cli.absolute_paths
+        && let Some(baseline) = baseline.as_mut()
+    {
+        output::relativize_sources(baseline, cwd);
+    }
+
+    let scan_cache = if cli.no_scan_cache {
+        None
+    } else {
+        Some(Arc::new(ScanCache::load_near(cli.cache_path.as_deref())?))
+    };
+    let scan_options = ScanOptions {
+        scan_cache: scan_cache.clone(),
+        ..walk_options(cli)
+    };
+    let scanned = progress::with_spinner("依存関係を解析中...", |spinner| {
+        if !search_paths.is_empty() {
+            let in_flight = search_paths
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            spinner.set_message(format!("解析中: {in_flight}"));
+        }
+        let mut acc = license_scout::scan_paths_with_files(&search_paths, &scan_options)?;
+        for spec in &cli.git_show {
+            spinner.set_message(format!("解析中: {spec}"));
+            acc.extend(git::scan_git_object(spec, cwd)?);
+        }
+        for archive in &cli.archive {
+            spinner.set_message(format!("解析中: {}", archive.display()));
+            acc.extend(archive::scan_archive(&cwd.join(archive), &scan_options)?);
+        }
+        if let Some(url) = cli.git.as_deref() {
+            spinner.set_message(format!("クローン中: {url}"));
+            acc.extend(git::scan_remote(
+                url,
+                cli.git_ref.as_deref(),
+                &scan_options,
+            )?);
+        }
+        if let Some(kind) = cli.stdin {
+            spinner.set_message("解析中: 標準入力");
+            let records = read_stdin_manifest(kind)?;
+            acc.extend(Scanned::single(PathBuf::from(STDIN_SOURCE), kind, records));
         }
         Ok(acc)
     })?;
+    if let Some(cache) = &scan_cache {
+        cache.save()?;
+    }
+    let Scanned {
+        mut records,
+        files: mut scanned_files,
+    } = scanned;
+    let mut sources: Vec<PathBuf> = records
+        .iter()
+        .map(|record| record.source.clone())
+        .filter(|source| source.is_file())
+        .collect();
+    sources.sort();
+    sources.dedup();
+    // ロックファイルの記述そのものではなく、解析して得たレコードを記録する
+    for record in &records {
+        logging::explain(&record.name, || {
+            format!(
+                "[{}] 解析: {}: {}",
+                record.manager,
+                record.source.display(),
+                serde_json::to_string(record).unwrap_or_default()
+            )
+        });
+    }
+    if !cli.include_self {
+        records.retain(|record| {
+            if record.is_self {
+                logging::explain(&record.name, || {
+                    format!(
+                        "[{}] プロジェクト自身のため除外しました（--include-selfで表示）",
+                        record.manager
+                    )
+                });
+            }
+            !record.is_self
+        });
+    }
+    license_scout::sort_records(&mut records);
+    let mut timings = PhaseTimings {
+        scan: started.elapsed(),
+        enrich: None,
+        output: None,
+    };
 
-    records.sort_by(|a, b| {
-        a.manager
-            .cmp(&b.manager)
-            .then(a.name.cmp(&b.name))
-            .then(a.version.cmp(&b.version))
-            .then(a.source.cmp(&b.source))
-    });
+    if cli.stat {
+        let counts = ScanCounts::from_records(&records);
+        print!("{}", output::render_stat(&counts));
+        if cli.timing {
+            eprint!("{}", output::render_timing(&timings, started.elapsed()));
+        }
+        let exit_code = if records.is_empty() {
+            ExitCode::from(EXIT_NO_DEPENDENCIES)
+        } else {
+            ExitCode::SUCCESS
+        };
+        return Ok(Report { exit_code, sources });
+    }
+
+    if cli.local_licenses {
+        let filled = explain_changes(
+            "ローカルのライセンスファイル",
+            &mut records,
+            scan::fill_licenses_from_local_files,
+        );
+        logging::status(format!(
+            "> ローカルのライセンスファイルから{filled}件を補完しました"
+        ));
+    }
 
-    if cli.fetch_licenses {
-        let mut cache = LicenseCache::load()?;
-        progress::with_spinner("ライセンス情報を取得中...", |spinner| {
-            metadata::enrich_metadata(&mut records, Some(spinner), &mut cache)
+    if let Some(path) = cli.guess_licenses.as_deref() {
+        let guesses = LicenseGuesses::load(&cwd.join(path))?;
+        let guessed = explain_changes("--guess-licenses", &mut records, |records| {
+            guesses.apply(records)
+        });
+        logging::status(format!(
+            "> ライセンス推定を{guessed}件の依存に適用しました（パターン{}件）",
+            guesses.len()
+        ));
+    }
+
+    let mut fetch_failures = Vec::new();
+    if cli.fetch_licenses || cli.flag_outdated || cli.warm_cache {
+        let enrich_started = Instant::now();
+        let options = enrich_options(cli);
+        let mut cache = load_license_cache(cli)?;
+        let cached_before = cache.len();
+        fetch_failures =
+            progress::with_progress_bar("ライセンス情報を取得中...", |bar| {
+                metadata::enrich_metadata(&mut records, Some(bar), &mut cache, &options)
+            })?;
+        cache.save()?;
+        timings.enrich = Some((enrich_started.elapsed(), cache.stats()));
+        if cli.warm_cache {
+            logging::status(format!(
+                "✔ キャッシュを更新しました: {}件を追加（合計{}件）",
+                cache.len() - cached_before,
+                cache.len()
+            ));
+        }
+        if !fetch_failures.is_empty() {
+            let details = fetch_failures
+                .iter()
+                .map(|failure| format!("\n  - {failure}"))
+                .collect::<String>();
+            logging::warn(format!(
+                "{}件のパッケージでライセンス取得に失敗しました:{details}",
+                fetch_failures.len()
+            ));
+        }
+    }
+
+    if cli.warm_cache {
+        if cli.timing {
+            eprint!("{}", output::render_timing(&timings, started.elapsed()));
+        }
+        let exit_code = if !fetch_failures.is_empty() {
+            ExitCode::from(EXIT_FETCH_FAILURE)
+        } else if records.is_empty() {
+            ExitCode::from(EXIT_NO_DEPENDENCIES)
+        } else {
+            ExitCode::SUCCESS
+        };
+        return Ok(Report { exit_code, sources });
+    }
+
+    if let Some(path) = cli.overrides.as_deref() {
+        let overrides = Overrides::load(&cwd.join(path))?;
+        let applied = explain_changes("--overrides", &mut records, |records| {
+            overrides.apply(records)
+        });
+        logging::status(format!(
+            "> 上書き設定を{applied}件の依存に適用しました（設定{}件）",
+            overrides.len()
+        ));
+    }
+
+    let ignore_path = match cli.ignore_file.as_deref() {
+        Some(path) => Some(cwd.join(path)),
+        None => Some(cwd.join(DEFAULT_IGNORE_FILE)).filter(|path| path.is_file()),
+    };
+    if let Some(path) = ignore_path {
+        let ignore = IgnoreList::load(&path)?;
+        let explained = records
+            .iter()
+            .filter(|record| logging::explaining(&record.name))
+            .count();
+        let removed = ignore.apply(&mut records);
+        logging::status(format!(
+            "> 除外設定により{removed}件の依存を除外しました（パターン{}件）",
+            ignore.len()
+        ));
+        let kept = records
+            .iter()
+            .filter(|record| logging::explaining(&record.name))
+            .count();
+        if let Some(name) = cli.explain.as_deref()
+            && kept < explained
+        {
+            logging::explain(name, || {
+                format!(
+                    "除外設定（{}）により{}件を除外しました",
+                    path.display(),
+                    explained - kept
+                )
+            });
+        }
+    }
+
+    if let Some(name) = cli.explain.as_deref() {
+        let matched: Vec<&DependencyRecord> = records
+            .iter()
+            .filter(|record| logging::explaining(&record.name))
+            .collect();
+        output::print_explanation(name, &logging::take_explanation(), &matched);
+        let exit_code = if matched.is_empty() {
+            ExitCode::from(EXIT_NO_DEPENDENCIES)
+        } else if !fetch_failures.is_empty() {
+            ExitCode::from(EXIT_FETCH_FAILURE)
+        } else {
+            ExitCode::SUCCESS
+        };
+        return Ok(Report { exit_code, sources });
+    }
+
+    // ライセンスやバージョンが確定してから並べ替える
+    output::sort_records_by(&mut records, cli.sort_by, cli.reverse);
+
+    let broken_homepages = if cli.check_homepages {
+        let options = HomepageCheckOptions {
+            concurrency: cli.concurrency.unwrap_or(DEFAULT_CONCURRENCY),
+            rate_limit: cli.rate_limit,
+            request_timeout: Some(Duration::from_secs(cli.request_timeout)),
+            user_agent: cli.user_agent.clone(),
+        };
+        let mut cache = HomepageCache::load_near(cli.cache_path.as_deref())?;
+        let broken = progress::with_spinner("ホームページを確認中...", |spinner| {
+            homepage::check_homepages(&records, Some(spinner), &mut cache, &options)
         })?;
         cache.save()?;
+        homepage::mark_reachability(&mut records, &broken);
+        Some(broken)
+    } else {
+        None
+    };
+
+    if cli.warn_name_collisions {
+        for (name, managers) in output::name_collisions(&records) {
+            logging::warn(format!(
+                "同名のパッケージが複数のマネージャに存在します: {name} ({})",
+                managers.join(", ")
+            ));
+        }
+    }
+
+    if cli.version_conflicts {
+        let conflicts = output::version_conflicts(&records);
+        for conflict in &conflicts {
+            logging::warn(format!(
+                "複数のバージョンが使われています: {} ({}){}",
+                conflict.name,
+                conflict.manager,
+                output::conflict_details(&conflict.versions)
+            ));
+        }
+        logging::status(format!(
+            "> 複数のバージョンが使われているパッケージ: {}件",
+            conflicts.len()
+        ));
+    }
+
+    if cli.check_conflicts {
+        for conflict in output::license_conflicts(&records) {
+            logging::warn(format!(
+                "ソースによってライセンスが異なります: {} {} ({}){}",
+                conflict.name,
+                conflict.version.as_deref().unwrap_or("-"),
+                conflict.manager,
+                output::conflict_details(&conflict.licenses)
+            ));
+        }
     }
 
-    if let Some(query) = cli.search.as_deref() {
-        let needle = query.to_ascii_lowercase();
+    let policy = Policy {
+        deny: cli.deny.clone(),
+        allow_only: cli.allow_only.clone(),
+        deny_unknown: cli.deny_unknown,
+        severities: cli.severity.clone(),
+        exceptions: match cli.exceptions_file.as_deref() {
+            Some(path) => PolicyException::load(&cwd.join(path))?,
+            None => Vec::new(),
+        },
+        min_versions: cli.min_version.clone(),
+    };
+    policy.annotate(&mut records);
+    if let Some(project) = &cli.project_license {
+        project.annotate(&mut records);
+    }
+    let violations = policy.evaluate(&records);
+    let assertion_failures = assertion::check_assertions(&records, &cli.assert_license);
+    let deprecated: Vec<String> = records
+        .iter()
+        .filter_map(|record| {
+            let reason = record.deprecated.as_deref()?;
+            Some(format!(
+                "{} {} ({}): {reason}",
+                record.name,
+                record.version.as_deref().unwrap_or("-"),
+                record.manager
+            ))
+        })
+        .collect();
+    let scan_counts = ScanCounts::from_records(&records);
+
+    if let (Some(query), Some(search)) = (cli.search.as_deref(), &search) {
         let before = records.len();
-        records.retain(|record| record_matches_query(record, &needle));
-        println!(
+        records.retain(|record| search.matches(record));
+        logging::status(format!(
             "> 検索クエリ \"{query}\" を適用: {before}件 -> {}件",
             records.len()
-        );
-        if records.is_empty() {
-            println!("指定の検索条件に一致する依存関係はありません。");
+        ));
+    }
+    if !cli.manager.is_empty() || !cli.license.is_empty() {
+        let before = records.len();
+        records.retain(|record| {
+            matches_any(&cli.manager, &record.manager) && matches_any(&cli.license, &record.license)
+        });
+        logging::status(format!(
+            "> マネージャ・ライセンスで絞り込み: {before}件 -> {}件",
+            records.len()
+        ));
+    }
+    if cli.direct_only {
+        let before = records.len();
+        records.retain(|record| record.direct);
+        logging::status(format!(
+            "> 直接依存のみに絞り込み: {before}件 -> {}件",
+            records.len()
+        ));
+    }
+    if cli.no_dev {
+        let before = records.len();
+        records.retain(|record| !record.dev);
+        logging::status(format!(
+            "> 開発用依存を除外: {before}件 -> {}件",
+            records.len()
+        ));
+    }
+    if cli.no_optional {
+        let before = records.len();
+        records.retain(|record| !record.optional);
+        logging::status(format!(
+            "> 任意依存を除外: {before}件 -> {}件",
+            records.len()
+        ));
+    }
+    if cli.no_peer {
+        let before = records.len();
+        records.retain(|record| !record.peer);
+        logging::status(format!(
+            "> peer依存を除外: {before}件 -> {}件",
+            records.len()
+        ));
+    }
+    let filtered = cli.search.is_some()
+        || !cli.manager.is_empty()
+        || !cli.license.is_empty()
+        || cli.direct_only
+        || cli.no_dev
+        || cli.no_optional
+        || cli.no_peer;
+    if filtered && records.is_empty() {
+        logging::status("指定の検索条件に一致する依存関係はありません。");
+    }
+    // 絞り込んだ依存が削除として表示されないよう、ベースラインにも同じ条件を適用する
+    if filtered && let Some(baseline) = baseline.as_mut() {
+        baseline.retain(|record| matches_filters(cli, search.as_ref(), record));
+    }
+
+    let dependency_diff = baseline
+        .as_deref()
+        .map(|baseline| diff::diff_records(baseline, &records));
+
+    if !cli.show_provenance {
+        for record in records.iter_mut() {
+            record.provenance = None;
         }
     }
+    if !cli.absolute_paths {
+        output::relativize_sources(&mut records, cwd);
+        output::relativize_scanned_files(&mut scanned_files, cwd);
+    }
 
     let home_dir = dirs::home_dir();
-    println!("> レポートを出力中...");
-    output::print_table(
-        &records,
-        &cwd,
-        &search_paths,
-        home_dir.as_deref(),
-        cli.hide_source,
-    )?;
-    output::output_json(&records, cli.print_json, cli.json_output.as_deref())?;
-    println!("✔ レポート出力完了");
+    let table_options = TableOptions {
+        hide_source: cli.hide_source,
+        show_latest: cli.flag_outdated,
+        show_direct: cli.show_direct,
+        show_dev: cli.show_dev,
+        show_description: cli.show_description,
+        show_severity: !cli.severity.is_empty(),
+        show_compat: cli.project_license.is_some(),
+        show_provenance: cli.show_provenance,
+        group_by: cli.group_by,
+        url_width: cli.url_width,
+        hyperlinks: !cli.no_color && output::terminal_supports_hyperlinks(),
+        width: cli.width.or_else(output::terminal_width),
+    };
+    logging::status("> レポートを出力中...");
+    let output_started = Instant::now();
+    let source_paths = SourcePaths {
+        cwd,
+        search_paths: &search_paths,
+        home_dir: home_dir.as_deref(),
+    };
+    let report_metadata = cli
+        .with_metadata
+        .then(|| ReportMetadata::new(report_paths(cli, cwd, &search_paths), scanned_files.clone()));
+    let markdown = MarkdownWriter {
+        paths: source_paths,
+        options: table_options,
+    };
+    let json_writer = JsonWriter {
+        stream: cli.stream,
+        metadata: report_metadata.as_ref(),
+        compact: cli.json_compact,
+    };
+    if let Some(dependency_diff) = &dependency_diff
+        && cli.diff_format == DiffFormat::Json
+    {
+        output::print_diff(dependency_diff, DiffFormat::Json)?;
+    } else {
+        match cli.format {
+            OutputFormat::Table => {
+                if cli.tui {
+                    tui::browse(&records)?;
+                } else {
+                    let writer: Box<dyn ReportWriter> = if cli.obligations {
+                        Box::new(ObligationsWriter)
+                    } else if let Some(limit) = cli.top_licenses {
+                        Box::new(TopLicensesWriter {
+                            limit: limit as usize,
+                        })
+                    } else if cli.by_project {
+                        Box::new(ByProjectWriter {
+                            paths: source_paths,
+                        })
+                    } else if cli.tree {
+                        Box::new(TreeWriter {
+                            paths: source_paths,
+                        })
+                    } else if cli.by_source {
+                        Box::new(BySourceWriter {
+                            paths: source_paths,
+                        })
+                    } else {
+                        Box::new(TableWriter {
+                            paths: source_paths,
+                            options: table_options,
+                        })
+                    };
+                    output::print_report(writer.as_ref(), &records)?;
+                }
+                if cli.summary {
+                    output::print_summary(&records);
+                }
+                if let Some(broken) = &broken_homepages {
+                    output::print_broken_homepages(broken, cli.url_width);
+                }
+                if let Some(dependency_diff) = &dependency_diff {
+                    output::print_diff(dependency_diff, DiffFormat::Text)?;
+                }
+            }
+            OutputFormat::Json => output::print_report(&json_writer, &records)?,
+            OutputFormat::Csv => output::print_report(&CsvWriter, &records)?,
+            OutputFormat::Markdown => output::print_report(&markdown, &records)?,
+        }
+    }
+    for (kind, path) in report_files(cli) {
+        let writer: &dyn ReportWriter = match kind {
+            ReportFile::Json => &json_writer,
+            ReportFile::Ndjson => &NdjsonWriter,
+            ReportFile::Csv => &CsvWriter,
+            ReportFile::Markdown => &markdown,
+            ReportFile::CycloneDx => &CycloneDxWriter,
+        };
+        output::save_report(writer, &records, path)?;
+    }
+    if cli.print_json && cli.format == OutputFormat::Table {
+        logging::status("JSON出力:");
+        output::print_report(&json_writer, &records)?;
+    }
+    if let Some(path) = cli.policy_report.as_deref() {
+        output::output_policy_report(&violations, path)?;
+    }
+    if let (Some(dependency_diff), Some(path)) = (&dependency_diff, cli.diff_json.as_deref()) {
+        output::output_diff_json(dependency_diff, path)?;
+    }
+    if cli.fetch_license_texts || cli.notices.is_some() {
+        output::output_notices(&records, cli.notices.as_deref())?;
+    }
+    timings.output = Some(output_started.elapsed());
+    if let Some(path) = cli.scanned_files.as_deref() {
+        output::output_scanned_files(&scanned_files, path)?;
+    }
+    logging::status("✔ レポート出力完了");
+    logging::status(scan_counts.footer(
+        report_paths(cli, cwd, &search_paths).len(),
+        started.elapsed(),
+    ));
+    if cli.timing {
+        eprint!("{}", output::render_timing(&timings, started.elapsed()));
+    }
+
+    let mut failed = false;
+    if !assertion_failures.is_empty() {
+        eprintln!(
+            "ライセンスの検証に失敗しました ({}件)",
+            assertion_failures.len()
+        );
+        for failure in &assertion_failures {
+            eprintln!("  - {failure}");
+        }
+        failed = true;
+    }
+
+    if let Some(threshold) = cli.fail_on_unknown {
+        let percent = scan_counts.unknown_percent();
+        if percent > threshold {
+            eprintln!(
+                "ライセンス不明の依存が閾値を超えています: {}件 / {}件 ({percent:.1}% > {threshold}%)",
+                scan_counts.unknown, scan_counts.total
+            );
+            failed = true;
+        }
+    }
+
+    if cli.fail_on_deprecated && !deprecated.is_empty() {
+        eprintln!(
+            "非推奨・取り下げ済みの依存が含まれています ({}件)",
+            deprecated.len()
+        );
+        for entry in &deprecated {
+            eprintln!("  - {entry}");
+        }
+        failed = true;
+    }
+
+    output::print_severity_notes(&records);
+    if cli.reconcile {
+        output::print_license_discrepancies(&records);
+    }
+    if cli.validate_spdx {
+        output::print_non_spdx(&records);
+    }
+    if let Some(project) = &cli.project_license
+        && output::print_incompatible(&records, project) > 0
+        && cli.fail_on_incompatible
+    {
+        failed = true;
+    }
+    if !violations.is_empty() {
+        output::print_violations(&violations);
+        failed |= violations.iter().any(|violation| !violation.is_approved());
+    }
+
+    let exit_code = if failed {
+        ExitCode::from(EXIT_POLICY_VIOLATION)
+    } else if !fetch_failures.is_empty() {
+        ExitCode::from(EXIT_FETCH_FAILURE)
+    } else if scan_counts.total == 0 {
+        ExitCode::from(EXIT_NO_DEPENDENCIES)
+    } else {
+        ExitCode::SUCCESS
+    };
+    Ok(Report { exit_code, sources })
+}
+
+fn read_stdin_manifest(kind: ManifestKind) -> Result<Vec<DependencyRecord>> {
+    let mut content = String::new();
+    io::stdin()
+        .read_to_string(&mut content)
+        .context("標準入力の読み込みに失敗しました")?;
+    scan::parse_manifest(kind, &content, Path::new(STDIN_SOURCE))
+        .with_context(|| format!("{}の解析に失敗: 標準入力", kind.label()))
+}
 
-    Ok(())
+/// `--with-metadata`に記録する解析対象（ディレクトリ・`--git-show`・`--archive`・`--git`・標準入力）。
+fn report_paths(cli: &Cli, cwd: &Path, search_paths: &[PathBuf]) -> Vec<String> {
+    let mut paths: Vec<String> = search_paths
+        .iter()
+        .map(|path| {
+            let relative = path.strip_prefix(cwd).ok().filter(|_| !cli.absolute_paths);
+            match relative {
+                Some(relative) if relative.as_os_str().is_empty() => ".".to_string(),
+                Some(relative) => relative.display().to_string(),
+                None => path.display().to_string(),
+            }
+        })
+        .collect();
+    paths.extend(cli.git_show.iter().cloned());
+    paths.extend(cli.archive.iter().map(|path| path.display().to_string()));
+    if let Some(url) = &cli.git {
+        paths.push(match &cli.git_ref {
+            Some(git_ref) => format!("{url}#{git_ref}"),
+            None => url.clone(),
+        });
+    }
+    if cli.stdin.is_some() {
+        paths.push("-".to_string());
+    }
+    paths
 }
 
-fn resolve_search_paths(paths: &[PathBuf], cwd: &std::path::Path) -> Vec<PathBuf> {
+/// `~`をホームディレクトリに展開し、`*`・`?`・`[`を含むパスはglobとして一致するディレクトリ・マニフェストに展開します。
+fn resolve_search_paths(paths: &[PathBuf], cwd: &Path) -> Result<Vec<PathBuf>> {
     if paths.is_empty() {
-        vec![cwd.to_path_buf()]
-    } else {
-        paths
+        return Ok(vec![cwd.to_path_buf()]);
+    }
+    let mut resolved = Vec::new();
+    for path in paths {
+        let path = expand_home(path);
+        let text = path.to_string_lossy();
+        if !text.contains(['*', '?', '[']) {
+            resolved.push(if path.is_relative() {
+                cwd.join(&path)
+            } else {
+                path.clone()
+            });
+            continue;
+        }
+        let pattern = if path.is_relative() {
+            format!("{}/{text}", glob::Pattern::escape(&cwd.to_string_lossy()))
+        } else {
+            text.to_string()
+        };
+        let matches: Vec<PathBuf> = glob::glob(&pattern)
+            .with_context(|| format!("--pathのパターンが不正です: {text}"))?
+            .filter_map(Result::ok)
+            .filter(|path| path.is_dir() || ManifestKind::from_path(path).is_some())
+            .collect();
+        if matches.is_empty() {
+            bail!("--pathのパターンに一致するディレクトリがありません: {text}");
+        }
+        resolved.extend(matches);
+    }
+    Ok(resolved)
+}
+
+fn expand_home(path: &Path) -> PathBuf {
+    let Ok(rest) = path.strip_prefix("~") else {
+        return path.to_path_buf();
+    };
+    match dirs::home_dir() {
+        Some(home) => home.join(rest),
+        None => path.to_path_buf(),
+    }
+}
+
+/// `--search`の条件。正規表現は走査を始める前に一度だけコンパイルします。
+struct SearchQuery {
+    matcher: SearchMatcher,
+    field: Option<SearchField>,
+}
+
+enum SearchMatcher {
+    /// 小文字にした部分文字列
+    Substring(String),
+    Regex(Regex),
+}
+
+impl SearchQuery {
+    /// 全項目を対象にした大文字小文字を区別しない部分一致（TUIの検索）。
+    fn substring(query: &str) -> Self {
+        Self {
+            matcher: SearchMatcher::Substring(query.to_ascii_lowercase()),
+            field: None,
+        }
+    }
+
+    fn new(query: &str, regex: bool, field: Option<SearchField>) -> Result<Self> {
+        if !regex {
+            return Ok(Self {
+                field,
+                ..Self::substring(query)
+            });
+        }
+        let regex = RegexBuilder::new(query)
+            .case_insensitive(true)
+            .build()
+            .with_context(|| format!("--searchの正規表現が不正です: {query}"))?;
+        Ok(Self {
+            matcher: SearchMatcher::Regex(regex),
+            field,
+        })
+    }
+
+    fn matches(&self, record: &DependencyRecord) -> bool {
+        let version = record.version.as_deref().unwrap_or("");
+        let homepage = record.homepage.as_deref().unwrap_or("");
+        let source = record.source.display().to_string();
+
+        let targets = [
+            (SearchField::Manager, record.manager.as_str()),
+            (SearchField::Name, record.name.as_str()),
+            (SearchField::License, record.license.as_str()),
+            (SearchField::Version, version),
+            (SearchField::Homepage, homepage),
+            (SearchField::Source, source.as_str()),
+        ];
+
+        targets
             .iter()
-            .map(|p| {
-                if p.is_relative() {
-                    cwd.join(p)
-                } else {
-                    p.clone()
-                }
+            .filter(|(field, _)| self.field.is_none_or(|only| only == *field))
+            .any(|(_, value)| match &self.matcher {
+                SearchMatcher::Substring(needle) => value.to_ascii_lowercase().contains(needle),
+                SearchMatcher::Regex(regex) => regex.is_match(value),
             })
-            .collect()
     }
 }
 
-fn record_matches_query(record: &DependencyRecord, needle: &str) -> bool {
-    let version = record.version.as_deref().unwrap_or("");
-    let homepage = record.homepage.as_deref().unwrap_or("");
-    let source = record.source.display().to_string();
+/// `--search`・`--manager`・`--license`・`--direct-only`・`--no-dev`などの絞り込み条件をすべて満たすか。
+fn matches_filters(cli: &Cli, search: Option<&SearchQuery>, record: &DependencyRecord) -> bool {
+    search.is_none_or(|search| search.matches(record))
+        && matches_any(&cli.manager, &record.manager)
+        && matches_any(&cli.license, &record.license)
+        && (!cli.direct_only || record.direct)
+        && !(cli.no_dev && record.dev)
+        && !(cli.no_optional && record.optional)
+        && !(cli.no_peer && record.peer)
+}
+
+fn matches_any(filters: &[String], value: &str) -> bool {
+    filters.is_empty() || filters.iter().any(|f| f.eq_ignore_ascii_case(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
 
-    let targets = [
-        record.manager.as_str(),
-        record.name.as_str(),
-        record.license.as_str(),
-        version,
-        homepage,
-        source.as_str(),
-    ];
+    use clap_complete::Shell;
 
-    targets
-        .iter()
-        .map(|value| value.to_ascii_lowercase())
-        .any(|value| value.contains(needle))
+    use super::*;
+
+    #[test]
+    fn search_supports_regex_and_single_fields() {
+        let record = DependencyRecord {
+            manager: "npm".to_string(),
+            name: "@babel/core".to_string(),
+            license: "MIT".to_string(),
+            source: PathBuf::from("frontend/package-lock.json"),
+            ..Default::default()
+        };
+        let matches = |query: &str, regex: bool, field: Option<SearchField>| {
+            SearchQuery::new(query, regex, field)
+                .unwrap()
+                .matches(&record)
+        };
+
+        assert!(matches("BABEL", false, None));
+        assert!(matches("^@babel/", true, None));
+        assert!(!matches("^babel", true, None));
+        assert!(matches("frontend", false, None));
+        assert!(!matches("frontend", false, Some(SearchField::Name)));
+        assert!(matches("^mit$", true, Some(SearchField::License)));
+        assert!(SearchQuery::new("GPL-[23", true, None).is_err());
+    }
+
+    #[test]
+    fn completions_subcommand_coexists_with_flags() {
+        let cli = Cli::parse_from(["license-scout", "completions", "zsh"]);
+        assert!(matches!(
+            cli.command,
+            Some(Command::Completions { shell: Shell::Zsh })
+        ));
+        let cli = Cli::parse_from(["license-scout", "--path", "completions"]);
+        assert!(cli.command.is_none());
+        assert_eq!(cli.paths, vec![PathBuf::from("completions")]);
+    }
+
+    #[test]
+    fn filtered_out_baseline_records_are_not_reported_as_removed() {
+        let cli = Cli::parse_from(["license-scout", "--no-dev", "--manager", "npm"]);
+        let record = |manager: &str, name: &str, dev: bool| DependencyRecord {
+            manager: manager.to_string(),
+            name: name.to_string(),
+            license: "MIT".to_string(),
+            dev,
+            ..Default::default()
+        };
+        let mut baseline = vec![
+            record("npm", "react", false),
+            record("npm", "jest", true),
+            record("pip", "flask", false),
+        ];
+        baseline.retain(|record| matches_filters(&cli, None, record));
+        let current = vec![record("npm", "react", false)];
+
+        assert!(diff::diff_records(&baseline, &current).is_empty());
+    }
+
+    #[test]
+    fn no_peer_keeps_packages_that_are_also_regular_dependencies() {
+        let lockfile = r#"{
+            "lockfileVersion": 3,
+            "packages": {
+                "node_modules/react": { "version": "18.2.0" },
+                "node_modules/ui-kit/node_modules/react": { "version": "18.2.0", "peer": true },
+                "node_modules/react-dom": { "version": "18.2.0", "peer": true }
+            }
+        }"#;
+        let records = scan::parse_manifest_entry(
+            Path::new("package-lock.json"),
+            lockfile,
+            &ScanOptions::default(),
+        )
+        .unwrap()
+        .unwrap();
+        let cli = Cli::parse_from(["license-scout", "--no-peer"]);
+        let kept: Vec<&str> = records
+            .iter()
+            .filter(|record| matches_filters(&cli, None, record))
+            .map(|record| record.name.as_str())
+            .collect();
+        assert_eq!(kept, vec!["react"]);
+    }
+
+    #[test]
+    fn search_paths_expand_globs_and_keep_plain_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        for service in ["api", "web"] {
+            fs::create_dir_all(root.join("services").join(service)).unwrap();
+        }
+        fs::write(root.join("services").join("README.md"), "# services\n").unwrap();
+
+        let resolved = resolve_search_paths(
+            &[PathBuf::from("services/*"), PathBuf::from("missing")],
+            root,
+        )
+        .unwrap();
+        assert_eq!(
+            resolved,
+            vec![
+                root.join("services").join("api"),
+                root.join("services").join("web"),
+                root.join("missing"),
+            ]
+        );
+        assert!(resolve_search_paths(&[PathBuf::from("nothing/*")], root).is_err());
+    }
 }
@@ -1,112 +1,722 @@
-use std::collections::HashMap;
-use std::time::Duration;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::env;
+use std::fmt;
+use std::sync::{Mutex, mpsc};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use anyhow::{Context, Result, bail};
-use reqwest::{StatusCode, blocking::Client};
+use glob::Pattern;
+use reqwest::header::RETRY_AFTER;
+use reqwest::{
+    StatusCode, Url,
+    blocking::{Client, Response},
+};
 use serde::Deserialize;
 use serde_json::Value;
 use urlencoding::encode;
 
 use indicatif::ProgressBar;
 
-use crate::cache::LicenseCache;
-use crate::scan::extract_license;
-use crate::types::{DependencyRecord, PackageMetadata};
+use crate::cache::{LicenseCache, cache_key};
+use crate::logging;
+use crate::scan::{child_element, child_text, extract_license};
+use crate::spdx::{normalize_spdx, same_license};
+use crate::types::{DependencyRecord, PackageMetadata, Provenance};
+use crate::version;
+
+#[derive(Debug, Default)]
+pub struct EnrichOptions {
+    pub skip_patterns: Vec<Pattern>,
+    pub flag_outdated: bool,
+    pub concurrency: usize,
+    pub offline: bool,
+    pub max_retries: u32,
+    pub pypi_url: Option<String>,
+    pub npm_registry: Option<String>,
+    /// 1秒あたりの最大リクエスト数（全ワーカー合計）。0以下なら制限しません。
+    pub rate_limit: f64,
+    /// ホストごとに保持するアイドル接続の上限。`None`なら上限を設けず、`Some(0)`なら接続を再利用しません。
+    pub pool_size: Option<usize>,
+    /// 1リクエストあたりのタイムアウト。`None`なら10秒です。
+    pub request_timeout: Option<Duration>,
+    /// 取得処理全体の期限。過ぎた時点で未取得のパッケージは補完せずに残します。
+    pub deadline: Option<Duration>,
+    /// レジストリに送るUser-Agent。`None`なら`DEFAULT_USER_AGENT`です。
+    pub user_agent: Option<String>,
+    /// ライセンスが判明済みの依存もレジストリに問い合わせ、異なる値を`registry_license`に記録します。
+    pub reconcile: bool,
+    /// キャッシュ済みのパッケージでも、非推奨・取り下げを確認していないバージョンがあれば取得し直します
+    /// （`--fail-on-deprecated`）。`false`ならキャッシュの内容をそのまま使います。
+    pub check_deprecations: bool,
+}
+
+/// レジストリからの取得に失敗したパッケージ。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FetchError {
+    pub manager: String,
+    pub name: String,
+    pub reason: String,
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}({}): {}", self.name, self.manager, self.reason)
+    }
+}
+
+struct FetchJob {
+    manager: String,
+    name: String,
+    version: Option<String>,
+    /// 同じパッケージの依存が使うすべてのバージョン。非推奨・取り下げはバージョンごとに確認します。
+    versions: BTreeSet<String>,
+}
 
 pub fn enrich_metadata(
     records: &mut [DependencyRecord],
     progress: Option<&ProgressBar>,
     cache: &mut LicenseCache,
-) -> Result<()> {
+    options: &EnrichOptions,
+) -> Result<Vec<FetchError>> {
+    let mut failures = Vec::new();
     if records.is_empty() {
-        return Ok(());
+        return Ok(failures);
     }
 
-    let total_targets = records.iter().filter(|r| needs_metadata(r)).count();
-    if total_targets == 0 {
-        if let Some(pb) = progress {
-            pb.set_message("ライセンス情報を取得中... (0/0)");
+    // `--guess-licenses`で推定した依存は取得しても見つからないことが多く、
+    // gitなどレジストリ以外から取得した依存は同名の別パッケージと取り違えるおそれがあるため問い合わせない
+    let is_target = |record: &DependencyRecord| {
+        record.provenance != Some(Provenance::Guessed)
+            && record.origin.is_none()
+            && (options.flag_outdated || options.reconcile || needs_metadata(record))
+            && !is_skipped(record, &options.skip_patterns)
+    };
+
+    // キャッシュと同じキーで重複を除き、`Flask_SQLAlchemy`と`flask-sqlalchemy`のような表記揺れも1回だけ取得する
+    let mut resolved: HashMap<String, Option<PackageMetadata>> = HashMap::new();
+    let mut seen = HashSet::new();
+    let mut jobs = Vec::new();
+    let mut versions_by_key: HashMap<String, BTreeSet<String>> = HashMap::new();
+    for record in records.iter().filter(|r| is_target(r)) {
+        if let Some(version) = &record.version {
+            versions_by_key
+                .entry(cache_key(&record.manager, &record.name))
+                .or_default()
+                .insert(version.clone());
+        }
+    }
+    for record in records.iter().filter(|r| !is_target(r)) {
+        logging::explain(&record.name, || {
+            format!(
+                "[{}] レジストリに問い合わせません: {}",
+                record.manager,
+                not_target_reason(record, options)
+            )
+        });
+    }
+    for record in records.iter().filter(|r| is_target(r)) {
+        let key = cache_key(&record.manager, &record.name);
+        if !seen.insert(key.clone()) {
+            continue;
         }
-        return Ok(());
+        let versions = versions_by_key.remove(&key).unwrap_or_default();
+        if let Some(cached) = cache.get(&record.manager, &record.name)
+            && (options.offline
+                || ((!options.flag_outdated || cached.latest_version.is_some())
+                    && (!options.check_deprecations
+                        || checked_deprecations(&record.manager, &cached, &versions))))
+        {
+            logging::verbose(format!("キャッシュ: {}({})", record.name, record.manager));
+            logging::explain(&record.name, || {
+                format!(
+                    "[{}] キャッシュ: {}",
+                    record.manager,
+                    describe_metadata(&cached)
+                )
+            });
+            cache.record_lookup(true);
+            resolved.insert(key, Some(cached));
+            continue;
+        }
+        if cache.is_known_not_found(&record.manager, &record.name) {
+            logging::verbose(format!(
+                "キャッシュ(未登録): {}({})",
+                record.name, record.manager
+            ));
+            logging::explain(&record.name, || {
+                format!(
+                    "[{}] キャッシュ: レジストリに未登録として記録済み（--refreshで再取得）",
+                    record.manager
+                )
+            });
+            cache.record_lookup(true);
+            resolved.insert(key, None);
+            continue;
+        }
+        cache.record_lookup(false);
+        logging::explain(&record.name, || {
+            let next = if options.offline {
+                "--offlineのため取得しません"
+            } else {
+                "レジストリに問い合わせます"
+            };
+            format!("[{}] キャッシュ: 該当なし（{next}）", record.manager)
+        });
+        if options.offline {
+            continue;
+        }
+        jobs.push(FetchJob {
+            manager: record.manager.clone(),
+            name: record.name.clone(),
+            version: record.version.clone(),
+            versions,
+        });
     }
 
-    let client = Client::builder()
-        .user_agent("license-scout/0.1.0")
-        .timeout(Duration::from_secs(10))
-        .build()
-        .context("HTTPクライアントの初期化に失敗しました")?;
+    let total = resolved.len() + jobs.len();
+    let mut done = resolved.len();
+    if let Some(pb) = progress {
+        pb.set_length(total as u64);
+        pb.set_position(done as u64);
+    }
+    logging::progress(PROGRESS_PHASE, done, total);
 
-    let mut session_cache: HashMap<(String, String), Option<PackageMetadata>> = HashMap::new();
-    let mut processed = 0usize;
+    if !jobs.is_empty() {
+        let deadline = options.deadline.map(|budget| Instant::now() + budget);
+        let pypi_url = base_url(options.pypi_url.as_deref(), DEFAULT_PYPI_URL);
+        let npm_registry = base_url(options.npm_registry.as_deref(), DEFAULT_NPM_REGISTRY);
+        let tokens = [
+            registry_token(&npm_registry, DEFAULT_NPM_REGISTRY, NPM_TOKEN_VAR),
+            registry_token(&pypi_url, DEFAULT_PYPI_URL, PYPI_TOKEN_VAR),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        let registry = Registry {
+            clients: Mutex::new(HashMap::new()),
+            pool_size: options.pool_size,
+            request_timeout: options.request_timeout.unwrap_or(DEFAULT_REQUEST_TIMEOUT),
+            deadline,
+            max_retries: options.max_retries,
+            pypi_url,
+            npm_registry,
+            tokens,
+            crates_io_last_request: Mutex::new(None),
+            rate_limiter: RateLimiter::new(options.rate_limit),
+            flag_outdated: options.flag_outdated,
+            user_agent: options
+                .user_agent
+                .clone()
+                .unwrap_or_else(|| DEFAULT_USER_AGENT.to_string()),
+        };
 
-    for record in records.iter_mut() {
-        if !needs_metadata(record) {
-            continue;
-        }
+        let workers = options.concurrency.clamp(1, jobs.len());
+        let queue = Mutex::new(jobs.into_iter());
+        let (sender, receiver) = mpsc::channel();
 
-        processed += 1;
-        if let Some(pb) = progress {
-            pb.set_message(format!(
-                "ライセンス情報を取得中... ({processed}/{total_targets})"
+        thread::scope(|scope| {
+            for _ in 0..workers {
+                let sender = sender.clone();
+                let queue = &queue;
+                let registry = &registry;
+                scope.spawn(move || {
+                    loop {
+                        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                            break;
+                        }
+                        let next = queue.lock().ok().and_then(|mut jobs| jobs.next());
+                        let Some(job) = next else {
+                            break;
+                        };
+                        let fetched = fetch_metadata(registry, &job);
+                        if sender.send((job, fetched)).is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+            drop(sender);
+
+            for (job, fetched) in receiver {
+                done += 1;
+                if let Some(pb) = progress {
+                    pb.inc(1);
+                }
+                logging::progress(PROGRESS_PHASE, done, total);
+
+                let metadata = match fetched {
+                    Ok(Some(metadata)) => {
+                        logging::verbose(format!("取得: {}({})", job.name, job.manager));
+                        logging::explain(&job.name, || {
+                            format!(
+                                "[{}] レジストリ: {}",
+                                job.manager,
+                                describe_metadata(&metadata)
+                            )
+                        });
+                        cache.insert(&job.manager, &job.name, metadata.clone());
+                        Some(metadata)
+                    }
+                    Ok(None) => {
+                        logging::verbose(format!("見つかりません: {}({})", job.name, job.manager));
+                        logging::explain(&job.name, || {
+                            format!("[{}] レジストリ: 見つかりませんでした", job.manager)
+                        });
+                        cache.insert_not_found(&job.manager, &job.name);
+                        None
+                    }
+                    Err(err) => {
+                        logging::verbose(format!("失敗: {}({}): {err:#}", job.name, job.manager));
+                        logging::explain(&job.name, || {
+                            format!("[{}] レジストリ: 取得に失敗しました: {err:#}", job.manager)
+                        });
+                        failures.push(FetchError {
+                            manager: job.manager.clone(),
+                            name: job.name.clone(),
+                            reason: failure_reason(&err),
+                        });
+                        None
+                    }
+                };
+                resolved.insert(cache_key(&job.manager, &job.name), metadata);
+            }
+        });
+
+        let skipped = queue.into_inner().map_or(0, |jobs| jobs.count());
+        if skipped > 0 {
+            logging::warn(format!(
+                "取得期限（{}秒）を過ぎたため、{skipped}件のパッケージは補完しませんでした",
+                options.deadline.unwrap_or_default().as_secs_f64()
             ));
         }
+    }
 
-        let key = (record.manager.clone(), record.name.clone());
-        if let Some(cached) = session_cache.get(&key) {
-            apply_metadata(record, cached);
+    for record in records.iter_mut() {
+        if !is_target(record) {
             continue;
         }
-
-        if let Some(cached) = cache.get(&record.manager, &record.name) {
-            apply_metadata(record, &Some(cached.clone()));
-            session_cache.insert(key.clone(), Some(cached));
-            continue;
+        if let Some(metadata) = resolved.get(&cache_key(&record.manager, &record.name)) {
+            apply_metadata(record, metadata, options.reconcile);
         }
+    }
+
+    failures.sort_by(|a, b| a.manager.cmp(&b.manager).then(a.name.cmp(&b.name)));
+    Ok(failures)
+}
+
+fn failure_reason(err: &anyhow::Error) -> String {
+    let http_error = err
+        .chain()
+        .find_map(|cause| cause.downcast_ref::<reqwest::Error>());
+    match http_error {
+        Some(http) if http.is_timeout() => "タイムアウト".to_string(),
+        Some(http) if http.is_connect() => "接続エラー".to_string(),
+        _ => err.root_cause().to_string(),
+    }
+}
+
+fn fetch_metadata(registry: &Registry, job: &FetchJob) -> Result<Option<PackageMetadata>> {
+    match job.manager.as_str() {
+        "pip" => fetch_pypi_metadata(registry, job),
+        "npm" => fetch_npm_metadata(registry, job),
+        "cargo" => fetch_crates_io_metadata(registry, &job.name, job.version.as_deref()),
+        "gem" => fetch_rubygems_metadata(registry, &job.name),
+        "maven" => fetch_maven_metadata(registry, &job.name, job.version.as_deref()),
+        _ => Ok(None),
+    }
+}
 
-        let fetched = match record.manager.as_str() {
-            "pip" => fetch_pypi_metadata(&client, &record.name),
-            "npm" => fetch_npm_metadata(&client, &record.name, record.version.as_deref()),
-            _ => Ok(None),
+/// レジストリからライセンス情報を取得できるマネージャ。
+pub const REGISTRY_MANAGERS: [&str; 5] = ["pip", "npm", "cargo", "gem", "maven"];
+
+/// `license-scout query npm lodash@4.17.21`のような指定から、取得対象の依存を1件作ります。
+/// バージョンはpipなら`==`、それ以外は`@`で区切り、省略すると最新版の情報を取得します。
+pub fn query_record(manager: &str, package: &str) -> Result<DependencyRecord> {
+    let manager = manager.trim().to_ascii_lowercase();
+    if !REGISTRY_MANAGERS.contains(&manager.as_str()) {
+        bail!(
+            "レジストリから取得できないマネージャです: {manager}（{}）",
+            REGISTRY_MANAGERS.join(", ")
+        );
+    }
+    let package = package.trim();
+    let (name, version) = match package.split_once("==") {
+        Some((name, version)) if manager == "pip" => (name, Some(version)),
+        _ => match package.rsplit_once('@') {
+            // `@types/node`のようなスコープ付きパッケージの先頭の`@`は区切りではない
+            Some((name, version)) if !name.is_empty() => (name, Some(version)),
+            _ => (package, None),
+        },
+    };
+    let name = name.trim();
+    if name.is_empty() {
+        bail!("パッケージ名を指定してください: {package}");
+    }
+    Ok(DependencyRecord {
+        manager,
+        name: name.to_string(),
+        version: version
+            .map(str::trim)
+            .filter(|version| !version.is_empty())
+            .map(str::to_string),
+        license: "Unknown".to_string(),
+        ..Default::default()
+    })
+}
+
+/// `--user-agent`を指定しない場合のUser-Agent。
+pub const DEFAULT_USER_AGENT: &str = concat!(
+    "license-scout/",
+    env!("CARGO_PKG_VERSION"),
+    " (+https://github.com/KASAHARA-Kyohei/license-scout)"
+);
+const CRATES_IO_INTERVAL: Duration = Duration::from_secs(1);
+const DEFAULT_PYPI_URL: &str = "https://pypi.org";
+const DEFAULT_NPM_REGISTRY: &str = "https://registry.npmjs.org";
+const NPM_TOKEN_VAR: &str = "NPM_TOKEN";
+const PYPI_TOKEN_VAR: &str = "PYPI_TOKEN";
+const MAVEN_CENTRAL: &str = "https://repo1.maven.org/maven2";
+const MAVEN_PARENT_DEPTH: usize = 3;
+const PROGRESS_PHASE: &str = "enrich";
+const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+const POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+const TCP_KEEPALIVE: Duration = Duration::from_secs(60);
+
+struct Registry {
+    /// ホストごとのHTTPクライアント。npmとPyPIなどで接続プールを分けます。
+    clients: Mutex<HashMap<String, Client>>,
+    pool_size: Option<usize>,
+    request_timeout: Duration,
+    /// `--deadline`の期限。各リクエストのタイムアウトと再試行の待ち時間はこれを超えません。
+    deadline: Option<Instant>,
+    max_retries: u32,
+    pypi_url: String,
+    npm_registry: String,
+    /// 認証トークンと、それを送るレジストリのベースURL。
+    tokens: Vec<(String, String)>,
+    crates_io_last_request: Mutex<Option<Instant>>,
+    rate_limiter: Option<RateLimiter>,
+    /// 最新バージョンも取得します（`--flag-outdated`）。npmではdist-tagsの取得が1回増えます。
+    flag_outdated: bool,
+    user_agent: String,
+}
+
+impl Registry {
+    fn wait_for_crates_io(&self) {
+        let Ok(mut last) = self.crates_io_last_request.lock() else {
+            return;
         };
+        if let Some(previous) = *last {
+            let elapsed = previous.elapsed();
+            if elapsed < CRATES_IO_INTERVAL {
+                thread::sleep(CRATES_IO_INTERVAL - elapsed);
+            }
+        }
+        *last = Some(Instant::now());
+    }
+
+    fn client_for(&self, url: &str) -> reqwest::Result<Client> {
+        let host = Url::parse(url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_ascii_lowercase))
+            .unwrap_or_default();
+        let mut clients = self.clients.lock().unwrap_or_else(|err| err.into_inner());
+        if let Some(client) = clients.get(&host) {
+            return Ok(client.clone());
+        }
+        let client = build_client(&self.user_agent, self.pool_size, self.request_timeout)?;
+        clients.insert(host, client.clone());
+        Ok(client)
+    }
+
+    /// URLが認証付きのレジストリ配下にあれば、そのトークンを返します。
+    fn token_for(&self, url: &str) -> Option<&str> {
+        self.tokens.iter().find_map(|(base, token)| {
+            url.strip_prefix(base.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+                .then_some(token.as_str())
+        })
+    }
 
-        match fetched {
-            Ok(Some(metadata)) => {
-                apply_metadata(record, &Some(metadata.clone()));
-                cache.insert(&record.manager, &record.name, metadata.clone());
-                session_cache.insert(key, Some(metadata));
+    /// `--deadline`までの残り時間。期限が無ければ`None`です。
+    fn remaining(&self) -> Option<Duration> {
+        self.deadline
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+
+    fn get(&self, url: &str) -> reqwest::Result<Response> {
+        let client = self.client_for(url)?;
+        let token = self.token_for(url);
+        let mut attempt = 0;
+        loop {
+            if let Some(limiter) = &self.rate_limiter {
+                limiter.acquire();
             }
-            Ok(None) => {
-                session_cache.insert(key, None);
+            let mut request = client.get(url);
+            if let Some(remaining) = self.remaining() {
+                request = request.timeout(remaining.min(self.request_timeout));
             }
-            Err(err) => {
-                eprintln!(
-                    "警告: {}({})のライセンス取得に失敗しました: {err}",
-                    record.name, record.manager
-                );
-                session_cache.insert(key, None);
+            if let Some(token) = token {
+                request = request.bearer_auth(token);
             }
+            let result = request.send();
+            let retry_after = match &result {
+                Ok(response) if is_retryable_status(response.status()) => {
+                    Some(parse_retry_after(response))
+                }
+                Ok(_) => None,
+                Err(err) if err.is_timeout() || err.is_connect() || err.is_request() => Some(None),
+                Err(_) => None,
+            };
+            match retry_after {
+                Some(retry_after) if attempt < self.max_retries => {
+                    let delay = retry_delay(attempt, retry_after);
+                    // 待っている間に期限が来る場合は再試行しない
+                    if self.remaining().is_some_and(|remaining| remaining <= delay) {
+                        return result;
+                    }
+                    thread::sleep(delay);
+                    attempt += 1;
+                }
+                _ => return result,
+            }
+        }
+    }
+}
+
+fn build_client(
+    user_agent: &str,
+    pool_size: Option<usize>,
+    timeout: Duration,
+) -> reqwest::Result<Client> {
+    let mut builder = Client::builder()
+        .user_agent(user_agent)
+        .timeout(timeout)
+        .pool_idle_timeout(POOL_IDLE_TIMEOUT)
+        .tcp_keepalive(TCP_KEEPALIVE);
+    if let Some(pool_size) = pool_size {
+        builder = builder.pool_max_idle_per_host(pool_size);
+    }
+    builder.build()
+}
+
+/// 全ワーカーで共有するトークンバケット。1秒分までのバーストを許可します。
+pub(crate) struct RateLimiter {
+    per_second: f64,
+    state: Mutex<(f64, Instant)>,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(per_second: f64) -> Option<Self> {
+        (per_second > 0.0).then(|| Self {
+            per_second,
+            state: Mutex::new((per_second.max(1.0), Instant::now())),
+        })
+    }
+
+    pub(crate) fn acquire(&self) {
+        loop {
+            let wait = {
+                let Ok(mut state) = self.state.lock() else {
+                    return;
+                };
+                let (tokens, last) = &mut *state;
+                let now = Instant::now();
+                let capacity = self.per_second.max(1.0);
+                *tokens = (*tokens + now.duration_since(*last).as_secs_f64() * self.per_second)
+                    .min(capacity);
+                *last = now;
+                if *tokens >= 1.0 {
+                    *tokens -= 1.0;
+                    return;
+                }
+                Duration::from_secs_f64((1.0 - *tokens) / self.per_second)
+            };
+            thread::sleep(wait);
         }
     }
+}
+
+fn base_url(custom: Option<&str>, default: &str) -> String {
+    custom
+        .map(str::trim)
+        .filter(|url| !url.is_empty())
+        .unwrap_or(default)
+        .trim_end_matches('/')
+        .to_string()
+}
 
-    Ok(())
+/// 環境変数のトークンは、既定以外のレジストリ（`--npm-registry`・`--pypi-url`）を指定した場合だけ
+/// そのURLに送ります。公開レジストリに認証情報を送らないためです。
+fn registry_token(base: &str, default: &str, var: &str) -> Option<(String, String)> {
+    let token = env::var(var).ok()?.trim().to_string();
+    if token.is_empty() {
+        return None;
+    }
+    if base == default {
+        logging::verbose(format!(
+            "{var}が設定されていますが、既定のレジストリ（{default}）には送信しません"
+        ));
+        return None;
+    }
+    Some((base.to_string(), token))
+}
+
+fn is_retryable_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+fn parse_retry_after(response: &Response) -> Option<Duration> {
+    let status = response.status();
+    if status != StatusCode::TOO_MANY_REQUESTS && status != StatusCode::SERVICE_UNAVAILABLE {
+        return None;
+    }
+    response
+        .headers()
+        .get(RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .map(Duration::from_secs)
+}
+
+fn retry_delay(attempt: u32, retry_after: Option<Duration>) -> Duration {
+    const MAX_DELAY: Duration = Duration::from_secs(60);
+    if let Some(wait) = retry_after {
+        return wait.min(MAX_DELAY);
+    }
+    let base = Duration::from_millis(500) * 2u32.saturating_pow(attempt.min(6));
+    let jitter_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| u64::from(elapsed.subsec_nanos()) % 250)
+        .unwrap_or_default();
+    (base + Duration::from_millis(jitter_ms)).min(MAX_DELAY)
 }
 
 fn needs_metadata(record: &DependencyRecord) -> bool {
-    record.homepage.is_none()
-        || record.license.trim().is_empty()
-        || record.license.eq_ignore_ascii_case("unknown")
+    record.homepage.is_none() || record.has_unknown_license()
+}
+
+fn not_target_reason(record: &DependencyRecord, options: &EnrichOptions) -> String {
+    if record.provenance == Some(Provenance::Guessed) {
+        "--guess-licensesで推定済み".to_string()
+    } else if let Some(origin) = &record.origin {
+        format!("レジストリ以外から取得した依存（{origin}）")
+    } else if is_skipped(record, &options.skip_patterns) {
+        "--fetch-skipに一致".to_string()
+    } else {
+        "ライセンスとホームページが判明済み".to_string()
+    }
+}
+
+/// `--explain`向けに取得したメタデータを1行にまとめます。
+fn describe_metadata(metadata: &PackageMetadata) -> String {
+    format!(
+        "license={}, homepage={}, latest={}, provenance={}",
+        metadata.license.as_deref().unwrap_or("-"),
+        metadata.homepage.as_deref().unwrap_or("-"),
+        metadata.latest_version.as_deref().unwrap_or("-"),
+        metadata.provenance.map_or("-", Provenance::label)
+    )
 }
 
-fn apply_metadata(record: &mut DependencyRecord, metadata: &Option<PackageMetadata>) {
+/// `--explain`向けにレジストリの生の値と正規化後の値を記録します。
+fn explain_license_field(manager: &str, name: &str, field: &str, raw: &str) {
+    logging::explain(name, || {
+        let normalized = normalize_license_text(raw);
+        match normalized.as_deref() {
+            Some(normalized) if normalized != raw.trim() => {
+                format!("[{manager}] {field}: {raw:?} → {normalized}（SPDX IDに正規化）")
+            }
+            Some(_) => format!("[{manager}] {field}: {raw:?}"),
+            None => format!("[{manager}] {field}: {raw:?}（ライセンスとして扱わない）"),
+        }
+    });
+}
+
+fn is_skipped(record: &DependencyRecord, skip_patterns: &[Pattern]) -> bool {
+    skip_patterns
+        .iter()
+        .any(|pattern| pattern.matches(&record.name))
+}
+
+fn apply_metadata(
+    record: &mut DependencyRecord,
+    metadata: &Option<PackageMetadata>,
+    reconcile: bool,
+) {
     if let Some(meta) = metadata {
-        if should_update_license(&record.license, meta.license.as_deref()) {
-            if let Some(license) = &meta.license {
-                record.license = license.clone();
+        if should_update_license(&record.license, meta.license.as_deref())
+            && let Some(license) = &meta.license
+        {
+            record.license = license.clone();
+            record.provenance = meta
+                .provenance
+                .or_else(|| registry_provenance(&record.manager));
+            logging::explain(&record.name, || {
+                format!(
+                    "[{}] ライセンスをレジストリの値にしました: {license}",
+                    record.manager
+                )
+            });
+        } else if let Some(license) = &meta.license {
+            logging::explain(&record.name, || {
+                format!(
+                    "[{}] ロックファイルのライセンスを優先しました: {}",
+                    record.manager, record.license
+                )
+            });
+            if reconcile && !same_license(&record.license, license) {
+                record.registry_license = Some(license.clone());
+                logging::explain(&record.name, || {
+                    format!(
+                        "[{}] レジストリのライセンスはロックファイルと異なります: {license}",
+                        record.manager
+                    )
+                });
             }
         }
         if record.homepage.is_none() {
             record.homepage = meta.homepage.clone();
         }
+        if record.description.is_none() {
+            record.description = meta.description.clone();
+        }
+        if record.deprecated.is_none() {
+            record.deprecated = record
+                .version
+                .as_deref()
+                .and_then(|version| meta.deprecation(version))
+                .map(str::to_string);
+        }
+        if let (Some(latest), Some(current)) = (&meta.latest_version, &record.version)
+            && latest != current
+        {
+            record.outdated = Some(latest.clone());
+        }
+    }
+}
+
+/// pip・npmは非推奨・取り下げをバージョンごとに記録するため、キャッシュに無いバージョンを使う依存があれば取得し直します。
+fn checked_deprecations(
+    manager: &str,
+    metadata: &PackageMetadata,
+    versions: &BTreeSet<String>,
+) -> bool {
+    !matches!(manager, "pip" | "npm")
+        || versions
+            .iter()
+            .all(|version| metadata.deprecations.contains_key(version))
+}
+
+/// 取得元を記録していない古いキャッシュエントリ向けの既定値。PyPIは分類子かlicenseフィールドかを区別できないため返しません。
+fn registry_provenance(manager: &str) -> Option<Provenance> {
+    match manager {
+        "npm" => Some(Provenance::NpmRegistry),
+        "cargo" => Some(Provenance::CratesIo),
+        "gem" => Some(Provenance::Rubygems),
+        "maven" => Some(Provenance::MavenCentral),
+        _ => None,
     }
 }
 
@@ -117,10 +727,21 @@ fn should_update_license(current: &str, candidate: Option<&str>) -> bool {
 #[derive(Debug, Deserialize)]
 struct PyPiResponse {
     info: PyPiInfo,
+    #[serde(default)]
+    releases: HashMap<String, Vec<PyPiFile>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PyPiFile {
+    #[serde(default)]
+    yanked: bool,
+    yanked_reason: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
 struct PyPiInfo {
+    version: Option<String>,
+    summary: Option<String>,
     license: Option<String>,
     classifiers: Option<Vec<String>>,
     #[serde(rename = "home_page")]
@@ -129,12 +750,12 @@ struct PyPiInfo {
     project_urls: Option<HashMap<String, String>>,
 }
 
-fn fetch_pypi_metadata(client: &Client, package_name: &str) -> Result<Option<PackageMetadata>> {
+fn fetch_pypi_metadata(registry: &Registry, job: &FetchJob) -> Result<Option<PackageMetadata>> {
+    let package_name = job.name.as_str();
     let encoded = encode(package_name);
-    let url = format!("https://pypi.org/pypi/{encoded}/json");
-    let response = client
+    let url = format!("{}/pypi/{encoded}/json", registry.pypi_url);
+    let response = registry
         .get(&url)
-        .send()
         .with_context(|| format!("PyPIリクエストに失敗しました: {package_name}"))?;
 
     if response.status() == StatusCode::NOT_FOUND {
@@ -151,42 +772,110 @@ fn fetch_pypi_metadata(client: &Client, package_name: &str) -> Result<Option<Pac
     let data: PyPiResponse = response
         .json()
         .with_context(|| format!("PyPIレスポンスの解析に失敗: {package_name}"))?;
-
-    let license = data
-        .info
-        .license
-        .as_deref()
-        .and_then(normalize_license_text)
-        .or_else(|| {
+    if let Some(license) = &data.info.license {
+        explain_license_field("pip", package_name, "PyPIのlicense", license);
+    }
+    logging::explain(package_name, || {
+        let classifiers: Vec<&str> = data
+            .info
+            .classifiers
+            .iter()
+            .flatten()
+            .map(String::as_str)
+            .filter(|classifier| classifier.starts_with("License ::"))
+            .collect();
+        format!(
+            "[pip] PyPIのLicense分類子: {classifiers:?}, home_page: {:?}, project_urls: {:?}",
+            data.info.home_page,
             data.info
-                .classifiers
-                .as_ref()
-                .and_then(|c| license_from_classifiers(c))
-        });
+                .project_urls
+                .iter()
+                .flat_map(|urls| urls.keys())
+                .collect::<BTreeSet<_>>()
+        )
+    });
+
+    let (license, provenance) = pypi_license(&data.info).unzip();
 
     let homepage = extract_pypi_homepage(&data.info);
+    let latest_version = data.info.version.clone();
+    let description = data.info.summary.as_deref().and_then(normalize_description);
+    let deprecations = job
+        .versions
+        .iter()
+        .map(|version| (version.clone(), pypi_yanked_reason(&data.releases, version)))
+        .collect();
 
-    if license.is_some() || homepage.is_some() {
-        Ok(Some(PackageMetadata { license, homepage }))
+    if license.is_some() || homepage.is_some() || latest_version.is_some() {
+        Ok(Some(PackageMetadata {
+            license,
+            homepage,
+            latest_version,
+            description,
+            provenance,
+            deprecations,
+        }))
     } else {
         Ok(None)
     }
 }
 
-fn license_from_classifiers(classifiers: &[String]) -> Option<String> {
-    classifiers
-        .iter()
-        .filter_map(|classifier| {
-            if classifier.contains("License ::") {
-                classifier
-                    .split("::")
-                    .last()
-                    .map(|part| part.trim().to_string())
-            } else {
-                None
-            }
+/// リリースのすべてのファイルがyankされていれば、その理由（無ければ`yanked`）を返します。
+fn pypi_yanked_reason(releases: &HashMap<String, Vec<PyPiFile>>, version: &str) -> Option<String> {
+    let files = releases.get(version.trim_start_matches("=="))?;
+    if files.is_empty() || !files.iter().all(|file| file.yanked) {
+        return None;
+    }
+    Some(
+        files
+            .iter()
+            .find_map(|file| {
+                file.yanked_reason
+                    .as_deref()
+                    .and_then(normalize_description)
+            })
+            .unwrap_or_else(|| "yanked".to_string()),
+    )
+}
+
+fn pypi_license(info: &PyPiInfo) -> Option<(String, Provenance)> {
+    info.license
+        .as_deref()
+        .and_then(normalize_license_text)
+        .map(|license| (license, Provenance::PypiLicense))
+        .or_else(|| {
+            info.classifiers
+                .as_ref()
+                .and_then(|c| license_from_classifiers(c))
+                .map(|license| (license, Provenance::PypiClassifier))
         })
-        .find(|value| !value.is_empty())
+}
+
+/// ライセンス名を含まない分類子（`License :: OSI Approved`など）。
+const GENERIC_LICENSE_CLASSIFIERS: &[&str] =
+    &["OSI Approved", "DFSG approved", "Freely Distributable"];
+
+/// `License ::`分類子のうち、最も階層の深い（具体的な）もののライセンス名を返します。同じ深さなら先のものを優先します。
+fn license_from_classifiers(classifiers: &[String]) -> Option<String> {
+    let mut best: Option<(usize, &str)> = None;
+    for classifier in classifiers {
+        let segments: Vec<&str> = classifier.split("::").map(str::trim).collect();
+        let [first, .., name] = segments.as_slice() else {
+            continue;
+        };
+        if *first != "License"
+            || name.is_empty()
+            || GENERIC_LICENSE_CLASSIFIERS
+                .iter()
+                .any(|generic| generic.eq_ignore_ascii_case(name))
+        {
+            continue;
+        }
+        if best.is_none_or(|(depth, _)| segments.len() > depth) {
+            best = Some((segments.len(), name));
+        }
+    }
+    best.map(|(_, name)| normalize_spdx(name).unwrap_or_else(|| name.to_string()))
 }
 
 fn extract_pypi_homepage(info: &PyPiInfo) -> Option<String> {
@@ -227,25 +916,90 @@ fn normalize_homepage(url: &str) -> Option<String> {
     }
 }
 
+fn normalize_description(text: &str) -> Option<String> {
+    let collapsed = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!collapsed.is_empty()).then_some(collapsed)
+}
+
 fn normalize_license_text(text: &str) -> Option<String> {
     let trimmed = text.trim();
     if trimmed.is_empty() || trimmed.eq_ignore_ascii_case("unknown") {
         None
     } else {
-        Some(trimmed.to_string())
+        Some(normalize_spdx(trimmed).unwrap_or_else(|| trimmed.to_string()))
     }
 }
 
-fn fetch_npm_metadata(
-    client: &Client,
+/// npm Registryにはパッケージをまとめて取得するAPIがないため、バージョンが1つに確定している場合は
+/// 全バージョンを含むパッケージ文書の代わりに、そのバージョンの文書（`--flag-outdated`ではdist-tagsも）だけを取得します。
+/// ライセンスが得られない場合や取得に失敗した場合、複数のバージョンが使われている場合はパッケージ文書を取得します。
+fn fetch_npm_metadata(registry: &Registry, job: &FetchJob) -> Result<Option<PackageMetadata>> {
+    let package_name = job.name.as_str();
+    if job.versions.len() <= 1
+        && let Some(version) = job
+            .version
+            .as_deref()
+            .filter(|version| version::is_exact(version))
+    {
+        match fetch_npm_version_manifest(registry, package_name, version) {
+            Ok(Some(metadata)) => return Ok(Some(metadata)),
+            Ok(None) => {}
+            Err(err) => logging::verbose(format!(
+                "npmのバージョン別メタデータを取得できないためパッケージ全体を取得します: {package_name}@{version}: {err:#}"
+            )),
+        }
+    }
+    fetch_npm_packument(registry, job)
+}
+
+fn fetch_npm_version_manifest(
+    registry: &Registry,
     package_name: &str,
-    version: Option<&str>,
+    version: &str,
 ) -> Result<Option<PackageMetadata>> {
     let encoded = encode(package_name);
-    let url = format!("https://registry.npmjs.org/{encoded}");
-    let response = client
-        .get(&url)
-        .send()
+    let Some(manifest) = fetch_npm_json(
+        registry,
+        &format!("{}/{encoded}/{}", registry.npm_registry, encode(version)),
+        package_name,
+    )?
+    else {
+        return Ok(None);
+    };
+    let Some(metadata) = npm_manifest_metadata(&manifest).filter(|m| m.license.is_some()) else {
+        return Ok(None);
+    };
+    // dist-tagsは最新バージョンにしか使わないため、取得に失敗してもバージョンの文書の情報は捨てない
+    let dist_tags = if registry.flag_outdated {
+        fetch_npm_json(
+            registry,
+            &format!("{}/-/package/{encoded}/dist-tags", registry.npm_registry),
+            package_name,
+        )
+        .unwrap_or_else(|err| {
+            logging::verbose(format!(
+                "npmのdist-tagsを取得できませんでした: {package_name}: {err:#}"
+            ));
+            None
+        })
+    } else {
+        None
+    };
+    Ok(Some(PackageMetadata {
+        latest_version: dist_tags
+            .as_ref()
+            .and_then(|tags| tags.get("latest"))
+            .and_then(|v| v.as_str())
+            .map(|v| v.to_string()),
+        provenance: Some(Provenance::NpmRegistry),
+        deprecations: BTreeMap::from([(version.to_string(), npm_deprecated(&manifest))]),
+        ..metadata
+    }))
+}
+
+fn fetch_npm_json(registry: &Registry, url: &str, package_name: &str) -> Result<Option<Value>> {
+    let response = registry
+        .get(url)
         .with_context(|| format!("npm Registryリクエストに失敗しました: {package_name}"))?;
 
     if response.status() == StatusCode::NOT_FOUND {
@@ -259,50 +1013,457 @@ fn fetch_npm_metadata(
         );
     }
 
-    let data: Value = response
+    let data = response
         .json()
         .with_context(|| format!("npmレスポンスの解析に失敗: {package_name}"))?;
+    Ok(Some(data))
+}
 
-    if let Some(ver) = version {
-        if let Some(metadata) = lookup_npm_version_metadata(&data, ver) {
-            return Ok(Some(metadata));
-        }
+fn fetch_npm_packument(registry: &Registry, job: &FetchJob) -> Result<Option<PackageMetadata>> {
+    let (package_name, version) = (job.name.as_str(), job.version.as_deref());
+    let url = format!("{}/{}", registry.npm_registry, encode(package_name));
+    let Some(data) = fetch_npm_json(registry, &url, package_name)? else {
+        return Ok(None);
+    };
+
+    let latest = data
+        .get("dist-tags")
+        .and_then(|tags| tags.get("latest"))
+        .and_then(|v| v.as_str());
+    let description = data
+        .get("description")
+        .and_then(|v| v.as_str())
+        .and_then(normalize_description);
+    let with_latest = |metadata: PackageMetadata| PackageMetadata {
+        latest_version: latest.map(|v| v.to_string()),
+        description: metadata.description.or_else(|| description.clone()),
+        provenance: metadata.license.as_ref().map(|_| Provenance::NpmRegistry),
+        deprecations: job
+            .versions
+            .iter()
+            .map(|version| {
+                let entry = data.get("versions").and_then(|all| all.get(version));
+                (version.clone(), entry.and_then(npm_deprecated))
+            })
+            .collect(),
+        ..metadata
+    };
+
+    if let Some(ver) = version
+        && let Some(metadata) = lookup_npm_version_metadata(&data, ver)
+    {
+        return Ok(Some(with_latest(metadata)));
     }
 
-    let license = data.get("license").and_then(extract_license);
+    explain_npm_fields(package_name, &data);
+    let license = npm_license(&data);
     let homepage = extract_npm_homepage(&data);
 
     if license.is_some() || homepage.is_some() {
-        return Ok(Some(PackageMetadata { license, homepage }));
+        return Ok(Some(with_latest(PackageMetadata {
+            license,
+            homepage,
+            latest_version: None,
+            description: None,
+            provenance: None,
+            deprecations: BTreeMap::new(),
+        })));
     }
 
-    if let Some(latest) = data
-        .get("dist-tags")
-        .and_then(|tags| tags.get("latest"))
-        .and_then(|v| v.as_str())
+    if let Some(latest) = latest
+        && let Some(metadata) = lookup_npm_version_metadata(&data, latest)
     {
-        if let Some(metadata) = lookup_npm_version_metadata(&data, latest) {
-            return Ok(Some(metadata));
+        return Ok(Some(with_latest(metadata)));
+    }
+
+    Ok(latest.map(|_| {
+        with_latest(PackageMetadata {
+            license: None,
+            homepage: None,
+            latest_version: None,
+            description: None,
+            provenance: None,
+            deprecations: BTreeMap::new(),
+        })
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+struct CratesIoResponse {
+    #[serde(rename = "crate")]
+    krate: CratesIoCrate,
+    #[serde(default)]
+    versions: Vec<CratesIoVersion>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CratesIoCrate {
+    homepage: Option<String>,
+    repository: Option<String>,
+    max_stable_version: Option<String>,
+    max_version: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CratesIoVersion {
+    num: String,
+    license: Option<String>,
+}
+
+fn fetch_crates_io_metadata(
+    registry: &Registry,
+    crate_name: &str,
+    version: Option<&str>,
+) -> Result<Option<PackageMetadata>> {
+    let encoded = encode(crate_name);
+    let url = format!("https://crates.io/api/v1/crates/{encoded}");
+    registry.wait_for_crates_io();
+    let response = registry
+        .get(&url)
+        .with_context(|| format!("crates.ioリクエストに失敗しました: {crate_name}"))?;
+
+    if response.status() == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+
+    if !response.status().is_success() {
+        bail!(
+            "crates.ioがエラーを返しました({crate_name}): {}",
+            response.status()
+        );
+    }
+
+    let data: CratesIoResponse = response
+        .json()
+        .with_context(|| format!("crates.ioレスポンスの解析に失敗: {crate_name}"))?;
+    let wanted = version.or(data
+        .krate
+        .max_stable_version
+        .as_deref()
+        .or(data.krate.max_version.as_deref()));
+    if let Some(found) = data
+        .versions
+        .iter()
+        .find(|v| Some(v.num.as_str()) == wanted)
+        .or_else(|| data.versions.first())
+        && let Some(license) = &found.license
+    {
+        explain_license_field(
+            "cargo",
+            crate_name,
+            &format!("crates.ioのlicense（{}）", found.num),
+            &license.replace('/', " OR "),
+        );
+    }
+    Ok(crates_io_metadata(data, version))
+}
+
+fn crates_io_metadata(data: CratesIoResponse, version: Option<&str>) -> Option<PackageMetadata> {
+    let latest_version = data.krate.max_stable_version.or(data.krate.max_version);
+    let wanted = version.or(latest_version.as_deref());
+    let license = data
+        .versions
+        .iter()
+        .find(|v| Some(v.num.as_str()) == wanted)
+        .or_else(|| data.versions.first())
+        .and_then(|v| v.license.as_deref())
+        .map(|license| license.replace('/', " OR "))
+        .and_then(|license| normalize_license_text(&license));
+    let homepage = data
+        .krate
+        .homepage
+        .as_deref()
+        .and_then(normalize_homepage)
+        .or_else(|| {
+            data.krate
+                .repository
+                .as_deref()
+                .and_then(normalize_repository_url)
+        });
+
+    let provenance = license.as_ref().map(|_| Provenance::CratesIo);
+    if license.is_some() || homepage.is_some() || latest_version.is_some() {
+        Some(PackageMetadata {
+            license,
+            homepage,
+            latest_version,
+            description: None,
+            provenance,
+            deprecations: BTreeMap::new(),
+        })
+    } else {
+        None
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RubyGemsResponse {
+    #[serde(default)]
+    licenses: Option<Vec<String>>,
+    homepage_uri: Option<String>,
+    source_code_uri: Option<String>,
+    version: Option<String>,
+}
+
+fn fetch_rubygems_metadata(registry: &Registry, gem_name: &str) -> Result<Option<PackageMetadata>> {
+    let encoded = encode(gem_name);
+    let url = format!("https://rubygems.org/api/v1/gems/{encoded}.json");
+    let response = registry
+        .get(&url)
+        .with_context(|| format!("RubyGemsリクエストに失敗しました: {gem_name}"))?;
+
+    if response.status() == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+
+    if !response.status().is_success() {
+        bail!(
+            "RubyGemsがエラーを返しました({gem_name}): {}",
+            response.status()
+        );
+    }
+
+    let data: RubyGemsResponse = response
+        .json()
+        .with_context(|| format!("RubyGemsレスポンスの解析に失敗: {gem_name}"))?;
+    Ok(rubygems_metadata(data))
+}
+
+fn rubygems_metadata(data: RubyGemsResponse) -> Option<PackageMetadata> {
+    let licenses: Vec<String> = data
+        .licenses
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|license| normalize_license_text(license))
+        .collect();
+    let license = (!licenses.is_empty()).then(|| licenses.join(" OR "));
+    let homepage = data
+        .homepage_uri
+        .as_deref()
+        .and_then(normalize_homepage)
+        .or_else(|| data.source_code_uri.as_deref().and_then(normalize_homepage));
+    let latest_version = data.version;
+
+    let provenance = license.as_ref().map(|_| Provenance::Rubygems);
+    if license.is_some() || homepage.is_some() || latest_version.is_some() {
+        Some(PackageMetadata {
+            license,
+            homepage,
+            latest_version,
+            description: None,
+            provenance,
+            deprecations: BTreeMap::new(),
+        })
+    } else {
+        None
+    }
+}
+
+fn fetch_maven_metadata(
+    registry: &Registry,
+    coordinate: &str,
+    version: Option<&str>,
+) -> Result<Option<PackageMetadata>> {
+    let Some((group_id, artifact_id)) = coordinate.split_once(':') else {
+        return Ok(None);
+    };
+    let index_url = format!(
+        "{}/maven-metadata.xml",
+        maven_artifact_url(group_id, artifact_id)
+    );
+    let Some(index) = fetch_maven_xml(registry, &index_url, coordinate)? else {
+        return Ok(None);
+    };
+    let latest_version = maven_release_version(&index)
+        .with_context(|| format!("maven-metadata.xmlの解析に失敗: {coordinate}"))?;
+
+    let mut license = None;
+    let mut homepage = None;
+    let mut current = version
+        .filter(|version| !version.contains("${"))
+        .or(latest_version.as_deref())
+        .map(|version| {
+            (
+                group_id.to_string(),
+                artifact_id.to_string(),
+                version.to_string(),
+            )
+        });
+    // ライセンスは親POMから継承されることが多いため、数階層まで遡る
+    for _ in 0..=MAVEN_PARENT_DEPTH {
+        let Some((group_id, artifact_id, version)) = current.take() else {
+            break;
+        };
+        let pom_url = format!(
+            "{}/{version}/{artifact_id}-{version}.pom",
+            maven_artifact_url(&group_id, &artifact_id)
+        );
+        let Some(text) = fetch_maven_xml(registry, &pom_url, coordinate)? else {
+            break;
+        };
+        let pom = MavenPom::parse(&text)
+            .with_context(|| format!("POMの解析に失敗: {group_id}:{artifact_id}:{version}"))?;
+        license = license.or(pom.license);
+        homepage = homepage.or(pom.homepage);
+        if license.is_none() || homepage.is_none() {
+            current = pom.parent;
         }
     }
 
-    Ok(None)
+    let provenance = license.as_ref().map(|_| Provenance::MavenCentral);
+    if license.is_some() || homepage.is_some() || latest_version.is_some() {
+        Ok(Some(PackageMetadata {
+            license,
+            homepage,
+            latest_version,
+            description: None,
+            provenance,
+            deprecations: BTreeMap::new(),
+        }))
+    } else {
+        Ok(None)
+    }
+}
+
+fn maven_artifact_url(group_id: &str, artifact_id: &str) -> String {
+    format!(
+        "{MAVEN_CENTRAL}/{}/{}",
+        group_id.replace('.', "/"),
+        encode(artifact_id)
+    )
+}
+
+fn fetch_maven_xml(registry: &Registry, url: &str, coordinate: &str) -> Result<Option<String>> {
+    let response = registry
+        .get(url)
+        .with_context(|| format!("Maven Centralリクエストに失敗しました: {coordinate}"))?;
+
+    if response.status() == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+
+    if !response.status().is_success() {
+        bail!(
+            "Maven Centralがエラーを返しました({coordinate}): {}",
+            response.status()
+        );
+    }
+
+    let text = response
+        .text()
+        .with_context(|| format!("Maven Centralレスポンスの読み込みに失敗: {coordinate}"))?;
+    Ok(Some(text))
+}
+
+fn maven_release_version(xml: &str) -> Result<Option<String>> {
+    let document = roxmltree::Document::parse(xml)?;
+    Ok(
+        child_element(document.root_element(), "versioning").and_then(|versioning| {
+            child_text(versioning, "release").or_else(|| child_text(versioning, "latest"))
+        }),
+    )
+}
+
+struct MavenPom {
+    license: Option<String>,
+    homepage: Option<String>,
+    parent: Option<(String, String, String)>,
+}
+
+impl MavenPom {
+    fn parse(xml: &str) -> Result<Self> {
+        let document = roxmltree::Document::parse(xml)?;
+        let project = document.root_element();
+        let licenses: Vec<String> = child_element(project, "licenses")
+            .into_iter()
+            .flat_map(|licenses| licenses.children())
+            .filter(|node| node.has_tag_name("license"))
+            .filter_map(|license| child_text(license, "name"))
+            .filter_map(|name| normalize_license_text(&name))
+            .collect();
+        let homepage = child_text(project, "url")
+            .filter(|url| !url.contains("${"))
+            .as_deref()
+            .and_then(normalize_homepage);
+        let parent = child_element(project, "parent").and_then(|parent| {
+            Some((
+                child_text(parent, "groupId")?,
+                child_text(parent, "artifactId")?,
+                child_text(parent, "version")?,
+            ))
+        });
+        Ok(Self {
+            license: (!licenses.is_empty()).then(|| licenses.join(" OR ")),
+            homepage,
+            parent,
+        })
+    }
 }
 
 fn lookup_npm_version_metadata(json: &Value, version: &str) -> Option<PackageMetadata> {
-    let entry = json
-        .get("versions")
-        .and_then(|versions| versions.get(version))?;
-    let license = entry.get("license").and_then(extract_license);
+    json.get("versions")
+        .and_then(|versions| versions.get(version))
+        .and_then(npm_manifest_metadata)
+}
+
+/// `license`が無ければ、古いパッケージが使う非推奨の`licenses`配列を読みます。
+fn explain_npm_fields(package_name: &str, entry: &Value) {
+    logging::explain(package_name, || {
+        let field = |key: &str| entry.get(key).map_or("-".to_string(), Value::to_string);
+        format!(
+            "[npm] npm Registry{}: license: {}, licenses: {}, homepage: {}, repository: {}",
+            entry
+                .get("version")
+                .and_then(|v| v.as_str())
+                .map(|version| format!("（{version}）"))
+                .unwrap_or_default(),
+            field("license"),
+            field("licenses"),
+            field("homepage"),
+            field("repository")
+        )
+    });
+}
+
+fn npm_license(value: &Value) -> Option<String> {
+    value
+        .get("license")
+        .and_then(extract_license)
+        .or_else(|| value.get("licenses").and_then(extract_license))
+}
+
+fn npm_manifest_metadata(entry: &Value) -> Option<PackageMetadata> {
+    if let Some(name) = entry.get("name").and_then(|v| v.as_str()) {
+        explain_npm_fields(name, entry);
+    }
+    let license = npm_license(entry);
     let homepage = extract_npm_homepage(entry);
 
     if license.is_none() && homepage.is_none() {
         None
     } else {
-        Some(PackageMetadata { license, homepage })
+        Some(PackageMetadata {
+            license,
+            homepage,
+            latest_version: None,
+            description: entry
+                .get("description")
+                .and_then(|v| v.as_str())
+                .and_then(normalize_description),
+            provenance: None,
+            deprecations: BTreeMap::new(),
+        })
     }
 }
 
+/// バージョン文書の`deprecated`（非推奨の理由の文字列）。
+fn npm_deprecated(entry: &Value) -> Option<String> {
+    entry
+        .get("deprecated")
+        .and_then(|v| v.as_str())
+        .and_then(normalize_description)
+}
+
 fn extract_npm_homepage(value: &Value) -> Option<String> {
     value
         .get("homepage")
@@ -329,6 +1490,917 @@ fn normalize_repository_url(url: &str) -> Option<String> {
     }
 
     let cleaned = trimmed.strip_prefix("git+").unwrap_or(trimmed);
-    let cleaned = cleaned.trim_end_matches(".git");
-    normalize_homepage(cleaned)
+    let expanded = expand_repository_shorthand(cleaned);
+    let cleaned = expanded.as_deref().unwrap_or(cleaned);
+    normalize_homepage(cleaned.trim_end_matches('/').trim_end_matches(".git"))
+}
+
+/// npmの`repository`の省略形（`user/repo`・`github:`・`gitlab:`・`bitbucket:`）と、
+/// `git://`・`git@host:`・`ssh://git@host/`の形式をブラウザで開けるhttpsのURLにします。
+fn expand_repository_shorthand(url: &str) -> Option<String> {
+    const HOSTS: [(&str, &str); 3] = [
+        ("github:", "github.com"),
+        ("gitlab:", "gitlab.com"),
+        ("bitbucket:", "bitbucket.org"),
+    ];
+    for (prefix, host) in HOSTS {
+        if let Some(path) = url.strip_prefix(prefix) {
+            let path = path.split('#').next().unwrap_or_default();
+            return Some(format!("https://{host}/{path}"));
+        }
+    }
+    if let Some(rest) = url.strip_prefix("git://") {
+        return Some(format!("https://{rest}"));
+    }
+    if let Some(rest) = url.strip_prefix("ssh://") {
+        let rest = rest.split_once('@').map_or(rest, |(_, host)| host);
+        return Some(format!("https://{rest}"));
+    }
+    if let Some(rest) = url.strip_prefix("git@") {
+        let (host, path) = rest.split_once(':')?;
+        return Some(format!("https://{host}/{path}"));
+    }
+
+    let path = url.split('#').next().unwrap_or_default();
+    let is_segment = |segment: &str| {
+        !segment.is_empty()
+            && segment
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    };
+    match path.split_once('/') {
+        Some((owner, repo)) if is_segment(owner) && is_segment(repo) => {
+            Some(format!("https://github.com/{path}"))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::Arc;
+
+    use super::*;
+
+    #[test]
+    fn query_record_splits_name_and_version() {
+        let record = query_record("pip", "requests==2.32.0").unwrap();
+        assert_eq!(
+            (record.name.as_str(), record.version.as_deref()),
+            ("requests", Some("2.32.0"))
+        );
+        assert!(record.has_unknown_license());
+
+        let record = query_record("NPM", "@types/node@20.1.0").unwrap();
+        assert_eq!(record.manager, "npm");
+        assert_eq!(
+            (record.name.as_str(), record.version.as_deref()),
+            ("@types/node", Some("20.1.0"))
+        );
+        let record = query_record("npm", "@types/node").unwrap();
+        assert_eq!(
+            (record.name.as_str(), record.version),
+            ("@types/node", None)
+        );
+
+        assert!(query_record("go", "github.com/pkg/errors").is_err());
+        assert!(query_record("pip", "==1.0").is_err());
+    }
+
+    #[test]
+    fn reconcile_records_differing_registry_license() {
+        let metadata = |license: &str| {
+            Some(PackageMetadata {
+                license: Some(license.to_string()),
+                homepage: None,
+                latest_version: None,
+                description: None,
+                provenance: None,
+                deprecations: BTreeMap::new(),
+            })
+        };
+        let mut record = DependencyRecord {
+            manager: "npm".to_string(),
+            name: "left-pad".to_string(),
+            license: "ISC".to_string(),
+            ..Default::default()
+        };
+        apply_metadata(&mut record, &metadata("MIT"), false);
+        assert_eq!(record.registry_license, None);
+        apply_metadata(&mut record, &metadata("MIT"), true);
+        assert_eq!(record.license, "ISC");
+        assert_eq!(record.registry_license.as_deref(), Some("MIT"));
+
+        record.registry_license = None;
+        apply_metadata(&mut record, &metadata("ISC License"), true);
+        assert_eq!(record.registry_license, None);
+    }
+
+    #[test]
+    fn retry_delay_honors_retry_after_and_backs_off() {
+        assert_eq!(
+            retry_delay(0, Some(Duration::from_secs(3))),
+            Duration::from_secs(3)
+        );
+        assert_eq!(
+            retry_delay(0, Some(Duration::from_secs(3600))),
+            Duration::from_secs(60)
+        );
+
+        let first = retry_delay(0, None);
+        let third = retry_delay(2, None);
+        assert!(first >= Duration::from_millis(500) && first < Duration::from_millis(750));
+        assert!(third >= Duration::from_millis(2000) && third < Duration::from_millis(2250));
+    }
+
+    #[test]
+    fn repository_shorthands_expand_to_https_urls() {
+        let cases = [
+            ("user/repo", "https://github.com/user/repo"),
+            ("github:user/repo", "https://github.com/user/repo"),
+            ("github:user/repo#v1.0.0", "https://github.com/user/repo"),
+            ("gitlab:group/project", "https://gitlab.com/group/project"),
+            ("bitbucket:team/repo", "https://bitbucket.org/team/repo"),
+            (
+                "git://github.com/user/repo.git",
+                "https://github.com/user/repo",
+            ),
+            (
+                "git@github.com:user/repo.git",
+                "https://github.com/user/repo",
+            ),
+            (
+                "git+ssh://git@github.com/user/repo.git",
+                "https://github.com/user/repo",
+            ),
+            (
+                "git+https://github.com/user/repo.git",
+                "https://github.com/user/repo",
+            ),
+            ("https://example.com/docs/", "https://example.com/docs"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(
+                normalize_repository_url(input).as_deref(),
+                Some(expected),
+                "{input}"
+            );
+        }
+        assert_eq!(
+            normalize_repository_url("not a url").as_deref(),
+            Some("not a url")
+        );
+        assert_eq!(normalize_repository_url("  "), None);
+    }
+
+    #[test]
+    fn crates_io_metadata_uses_locked_version_license() {
+        let data: CratesIoResponse = serde_json::from_str(
+            r#"{
+                "crate": {
+                    "homepage": null,
+                    "repository": "https://github.com/serde-rs/serde.git",
+                    "max_stable_version": "1.0.200",
+                    "max_version": "1.0.200"
+                },
+                "versions": [
+                    {"num": "1.0.200", "license": "MIT OR Apache-2.0"},
+                    {"num": "0.9.0", "license": "MIT/Apache-2.0"}
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let metadata = crates_io_metadata(data, Some("0.9.0")).unwrap();
+        assert_eq!(metadata.license.as_deref(), Some("MIT OR Apache-2.0"));
+        assert_eq!(
+            metadata.homepage.as_deref(),
+            Some("https://github.com/serde-rs/serde")
+        );
+        assert_eq!(metadata.latest_version.as_deref(), Some("1.0.200"));
+    }
+
+    #[test]
+    fn rubygems_metadata_joins_licenses() {
+        let data: RubyGemsResponse = serde_json::from_str(
+            r#"{"licenses": ["MIT", "Ruby"], "homepage_uri": "https://rubyonrails.org/", "version": "7.1.0"}"#,
+        )
+        .unwrap();
+        let metadata = rubygems_metadata(data).unwrap();
+        assert_eq!(metadata.license.as_deref(), Some("MIT OR Ruby"));
+        assert_eq!(
+            metadata.homepage.as_deref(),
+            Some("https://rubyonrails.org")
+        );
+        assert_eq!(metadata.latest_version.as_deref(), Some("7.1.0"));
+    }
+
+    #[test]
+    fn maven_pom_reads_licenses_url_and_parent() {
+        let pom = MavenPom::parse(
+            r#"<project xmlns="http://maven.apache.org/POM/4.0.0">
+  <parent>
+    <groupId>com.google.guava</groupId>
+    <artifactId>guava-parent</artifactId>
+    <version>32.1.3-jre</version>
+  </parent>
+  <url>https://github.com/google/guava/</url>
+  <licenses>
+    <license><name>The Apache Software License, Version 2.0</name></license>
+  </licenses>
+</project>"#,
+        )
+        .unwrap();
+        assert_eq!(pom.license.as_deref(), Some("Apache-2.0"));
+        assert_eq!(
+            pom.homepage.as_deref(),
+            Some("https://github.com/google/guava")
+        );
+        assert_eq!(
+            pom.parent,
+            Some((
+                "com.google.guava".to_string(),
+                "guava-parent".to_string(),
+                "32.1.3-jre".to_string()
+            ))
+        );
+
+        let index = "<metadata><groupId>junit</groupId><artifactId>junit</artifactId><versioning><latest>4.13.3-SNAPSHOT</latest><release>4.13.2</release></versioning></metadata>";
+        assert_eq!(
+            maven_release_version(index).unwrap().as_deref(),
+            Some("4.13.2")
+        );
+    }
+
+    #[test]
+    fn base_url_falls_back_and_trims_trailing_slash() {
+        assert_eq!(base_url(None, DEFAULT_PYPI_URL), "https://pypi.org");
+        assert_eq!(
+            base_url(
+                Some("https://npm.example.com/repository/npm/"),
+                DEFAULT_NPM_REGISTRY
+            ),
+            "https://npm.example.com/repository/npm"
+        );
+        assert_eq!(
+            base_url(Some("  "), DEFAULT_NPM_REGISTRY),
+            DEFAULT_NPM_REGISTRY
+        );
+    }
+
+    #[test]
+    fn fetch_error_reports_root_cause() {
+        let err = anyhow::anyhow!("PyPIがエラーを返しました(foo): 500 Internal Server Error")
+            .context("外側のコンテキスト");
+        let failure = FetchError {
+            manager: "pip".to_string(),
+            name: "foo".to_string(),
+            reason: failure_reason(&err),
+        };
+        assert_eq!(
+            failure.to_string(),
+            "foo(pip): PyPIがエラーを返しました(foo): 500 Internal Server Error"
+        );
+    }
+
+    #[test]
+    fn retries_give_up_when_the_deadline_comes_first() {
+        let registry = Registry {
+            deadline: Some(Instant::now() + Duration::from_millis(300)),
+            max_retries: 5,
+            ..Registry::for_test(DEFAULT_NPM_REGISTRY)
+        };
+        let started = Instant::now();
+        assert!(registry.get("http://127.0.0.1:1/flask/json").is_err());
+        assert!(started.elapsed() < Duration::from_millis(500));
+    }
+
+    #[test]
+    fn clients_are_shared_per_host() {
+        let registry = Registry {
+            pool_size: Some(4),
+            ..Registry::for_test(DEFAULT_NPM_REGISTRY)
+        };
+        registry
+            .client_for("https://pypi.org/pypi/flask/json")
+            .unwrap();
+        registry
+            .client_for("https://PyPI.org/pypi/requests/json")
+            .unwrap();
+        registry
+            .client_for("https://registry.npmjs.org/react")
+            .unwrap();
+
+        let clients = registry.clients.lock().unwrap();
+        let mut hosts: Vec<&str> = clients.keys().map(String::as_str).collect();
+        hosts.sort();
+        assert_eq!(hosts, vec!["pypi.org", "registry.npmjs.org"]);
+    }
+
+    #[test]
+    fn tokens_are_sent_only_under_their_registry() {
+        let registry = Registry {
+            tokens: vec![(
+                "https://npm.example.com/repo".to_string(),
+                "secret".to_string(),
+            )],
+            ..Registry::for_test("https://npm.example.com/repo")
+        };
+        assert_eq!(
+            registry.token_for("https://npm.example.com/repo/@scope%2Fpkg"),
+            Some("secret")
+        );
+        assert_eq!(
+            registry.token_for("https://npm.example.com/repository"),
+            None
+        );
+        assert_eq!(registry.token_for("https://registry.npmjs.org/react"), None);
+        assert_eq!(registry.token_for("https://pypi.org/pypi/flask/json"), None);
+    }
+
+    #[test]
+    fn expired_deadline_leaves_packages_unenriched() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("deadline.json");
+        let mut cache = LicenseCache::load(Some(&path)).unwrap();
+        let mut records = vec![DependencyRecord {
+            manager: "pip".to_string(),
+            name: "requests".to_string(),
+            license: "Unknown".to_string(),
+            ..Default::default()
+        }];
+        let options = EnrichOptions {
+            concurrency: 2,
+            deadline: Some(Duration::ZERO),
+            ..Default::default()
+        };
+
+        let failures = enrich_metadata(&mut records, None, &mut cache, &options).unwrap();
+        assert!(failures.is_empty());
+        assert_eq!(records[0].license, "Unknown");
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn rate_limiter_paces_requests_after_burst() {
+        assert!(RateLimiter::new(0.0).is_none());
+
+        let limiter = RateLimiter::new(20.0).unwrap();
+        let started = Instant::now();
+        for _ in 0..22 {
+            limiter.acquire();
+        }
+        let elapsed = started.elapsed();
+        assert!(elapsed >= Duration::from_millis(90), "{elapsed:?}");
+        assert!(elapsed < Duration::from_secs(1), "{elapsed:?}");
+    }
+
+    #[test]
+    fn npm_version_metadata_includes_description() {
+        let json: Value = serde_json::from_str(
+            r#"{"versions": {"1.0.0": {"license": "MIT", "description": "  A tiny\n  helper  "}}}"#,
+        )
+        .unwrap();
+        let metadata = lookup_npm_version_metadata(&json, "1.0.0").unwrap();
+        assert_eq!(metadata.description.as_deref(), Some("A tiny helper"));
+    }
+
+    #[test]
+    fn npm_legacy_licenses_array_becomes_or_expression() {
+        let json: Value = serde_json::from_str(
+            r#"{
+                "licenses": [
+                    {"type": "MIT", "url": "https://github.com/example/legacy/raw/master/LICENSE-MIT"},
+                    {"type": "Apache-2.0", "url": "https://github.com/example/legacy/raw/master/LICENSE-APACHE"}
+                ],
+                "versions": {"0.2.1": {"licenses": [{"type": "BSD"}]}}
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(npm_license(&json).as_deref(), Some("MIT OR Apache-2.0"));
+        let metadata = lookup_npm_version_metadata(&json, "0.2.1").unwrap();
+        assert_eq!(metadata.license.as_deref(), Some("BSD"));
+
+        let both: Value =
+            serde_json::from_str(r#"{"license": "ISC", "licenses": [{"type": "MIT"}]}"#).unwrap();
+        assert_eq!(npm_license(&both).as_deref(), Some("ISC"));
+    }
+
+    #[test]
+    fn pypi_license_records_field_or_classifier_provenance() {
+        let info: PyPiInfo = serde_json::from_str(
+            r#"{"license": "", "classifiers": ["License :: OSI Approved :: MIT License"]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            pypi_license(&info),
+            Some(("MIT".to_string(), Provenance::PypiClassifier))
+        );
+
+        let info: PyPiInfo = serde_json::from_str(
+            r#"{"license": "Apache-2.0", "classifiers": ["License :: OSI Approved :: MIT License"]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            pypi_license(&info),
+            Some(("Apache-2.0".to_string(), Provenance::PypiLicense))
+        );
+    }
+
+    #[test]
+    fn classifiers_prefer_the_most_specific_license() {
+        let classifiers = |list: &[&str]| {
+            license_from_classifiers(&list.iter().map(|c| c.to_string()).collect::<Vec<_>>())
+        };
+        // 汎用の`OSI Approved`が先にあっても具体的なライセンスを選ぶ（例: requests 2.x）
+        assert_eq!(
+            classifiers(&[
+                "Development Status :: 5 - Production/Stable",
+                "License :: OSI Approved",
+                "License :: OSI Approved :: Apache Software License",
+                "Programming Language :: Python :: 3",
+            ]),
+            Some("Apache-2.0".to_string())
+        );
+        assert_eq!(
+            classifiers(&[
+                "Intended Audience :: Developers",
+                "License :: OSI Approved :: GNU Lesser General Public License v2 or later (LGPLv2+)",
+            ]),
+            Some("LGPL-2.0-or-later".to_string())
+        );
+        assert_eq!(
+            classifiers(&[
+                "License :: Public Domain",
+                "Operating System :: OS Independent"
+            ]),
+            Some("Public Domain".to_string())
+        );
+        assert_eq!(
+            classifiers(&[
+                "License :: OSI Approved :: MIT License",
+                "License :: OSI Approved :: Apache Software License",
+            ]),
+            Some("MIT".to_string())
+        );
+        assert_eq!(classifiers(&["License :: OSI Approved"]), None);
+        assert_eq!(
+            classifiers(&["Topic :: Software Development :: Libraries"]),
+            None
+        );
+    }
+
+    /// `routes`の応答を返すHTTPサーバーを起動し、ベースURLと受け付けたパスの記録を返します。
+    fn serve(
+        routes: &'static [(&'static str, u16, &'static str)],
+    ) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let requested = Arc::new(Mutex::new(Vec::new()));
+        let log = Arc::clone(&requested);
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut line = String::new();
+                while reader.read_line(&mut line).unwrap() > 2 {
+                    line.clear();
+                }
+                let path = request_line
+                    .split(' ')
+                    .nth(1)
+                    .unwrap_or_default()
+                    .to_string();
+                let (status, body) = routes
+                    .iter()
+                    .find(|(route, _, _)| *route == path)
+                    .map_or((404, "{}"), |(_, status, body)| (*status, *body));
+                log.lock().unwrap().push(path);
+                write!(
+                    stream,
+                    "HTTP/1.1 {status} X\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                )
+                .unwrap();
+            }
+        });
+        (format!("http://{address}"), requested)
+    }
+
+    fn npm_job(name: &str, versions: &[&str]) -> FetchJob {
+        FetchJob {
+            manager: "npm".to_string(),
+            name: name.to_string(),
+            version: versions.first().map(|version| version.to_string()),
+            versions: versions.iter().map(|version| version.to_string()).collect(),
+        }
+    }
+
+    /// パスごとに固定のレスポンスを返すnpm Registryの代わり。受け取ったパスを記録します。
+    impl Registry {
+        /// PyPIとnpmのどちらも`base_url`に問い合わせ、再試行しないレジストリ。
+        fn for_test(base_url: &str) -> Self {
+            Self {
+                clients: Mutex::new(HashMap::new()),
+                pool_size: None,
+                request_timeout: DEFAULT_REQUEST_TIMEOUT,
+                deadline: None,
+                max_retries: 0,
+                pypi_url: base_url.to_string(),
+                npm_registry: base_url.to_string(),
+                tokens: Vec::new(),
+                crates_io_last_request: Mutex::new(None),
+                rate_limiter: None,
+                flag_outdated: false,
+                user_agent: DEFAULT_USER_AGENT.to_string(),
+            }
+        }
+    }
+
+    /// パスごとに固定のレスポンスを返すnpm Registryの代わり。受け取ったパスを記録します。
+    fn serve_npm(
+        routes: &'static [(&'static str, u16, &'static str)],
+    ) -> (Registry, Arc<Mutex<Vec<String>>>) {
+        let (base_url, requested) = serve(routes);
+        let registry = Registry::for_test(&base_url);
+        (registry, requested)
+    }
+
+    #[test]
+    fn concurrent_enrichment_fetches_each_package_once() {
+        static ROUTES: &[(&str, u16, &str)] = &[
+            (
+                "/pypi/requests/json",
+                200,
+                r#"{"info": {"license": "Apache-2.0", "version": "2.32.0", "home_page": "https://requests.readthedocs.io"}}"#,
+            ),
+            (
+                "/pypi/Flask_SQLAlchemy/json",
+                200,
+                r#"{"info": {"license": "BSD-3-Clause", "version": "3.1.1"}}"#,
+            ),
+            ("/left-pad/1.3.0", 200, r#"{"license": "WTFPL"}"#),
+            (
+                "/react",
+                200,
+                r#"{"license": "MIT", "homepage": "https://react.dev", "dist-tags": {"latest": "18.3.1"}}"#,
+            ),
+        ];
+        let record =
+            |manager: &str, name: &str, version: Option<&str>, source: &str| DependencyRecord {
+                manager: manager.to_string(),
+                name: name.to_string(),
+                version: version.map(str::to_string),
+                license: "Unknown".to_string(),
+                source: std::path::PathBuf::from(source),
+                ..Default::default()
+            };
+        let records = vec![
+            record("pip", "requests", Some("2.32.0"), "api/requirements.txt"),
+            record("pip", "requests", Some("2.31.0"), "worker/requirements.txt"),
+            record("pip", "Flask_SQLAlchemy", None, "api/requirements.txt"),
+            record("pip", "flask-sqlalchemy", None, "worker/requirements.txt"),
+            record("pip", "internal-tool", None, "api/requirements.txt"),
+            record("npm", "left-pad", Some("1.3.0"), "web/package-lock.json"),
+            record("npm", "left-pad", Some("1.3.0"), "admin/package-lock.json"),
+            record("npm", "react", None, "web/package.json"),
+        ];
+
+        let mut results = Vec::new();
+        for concurrency in [1, 8] {
+            let (base_url, requested) = serve(ROUTES);
+            let dir = tempfile::tempdir().unwrap();
+            let cache_path = dir.path().join("enrich.json");
+            let mut cache = LicenseCache::load(Some(&cache_path)).unwrap();
+            let options = EnrichOptions {
+                concurrency,
+                pypi_url: Some(base_url.clone()),
+                npm_registry: Some(base_url),
+                ..Default::default()
+            };
+            let progress = ProgressBar::hidden();
+            let mut enriched = records.clone();
+            let failures =
+                enrich_metadata(&mut enriched, Some(&progress), &mut cache, &options).unwrap();
+            assert!(failures.is_empty(), "{failures:?}");
+            assert_eq!(Some(progress.position()), progress.length());
+            assert_eq!(progress.position(), 5);
+
+            let mut paths = requested.lock().unwrap().clone();
+            paths.sort();
+            assert_eq!(
+                paths,
+                vec![
+                    "/left-pad/1.3.0",
+                    "/pypi/Flask_SQLAlchemy/json",
+                    "/pypi/internal-tool/json",
+                    "/pypi/requests/json",
+                    "/react",
+                ]
+            );
+            assert_eq!(cache.len(), 5);
+            assert_eq!(
+                cache.stats(),
+                crate::cache::CacheStats { hits: 0, misses: 5 }
+            );
+            assert_eq!(
+                cache
+                    .get("pip", "flask_sqlalchemy")
+                    .and_then(|m| m.license)
+                    .as_deref(),
+                Some("BSD-3-Clause")
+            );
+            assert!(cache.is_known_not_found("pip", "internal-tool"));
+
+            let mut again = records.clone();
+            enrich_metadata(&mut again, None, &mut cache, &options).unwrap();
+            assert_eq!(requested.lock().unwrap().len(), paths.len());
+            assert_eq!(
+                cache.stats(),
+                crate::cache::CacheStats { hits: 5, misses: 5 }
+            );
+
+            let summary: Vec<(String, String, Option<String>)> = enriched
+                .iter()
+                .chain(&again)
+                .map(|r| (r.name.clone(), r.license.clone(), r.homepage.clone()))
+                .collect();
+            results.push(summary);
+        }
+        assert_eq!(results[0], results[1]);
+        assert_eq!(results[0][2].1, "BSD-3-Clause");
+        assert_eq!(results[0][3].1, "BSD-3-Clause");
+        assert_eq!(results[0][4].1, "Unknown");
+    }
+
+    #[test]
+    fn deprecation_comes_from_npm_and_yanked_pypi_releases() {
+        static ROUTES: &[(&str, u16, &str)] = &[
+            (
+                "/pypi/urllib3/json",
+                200,
+                r#"{"info": {"license": "MIT", "version": "2.2.0"},
+                    "releases": {"2.0.0": [{"yanked": true, "yanked_reason": "Broken   wheel"}, {"yanked": true}],
+                                 "2.2.0": [{"yanked": false}]}}"#,
+            ),
+            (
+                "/request/2.88.2",
+                200,
+                r#"{"license": "Apache-2.0", "deprecated": "request has been deprecated"}"#,
+            ),
+        ];
+        let (base_url, _) = serve(ROUTES);
+        let dir = tempfile::tempdir().unwrap();
+        let cache_path = dir.path().join("deprecated.json");
+        let mut cache = LicenseCache::load(Some(&cache_path)).unwrap();
+        let options = EnrichOptions {
+            pypi_url: Some(base_url.clone()),
+            npm_registry: Some(base_url),
+            ..Default::default()
+        };
+        let mut records = vec![
+            DependencyRecord {
+                manager: "pip".to_string(),
+                name: "urllib3".to_string(),
+                version: Some("2.0.0".to_string()),
+                ..Default::default()
+            },
+            DependencyRecord {
+                manager: "npm".to_string(),
+                name: "request".to_string(),
+                version: Some("2.88.2".to_string()),
+                ..Default::default()
+            },
+        ];
+        enrich_metadata(&mut records, None, &mut cache, &options).unwrap();
+        assert_eq!(records[0].deprecated.as_deref(), Some("Broken wheel"));
+        assert_eq!(
+            records[1].deprecated.as_deref(),
+            Some("request has been deprecated")
+        );
+
+        let releases: HashMap<String, Vec<PyPiFile>> =
+            serde_json::from_str(r#"{"1.0": [{"yanked": true}, {"yanked": false}], "0.9": []}"#)
+                .unwrap();
+        assert_eq!(pypi_yanked_reason(&releases, "1.0"), None);
+        assert_eq!(pypi_yanked_reason(&releases, "0.9"), None);
+    }
+
+    #[test]
+    fn deprecation_is_tracked_per_version() {
+        static ROUTES: &[(&str, u16, &str)] = &[
+            (
+                "/pypi/foo/json",
+                200,
+                r#"{"info": {"license": "MIT", "version": "3.0"},
+                    "releases": {"1.0": [{"yanked": true, "yanked_reason": "CVE"}],
+                                 "2.0": [{"yanked": false}], "3.0": [{"yanked": false}]}}"#,
+            ),
+            (
+                "/bar",
+                200,
+                r#"{"dist-tags": {"latest": "2.0.0"},
+                    "versions": {"1.0.0": {"license": "MIT", "deprecated": "use 2.x"},
+                                 "2.0.0": {"license": "MIT"}}}"#,
+            ),
+        ];
+        let (base_url, requested) = serve(ROUTES);
+        let dir = tempfile::tempdir().unwrap();
+        let cache_path = dir.path().join("deprecated-versions.json");
+        let mut cache = LicenseCache::load(Some(&cache_path)).unwrap();
+        let options = EnrichOptions {
+            concurrency: 1,
+            pypi_url: Some(base_url.clone()),
+            npm_registry: Some(base_url),
+            check_deprecations: true,
+            ..Default::default()
+        };
+        let record = |manager: &str, name: &str, version: &str| DependencyRecord {
+            manager: manager.to_string(),
+            name: name.to_string(),
+            version: Some(version.to_string()),
+            ..Default::default()
+        };
+        let deprecated = |records: &[DependencyRecord]| -> Vec<Option<String>> {
+            records.iter().map(|r| r.deprecated.clone()).collect()
+        };
+
+        let mut records = vec![
+            record("pip", "foo", "1.0"),
+            record("pip", "foo", "2.0"),
+            record("npm", "bar", "1.0.0"),
+            record("npm", "bar", "2.0.0"),
+        ];
+        enrich_metadata(&mut records, None, &mut cache, &options).unwrap();
+        assert_eq!(
+            deprecated(&records),
+            vec![
+                Some("CVE".to_string()),
+                None,
+                Some("use 2.x".to_string()),
+                None
+            ]
+        );
+        assert_eq!(*requested.lock().unwrap(), vec!["/pypi/foo/json", "/bar"]);
+
+        let mut upgraded = vec![record("pip", "foo", "2.0")];
+        enrich_metadata(&mut upgraded, None, &mut cache, &options).unwrap();
+        assert_eq!(deprecated(&upgraded), vec![None]);
+        assert_eq!(requested.lock().unwrap().len(), 2);
+
+        let mut unchecked = vec![record("pip", "foo", "3.0")];
+        enrich_metadata(&mut unchecked, None, &mut cache, &options).unwrap();
+        assert_eq!(deprecated(&unchecked), vec![None]);
+        assert_eq!(requested.lock().unwrap().len(), 3);
+
+        // 非推奨を確認しない場合は、未確認のバージョンがあってもキャッシュを使う
+        let options = EnrichOptions {
+            check_deprecations: false,
+            ..options
+        };
+        let hits = cache.stats().hits;
+        let mut cached = vec![record("npm", "bar", "3.0.0")];
+        enrich_metadata(&mut cached, None, &mut cache, &options).unwrap();
+        assert_eq!(requested.lock().unwrap().len(), 3);
+        assert_eq!(cache.stats().hits, hits + 1);
+    }
+
+    #[test]
+    fn npm_exact_versions_skip_the_full_package_document() {
+        let (registry, requested) = serve_npm(&[
+            (
+                "/left-pad/1.3.0",
+                200,
+                r#"{"license": "WTFPL", "homepage": "https://github.com/left-pad/left-pad"}"#,
+            ),
+            (
+                "/-/package/left-pad/dist-tags",
+                200,
+                r#"{"latest": "1.3.0"}"#,
+            ),
+        ]);
+
+        let metadata = fetch_npm_metadata(&registry, &npm_job("left-pad", &["1.3.0"]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(metadata.license.as_deref(), Some("WTFPL"));
+        assert_eq!(metadata.latest_version, None);
+        assert_eq!(metadata.provenance, Some(Provenance::NpmRegistry));
+        assert_eq!(*requested.lock().unwrap(), vec!["/left-pad/1.3.0"]);
+
+        let registry = Registry {
+            flag_outdated: true,
+            ..registry
+        };
+        let metadata = fetch_npm_metadata(&registry, &npm_job("left-pad", &["1.3.0"]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(metadata.latest_version.as_deref(), Some("1.3.0"));
+        assert_eq!(
+            requested.lock().unwrap()[1..],
+            ["/left-pad/1.3.0", "/-/package/left-pad/dist-tags"]
+        );
+    }
+
+    #[test]
+    fn npm_dist_tags_errors_keep_the_version_document() {
+        let (registry, requested) = serve_npm(&[
+            ("/left-pad/1.3.0", 200, r#"{"license": "WTFPL"}"#),
+            ("/-/package/left-pad/dist-tags", 500, "{}"),
+        ]);
+        let registry = Registry {
+            flag_outdated: true,
+            ..registry
+        };
+
+        let metadata = fetch_npm_metadata(&registry, &npm_job("left-pad", &["1.3.0"]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(metadata.license.as_deref(), Some("WTFPL"));
+        assert_eq!(metadata.latest_version, None);
+        assert_eq!(
+            *requested.lock().unwrap(),
+            vec!["/left-pad/1.3.0", "/-/package/left-pad/dist-tags"]
+        );
+    }
+
+    #[test]
+    fn npm_version_manifest_errors_fall_back_to_package_document() {
+        let (registry, requested) = serve_npm(&[
+            ("/left-pad/1.3.0", 500, "{}"),
+            (
+                "/left-pad",
+                200,
+                r#"{"dist-tags": {"latest": "1.3.0"}, "versions": {"1.3.0": {"license": "WTFPL"}}}"#,
+            ),
+        ]);
+
+        let metadata = fetch_npm_metadata(&registry, &npm_job("left-pad", &["1.3.0"]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(metadata.license.as_deref(), Some("WTFPL"));
+        assert_eq!(metadata.latest_version.as_deref(), Some("1.3.0"));
+        assert_eq!(
+            *requested.lock().unwrap(),
+            vec!["/left-pad/1.3.0", "/left-pad"]
+        );
+    }
+
+    #[test]
+    fn explain_traces_registry_fields_and_chosen_values() {
+        static ROUTES: &[(&str, u16, &str)] = &[(
+            "/pypi/explain_me/json",
+            200,
+            r#"{"info": {"license": "Apache License 2.0", "version": "1.0.0", "classifiers": ["License :: OSI Approved :: Apache Software License"]}}"#,
+        )];
+        let (base_url, _) = serve(ROUTES);
+        let dir = tempfile::tempdir().unwrap();
+        let cache_path = dir.path().join("explain.json");
+        let mut cache = LicenseCache::load(Some(&cache_path)).unwrap();
+        let options = EnrichOptions {
+            concurrency: 1,
+            pypi_url: Some(base_url),
+            ..Default::default()
+        };
+        let mut records = vec![DependencyRecord {
+            manager: "pip".to_string(),
+            name: "explain_me".to_string(),
+            license: "Unknown".to_string(),
+            ..Default::default()
+        }];
+
+        logging::set_explain_target(Some("Explain-Me"));
+        enrich_metadata(&mut records, None, &mut cache, &options).unwrap();
+        let steps = logging::take_explanation();
+        logging::set_explain_target(None);
+
+        assert_eq!(records[0].license, "Apache-2.0");
+        assert_eq!(
+            steps[0],
+            "[pip] キャッシュ: 該当なし（レジストリに問い合わせます）"
+        );
+        assert!(
+            steps.contains(
+                &"[pip] PyPIのlicense: \"Apache License 2.0\" → Apache-2.0（SPDX IDに正規化）"
+                    .to_string()
+            ),
+            "{steps:?}"
+        );
+        assert_eq!(
+            steps.last().map(String::as_str),
+            Some("[pip] ライセンスをレジストリの値にしました: Apache-2.0")
+        );
+    }
+
+    #[test]
+    fn only_throttling_and_server_errors_are_retried() {
+        assert!(is_retryable_status(StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_retryable_status(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(!is_retryable_status(StatusCode::NOT_FOUND));
+        assert!(!is_retryable_status(StatusCode::OK));
+    }
 }
@@ -0,0 +1,146 @@
+use std::collections::{BTreeMap, HashSet};
+
+use crate::spdx::normalize_spdx;
+use crate::types::DependencyRecord;
+
+/// `licenses/*.txt`はSPDXのライセンス本文のテンプレート。著作権者は各パッケージの配布物で確認します。
+const LICENSE_TEXTS: &[(&str, &str)] = &[
+    ("0BSD", include_str!("../licenses/0BSD.txt")),
+    ("Apache-2.0", include_str!("../licenses/Apache-2.0.txt")),
+    ("BSD-2-Clause", include_str!("../licenses/BSD-2-Clause.txt")),
+    ("BSD-3-Clause", include_str!("../licenses/BSD-3-Clause.txt")),
+    ("ISC", include_str!("../licenses/ISC.txt")),
+    ("MIT", include_str!("../licenses/MIT.txt")),
+    ("Unlicense", include_str!("../licenses/Unlicense.txt")),
+    ("Zlib", include_str!("../licenses/Zlib.txt")),
+];
+
+/// 同梱しているライセンス本文を返します。ネットワークにはアクセスしません。
+/// 本文はテンプレートで、著作権表示は`<year> <copyright holders>`のままです。
+pub fn license_text(license: &str) -> Option<(&'static str, &'static str)> {
+    let id = normalize_spdx(license)?;
+    LICENSE_TEXTS
+        .iter()
+        .find(|(known, _)| *known == id)
+        .copied()
+}
+
+/// ライセンスごとにまとめた帰属表示（NOTICES）の内容。
+#[derive(Debug, Default)]
+pub struct Notices<'a> {
+    pub licenses: BTreeMap<&'static str, (&'static str, Vec<&'a DependencyRecord>)>,
+    /// Unknown・複合ライセンス・本文を同梱していないライセンスの依存。
+    pub needs_review: Vec<&'a DependencyRecord>,
+}
+
+impl<'a> Notices<'a> {
+    pub fn collect(records: &'a [DependencyRecord]) -> Self {
+        let mut notices = Self::default();
+        let mut seen = HashSet::new();
+        for record in records {
+            if !seen.insert((&record.manager, &record.name, &record.version)) {
+                continue;
+            }
+            match license_text(&record.license).filter(|_| !record.has_unknown_license()) {
+                Some((id, text)) => notices
+                    .licenses
+                    .entry(id)
+                    .or_insert_with(|| (text, Vec::new()))
+                    .1
+                    .push(record),
+                None => notices.needs_review.push(record),
+            }
+        }
+        notices
+    }
+
+    pub fn package_count(&self) -> usize {
+        self.licenses
+            .values()
+            .map(|(_, records)| records.len())
+            .sum()
+    }
+
+    pub fn render(&self) -> String {
+        let rule = "=".repeat(80);
+        let mut out = String::from(
+            "THIRD-PARTY SOFTWARE NOTICES\n\nThis file lists the third-party packages used by this project and the full text of their licenses.\n",
+        );
+        for (id, (text, records)) in &self.licenses {
+            out.push_str(&format!("\n{rule}\n{id}\n{rule}\n\nUsed by:\n"));
+            for record in records {
+                out.push_str(&format!("  - {}\n", package_label(record)));
+            }
+            out.push('\n');
+            out.push_str(text.trim_end());
+            out.push('\n');
+        }
+        if !self.needs_review.is_empty() {
+            out.push_str(&format!(
+                "\n{rule}\nPackages requiring manual review\n{rule}\n\n"
+            ));
+            for record in &self.needs_review {
+                out.push_str(&format!(
+                    "  - {}: {}\n",
+                    package_label(record),
+                    record.license
+                ));
+            }
+        }
+        out
+    }
+}
+
+pub fn package_label(record: &DependencyRecord) -> String {
+    match &record.version {
+        Some(version) => format!("{} {version} ({})", record.name, record.manager),
+        None => format!("{} ({})", record.name, record.manager),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(name: &str, license: &str) -> DependencyRecord {
+        DependencyRecord {
+            manager: "npm".to_string(),
+            name: name.to_string(),
+            version: Some("1.0.0".to_string()),
+            license: license.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn groups_bundled_licenses_and_flags_the_rest_for_review() {
+        let records = vec![
+            record("react", "MIT"),
+            record("left-pad", "MIT License"),
+            record("react", "MIT"),
+            record("typescript", "Apache-2.0"),
+            record("dual", "MIT OR Apache-2.0"),
+            record("mystery", "Unknown"),
+            record("copyleft", "GPL-3.0-only"),
+        ];
+        let notices = Notices::collect(&records);
+
+        assert_eq!(
+            notices.licenses.keys().copied().collect::<Vec<_>>(),
+            vec!["Apache-2.0", "MIT"]
+        );
+        assert_eq!(notices.licenses["MIT"].1.len(), 2);
+        assert_eq!(notices.package_count(), 3);
+        let review: Vec<&str> = notices
+            .needs_review
+            .iter()
+            .map(|r| r.name.as_str())
+            .collect();
+        assert_eq!(review, vec!["dual", "mystery", "copyleft"]);
+
+        let text = notices.render();
+        assert!(text.contains("  - left-pad 1.0.0 (npm)\n"));
+        assert!(text.contains("Permission is hereby granted, free of charge"));
+        assert!(text.contains("  - dual 1.0.0 (npm): MIT OR Apache-2.0\n"));
+    }
+}
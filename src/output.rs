@@ -1,191 +1,3035 @@
-use std::fs;
+use std::cmp::Ordering as CmpOrdering;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{self, File};
+use std::io::{self, BufWriter, IsTerminal, Write};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use anyhow::{Context, Result};
-use comfy_table::{Attribute, Cell, Color, Table, presets::UTF8_BORDERS_ONLY};
+use anyhow::{Context, Result, bail};
+use base64::prelude::{BASE64_STANDARD, Engine as _};
+use clap::ValueEnum;
+use comfy_table::{Attribute, Cell, Color, ContentArrangement, Table, presets::UTF8_BORDERS_ONLY};
+use serde::Serialize;
+use serde_json::{Value, json};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
-use crate::types::DependencyRecord;
+use license_scout::cache::CacheStats;
+use license_scout::compat::{Compatibility, ProjectLicense};
+use license_scout::diff::DependencyDiff;
+use license_scout::homepage::BrokenHomepage;
+use license_scout::logging;
+use license_scout::notices::{Notices, package_label};
+use license_scout::policy::{Severity, Violation};
+use license_scout::scan::ScannedFile;
+use license_scout::spdx::{
+    Category, LicenseExpr, Obligation, license_category, license_obligations, non_spdx_terms,
+    normalize_spdx,
+};
+use license_scout::version::compare_versions;
+use license_scout::{DependencyRecord, Provenance};
 
-pub fn print_table(
+static COLOR_ENABLED: AtomicBool = AtomicBool::new(true);
+
+pub fn set_color_enabled(enabled: bool) {
+    COLOR_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+fn color_enabled() -> bool {
+    COLOR_ENABLED.load(Ordering::Relaxed)
+}
+
+fn new_table() -> Table {
+    let mut table = Table::new();
+    table.load_preset(UTF8_BORDERS_ONLY);
+    if !color_enabled() {
+        table.force_no_tty();
+    }
+    table
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    Table,
+    Json,
+    Csv,
+    Markdown,
+}
+
+/// レポートの形式ごとの書き出し処理。書き出し先は標準出力・ファイル・テスト用のバッファなど任意の`Write`です。
+pub trait ReportWriter {
+    /// 状況メッセージ・エラーメッセージに使う形式名（例: `Markdown`）。
+    fn label(&self) -> &'static str;
+
+    fn write_report(&self, records: &[DependencyRecord], out: &mut dyn Write) -> Result<()>;
+}
+
+/// 標準出力にレポートを書き出します。
+pub fn print_report(writer: &dyn ReportWriter, records: &[DependencyRecord]) -> Result<()> {
+    let mut out = BufWriter::new(io::stdout().lock());
+    writer
+        .write_report(records, &mut out)
+        .and_then(|()| Ok(out.flush()?))
+        .with_context(|| format!("{}の標準出力に失敗しました", writer.label()))
+}
+
+/// レポートをファイルに書き出します。`-`なら標準出力。
+pub fn save_report(
+    writer: &dyn ReportWriter,
     records: &[DependencyRecord],
-    cwd: &Path,
-    search_paths: &[PathBuf],
-    home_dir: Option<&Path>,
-    hide_source: bool,
+    output_path: &Path,
 ) -> Result<()> {
-    if records.is_empty() {
-        println!("依存関係は見つかりませんでした。");
-        return Ok(());
+    if output_path == Path::new("-") {
+        return print_report(writer, records);
     }
+    let label = writer.label();
+    let file = File::create(output_path)
+        .with_context(|| format!("{label}ファイルの作成に失敗: {}", output_path.display()))?;
+    let mut out = BufWriter::new(file);
+    writer
+        .write_report(records, &mut out)
+        .and_then(|()| Ok(out.flush()?))
+        .with_context(|| format!("{label}ファイルの書き込みに失敗: {}", output_path.display()))?;
+    logging::status(format!(
+        "{label}を{}に書き出しました。",
+        output_path.display()
+    ));
+    Ok(())
+}
 
-    let mut table = Table::new();
-    table.load_preset(UTF8_BORDERS_ONLY);
-    let mut header = vec![
-        Cell::new("Manager").add_attribute(Attribute::Bold),
-        Cell::new("Name").add_attribute(Attribute::Bold),
-        Cell::new("Version").add_attribute(Attribute::Bold),
-        Cell::new("License").add_attribute(Attribute::Bold),
-        Cell::new("Homepage").add_attribute(Attribute::Bold),
-    ];
-    if !hide_source {
-        header.push(Cell::new("Source").add_attribute(Attribute::Bold));
+/// ソースのパスを表示用に短縮するための情報。
+#[derive(Debug, Clone, Copy)]
+pub struct SourcePaths<'a> {
+    pub cwd: &'a Path,
+    pub search_paths: &'a [PathBuf],
+    pub home_dir: Option<&'a Path>,
+}
+
+impl SourcePaths<'_> {
+    fn shorten(&self, path: &Path) -> String {
+        shorten_source_path(path, self.cwd, self.search_paths, self.home_dir)
     }
-    table.set_header(header);
+}
 
-    for record in records {
-        let manager_cell = colorize_manager(&record.manager);
-        let license_cell = colorize_license(&record.license);
-        let version_cell = Cell::new(record.version.clone().unwrap_or_else(|| "-".to_string()));
+const NO_DEPENDENCIES: &str = "依存関係は見つかりませんでした。";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum GroupBy {
+    License,
+    Manager,
+    Name,
+}
+
+/// `--search-field`で検索対象を絞る項目。
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum SearchField {
+    Manager,
+    Name,
+    License,
+    Version,
+    Homepage,
+    Source,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum SortField {
+    Manager,
+    Name,
+    Version,
+    License,
+    Source,
+}
+
+/// 指定した項目で並べ替えます。同じ値の間では既定の並び順（マネージャ→名前→バージョン→ソース）を保ちます。
+pub fn sort_records_by(records: &mut [DependencyRecord], field: Option<SortField>, reverse: bool) {
+    let Some(field) = field else {
+        if reverse {
+            records.reverse();
+        }
+        return;
+    };
+    records.sort_by(|a, b| {
+        let ordering = match field {
+            SortField::Manager => a.manager.cmp(&b.manager),
+            SortField::Name => a.name.cmp(&b.name),
+            SortField::License => a.license.cmp(&b.license),
+            SortField::Source => a.source.cmp(&b.source),
+            SortField::Version => {
+                return match (&a.version, &b.version) {
+                    (Some(left), Some(right)) => {
+                        let ordering = compare_versions(left, right);
+                        if reverse {
+                            ordering.reverse()
+                        } else {
+                            ordering
+                        }
+                    }
+                    (Some(_), None) => CmpOrdering::Less,
+                    (None, Some(_)) => CmpOrdering::Greater,
+                    (None, None) => CmpOrdering::Equal,
+                };
+            }
+        };
+        if reverse {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    });
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TableOptions {
+    pub hide_source: bool,
+    pub show_latest: bool,
+    pub show_direct: bool,
+    pub show_dev: bool,
+    pub show_description: bool,
+    pub show_severity: bool,
+    /// `--project-license`との互換性（Compat列）。
+    pub show_compat: bool,
+    pub show_provenance: bool,
+    pub group_by: Option<GroupBy>,
+    /// Homepage列の最大表示幅（桁数）。0なら省略しません。
+    pub url_width: usize,
+    /// Homepage列をOSC 8のハイパーリンクにします（色付けが有効な端末のみ）。
+    pub hyperlinks: bool,
+    /// 表全体の幅（桁数）。指定されていれば列内で折り返して収め、狭い場合は優先度の低い列を省きます。
+    pub width: Option<u16>,
+}
+
+/// 表の幅がこれ未満ならHomepage列を省きます。
+const HOMEPAGE_MIN_WIDTH: u16 = 100;
+/// 表の幅がこれ未満ならSource列も省きます。
+const SOURCE_MIN_WIDTH: u16 = 72;
+
+impl TableOptions {
+    fn show_homepage(&self) -> bool {
+        self.width.is_none_or(|width| width >= HOMEPAGE_MIN_WIDTH)
+    }
+
+    /// 幅に収まらない列を省いた設定。
+    fn fit_width(&self) -> Self {
+        Self {
+            hide_source: self.hide_source || self.width.is_some_and(|w| w < SOURCE_MIN_WIDTH),
+            ..*self
+        }
+    }
+
+    fn headers(&self) -> Vec<&'static str> {
+        let mut header = vec!["Manager", "Name", "Version"];
+        if self.show_latest {
+            header.push("Latest");
+        }
+        if self.show_direct {
+            header.push("Direct");
+        }
+        if self.show_dev {
+            header.push("Dev");
+        }
+        header.push("License");
+        if self.show_severity {
+            header.push("Severity");
+        }
+        if self.show_compat {
+            header.push("Compat");
+        }
+        if self.show_provenance {
+            header.push("Provenance");
+        }
+        if self.show_homepage() {
+            header.push("Homepage");
+        }
+        if self.show_description {
+            header.push("Description");
+        }
+        if !self.hide_source {
+            header.push("Source");
+        }
+        header
+    }
+}
+
+pub struct TableWriter<'a> {
+    pub paths: SourcePaths<'a>,
+    pub options: TableOptions,
+}
+
+impl ReportWriter for TableWriter<'_> {
+    fn label(&self) -> &'static str {
+        "テーブル"
+    }
+
+    fn write_report(&self, records: &[DependencyRecord], out: &mut dyn Write) -> Result<()> {
+        if records.is_empty() {
+            writeln!(out, "{NO_DEPENDENCIES}")?;
+            return Ok(());
+        }
+        writeln!(out, "{}", render_table(records, self.paths, &self.options))?;
+        Ok(())
+    }
+}
+
+fn render_table(records: &[DependencyRecord], paths: SourcePaths, options: &TableOptions) -> Table {
+    let options = &options.fit_width();
+    let mut table = new_table();
+    if let Some(width) = options.width {
+        table
+            .set_content_arrangement(ContentArrangement::Dynamic)
+            .set_width(width);
+    }
+    let header: Vec<Cell> = options
+        .headers()
+        .into_iter()
+        .map(|title| Cell::new(title).add_attribute(Attribute::Bold))
+        .collect();
+    let column_count = header.len();
+    table.set_header(header);
 
+    let table_row = |record: &DependencyRecord| {
         let mut row = vec![
-            manager_cell,
-            Cell::new(record.name.clone()),
-            version_cell,
-            license_cell,
-            homepage_cell(&record.homepage),
+            colorize_manager(&record.manager),
+            name_cell(record),
+            Cell::new(record.version.clone().unwrap_or_else(|| "-".to_string())),
         ];
+        if options.show_latest {
+            row.push(latest_cell(&record.outdated));
+        }
+        if options.show_direct {
+            row.push(Cell::new(flag_label(record.direct)));
+        }
+        if options.show_dev {
+            row.push(Cell::new(flag_label(record.dev)));
+        }
+        row.push(license_cell(record));
+        if options.show_severity {
+            row.push(severity_cell(record.severity));
+        }
+        if options.show_compat {
+            row.push(compat_cell(record.compatibility));
+        }
+        if options.show_provenance {
+            row.push(Cell::new(provenance_label(record.provenance)));
+        }
+        if options.show_homepage() {
+            row.push(homepage_cell(record, options.url_width, options.hyperlinks));
+        }
+        if options.show_description {
+            row.push(Cell::new(
+                record
+                    .description
+                    .as_deref()
+                    .map_or_else(|| "-".to_string(), shorten_description),
+            ));
+        }
 
-        if !hide_source {
-            let display_source = shorten_source_path(&record.source, cwd, search_paths, home_dir);
-            row.push(Cell::new(display_source));
+        if !options.hide_source {
+            row.push(Cell::new(paths.shorten(&record.source)));
         }
+        row
+    };
 
-        table.add_row(row);
+    match options.group_by {
+        Some(field) => {
+            for (key, members) in group_records(records, field) {
+                let mut heading = vec![
+                    Cell::new(format!("■ {key} ({}件)", format_count(members.len())))
+                        .add_attribute(Attribute::Bold),
+                ];
+                heading.resize_with(column_count, || Cell::new(""));
+                table.add_row(heading);
+                for record in members {
+                    table.add_row(table_row(record));
+                }
+            }
+        }
+        None => {
+            for record in records {
+                table.add_row(table_row(record));
+            }
+        }
     }
+    table
+}
 
-    println!("{table}");
-    Ok(())
+fn group_records(
+    records: &[DependencyRecord],
+    field: GroupBy,
+) -> Vec<(String, Vec<&DependencyRecord>)> {
+    let mut groups: BTreeMap<String, Vec<&DependencyRecord>> = BTreeMap::new();
+    for record in records {
+        let key = match field {
+            GroupBy::License if record.has_unknown_license() => "Unknown".to_string(),
+            GroupBy::License => record.license.clone(),
+            GroupBy::Manager => record.manager.clone(),
+            GroupBy::Name => record.name.clone(),
+        };
+        groups.entry(key).or_default().push(record);
+    }
+    groups.into_iter().collect()
 }
 
-fn shorten_source_path(
-    source: &Path,
+#[derive(Default)]
+struct ProjectUsage {
+    versions: BTreeSet<String>,
+    licenses: BTreeSet<String>,
+    projects: BTreeSet<String>,
+}
+
+pub struct TreeWriter<'a> {
+    pub paths: SourcePaths<'a>,
+}
+
+impl ReportWriter for TreeWriter<'_> {
+    fn label(&self) -> &'static str {
+        "ツリー"
+    }
+
+    fn write_report(&self, records: &[DependencyRecord], out: &mut dyn Write) -> Result<()> {
+        if records.is_empty() {
+            writeln!(out, "{NO_DEPENDENCIES}")?;
+            return Ok(());
+        }
+        let SourcePaths {
+            cwd,
+            search_paths,
+            home_dir,
+        } = self.paths;
+        out.write_all(
+            render_tree(records, cwd, search_paths, home_dir, color_enabled()).as_bytes(),
+        )?;
+        Ok(())
+    }
+}
+
+fn render_tree(
+    records: &[DependencyRecord],
     cwd: &Path,
     search_paths: &[PathBuf],
     home_dir: Option<&Path>,
+    color: bool,
 ) -> String {
-    if let Some(rel) = strip_relative(source, cwd) {
-        return rel;
+    let mut by_source: BTreeMap<String, Vec<&DependencyRecord>> = BTreeMap::new();
+    for record in records {
+        let source = shorten_source_path(&record.source, cwd, search_paths, home_dir);
+        by_source.entry(source).or_default().push(record);
     }
 
-    for base in search_paths {
-        if let Some(rel) = source.strip_prefix(base).ok() {
-            let rel_text = rel.display().to_string();
-            if let Some(name) = base.file_name().map(|n| n.to_string_lossy()) {
-                if rel_text.is_empty() {
-                    return name.into_owned();
-                }
-                return format!("{}/{}", name, rel_text);
-            } else if !rel_text.is_empty() {
-                return rel_text;
+    let mut out = String::new();
+    for (source, records) in by_source {
+        out.push_str(&format!("{source} ({}件)\n", records.len()));
+        for (index, record) in records.iter().enumerate() {
+            let branch = if index + 1 == records.len() {
+                "└──"
+            } else {
+                "├──"
+            };
+            let version = record
+                .version
+                .as_deref()
+                .map(|version| format!(" {version}"))
+                .unwrap_or_default();
+            let license = if !color {
+                record.license.clone()
+            } else if let Some(colored) = colored_license_expression(&record.license) {
+                colored
+            } else {
+                let (color, bold) = license_style(&record.license);
+                paint(&record.license, color, bold)
+            };
+            out.push_str(&format!("{branch} {}{version} ({license})\n", record.name));
+        }
+    }
+    out
+}
+
+pub struct ByProjectWriter<'a> {
+    pub paths: SourcePaths<'a>,
+}
+
+impl ReportWriter for ByProjectWriter<'_> {
+    fn label(&self) -> &'static str {
+        "プロジェクト別一覧"
+    }
+
+    fn write_report(&self, records: &[DependencyRecord], out: &mut dyn Write) -> Result<()> {
+        if records.is_empty() {
+            writeln!(out, "{NO_DEPENDENCIES}")?;
+            return Ok(());
+        }
+        writeln!(out, "{}", render_by_project(records, self.paths))?;
+        Ok(())
+    }
+}
+
+fn render_by_project(records: &[DependencyRecord], paths: SourcePaths) -> Table {
+    let mut usages: BTreeMap<(&str, &str), ProjectUsage> = BTreeMap::new();
+    for record in records {
+        let usage = usages
+            .entry((record.manager.as_str(), record.name.as_str()))
+            .or_default();
+        if let Some(version) = &record.version {
+            usage.versions.insert(version.clone());
+        }
+        usage.licenses.insert(record.license.clone());
+        let project_root = record.source.parent().unwrap_or(&record.source);
+        usage.projects.insert(paths.shorten(project_root));
+    }
+
+    let mut table = new_table();
+    table.set_header(vec![
+        Cell::new("Manager").add_attribute(Attribute::Bold),
+        Cell::new("Name").add_attribute(Attribute::Bold),
+        Cell::new("Versions").add_attribute(Attribute::Bold),
+        Cell::new("License").add_attribute(Attribute::Bold),
+        Cell::new("Projects").add_attribute(Attribute::Bold),
+    ]);
+
+    for ((manager, name), usage) in &usages {
+        let versions = if usage.versions.is_empty() {
+            "-".to_string()
+        } else {
+            join_set(&usage.versions, ", ")
+        };
+        let projects = usage
+            .projects
+            .iter()
+            .map(|project| if project.is_empty() { "." } else { project })
+            .collect::<Vec<_>>()
+            .join("\n");
+        table.add_row(vec![
+            colorize_manager(manager),
+            Cell::new(*name),
+            Cell::new(versions),
+            colorize_license(&join_set(&usage.licenses, " / ")),
+            Cell::new(format!("({}) {projects}", usage.projects.len())),
+        ]);
+    }
+    table
+}
+
+/// ソースファイルごとの依存件数とライセンス別件数を書き出します。
+pub struct BySourceWriter<'a> {
+    pub paths: SourcePaths<'a>,
+}
+
+impl ReportWriter for BySourceWriter<'_> {
+    fn label(&self) -> &'static str {
+        "ソースファイル別集計"
+    }
+
+    fn write_report(&self, records: &[DependencyRecord], out: &mut dyn Write) -> Result<()> {
+        if records.is_empty() {
+            writeln!(out, "{NO_DEPENDENCIES}")?;
+            return Ok(());
+        }
+        let SourcePaths {
+            cwd,
+            search_paths,
+            home_dir,
+        } = self.paths;
+        for (index, summary) in source_summaries(records, cwd, search_paths, home_dir)
+            .into_iter()
+            .enumerate()
+        {
+            if index > 0 {
+                writeln!(out)?;
             }
+            writeln!(
+                out,
+                "{} ({}件)",
+                summary.source,
+                format_count(summary.total)
+            )?;
+            let mut table = new_table();
+            table.set_header(vec![
+                Cell::new("License").add_attribute(Attribute::Bold),
+                Cell::new("Count").add_attribute(Attribute::Bold),
+            ]);
+            for (license, count) in summary.histogram {
+                table.add_row(vec![
+                    colorize_license(&license),
+                    Cell::new(format_count(count)),
+                ]);
+            }
+            writeln!(out, "{table}")?;
+        }
+        Ok(())
+    }
+}
+
+/// 件数の多いライセンスの上位だけを、順位・件数・割合の一覧で表示します。
+pub struct TopLicensesWriter {
+    pub limit: usize,
+}
+
+impl ReportWriter for TopLicensesWriter {
+    fn label(&self) -> &'static str {
+        "ライセンス上位一覧"
+    }
+
+    fn write_report(&self, records: &[DependencyRecord], out: &mut dyn Write) -> Result<()> {
+        if records.is_empty() {
+            writeln!(out, "{NO_DEPENDENCIES}")?;
+            return Ok(());
+        }
+        let mut table = new_table();
+        table.set_header(vec![
+            Cell::new("#").add_attribute(Attribute::Bold),
+            Cell::new("License").add_attribute(Attribute::Bold),
+            Cell::new("Count").add_attribute(Attribute::Bold),
+            Cell::new("Share").add_attribute(Attribute::Bold),
+        ]);
+        for (rank, (license, count, percent)) in
+            top_licenses(records, self.limit).into_iter().enumerate()
+        {
+            table.add_row(vec![
+                Cell::new(rank + 1),
+                colorize_license(&license),
+                Cell::new(format_count(count)),
+                Cell::new(format!("{percent:.1}%")),
+            ]);
         }
+        writeln!(out, "{table}")?;
+        Ok(())
     }
+}
+
+/// 件数順の上位`limit`件のライセンスと件数・全体に占める割合（%）。
+fn top_licenses(records: &[DependencyRecord], limit: usize) -> Vec<(String, usize, f64)> {
+    let total = records.len().max(1) as f64;
+    license_histogram(records)
+        .into_iter()
+        .take(limit)
+        .map(|(license, count)| {
+            let percent = count as f64 * 100.0 / total;
+            (license, count, percent)
+        })
+        .collect()
+}
+
+/// ライセンスごとの義務の一覧と、義務ごとにそれに該当する依存の一覧を表示します（`--obligations`）。
+pub struct ObligationsWriter;
+
+impl ReportWriter for ObligationsWriter {
+    fn label(&self) -> &'static str {
+        "義務チェックリスト"
+    }
+
+    fn write_report(&self, records: &[DependencyRecord], out: &mut dyn Write) -> Result<()> {
+        if records.is_empty() {
+            writeln!(out, "{NO_DEPENDENCIES}")?;
+            return Ok(());
+        }
+        let mut table = new_table();
+        table.set_header(vec![
+            Cell::new("License").add_attribute(Attribute::Bold),
+            Cell::new("Count").add_attribute(Attribute::Bold),
+            Cell::new("Obligations").add_attribute(Attribute::Bold),
+        ]);
+        for (license, count) in license_histogram(records) {
+            let obligations = license_obligations(&license);
+            let names = if obligations.is_empty() {
+                "-".to_string()
+            } else {
+                obligations
+                    .iter()
+                    .map(|obligation| obligation.name())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+            table.add_row(vec![
+                colorize_license(&license),
+                Cell::new(format_count(count)),
+                Cell::new(names),
+            ]);
+        }
+        writeln!(out, "{table}")?;
 
-    if let Some(home) = home_dir {
-        if let Some(rel) = strip_relative(source, home) {
-            if rel.is_empty() {
-                return "~".to_string();
+        for (obligation, packages) in obligation_checklist(records) {
+            writeln!(
+                out,
+                "\n{} ({}): {}件",
+                obligation.description(),
+                obligation.name(),
+                format_count(packages.len())
+            )?;
+            for package in packages {
+                writeln!(out, "  - {package}")?;
             }
-            return format!("~/{}", rel);
         }
+        Ok(())
     }
+}
 
-    source.display().to_string()
+/// 義務ごとに、それに該当する依存（`name version (manager) [license]`、重複なし）を並べます。
+fn obligation_checklist(records: &[DependencyRecord]) -> Vec<(Obligation, Vec<String>)> {
+    let mut packages: BTreeMap<Obligation, BTreeSet<String>> = BTreeMap::new();
+    for record in records {
+        let license = if record.has_unknown_license() {
+            "Unknown"
+        } else {
+            record.license.as_str()
+        };
+        for obligation in license_obligations(license) {
+            packages.entry(*obligation).or_default().insert(format!(
+                "{} {} ({}) [{license}]",
+                record.name,
+                record.version.as_deref().unwrap_or("-"),
+                record.manager
+            ));
+        }
+    }
+    packages
+        .into_iter()
+        .map(|(obligation, packages)| (obligation, packages.into_iter().collect()))
+        .collect()
 }
 
-fn strip_relative(source: &Path, base: &Path) -> Option<String> {
-    source
-        .strip_prefix(base)
-        .ok()
-        .map(|p| p.display().to_string())
+#[derive(Debug, PartialEq)]
+struct SourceSummary {
+    source: String,
+    total: usize,
+    histogram: Vec<(String, usize)>,
 }
 
-fn colorize_manager(manager: &str) -> Cell {
-    match manager {
-        "pip" => Cell::new(manager)
-            .fg(Color::Cyan)
-            .add_attribute(Attribute::Bold),
-        "npm" => Cell::new(manager)
-            .fg(Color::Green)
-            .add_attribute(Attribute::Bold),
-        _ => Cell::new(manager).fg(Color::White),
+fn source_summaries(
+    records: &[DependencyRecord],
+    cwd: &Path,
+    search_paths: &[PathBuf],
+    home_dir: Option<&Path>,
+) -> Vec<SourceSummary> {
+    let mut by_source: BTreeMap<String, Vec<&DependencyRecord>> = BTreeMap::new();
+    for record in records {
+        let source = shorten_source_path(&record.source, cwd, search_paths, home_dir);
+        by_source.entry(source).or_default().push(record);
     }
+    by_source
+        .into_iter()
+        .map(|(source, records)| SourceSummary {
+            total: records.len(),
+            histogram: license_histogram(records),
+            source,
+        })
+        .collect()
 }
 
-fn colorize_license(license: &str) -> Cell {
-    let lower = license.to_ascii_lowercase();
+fn join_set(values: &BTreeSet<String>, separator: &str) -> String {
+    values.iter().cloned().collect::<Vec<_>>().join(separator)
+}
+
+pub fn print_broken_homepages(broken: &[BrokenHomepage], url_width: usize) {
+    if broken.is_empty() {
+        println!("リンク切れのホームページはありません。");
+        return;
+    }
+
+    let mut table = new_table();
+    table.set_header(vec![
+        Cell::new("Homepage").add_attribute(Attribute::Bold),
+        Cell::new("Status").add_attribute(Attribute::Bold),
+        Cell::new("Packages").add_attribute(Attribute::Bold),
+    ]);
+    for entry in broken {
+        table.add_row(vec![
+            Cell::new(truncate_width(&entry.url, url_width)),
+            styled(&entry.reason, Color::Red, false),
+            Cell::new(entry.packages.join("\n")),
+        ]);
+    }
+
+    println!("リンク切れのホームページ: {}件", broken.len());
+    println!("{table}");
+}
+
+/// 重大度がwarn / reviewの依存を報告します。終了コードには影響しません。
+pub fn print_severity_notes(records: &[DependencyRecord]) {
+    for (severity, title) in [
+        (Severity::Review, "要確認（review）"),
+        (Severity::Warn, "警告（warn）"),
+    ] {
+        let matched: Vec<&DependencyRecord> = records
+            .iter()
+            .filter(|record| record.severity == Some(severity))
+            .collect();
+        if matched.is_empty() {
+            continue;
+        }
+        eprintln!("⚠ {title}のライセンス: {}件", matched.len());
+        for record in matched {
+            eprintln!("  - {}: {}", package_label(record), record.license);
+        }
+    }
+}
 
-    if lower.contains("gpl") {
-        return Cell::new(license)
-            .fg(Color::Red)
-            .add_attribute(Attribute::Bold);
+/// `--reconcile`: ロックファイルとレジストリでライセンスが異なる依存を一覧表示します。
+pub fn print_license_discrepancies(records: &[DependencyRecord]) -> usize {
+    let differing: Vec<&DependencyRecord> = records
+        .iter()
+        .filter(|record| record.registry_license.is_some())
+        .collect();
+    if !differing.is_empty() {
+        eprintln!(
+            "⚠ ロックファイルとレジストリでライセンスが異なる依存: {}件",
+            differing.len()
+        );
+        for record in &differing {
+            eprintln!(
+                "  - {}: ロックファイル {} / レジストリ {}",
+                package_label(record),
+                record.license,
+                record.registry_license.as_deref().unwrap_or_default()
+            );
+        }
     }
+    differing.len()
+}
 
-    if lower.contains("mit") {
-        return Cell::new(license)
-            .fg(Color::Green)
-            .add_attribute(Attribute::Bold);
+/// `--validate-spdx`: ライセンスがSPDX ID・SPDX式として認識できない依存を一覧表示します（Unknownは除く）。
+pub fn print_non_spdx(records: &[DependencyRecord]) -> usize {
+    let invalid: Vec<(&DependencyRecord, Vec<String>)> = records
+        .iter()
+        .filter(|record| !record.has_unknown_license())
+        .map(|record| (record, non_spdx_terms(&record.license)))
+        .filter(|(_, terms)| !terms.is_empty())
+        .collect();
+    if !invalid.is_empty() {
+        eprintln!("⚠ SPDXとして認識できないライセンス: {}件", invalid.len());
+        for (record, terms) in &invalid {
+            if terms.len() == 1 && terms[0] == record.license.trim() {
+                eprintln!("  - {}: {}", package_label(record), record.license);
+            } else {
+                eprintln!(
+                    "  - {}: {} (認識できない部分: {})",
+                    package_label(record),
+                    record.license,
+                    terms.join(", ")
+                );
+            }
+        }
     }
+    invalid.len()
+}
 
-    if lower.contains("bsd") {
-        return Cell::new(license)
-            .fg(Color::Blue)
-            .add_attribute(Attribute::Bold);
+/// `--explain`: 対象パッケージの処理過程と最終的な値を表示します。
+pub fn print_explanation(name: &str, steps: &[String], records: &[&DependencyRecord]) {
+    if records.is_empty() && steps.is_empty() {
+        println!("{name}に一致する依存は見つかりませんでした");
+        return;
+    }
+    println!("{name}の処理過程:");
+    for (index, step) in steps.iter().enumerate() {
+        println!("  {}. {step}", index + 1);
+    }
+    if records.is_empty() {
+        println!("最終結果: 一覧には含まれません");
+        return;
+    }
+    println!("最終結果:");
+    for record in records {
+        println!("  - {}", package_label(record));
+        println!(
+            "    license: {} (provenance: {})",
+            record.license,
+            provenance_label(record.provenance)
+        );
+        for (label, value) in [
+            ("homepage", &record.homepage),
+            ("latest", &record.outdated),
+            ("deprecated", &record.deprecated),
+            ("description", &record.description),
+        ] {
+            if let Some(value) = value {
+                println!("    {label}: {value}");
+            }
+        }
+        println!("    source: {}", record.source.display());
     }
+}
 
-    if lower.contains("apache") {
-        return Cell::new(license)
-            .fg(Color::Cyan)
-            .add_attribute(Attribute::Bold);
+/// `--project-license`と互換性の無い依存を一覧表示し、件数を返します。
+pub fn print_incompatible(records: &[DependencyRecord], project: &ProjectLicense) -> usize {
+    let incompatible: Vec<&DependencyRecord> = records
+        .iter()
+        .filter(|record| record.compatibility == Some(Compatibility::Incompatible))
+        .collect();
+    if !incompatible.is_empty() {
+        eprintln!(
+            "✘ プロジェクトのライセンス（{}）と互換性の無い依存: {}件",
+            project.id(),
+            incompatible.len()
+        );
+        for record in &incompatible {
+            eprintln!("  - {}: {}", package_label(record), record.license);
+        }
     }
+    incompatible.len()
+}
 
-    if lower == "unknown" {
-        return Cell::new(license)
-            .fg(Color::Yellow)
-            .add_attribute(Attribute::Bold);
+/// 承認済みの例外は理由とともに別に表示し、違反の件数には含めません。
+pub fn print_violations(violations: &[Violation]) {
+    let (approved, violations): (Vec<_>, Vec<_>) = violations
+        .iter()
+        .partition(|violation| violation.is_approved());
+    let line = |violation: &Violation, note: &str| {
+        eprintln!(
+            "  - {} {} {}: {} ({note})",
+            violation.manager,
+            violation.name,
+            violation.version.as_deref().unwrap_or("-"),
+            violation.license,
+        );
+    };
+    if !approved.is_empty() {
+        eprintln!("承認済みの例外: {}件", approved.len());
+        for violation in approved {
+            let reason = violation.exception.as_deref().unwrap_or_default();
+            line(
+                violation,
+                &format!("approved exception ({reason}) / {}", violation.rule),
+            );
+        }
+    }
+    if !violations.is_empty() {
+        eprintln!("✘ ライセンスポリシー違反: {}件", violations.len());
+        for violation in violations {
+            line(violation, &violation.rule);
+        }
     }
+}
 
-    Cell::new(license).fg(Color::Magenta)
+/// `--diff-format`で選ぶベースラインとの差分の表示形式。
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum DiffFormat {
+    /// `+`追加・`-`削除・`~`変更の1行1件（ライセンスの変更は黄色）
+    #[default]
+    Text,
+    /// `added` / `removed` / `changed`を持つJSON
+    Json,
 }
 
-fn homepage_cell(homepage: &Option<String>) -> Cell {
-    match homepage {
-        Some(url) => Cell::new(shorten_url(url)),
-        None => Cell::new("-"),
+pub fn print_diff(diff: &DependencyDiff, format: DiffFormat) -> Result<()> {
+    match format {
+        DiffFormat::Text => print!("\n{}", render_diff(diff, color_enabled())),
+        DiffFormat::Json => println!("{}", diff_json(diff)?),
     }
+    Ok(())
 }
 
-fn shorten_url(url: &str) -> String {
-    const MAX_CHARS: usize = 60;
-    let mut buf = String::new();
-    for (idx, ch) in url.chars().enumerate() {
-        if idx >= MAX_CHARS {
-            buf.push_str("...");
-            return buf;
+/// 追加は緑、削除は赤、ライセンスの変更は黄色で、変更前後の値を並べて表示します。
+pub fn render_diff(diff: &DependencyDiff, color: bool) -> String {
+    let mut out = String::from("ベースラインとの差分:\n");
+    if diff.is_empty() {
+        out.push_str("  変更はありません。\n");
+        return out;
+    }
+    out.push_str(&format!(
+        "  追加: {}件 / 削除: {}件 / 変更: {}件\n",
+        diff.added.len(),
+        diff.removed.len(),
+        diff.changed.len()
+    ));
+    let mut push_line = |line: String, color_code: Option<Color>| {
+        match color_code.filter(|_| color) {
+            Some(color_code) => out.push_str(&paint(&line, color_code, false)),
+            None => out.push_str(&line),
+        }
+        out.push('\n');
+    };
+    for (prefix, color_code, entries) in [
+        ('+', Color::Green, &diff.added),
+        ('-', Color::Red, &diff.removed),
+    ] {
+        for entry in entries {
+            push_line(
+                format!(
+                    "{prefix} {} {} {}: {}",
+                    entry.manager, entry.name, entry.version, entry.license
+                ),
+                Some(color_code),
+            );
         }
-        buf.push(ch);
     }
-    buf
+    for change in &diff.changed {
+        let version = if change.old_version == change.new_version {
+            change.new_version.clone()
+        } else {
+            format!("{} -> {}", change.old_version, change.new_version)
+        };
+        let (license, color_code) = if change.license_changed() {
+            (
+                format!("{} -> {}", change.old_license, change.new_license),
+                Some(Color::Yellow),
+            )
+        } else {
+            (change.new_license.clone(), None)
+        };
+        push_line(
+            format!("~ {} {} {version}: {license}", change.manager, change.name),
+            color_code,
+        );
+    }
+    out
 }
 
-pub fn output_json(
-    records: &[DependencyRecord],
-    print_json: bool,
-    output_path: Option<&Path>,
-) -> Result<()> {
-    if !print_json && output_path.is_none() {
+fn diff_json(diff: &DependencyDiff) -> Result<String> {
+    Ok(serde_json::to_string_pretty(diff)?)
+}
+
+pub fn output_diff_json(diff: &DependencyDiff, output_path: &Path) -> Result<()> {
+    let json = diff_json(diff)?;
+    if output_path == Path::new("-") {
+        println!("{json}");
         return Ok(());
     }
+    fs::write(output_path, json)
+        .with_context(|| format!("差分JSONの書き込みに失敗: {}", output_path.display()))?;
+    logging::status(format!(
+        "ベースラインとの差分を{}に書き出しました。",
+        output_path.display()
+    ));
+    Ok(())
+}
 
-    let json = serde_json::to_string_pretty(records)?;
-    if let Some(path) = output_path {
-        fs::write(path, &json)
-            .with_context(|| format!("JSONファイルの書き込みに失敗: {}", path.display()))?;
-        println!("JSONを{}に書き出しました。", path.display());
+pub fn output_policy_report(violations: &[Violation], output_path: &Path) -> Result<()> {
+    let json = serde_json::to_string_pretty(violations)?;
+    fs::write(output_path, json).with_context(|| {
+        format!(
+            "ポリシー違反レポートの書き込みに失敗: {}",
+            output_path.display()
+        )
+    })?;
+    logging::status(format!(
+        "ポリシー違反レポート（{}件）を{}に書き出しました。",
+        violations.len(),
+        output_path.display()
+    ));
+    Ok(())
+}
+
+pub fn name_collisions(records: &[DependencyRecord]) -> Vec<(String, Vec<String>)> {
+    let mut managers_by_name: BTreeMap<String, BTreeSet<&str>> = BTreeMap::new();
+    for record in records {
+        managers_by_name
+            .entry(record.name.to_ascii_lowercase())
+            .or_default()
+            .insert(record.manager.as_str());
     }
 
-    if print_json {
-        println!("JSON出力:\n{json}");
+    managers_by_name
+        .into_iter()
+        .filter(|(_, managers)| managers.len() > 1)
+        .map(|(name, managers)| (name, managers.into_iter().map(|m| m.to_string()).collect()))
+        .collect()
+}
+
+/// 値（バージョンやライセンス）ごとの検出元ファイル。
+pub type ConflictSources = Vec<(String, Vec<PathBuf>)>;
+
+/// 同じ`(manager, name)`が複数のバージョンで使われている依存。
+#[derive(Debug, PartialEq)]
+pub struct VersionConflict {
+    pub manager: String,
+    pub name: String,
+    /// バージョンごとの検出元ファイル。
+    pub versions: ConflictSources,
+}
+
+/// バージョンが記録されていない依存は数えません。
+pub fn version_conflicts(records: &[DependencyRecord]) -> Vec<VersionConflict> {
+    group_divergent(records, |record| {
+        let version = record.version.as_deref()?;
+        Some((
+            (record.manager.as_str(), record.name.as_str()),
+            version.to_string(),
+            version,
+        ))
+    })
+    .into_iter()
+    .map(|((manager, name), mut versions)| {
+        versions.sort_by(|a, b| compare_versions(&a.0, &b.0));
+        VersionConflict {
+            manager: manager.to_string(),
+            name: name.to_string(),
+            versions,
+        }
+    })
+    .collect()
+}
+
+/// 同じ`(manager, name, version)`なのにUnknown以外のライセンスが食い違っている依存。
+#[derive(Debug, PartialEq)]
+pub struct LicenseConflict {
+    pub manager: String,
+    pub name: String,
+    pub version: Option<String>,
+    /// ライセンスごとの検出元ファイル。
+    pub licenses: ConflictSources,
+}
+
+pub fn license_conflicts(records: &[DependencyRecord]) -> Vec<LicenseConflict> {
+    group_divergent(records, |record| {
+        if record.has_unknown_license() {
+            return None;
+        }
+        let license = record.license.trim();
+        let normalized = normalize_spdx(license).unwrap_or_else(|| license.to_string());
+        Some((
+            (
+                record.manager.as_str(),
+                record.name.as_str(),
+                record.version.as_deref(),
+            ),
+            normalized,
+            license,
+        ))
+    })
+    .into_iter()
+    .map(|((manager, name, version), licenses)| LicenseConflict {
+        manager: manager.to_string(),
+        name: name.to_string(),
+        version: version.map(str::to_string),
+        licenses,
+    })
+    .collect()
+}
+
+/// `group`が返すキーごとに依存をまとめ、比較用の値が2つ以上に分かれているものを返します。
+/// 値ごとに最初に現れた表記と検出元ファイルを並べます。
+fn group_divergent<'a, K: Ord>(
+    records: &'a [DependencyRecord],
+    group: impl Fn(&'a DependencyRecord) -> Option<(K, String, &'a str)>,
+) -> Vec<(K, ConflictSources)> {
+    let mut groups: BTreeMap<K, BTreeMap<String, (&str, BTreeSet<&Path>)>> = BTreeMap::new();
+    for record in records {
+        if let Some((key, value, label)) = group(record) {
+            groups
+                .entry(key)
+                .or_default()
+                .entry(value)
+                .or_insert_with(|| (label, BTreeSet::new()))
+                .1
+                .insert(record.source.as_path());
+        }
+    }
+
+    groups
+        .into_iter()
+        .filter(|(_, values)| values.len() > 1)
+        .map(|(key, values)| {
+            let values = values
+                .into_values()
+                .map(|(label, sources)| {
+                    (
+                        label.to_string(),
+                        sources.into_iter().map(Path::to_path_buf).collect(),
+                    )
+                })
+                .collect();
+            (key, values)
+        })
+        .collect()
+}
+
+/// `--version-conflicts`・`--check-conflicts`の警告に付ける、値ごとの検出元ファイルの一覧。
+pub fn conflict_details(values: &[(String, Vec<PathBuf>)]) -> String {
+    values
+        .iter()
+        .map(|(value, sources)| {
+            let sources = sources
+                .iter()
+                .map(|source| source.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("\n  - {value}: {sources}")
+        })
+        .collect()
+}
+
+pub struct ScanCounts {
+    pub total: usize,
+    pub per_manager: Vec<(String, usize)>,
+    pub unknown: usize,
+}
+
+impl ScanCounts {
+    pub fn from_records(records: &[DependencyRecord]) -> Self {
+        let mut per_manager: Vec<(String, usize)> = Vec::new();
+        for record in records {
+            match per_manager
+                .iter_mut()
+                .find(|(manager, _)| *manager == record.manager)
+            {
+                Some((_, count)) => *count += 1,
+                None => per_manager.push((record.manager.clone(), 1)),
+            }
+        }
+        per_manager.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+        Self {
+            total: records.len(),
+            per_manager,
+            unknown: records.iter().filter(|r| r.has_unknown_license()).count(),
+        }
+    }
+
+    pub fn unknown_percent(&self) -> f64 {
+        if self.total == 0 {
+            0.0
+        } else {
+            self.unknown as f64 * 100.0 / self.total as f64
+        }
+    }
+
+    pub fn footer(&self, path_count: usize, elapsed: Duration) -> String {
+        let managers = self
+            .per_manager
+            .iter()
+            .map(|(manager, count)| format!("{manager} {}", format_count(*count)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!(
+            "> {}パスを解析: {}件 ({managers}), Unknown {}件, {:.1}秒",
+            format_count(path_count),
+            format_count(self.total),
+            format_count(self.unknown),
+            elapsed.as_secs_f64()
+        )
+    }
+}
+
+/// 端末がOSC 8のハイパーリンクを扱えそうかどうか（`TERM=dumb`では無効）。
+pub fn terminal_supports_hyperlinks() -> bool {
+    io::stdout().is_terminal() && std::env::var("TERM").map_or(true, |term| term != "dumb")
+}
+
+/// 標準出力が端末ならその幅（桁数）を返します。
+pub fn terminal_width() -> Option<u16> {
+    if !io::stdout().is_terminal() {
+        return None;
+    }
+    ratatui::crossterm::terminal::size()
+        .ok()
+        .map(|(columns, _)| columns)
+        .filter(|columns| *columns > 0)
+}
+
+/// `--stat`の出力。1行目に総数、続いてマネージャ別件数、最後にUnknownの件数を並べます。
+pub fn render_stat(counts: &ScanCounts) -> String {
+    let mut text = format!("依存関係: {}件\n", format_count(counts.total));
+    for (manager, count) in &counts.per_manager {
+        text.push_str(&format!("  {manager}: {}\n", format_count(*count)));
+    }
+    text.push_str(&format!("Unknown: {}件\n", format_count(counts.unknown)));
+    text
+}
+
+/// 件数が0のファイルを強調し、最後に0件のファイル数を示します。
+pub fn render_manifest_report(files: &[ScannedFile], cwd: &Path) -> String {
+    let mut text = String::new();
+    for file in files {
+        let number = format!("{:>6}", format_count(file.count));
+        let number = if file.count == 0 && color_enabled() {
+            paint(&number, Color::Yellow, true)
+        } else {
+            number
+        };
+        let path = file.path.strip_prefix(cwd).unwrap_or(&file.path);
+        text.push_str(&format!(
+            "{:<14} {number}  {}\n",
+            file.kind.format_name(),
+            path.display()
+        ));
+    }
+    let empty = files.iter().filter(|file| file.count == 0).count();
+    text.push_str(&format!(
+        "{}件のファイル、依存を抽出できなかったファイル: {empty}件\n",
+        format_count(files.len())
+    ));
+    text
+}
+
+/// `--timing`で表示する各フェーズの所要時間。実行しなかったフェーズは`None`です。
+pub struct PhaseTimings {
+    pub scan: Duration,
+    pub enrich: Option<(Duration, CacheStats)>,
+    pub output: Option<Duration>,
+}
+
+/// `total`は実行開始からの経過時間で、各フェーズの間の処理（ポリシーの判定など）も含みます。
+pub fn render_timing(timings: &PhaseTimings, total: Duration) -> String {
+    let seconds = |duration: Duration| format!("{:.2}秒", duration.as_secs_f64());
+    let mut text = format!("処理時間:\n  解析: {}\n", seconds(timings.scan));
+    if let Some((elapsed, stats)) = timings.enrich {
+        text.push_str(&format!(
+            "  ライセンス取得: {}（キャッシュ ヒット{}件 / ミス{}件）\n",
+            seconds(elapsed),
+            format_count(stats.hits),
+            format_count(stats.misses)
+        ));
+    }
+    if let Some(elapsed) = timings.output {
+        text.push_str(&format!("  出力: {}\n", seconds(elapsed)));
+    }
+    text.push_str(&format!("  合計: {}\n", seconds(total)));
+    text
+}
+
+pub fn license_histogram<'a>(
+    records: impl IntoIterator<Item = &'a DependencyRecord>,
+) -> Vec<(String, usize)> {
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for record in records {
+        let license = if record.has_unknown_license() {
+            "Unknown".to_string()
+        } else {
+            record.license.clone()
+        };
+        *counts.entry(license).or_default() += 1;
+    }
+    let mut histogram: Vec<(String, usize)> = counts.into_iter().collect();
+    histogram.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    histogram
+}
+
+pub fn print_summary(records: &[DependencyRecord]) {
+    if records.is_empty() {
+        return;
+    }
+
+    let counts = ScanCounts::from_records(records);
+    let managers = counts
+        .per_manager
+        .iter()
+        .map(|(manager, count)| format!("{manager}: {}", format_count(*count)))
+        .collect::<Vec<_>>()
+        .join(", ");
+    println!("\nサマリー:");
+    println!("  依存関係: {}件", format_count(counts.total));
+    println!("  マネージャ別: {managers}");
+
+    let mut table = new_table();
+    table.set_header(vec![
+        Cell::new("License").add_attribute(Attribute::Bold),
+        Cell::new("Count").add_attribute(Attribute::Bold),
+    ]);
+    for (license, count) in license_histogram(records) {
+        let count_cell = if license == "Unknown" {
+            styled(&format_count(count), Color::Yellow, true)
+        } else {
+            Cell::new(format_count(count))
+        };
+        table.add_row(vec![colorize_license(&license), count_cell]);
+    }
+    println!("{table}");
+
+    if counts.unknown > 0 {
+        println!(
+            "  ⚠ ライセンス不明の依存が{}件あります（--fetch-licensesで補完できます）",
+            format_count(counts.unknown)
+        );
+    }
+}
+
+fn format_count(value: usize) -> String {
+    let digits = value.to_string();
+    let mut buf = String::with_capacity(digits.len() + digits.len() / 3);
+    for (idx, ch) in digits.chars().enumerate() {
+        if idx > 0 && (digits.len() - idx).is_multiple_of(3) {
+            buf.push(',');
+        }
+        buf.push(ch);
+    }
+    buf
+}
+
+fn shorten_source_path(
+    source: &Path,
+    cwd: &Path,
+    search_paths: &[PathBuf],
+    home_dir: Option<&Path>,
+) -> String {
+    if let Some(rel) = strip_relative(source, cwd) {
+        return rel;
+    }
+
+    for base in search_paths {
+        if let Ok(rel) = source.strip_prefix(base) {
+            let rel_text = rel.display().to_string();
+            if let Some(name) = base.file_name().map(|n| n.to_string_lossy()) {
+                if rel_text.is_empty() {
+                    return name.into_owned();
+                }
+                return format!("{}/{}", name, rel_text);
+            } else if !rel_text.is_empty() {
+                return rel_text;
+            }
+        }
+    }
+
+    if let Some(home) = home_dir
+        && let Some(rel) = strip_relative(source, home)
+    {
+        if rel.is_empty() {
+            return "~".to_string();
+        }
+        return format!("~/{}", rel);
+    }
+
+    source.display().to_string()
+}
+
+/// カレントディレクトリ配下のソースファイルを相対パスに置き換えます。実行する場所によって
+/// JSONなどの内容が変わらないようにするためで、配下にないパスはそのまま残します。
+pub fn relativize_sources(records: &mut [DependencyRecord], cwd: &Path) {
+    for record in records.iter_mut() {
+        relativize(&mut record.source, cwd);
+    }
+}
+
+/// `--scanned-files`や`--with-metadata`の`scanned_files`のパスも同じように相対パスにします。
+pub fn relativize_scanned_files(files: &mut [ScannedFile], cwd: &Path) {
+    for file in files.iter_mut() {
+        relativize(&mut file.path, cwd);
+    }
+}
+
+fn relativize(path: &mut PathBuf, cwd: &Path) {
+    if let Ok(relative) = path.strip_prefix(cwd)
+        && !relative.as_os_str().is_empty()
+    {
+        *path = relative.to_path_buf();
+    }
+}
+
+fn strip_relative(source: &Path, base: &Path) -> Option<String> {
+    source
+        .strip_prefix(base)
+        .ok()
+        .map(|p| p.display().to_string())
+}
+
+fn colorize_manager(manager: &str) -> Cell {
+    match manager {
+        "pip" => styled(manager, Color::Cyan, true),
+        "npm" => styled(manager, Color::Green, true),
+        "cargo" => styled(manager, Color::DarkYellow, true),
+        "go" => styled(manager, Color::Blue, true),
+        "composer" => styled(manager, Color::Magenta, true),
+        "gem" => styled(manager, Color::Red, true),
+        "maven" => styled(manager, Color::DarkCyan, true),
+        "conda" => styled(manager, Color::DarkGreen, true),
+        _ => styled(manager, Color::White, false),
+    }
+}
+
+fn provenance_label(provenance: Option<Provenance>) -> &'static str {
+    provenance.map_or("-", Provenance::label)
+}
+
+/// `--reconcile`でレジストリの値が異なる場合は、ロックファイルの値に続けてレジストリの値を強調表示します。
+fn license_cell(record: &DependencyRecord) -> Cell {
+    let Some(registry) = &record.registry_license else {
+        return colorize_license(&record.license);
+    };
+    let text = format!("{} (registry: {registry})", record.license);
+    if color_enabled() {
+        Cell::new(paint(&text, Color::Yellow, true))
+    } else {
+        Cell::new(text)
+    }
+}
+
+fn colorize_license(license: &str) -> Cell {
+    if color_enabled()
+        && let Some(colored) = colored_license_expression(license)
+    {
+        return Cell::new(colored);
+    }
+    let (color, bold) = license_style(license);
+    styled(license, color, bold)
+}
+
+/// 複合ライセンス式の各ライセンスを個別に色付けします。単一ライセンスや解釈できない式は`None`。
+fn colored_license_expression(license: &str) -> Option<String> {
+    let expr = LicenseExpr::parse(license).filter(LicenseExpr::is_compound)?;
+    Some(expr.render(&|name| {
+        let (color, bold) = license_style(name);
+        paint(name, color, bold)
+    }))
+}
+
+fn paint(text: &str, color: Color, bold: bool) -> String {
+    let weight = if bold { "1;" } else { "" };
+    format!("\x1b[{weight}{}m{text}\x1b[0m", ansi_color_code(color))
+}
+
+fn license_style(license: &str) -> (Color, bool) {
+    match license_category(license) {
+        Category::Permissive => (Color::Green, true),
+        Category::WeakCopyleft => (Color::Yellow, true),
+        Category::StrongCopyleft => (Color::Red, true),
+        Category::Unknown => (Color::DarkGrey, false),
+    }
+}
+
+fn ansi_color_code(color: Color) -> u8 {
+    match color {
+        Color::Red => 91,
+        Color::Green => 92,
+        Color::Yellow => 93,
+        Color::Blue => 94,
+        Color::Magenta => 95,
+        Color::Cyan => 96,
+        Color::DarkGrey => 90,
+        _ => 39,
+    }
+}
+
+fn styled(text: &str, color: Color, bold: bool) -> Cell {
+    let cell = Cell::new(text);
+    if !color_enabled() {
+        return cell;
+    }
+    let cell = cell.fg(color);
+    if bold {
+        cell.add_attribute(Attribute::Bold)
+    } else {
+        cell
+    }
+}
+
+fn severity_cell(severity: Option<Severity>) -> Cell {
+    match severity {
+        Some(Severity::Forbidden) => styled("forbidden", Color::Red, true),
+        Some(Severity::Review) => styled("review", Color::Magenta, true),
+        Some(Severity::Warn) => styled("warn", Color::Yellow, false),
+        None => Cell::new("-"),
+    }
+}
+
+fn compat_cell(compatibility: Option<Compatibility>) -> Cell {
+    match compatibility {
+        Some(Compatibility::Incompatible) => styled("incompatible", Color::Red, true),
+        Some(Compatibility::Review) => styled("review", Color::Magenta, false),
+        Some(Compatibility::Compatible) => styled("compatible", Color::Green, false),
+        None => Cell::new("-"),
+    }
+}
+
+/// 端末ではURLを省略表示しても、OSC 8のハイパーリンクで完全なURLを開けるようにします。
+fn homepage_cell(record: &DependencyRecord, url_width: usize, hyperlinks: bool) -> Cell {
+    let Some(url) = &record.homepage else {
+        return Cell::new("-");
+    };
+    let text = truncate_width(url, url_width);
+    let text = if hyperlinks && color_enabled() {
+        hyperlink(&text, url)
+    } else {
+        text
+    };
+    if record.homepage_reachable == Some(false) {
+        styled(&text, Color::Red, false)
+    } else {
+        Cell::new(text)
+    }
+}
+
+fn hyperlink(text: &str, url: &str) -> String {
+    format!("\x1b]8;;{url}\x1b\\{text}\x1b]8;;\x1b\\")
+}
+
+/// 非推奨・取り下げ済みの依存は名前に`(deprecated)`を付けて強調し、
+/// `--project-license`と互換性の無い依存は名前を赤で強調します。
+fn name_cell(record: &DependencyRecord) -> Cell {
+    let name = match record.deprecated {
+        Some(_) => format!("{} (deprecated)", record.name),
+        None => record.name.clone(),
+    };
+    if record.compatibility == Some(Compatibility::Incompatible) {
+        styled(&name, Color::Red, true)
+    } else if record.deprecated.is_some() {
+        styled(&name, Color::Yellow, true)
+    } else {
+        Cell::new(name)
+    }
+}
+
+fn latest_cell(outdated: &Option<String>) -> Cell {
+    match outdated {
+        Some(latest) => styled(latest, Color::Yellow, false),
+        None => Cell::new("-"),
+    }
+}
+
+fn shorten_description(description: &str) -> String {
+    truncate_width(description, 50)
+}
+
+/// 表示幅（全角文字は2桁）が`max_width`を超える場合に、書記素の途中で切らずに切り詰めて`...`を付けます。
+/// `max_width`が0なら切り詰めません。
+fn truncate_width(text: &str, max_width: usize) -> String {
+    if max_width == 0 || text.width() <= max_width {
+        return text.to_string();
+    }
+    let mut buf = String::new();
+    let mut width = 0;
+    for grapheme in text.graphemes(true) {
+        width += grapheme.width();
+        if width > max_width {
+            break;
+        }
+        buf.push_str(grapheme);
+    }
+    buf.push_str("...");
+    buf
+}
+
+pub struct JsonWriter<'a> {
+    /// 1件ずつ書き出し、全件を整形済みの文字列として保持しません。
+    pub stream: bool,
+    /// 指定されていれば、配列の代わりに生成日時などを添えたオブジェクトで出力します（`--with-metadata`）。
+    pub metadata: Option<&'a ReportMetadata>,
+    /// 改行・インデントを入れずに1行で出力します（`--json-compact`）。
+    pub compact: bool,
+}
+
+/// `--with-metadata`でJSONの先頭に添える、レポートの生成情報。
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportMetadata {
+    pub generated_at: String,
+    pub tool: &'static str,
+    pub tool_version: &'static str,
+    pub paths: Vec<String>,
+    /// 解析したファイルごとの形式と抽出できた依存の件数。
+    pub scanned_files: Vec<ScannedFile>,
+}
+
+impl ReportMetadata {
+    pub fn new(paths: Vec<String>, scanned_files: Vec<ScannedFile>) -> Self {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or_default();
+        Self {
+            generated_at: format_rfc3339_utc(now),
+            tool: env!("CARGO_PKG_NAME"),
+            tool_version: env!("CARGO_PKG_VERSION"),
+            paths,
+            scanned_files,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct WrappedReport<'a> {
+    #[serde(flatten)]
+    metadata: &'a ReportMetadata,
+    dependencies: &'a [DependencyRecord],
+}
+
+impl ReportWriter for JsonWriter<'_> {
+    fn label(&self) -> &'static str {
+        "JSON"
+    }
+
+    fn write_report(&self, records: &[DependencyRecord], out: &mut dyn Write) -> Result<()> {
+        match (self.metadata, self.stream) {
+            (None, true) => {
+                write_json_stream(records, &mut *out, self.compact)?;
+                writeln!(out)?;
+                Ok(())
+            }
+            (None, false) => {
+                self.write_value(records, &mut *out)?;
+                writeln!(out)?;
+                Ok(())
+            }
+            (Some(metadata), true) => {
+                let head = serde_json::to_string(metadata)?;
+                let head = head.strip_suffix('}').unwrap_or(&head);
+                write!(out, "{head},\"dependencies\":")?;
+                write_json_stream(records, &mut *out, self.compact)?;
+                writeln!(out, "}}")?;
+                Ok(())
+            }
+            (Some(metadata), false) => {
+                let report = WrappedReport {
+                    metadata,
+                    dependencies: records,
+                };
+                self.write_value(&report, &mut *out)?;
+                writeln!(out)?;
+                Ok(())
+            }
+        }
+    }
+}
+
+impl JsonWriter<'_> {
+    fn write_value<T: Serialize + ?Sized>(&self, value: &T, out: &mut dyn Write) -> Result<()> {
+        if self.compact {
+            serde_json::to_writer(out, value)?;
+        } else {
+            serde_json::to_writer_pretty(out, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// UNIX時刻（秒）を`2024-01-02T03:04:05Z`形式にします。
+fn format_rfc3339_utc(secs: u64) -> String {
+    let days = secs / 86_400;
+    let rest = secs % 86_400;
+    // 1970-01-01からの日数を年月日に変換する（Howard Hinnantのcivil_from_days）
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        rest / 3_600,
+        rest % 3_600 / 60,
+        rest % 60
+    )
+}
+
+/// ファイルに書き出すレポートの種類（`--json-output`・`--csv`など）。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFile {
+    Json,
+    Ndjson,
+    Csv,
+    Markdown,
+    CycloneDx,
+}
+
+impl ReportFile {
+    pub fn flag(self) -> &'static str {
+        match self {
+            Self::Json => "--json-output",
+            Self::Ndjson => "--ndjson",
+            Self::Csv => "--csv",
+            Self::Markdown => "--markdown",
+            Self::CycloneDx => "--cyclonedx",
+        }
+    }
+}
+
+/// 同じファイル（または標準出力）に複数のレポートを書き出す指定を走査前に弾きます。
+/// `stdout_format`は`--format`でtable以外を標準出力に出す場合の形式名です。
+pub fn check_report_files(
+    files: &[(ReportFile, &Path)],
+    stdout_format: Option<&str>,
+) -> Result<()> {
+    let mut seen: Vec<(&str, &Path)> = stdout_format
+        .map(|format| (format, Path::new("-")))
+        .into_iter()
+        .collect();
+    for (kind, path) in files {
+        if let Some((other, _)) = seen.iter().find(|(_, seen)| seen == path) {
+            if *path == Path::new("-") {
+                bail!(
+                    "標準出力に書き出せるレポートは1つだけです: {other}と{}",
+                    kind.flag()
+                );
+            }
+            bail!(
+                "同じファイルに複数のレポートを書き出そうとしています: {}（{other}と{}）",
+                path.display(),
+                kind.flag()
+            );
+        }
+        seen.push((kind.flag(), path));
+    }
+    Ok(())
+}
+
+pub struct CsvWriter;
+
+impl ReportWriter for CsvWriter {
+    fn label(&self) -> &'static str {
+        "CSV"
+    }
+
+    fn write_report(&self, records: &[DependencyRecord], out: &mut dyn Write) -> Result<()> {
+        write_csv(records, out)
+    }
+}
+
+const CSV_HEADER: &str = "manager,name,version,license,homepage,outdated,direct,dev,source";
+
+fn write_csv<W: Write>(records: &[DependencyRecord], mut writer: W) -> Result<()> {
+    writeln!(writer, "{CSV_HEADER}")?;
+    write_csv_rows(records, writer)
+}
+
+fn write_csv_rows<W: Write>(records: &[DependencyRecord], mut writer: W) -> Result<()> {
+    for record in records {
+        let source = record.source.display().to_string();
+        let fields = [
+            record.manager.as_str(),
+            record.name.as_str(),
+            record.version.as_deref().unwrap_or_default(),
+            record.license.as_str(),
+            record.homepage.as_deref().unwrap_or_default(),
+            record.outdated.as_deref().unwrap_or_default(),
+            if record.direct { "true" } else { "false" },
+            if record.dev { "true" } else { "false" },
+            source.as_str(),
+        ];
+        let line = fields
+            .iter()
+            .map(|field| escape_csv_field(field))
+            .collect::<Vec<_>>()
+            .join(",");
+        writeln!(writer, "{line}")?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn escape_csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+pub struct MarkdownWriter<'a> {
+    pub paths: SourcePaths<'a>,
+    pub options: TableOptions,
+}
+
+impl ReportWriter for MarkdownWriter<'_> {
+    fn label(&self) -> &'static str {
+        "Markdown"
+    }
+
+    fn write_report(&self, records: &[DependencyRecord], out: &mut dyn Write) -> Result<()> {
+        let SourcePaths {
+            cwd,
+            search_paths,
+            home_dir,
+        } = self.paths;
+        let markdown = render_markdown(records, cwd, search_paths, home_dir, &self.options);
+        out.write_all(markdown.as_bytes())?;
+        Ok(())
+    }
+}
+
+fn render_markdown(
+    records: &[DependencyRecord],
+    cwd: &Path,
+    search_paths: &[PathBuf],
+    home_dir: Option<&Path>,
+    options: &TableOptions,
+) -> String {
+    let options = &TableOptions {
+        width: None,
+        ..*options
+    };
+    let header = options.headers();
+    let mut lines = vec![
+        format!("| {} |", header.join(" | ")),
+        format!("|{}", "---|".repeat(header.len())),
+    ];
+    for record in records {
+        let mut row = vec![
+            record.manager.clone(),
+            record.name.clone(),
+            record.version.clone().unwrap_or_else(|| "-".to_string()),
+        ];
+        if options.show_latest {
+            row.push(record.outdated.clone().unwrap_or_else(|| "-".to_string()));
+        }
+        if options.show_direct {
+            row.push(flag_label(record.direct).to_string());
+        }
+        if options.show_dev {
+            row.push(flag_label(record.dev).to_string());
+        }
+        row.push(match &record.registry_license {
+            Some(registry) => format!("{} (registry: {registry})", record.license),
+            None => record.license.clone(),
+        });
+        if options.show_severity {
+            row.push(record.severity.map_or("-", Severity::label).to_string());
+        }
+        if options.show_compat {
+            row.push(match record.compatibility {
+                Some(Compatibility::Incompatible) => "**incompatible**".to_string(),
+                compatibility => compatibility.map_or("-", Compatibility::label).to_string(),
+            });
+        }
+        if options.show_provenance {
+            row.push(provenance_label(record.provenance).to_string());
+        }
+        row.push(match &record.homepage {
+            Some(url) if record.homepage_reachable == Some(false) => format!("~~{url}~~"),
+            Some(url) => url.clone(),
+            None => "-".to_string(),
+        });
+        if options.show_description {
+            row.push(
+                record
+                    .description
+                    .as_deref()
+                    .map_or_else(|| "-".to_string(), shorten_description),
+            );
+        }
+        if !options.hide_source {
+            row.push(shorten_source_path(
+                &record.source,
+                cwd,
+                search_paths,
+                home_dir,
+            ));
+        }
+        let cells: Vec<String> = row.iter().map(|cell| escape_markdown_cell(cell)).collect();
+        lines.push(format!("| {} |", cells.join(" | ")));
+    }
+
+    let mut markdown = lines.join("\n");
+    markdown.push('\n');
+    markdown
+}
+
+fn flag_label(value: bool) -> &'static str {
+    if value { "✔" } else { "-" }
+}
+
+fn escape_markdown_cell(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('|', "\\|")
+        .replace(['\r', '\n'], " ")
+}
+
+pub struct CycloneDxWriter;
+
+impl ReportWriter for CycloneDxWriter {
+    fn label(&self) -> &'static str {
+        "CycloneDX SBOM"
+    }
+
+    fn write_report(&self, records: &[DependencyRecord], out: &mut dyn Write) -> Result<()> {
+        serde_json::to_writer_pretty(&mut *out, &cyclonedx_bom(records))?;
+        writeln!(out)?;
+        Ok(())
+    }
+}
+
+fn cyclonedx_bom(records: &[DependencyRecord]) -> Value {
+    let mut seen = BTreeSet::new();
+    let components: Vec<Value> = records
+        .iter()
+        .filter(|record| seen.insert(record.purl()))
+        .map(cyclonedx_component)
+        .collect();
+    json!({
+        "bomFormat": "CycloneDX",
+        "specVersion": "1.5",
+        "version": 1,
+        "metadata": {
+            "tools": {
+                "components": [{
+                    "type": "application",
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                }]
+            }
+        },
+        "components": components,
+    })
+}
+
+pub fn output_notices(records: &[DependencyRecord], output_path: Option<&Path>) -> Result<()> {
+    let notices = Notices::collect(records);
+    logging::status(format!(
+        "> ライセンス本文: {}種類・{}件の依存に対応",
+        notices.licenses.len(),
+        notices.package_count()
+    ));
+    if !notices.needs_review.is_empty() {
+        let details = notices
+            .needs_review
+            .iter()
+            .map(|record| format!("\n  - {}: {}", package_label(record), record.license))
+            .collect::<String>();
+        logging::warn(format!(
+            "{}件の依存はライセンス本文を特定できないため手動での確認が必要です:{details}",
+            notices.needs_review.len()
+        ));
+    }
+
+    if let Some(path) = output_path {
+        fs::write(path, notices.render())
+            .with_context(|| format!("NOTICESファイルの書き込みに失敗: {}", path.display()))?;
+        logging::status(format!(
+            "サードパーティライセンス一覧を{}に書き出しました。",
+            path.display()
+        ));
+    }
+    Ok(())
+}
+
+fn cyclonedx_component(record: &DependencyRecord) -> Value {
+    let purl = record.purl();
+    let mut component = json!({
+        "type": "library",
+        "bom-ref": purl,
+        "name": record.name,
+        "purl": purl,
+    });
+    if let Some(version) = &record.version {
+        component["version"] = json!(version);
+    }
+    if !record.has_unknown_license() {
+        component["licenses"] = cyclonedx_licenses(&record.license);
+    }
+    if let Some(homepage) = &record.homepage {
+        component["externalReferences"] = json!([{ "type": "website", "url": homepage }]);
+    }
+    let hashes = record
+        .integrity
+        .as_deref()
+        .map(cyclonedx_hashes)
+        .unwrap_or_default();
+    if !hashes.is_empty() {
+        component["hashes"] = Value::Array(hashes);
+    }
+    component
+}
+
+/// npmのSRI（`sha512-<base64>`）とpipの`sha256:<hex>`を、CycloneDXの16進表記のハッシュに変換します。
+fn cyclonedx_hashes(integrity: &str) -> Vec<Value> {
+    integrity
+        .split_whitespace()
+        .filter_map(|hash| {
+            let (alg, hex) = match hash.split_once(':') {
+                Some((alg, hex)) => (alg, hex.to_ascii_lowercase()),
+                None => {
+                    let (alg, encoded) = hash.split_once('-')?;
+                    let bytes = BASE64_STANDARD.decode(encoded).ok()?;
+                    (alg, bytes.iter().map(|b| format!("{b:02x}")).collect())
+                }
+            };
+            let alg = match alg.to_ascii_lowercase().as_str() {
+                "md5" => "MD5",
+                "sha1" => "SHA-1",
+                "sha256" => "SHA-256",
+                "sha384" => "SHA-384",
+                "sha512" => "SHA-512",
+                _ => return None,
+            };
+            Some(json!({ "alg": alg, "content": hex }))
+        })
+        .collect()
+}
+
+fn cyclonedx_licenses(license: &str) -> Value {
+    if license.contains(" OR ") || license.contains(" AND ") || license.contains(" WITH ") {
+        json!([{ "expression": license }])
+    } else {
+        json!([{ "license": { "name": license } }])
+    }
+}
+
+/// 解析したマニフェストごとの形式と件数（`{path, parser, count}`の配列）をJSONで書き出します。
+pub fn output_scanned_files(files: &[ScannedFile], path: &Path) -> Result<()> {
+    let json = serde_json::to_string_pretty(files)?;
+    fs::write(path, json)
+        .with_context(|| format!("JSONファイルの書き込みに失敗: {}", path.display()))?;
+    println!(
+        "解析したファイルの一覧を{}に書き出しました。",
+        path.display()
+    );
+    Ok(())
+}
+
+/// 末尾の改行は呼び出し側で書き出します。
+fn write_json_stream<W: Write>(
+    records: &[DependencyRecord],
+    mut writer: W,
+    compact: bool,
+) -> Result<()> {
+    writer.write_all(b"[")?;
+    for (idx, record) in records.iter().enumerate() {
+        let separator: &[u8] = match (idx, compact) {
+            (0, true) => b"",
+            (_, true) => b",",
+            (0, false) => b"\n  ",
+            (_, false) => b",\n  ",
+        };
+        writer.write_all(separator)?;
+        serde_json::to_writer(&mut writer, record)?;
+    }
+    let closing: &[u8] = if records.is_empty() || compact {
+        b"]"
+    } else {
+        b"\n]"
+    };
+    writer.write_all(closing)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// 1行に1件ずつJSONを書き出します（JSON Lines）。
+pub struct NdjsonWriter;
+
+impl ReportWriter for NdjsonWriter {
+    fn label(&self) -> &'static str {
+        "JSON Lines"
+    }
+
+    fn write_report(&self, records: &[DependencyRecord], out: &mut dyn Write) -> Result<()> {
+        write_ndjson(records, out)
+    }
+}
+
+/// `--stream-scan`: 解析できた依存を並べ替えずにその都度書き出します。表形式の代わりに1行1件で表示し、
+/// JSONはJSON Lines、CSVは最初の書き出し（`first`）でヘッダを付けます。
+pub fn write_streamed(
+    format: OutputFormat,
+    records: &[DependencyRecord],
+    first: bool,
+    out: &mut dyn Write,
+) -> Result<()> {
+    match format {
+        OutputFormat::Table => {
+            for record in records {
+                let license = if color_enabled() {
+                    let (color, bold) = license_style(&record.license);
+                    paint(&record.license, color, bold)
+                } else {
+                    record.license.clone()
+                };
+                writeln!(
+                    out,
+                    "{:<10} {} {} {license} {}",
+                    record.manager,
+                    record.name,
+                    record.version.as_deref().unwrap_or("-"),
+                    record.source.display()
+                )?;
+            }
+            out.flush()?;
+        }
+        OutputFormat::Json => write_ndjson(records, out)?,
+        OutputFormat::Csv => {
+            if first {
+                writeln!(out, "{CSV_HEADER}")?;
+            }
+            write_csv_rows(records, out)?;
+        }
+        OutputFormat::Markdown => bail!("--stream-scanはMarkdown形式に対応していません"),
+    }
+    Ok(())
+}
+
+fn write_ndjson<W: Write>(records: &[DependencyRecord], mut writer: W) -> Result<()> {
+    for record in records {
+        serde_json::to_writer(&mut writer, record)?;
+        writer.write_all(b"\n")?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use license_scout::scan::ManifestKind;
+
+    use super::*;
+
+    fn record(manager: &str, name: &str) -> DependencyRecord {
+        DependencyRecord {
+            manager: manager.to_string(),
+            name: name.to_string(),
+            license: "Unknown".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn sort_by_version_compares_numerically_and_keeps_none_last() {
+        let versions = [Some("1.10.0"), None, Some("1.9.0"), Some("1.9.0-beta")];
+        let mut records: Vec<DependencyRecord> = versions
+            .iter()
+            .map(|version| DependencyRecord {
+                version: version.map(str::to_string),
+                ..record("npm", "pkg")
+            })
+            .collect();
+        fn sorted(records: &[DependencyRecord]) -> Vec<Option<&str>> {
+            records.iter().map(|r| r.version.as_deref()).collect()
+        }
+
+        sort_records_by(&mut records, Some(SortField::Version), false);
+        assert_eq!(
+            sorted(&records),
+            vec![Some("1.9.0-beta"), Some("1.9.0"), Some("1.10.0"), None]
+        );
+        sort_records_by(&mut records, Some(SortField::Version), true);
+        assert_eq!(
+            sorted(&records),
+            vec![Some("1.10.0"), Some("1.9.0"), Some("1.9.0-beta"), None]
+        );
+    }
+
+    #[test]
+    fn sort_by_license_keeps_default_order_for_ties() {
+        let mut records = vec![record("npm", "b"), record("npm", "a"), record("pip", "c")];
+        records[0].license = "MIT".to_string();
+        records[2].license = "GPL-3.0".to_string();
+        sort_records_by(&mut records, Some(SortField::License), false);
+        let names: Vec<&str> = records.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["c", "b", "a"]);
+    }
+
+    #[test]
+    fn tree_groups_records_under_their_source() {
+        let mut records = vec![
+            record("npm", "react"),
+            record("pip", "requests"),
+            record("npm", "scheduler"),
+        ];
+        records[0].version = Some("18.2.0".to_string());
+        records[0].license = "MIT".to_string();
+        records[0].source = PathBuf::from("/repo/web/package-lock.json");
+        records[1].source = PathBuf::from("/repo/api/requirements.txt");
+        records[2].source = PathBuf::from("/repo/web/package-lock.json");
+
+        let tree = render_tree(&records, Path::new("/repo"), &[], None, false);
+        assert_eq!(
+            tree,
+            "api/requirements.txt (1件)\n└── requests (Unknown)\n\
+             web/package-lock.json (2件)\n├── react 18.2.0 (MIT)\n└── scheduler (Unknown)\n"
+        );
+    }
+
+    #[test]
+    fn by_source_counts_licenses_per_file() {
+        let mut records = vec![
+            record("npm", "react"),
+            record("npm", "scheduler"),
+            record("npm", "vue"),
+            record("pip", "requests"),
+        ];
+        for record in &mut records[..3] {
+            record.license = "MIT".to_string();
+            record.source = PathBuf::from("/repo/web/package-lock.json");
+        }
+        records[2].license = "unknown".to_string();
+        records[3].source = PathBuf::from("/repo/api/requirements.txt");
+
+        let summaries = source_summaries(&records, Path::new("/repo"), &[], None);
+        assert_eq!(
+            summaries,
+            vec![
+                SourceSummary {
+                    source: "api/requirements.txt".to_string(),
+                    total: 1,
+                    histogram: vec![("Unknown".to_string(), 1)],
+                },
+                SourceSummary {
+                    source: "web/package-lock.json".to_string(),
+                    total: 3,
+                    histogram: vec![("MIT".to_string(), 2), ("Unknown".to_string(), 1)],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn truncation_uses_display_width_and_keeps_graphemes() {
+        assert_eq!(
+            truncate_width("https://example.com", 19),
+            "https://example.com"
+        );
+        assert_eq!(truncate_width("https://example.com", 8), "https://...");
+        assert_eq!(
+            truncate_width("https://example.com", 0),
+            "https://example.com"
+        );
+        // 全角文字は2桁として数え、はみ出す文字は含めない
+        assert_eq!(truncate_width("https://例え.jp", 10), "https://例...");
+        assert_eq!(truncate_width("https://例え.jp", 9), "https://...");
+        // 結合文字（e + U+0301）を途中で切らない
+        assert_eq!(truncate_width("cafe\u{301}s", 4), "cafe\u{301}...");
+    }
+
+    #[test]
+    fn compound_licenses_color_each_operand() {
+        assert_eq!(
+            colored_license_expression("(MIT OR GPL-3.0)").as_deref(),
+            Some("\x1b[1;92mMIT\x1b[0m OR \x1b[1;91mGPL-3.0\x1b[0m")
+        );
+        assert_eq!(
+            colored_license_expression("LGPL-2.1-only OR Unknown").as_deref(),
+            Some("\x1b[1;93mLGPL-2.1-only\x1b[0m OR \x1b[90mUnknown\x1b[0m")
+        );
+        assert_eq!(colored_license_expression("MIT"), None);
+        assert_eq!(colored_license_expression("MIT OR"), None);
+    }
+
+    #[test]
+    fn unknown_percent_counts_unknown_licenses() {
+        let mut known = record("npm", "react");
+        known.license = "MIT".to_string();
+        let records = vec![
+            known,
+            record("npm", "a"),
+            record("pip", "b"),
+            record("pip", "c"),
+        ];
+        let counts = ScanCounts::from_records(&records);
+        assert_eq!(counts.unknown, 3);
+        assert_eq!(counts.unknown_percent(), 75.0);
+        assert_eq!(ScanCounts::from_records(&[]).unknown_percent(), 0.0);
+    }
+
+    #[test]
+    fn markdown_table_escapes_pipes_and_hides_source() {
+        let mut dep = record("npm", "left|pad");
+        dep.version = Some("1.0.0".to_string());
+        dep.license = "MIT | ISC".to_string();
+        dep.source = PathBuf::from("/repo/package-lock.json");
+
+        let options = TableOptions {
+            hide_source: true,
+            ..Default::default()
+        };
+        let markdown = render_markdown(&[dep], Path::new("/repo"), &[], None, &options);
+        let lines: Vec<&str> = markdown.lines().collect();
+        assert_eq!(
+            lines[0],
+            "| Manager | Name | Version | License | Homepage |"
+        );
+        assert_eq!(lines[1], "|---|---|---|---|---|");
+        assert_eq!(lines[2], r"| npm | left\|pad | 1.0.0 | MIT \| ISC | - |");
+    }
+
+    #[test]
+    fn markdown_table_shows_severity_after_license() {
+        let mut dep = record("npm", "copyleft");
+        dep.license = "GPL-3.0".to_string();
+        dep.severity = Some(Severity::Forbidden);
+
+        let options = TableOptions {
+            hide_source: true,
+            show_severity: true,
+            ..Default::default()
+        };
+        let markdown = render_markdown(&[dep], Path::new("/repo"), &[], None, &options);
+        let lines: Vec<&str> = markdown.lines().collect();
+        assert_eq!(
+            lines[0],
+            "| Manager | Name | Version | License | Severity | Homepage |"
+        );
+        assert_eq!(lines[2], "| npm | copyleft | - | GPL-3.0 | forbidden | - |");
+    }
+
+    #[test]
+    fn markdown_table_strikes_unreachable_homepages() {
+        let mut dep = record("npm", "abandoned");
+        dep.homepage = Some("https://example.com/gone".to_string());
+        dep.homepage_reachable = Some(false);
+
+        let options = TableOptions {
+            hide_source: true,
+            ..Default::default()
+        };
+        let markdown = render_markdown(&[dep], Path::new("/repo"), &[], None, &options);
+        assert_eq!(
+            markdown.lines().nth(2),
+            Some("| npm | abandoned | - | Unknown | ~~https://example.com/gone~~ |")
+        );
+    }
+
+    #[test]
+    fn markdown_table_shows_provenance() {
+        let mut dep = record("pip", "legacy");
+        dep.license = "BSD-3-Clause".to_string();
+        dep.provenance = Some(Provenance::PypiClassifier);
+        let unknown = record("pip", "mystery");
+
+        let options = TableOptions {
+            hide_source: true,
+            show_provenance: true,
+            ..Default::default()
+        };
+        let markdown = render_markdown(&[dep, unknown], Path::new("/repo"), &[], None, &options);
+        let lines: Vec<&str> = markdown.lines().collect();
+        assert_eq!(
+            lines[0],
+            "| Manager | Name | Version | License | Provenance | Homepage |"
+        );
+        assert_eq!(
+            lines[2],
+            "| pip | legacy | - | BSD-3-Clause | pypi-classifier | - |"
+        );
+        assert_eq!(lines[3], "| pip | mystery | - | Unknown | - | - |");
+    }
+
+    #[test]
+    fn license_histogram_groups_unknown_and_sorts_by_count() {
+        let mut records = vec![
+            record("npm", "a"),
+            record("npm", "b"),
+            record("pip", "c"),
+            record("pip", "d"),
+        ];
+        records[0].license = "MIT".to_string();
+        records[1].license = "MIT".to_string();
+        records[2].license = String::new();
+
+        assert_eq!(
+            license_histogram(&records),
+            vec![("MIT".to_string(), 2), ("Unknown".to_string(), 2)]
+        );
+    }
+
+    #[test]
+    fn relativize_sources_keeps_paths_outside_cwd() {
+        let cwd = Path::new("/work/repo");
+        let mut records = vec![record("npm", "a"), record("npm", "b"), record("npm", "c")];
+        records[0].source = PathBuf::from("/work/repo/frontend/package-lock.json");
+        records[1].source = PathBuf::from("/elsewhere/package-lock.json");
+        records[2].source = PathBuf::from("/work/repo");
+        relativize_sources(&mut records, cwd);
+        let sources: Vec<&Path> = records.iter().map(|r| r.source.as_path()).collect();
+        assert_eq!(
+            sources,
+            vec![
+                Path::new("frontend/package-lock.json"),
+                Path::new("/elsewhere/package-lock.json"),
+                Path::new("/work/repo"),
+            ]
+        );
+
+        let mut files = vec![ScannedFile {
+            path: PathBuf::from("/work/repo/frontend/yarn.lock"),
+            kind: ManifestKind::YarnLock,
+            count: 1,
+        }];
+        relativize_scanned_files(&mut files, cwd);
+        assert_eq!(files[0].path, Path::new("frontend/yarn.lock"));
+    }
+
+    #[test]
+    fn rfc3339_timestamps_cover_leap_years() {
+        assert_eq!(format_rfc3339_utc(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_rfc3339_utc(951_782_400), "2000-02-29T00:00:00Z");
+        assert_eq!(format_rfc3339_utc(1_709_251_199), "2024-02-29T23:59:59Z");
+    }
+
+    #[test]
+    fn json_with_metadata_wraps_records_in_both_modes() {
+        let records = vec![record("npm", "react")];
+        let metadata = ReportMetadata {
+            generated_at: "2024-01-02T03:04:05Z".to_string(),
+            tool: "license-scout",
+            tool_version: "0.1.0",
+            paths: vec!["frontend".to_string()],
+            scanned_files: vec![
+                ScannedFile {
+                    path: PathBuf::from("frontend/package-lock.json"),
+                    kind: ManifestKind::PackageLock,
+                    count: 2,
+                },
+                ScannedFile {
+                    path: PathBuf::from("frontend/yarn.lock"),
+                    kind: ManifestKind::YarnLock,
+                    count: 0,
+                },
+            ],
+        };
+        for stream in [false, true] {
+            let writer = JsonWriter {
+                stream,
+                metadata: Some(&metadata),
+                compact: false,
+            };
+            let mut out = Vec::new();
+            writer.write_report(&records, &mut out).unwrap();
+            let value: Value = serde_json::from_slice(&out).unwrap();
+            assert_eq!(value["generated_at"], "2024-01-02T03:04:05Z");
+            assert_eq!(value["tool_version"], "0.1.0");
+            assert_eq!(value["paths"], json!(["frontend"]));
+            assert_eq!(value["dependencies"][0]["name"], "react");
+            assert_eq!(
+                value["scanned_files"],
+                json!([
+                    {"path": "frontend/package-lock.json", "parser": "package-lock.json", "count": 2},
+                    {"path": "frontend/yarn.lock", "parser": "yarn.lock", "count": 0}
+                ])
+            );
+        }
+    }
+
+    #[test]
+    fn compact_json_is_a_single_line_in_both_modes() {
+        let records = vec![record("npm", "react"), record("pip", "requests")];
+        let render = |stream: bool, compact: bool| {
+            let writer = JsonWriter {
+                stream,
+                metadata: None,
+                compact,
+            };
+            let mut out = Vec::new();
+            writer.write_report(&records, &mut out).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        let pretty: Value = serde_json::from_str(&render(false, false)).unwrap();
+        for stream in [false, true] {
+            let compact = render(stream, true);
+            assert_eq!(compact.lines().count(), 1, "{compact}");
+            assert!(compact.ends_with("}]\n"), "{compact}");
+            assert_eq!(serde_json::from_str::<Value>(&compact).unwrap(), pretty);
+        }
+    }
+
+    #[test]
+    fn narrow_tables_drop_homepage_then_source() {
+        let headers = |width: Option<u16>| {
+            TableOptions {
+                width,
+                ..Default::default()
+            }
+            .fit_width()
+            .headers()
+        };
+        assert_eq!(
+            headers(None),
+            vec![
+                "Manager", "Name", "Version", "License", "Homepage", "Source"
+            ]
+        );
+        assert_eq!(
+            headers(Some(120)),
+            vec![
+                "Manager", "Name", "Version", "License", "Homepage", "Source"
+            ]
+        );
+        assert_eq!(
+            headers(Some(90)),
+            vec!["Manager", "Name", "Version", "License", "Source"]
+        );
+        assert_eq!(
+            headers(Some(60)),
+            vec!["Manager", "Name", "Version", "License"]
+        );
+
+        let mut dep = record("npm", "react");
+        dep.source = PathBuf::from("/repo/package-lock.json");
+        dep.homepage = Some("https://react.dev/a/very/long/path/that/would/not/fit".to_string());
+        let paths = SourcePaths {
+            cwd: Path::new("/repo"),
+            search_paths: &[],
+            home_dir: None,
+        };
+        let options = TableOptions {
+            width: Some(40),
+            ..Default::default()
+        };
+        let rendered = render_table(&[dep], paths, &options).to_string();
+        assert!(
+            rendered.lines().all(|line| line.width() <= 40),
+            "{rendered}"
+        );
+    }
+
+    #[test]
+    fn homepage_links_to_full_url_when_shortened() {
+        let mut dep = record("npm", "react");
+        dep.homepage = Some("https://react.dev/a/very/long/path".to_string());
+        let paths = SourcePaths {
+            cwd: Path::new("/repo"),
+            search_paths: &[],
+            home_dir: None,
+        };
+        let options = TableOptions {
+            hide_source: true,
+            url_width: 20,
+            hyperlinks: true,
+            ..Default::default()
+        };
+        let rendered = render_table(&[dep.clone()], paths, &options).to_string();
+        assert!(
+            rendered.contains("\x1b]8;;https://react.dev/a/very/long/path\x1b\\"),
+            "{rendered}"
+        );
+
+        let plain = TableOptions {
+            hyperlinks: false,
+            ..options
+        };
+        let rendered = render_table(&[dep], paths, &plain).to_string();
+        assert!(!rendered.contains("\x1b]8;;"), "{rendered}");
+    }
+
+    #[test]
+    fn report_files_must_not_share_a_destination() {
+        let json = Path::new("out/licenses.json");
+        let sbom = Path::new("out/sbom.json");
+        assert!(
+            check_report_files(
+                &[
+                    (ReportFile::Json, json),
+                    (ReportFile::CycloneDx, sbom),
+                    (ReportFile::Markdown, Path::new("-")),
+                ],
+                None,
+            )
+            .is_ok()
+        );
+
+        let err = check_report_files(&[(ReportFile::Json, json), (ReportFile::Csv, json)], None)
+            .unwrap_err();
+        assert!(err.to_string().contains("--json-output"), "{err}");
+
+        assert!(
+            check_report_files(&[(ReportFile::Ndjson, Path::new("-"))], Some("--format")).is_err()
+        );
+    }
+
+    #[test]
+    fn stat_lists_totals_per_manager_and_unknown() {
+        let mut records = vec![record("npm", "a"), record("npm", "b"), record("pip", "c")];
+        records[0].license = "MIT".to_string();
+        let counts = ScanCounts::from_records(&records);
+        assert_eq!(
+            render_stat(&counts),
+            "依存関係: 3件\n  npm: 2\n  pip: 1\nUnknown: 2件\n"
+        );
+    }
+
+    #[test]
+    fn timing_lists_phases_that_ran() {
+        let timings = PhaseTimings {
+            scan: Duration::from_millis(120),
+            enrich: Some((
+                Duration::from_millis(3400),
+                CacheStats {
+                    hits: 1200,
+                    misses: 30,
+                },
+            )),
+            output: Some(Duration::from_millis(10)),
+        };
+        assert_eq!(
+            render_timing(&timings, Duration::from_millis(3600)),
+            "処理時間:\n  解析: 0.12秒\n  ライセンス取得: 3.40秒（キャッシュ ヒット1,200件 / ミス30件）\n  出力: 0.01秒\n  合計: 3.60秒\n"
+        );
+        let scan_only = PhaseTimings {
+            scan: Duration::from_millis(50),
+            enrich: None,
+            output: None,
+        };
+        assert_eq!(
+            render_timing(&scan_only, Duration::from_millis(50)),
+            "処理時間:\n  解析: 0.05秒\n  合計: 0.05秒\n"
+        );
+    }
+
+    #[test]
+    fn obligation_checklist_groups_packages_by_obligation() {
+        let mut records = vec![
+            record("npm", "react"),
+            record("npm", "react"),
+            record("pip", "requests"),
+            record("pip", "mystery"),
+            record("npm", "dual"),
+        ];
+        records[0].license = "MIT".to_string();
+        records[1].license = "MIT".to_string();
+        records[2].license = "Apache-2.0".to_string();
+        records[4].license = "MIT OR GPL-3.0".to_string();
+        let checklist = obligation_checklist(&records);
+        let obligations: Vec<Obligation> = checklist.iter().map(|(o, _)| *o).collect();
+        assert_eq!(
+            obligations,
+            [
+                Obligation::Attribution,
+                Obligation::NoticeFile,
+                Obligation::StateChanges,
+                Obligation::ManualReview,
+            ]
+        );
+        assert_eq!(
+            checklist[0].1,
+            ["react - (npm) [MIT]", "requests - (pip) [Apache-2.0]"]
+        );
+        assert_eq!(
+            checklist[3].1,
+            ["dual - (npm) [MIT OR GPL-3.0]", "mystery - (pip) [Unknown]"]
+        );
+    }
+
+    #[test]
+    fn top_licenses_ranks_and_computes_shares() {
+        let mut records: Vec<DependencyRecord> =
+            (0..8).map(|i| record("npm", &format!("pkg{i}"))).collect();
+        for (record, license) in records.iter_mut().zip([
+            "MIT",
+            "MIT",
+            "MIT",
+            "MIT",
+            "Apache-2.0",
+            "Apache-2.0",
+            "ISC",
+            "",
+        ]) {
+            record.license = license.to_string();
+        }
+
+        assert_eq!(
+            top_licenses(&records, 2),
+            vec![
+                ("MIT".to_string(), 4, 50.0),
+                ("Apache-2.0".to_string(), 2, 25.0)
+            ]
+        );
+        assert_eq!(top_licenses(&records, 10).len(), 4);
+    }
+
+    #[test]
+    fn group_records_by_license_keeps_sorted_order() {
+        let mut records = vec![record("npm", "a"), record("npm", "b"), record("pip", "c")];
+        records[0].license = "MIT".to_string();
+        records[2].license = "MIT".to_string();
+
+        let groups = group_records(&records, GroupBy::License);
+        let summary: Vec<(&str, Vec<&str>)> = groups
+            .iter()
+            .map(|(key, members)| {
+                (
+                    key.as_str(),
+                    members.iter().map(|r| r.name.as_str()).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![("MIT", vec!["a", "c"]), ("Unknown", vec!["b"])]
+        );
+    }
+
+    #[test]
+    fn ndjson_writes_one_record_per_line() {
+        let records = vec![record("npm", "react"), record("pip", "requests")];
+        let mut out = Vec::new();
+        NdjsonWriter.write_report(&records, &mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 2);
+        let first: Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(first["name"], "react");
+        assert!(text.ends_with("}\n"));
+    }
+
+    #[test]
+    fn cyclonedx_hashes_are_converted_to_hex() {
+        let mut dep = record("npm", "react");
+        dep.integrity = Some("sha512-3q2+7w== sha256:ABCDEF md4-xyz".to_string());
+        let component = cyclonedx_component(&dep);
+        assert_eq!(
+            component["hashes"],
+            json!([
+                { "alg": "SHA-512", "content": "deadbeef" },
+                { "alg": "SHA-256", "content": "abcdef" },
+            ])
+        );
+        assert!(
+            cyclonedx_component(&record("npm", "vue"))
+                .get("hashes")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn report_writers_write_to_any_writer() {
+        let records = vec![record("npm", "react")];
+        let paths = SourcePaths {
+            cwd: Path::new("/repo"),
+            search_paths: &[],
+            home_dir: None,
+        };
+        let render = |writer: &dyn ReportWriter, records: &[DependencyRecord]| {
+            let mut out = Vec::new();
+            writer.write_report(records, &mut out).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+
+        let json: Value = serde_json::from_str(&render(
+            &JsonWriter {
+                stream: false,
+                metadata: None,
+                compact: false,
+            },
+            &records,
+        ))
+        .unwrap();
+        assert_eq!(json[0]["name"], "react");
+        let streamed: Value = serde_json::from_str(&render(
+            &JsonWriter {
+                stream: true,
+                metadata: None,
+                compact: false,
+            },
+            &records,
+        ))
+        .unwrap();
+        assert_eq!(streamed, json);
+        assert!(render(&CsvWriter, &records).starts_with("manager,name,"));
+        let markdown = MarkdownWriter {
+            paths,
+            options: TableOptions::default(),
+        };
+        assert!(render(&markdown, &records).contains("| npm | react |"));
+        let bom: Value = serde_json::from_str(&render(&CycloneDxWriter, &records)).unwrap();
+        assert_eq!(bom["components"][0]["purl"], "pkg:npm/react");
+        assert_eq!(
+            render(&TreeWriter { paths }, &[]),
+            format!("{NO_DEPENDENCIES}\n")
+        );
+    }
+
+    #[test]
+    fn csv_fields_are_quoted_when_needed() {
+        let mut dep = record("npm", "pkg");
+        dep.license = "MIT, ISC".to_string();
+        dep.version = Some("1.0.0".to_string());
+        dep.source = PathBuf::from("package-lock.json");
+
+        let mut buf = Vec::new();
+        write_csv(&[dep], &mut buf).unwrap();
+        let csv = String::from_utf8(buf).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(
+            lines[0],
+            "manager,name,version,license,homepage,outdated,direct,dev,source"
+        );
+        assert_eq!(
+            lines[1],
+            "npm,pkg,1.0.0,\"MIT, ISC\",,,false,false,package-lock.json"
+        );
+        assert_eq!(escape_csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+
+    #[test]
+    fn license_conflicts_ignore_unknown_and_spelling_variants() {
+        let entry = |license: &str, source: &str| DependencyRecord {
+            version: Some("1.0.0".to_string()),
+            license: license.to_string(),
+            source: PathBuf::from(source),
+            ..record("npm", "left-pad")
+        };
+        let records = vec![
+            entry("MIT", "a/package-lock.json"),
+            entry("MIT License", "b/package-lock.json"),
+            entry("Unknown", "c/package-lock.json"),
+            entry("ISC", "d/package-lock.json"),
+            DependencyRecord {
+                version: Some("2.0.0".to_string()),
+                ..entry("Apache-2.0", "e/package-lock.json")
+            },
+        ];
+
+        let conflicts = license_conflicts(&records);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].version.as_deref(), Some("1.0.0"));
+        assert_eq!(
+            conflicts[0].licenses,
+            vec![
+                (
+                    "ISC".to_string(),
+                    vec![PathBuf::from("d/package-lock.json")]
+                ),
+                (
+                    "MIT".to_string(),
+                    vec![
+                        PathBuf::from("a/package-lock.json"),
+                        PathBuf::from("b/package-lock.json")
+                    ]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn version_conflicts_list_sources_per_version() {
+        let entry = |name: &str, version: Option<&str>, source: &str| DependencyRecord {
+            version: version.map(str::to_string),
+            source: PathBuf::from(source),
+            ..record("npm", name)
+        };
+        let records = vec![
+            entry("react", Some("9.0.0"), "a/package-lock.json"),
+            entry("react", Some("10.0.0"), "b/package-lock.json"),
+            entry("react", Some("9.0.0"), "c/package-lock.json"),
+            entry("react", None, "d/package-lock.json"),
+            entry("vue", Some("3.4.0"), "a/package-lock.json"),
+            entry("vue", Some("3.4.0"), "b/package-lock.json"),
+            DependencyRecord {
+                manager: "pip".to_string(),
+                ..entry("react", Some("1.0.0"), "requirements.txt")
+            },
+        ];
+
+        assert_eq!(
+            version_conflicts(&records),
+            vec![VersionConflict {
+                manager: "npm".to_string(),
+                name: "react".to_string(),
+                versions: vec![
+                    (
+                        "9.0.0".to_string(),
+                        vec![
+                            PathBuf::from("a/package-lock.json"),
+                            PathBuf::from("c/package-lock.json")
+                        ]
+                    ),
+                    (
+                        "10.0.0".to_string(),
+                        vec![PathBuf::from("b/package-lock.json")]
+                    ),
+                ],
+            }]
+        );
+        assert_eq!(
+            conflict_details(&version_conflicts(&records)[0].versions),
+            "\n  - 9.0.0: a/package-lock.json, c/package-lock.json\n  - 10.0.0: b/package-lock.json"
+        );
+    }
+
+    #[test]
+    fn name_collisions_only_span_managers() {
+        let records = vec![
+            record("pip", "six"),
+            record("npm", "six"),
+            record("npm", "react"),
+            record("npm", "react"),
+        ];
+        assert_eq!(
+            name_collisions(&records),
+            vec![(
+                "six".to_string(),
+                vec!["npm".to_string(), "pip".to_string()]
+            )]
+        );
+    }
+
+    fn sample_diff() -> DependencyDiff {
+        let versioned = |name: &str, version: &str, license: &str| DependencyRecord {
+            version: Some(version.to_string()),
+            license: license.to_string(),
+            ..record("npm", name)
+        };
+        license_scout::diff::diff_records(
+            &[
+                versioned("a", "1.0.0", "GPL-3.0-only"),
+                versioned("c", "1.0.0", "MPL-2.0"),
+                versioned("x", "2.0.0", "ISC"),
+            ],
+            &[
+                versioned("a", "1.1.0", "MIT"),
+                versioned("b", "1.0.0", "MIT"),
+                versioned("c", "2.0.0", "MPL-2.0"),
+            ],
+        )
+    }
+
+    #[test]
+    fn text_diff_prefixes_each_change_and_colors_license_changes() {
+        let diff = sample_diff();
+        assert_eq!(
+            render_diff(&diff, false),
+            "ベースラインとの差分:\n  追加: 1件 / 削除: 1件 / 変更: 2件\n\
+             + npm b 1.0.0: MIT\n\
+             - npm x 2.0.0: ISC\n\
+             ~ npm a 1.0.0 -> 1.1.0: GPL-3.0-only -> MIT\n\
+             ~ npm c 1.0.0 -> 2.0.0: MPL-2.0\n"
+        );
+
+        let colored = render_diff(&diff, true);
+        assert!(colored.contains(&paint("+ npm b 1.0.0: MIT", Color::Green, false)));
+        assert!(colored.contains(&paint("- npm x 2.0.0: ISC", Color::Red, false)));
+        assert!(colored.contains(&paint(
+            "~ npm a 1.0.0 -> 1.1.0: GPL-3.0-only -> MIT",
+            Color::Yellow,
+            false
+        )));
+        assert!(colored.contains("\n~ npm c 1.0.0 -> 2.0.0: MPL-2.0\n"));
+        assert!(render_diff(&DependencyDiff::default(), true).ends_with("変更はありません。\n"));
+    }
+
+    #[test]
+    fn json_diff_lists_added_removed_and_changed() {
+        let value: Value = serde_json::from_str(&diff_json(&sample_diff()).unwrap()).unwrap();
+        assert_eq!(value["added"][0]["name"], "b");
+        assert_eq!(value["removed"][0]["name"], "x");
+        assert_eq!(
+            value["changed"][0],
+            json!({
+                "manager": "npm",
+                "name": "a",
+                "old_version": "1.0.0",
+                "new_version": "1.1.0",
+                "old_license": "GPL-3.0-only",
+                "new_license": "MIT"
+            })
+        );
+    }
+
+    #[test]
+    fn streamed_csv_writes_header_only_once() {
+        let mut out = Vec::new();
+        write_streamed(OutputFormat::Csv, &[record("npm", "a")], true, &mut out).unwrap();
+        write_streamed(OutputFormat::Csv, &[record("npm", "b")], false, &mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert_eq!(text.matches(CSV_HEADER).count(), 1);
+        assert_eq!(text.lines().count(), 3);
+
+        let mut out = Vec::new();
+        write_streamed(
+            OutputFormat::Json,
+            &[record("npm", "a"), record("pip", "b")],
+            true,
+            &mut out,
+        )
+        .unwrap();
+        assert_eq!(String::from_utf8(out).unwrap().lines().count(), 2);
+        assert!(write_streamed(OutputFormat::Markdown, &[], true, &mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn manifest_report_lists_each_parsed_file_with_its_format() {
+        let file = |path: &str, kind: ManifestKind, count: usize| ScannedFile {
+            path: PathBuf::from(path),
+            kind,
+            count,
+        };
+        let files = vec![
+            file("/repo/requirements.txt", ManifestKind::Requirements, 2),
+            file("/repo/reqs/base.in", ManifestKind::Requirements, 1),
+            file("/repo/web/package-lock.json", ManifestKind::PackageLock, 0),
+        ];
+
+        let text = render_manifest_report(&files, Path::new("/repo"));
+        assert!(text.contains("requirements        2  requirements.txt\n"));
+        assert!(text.contains("requirements        1  reqs/base.in\n"));
+        assert!(text.contains("\npackage-lock   "));
+        assert!(text.contains("  web/package-lock.json\n"));
+        assert!(text.ends_with("3件のファイル、依存を抽出できなかったファイル: 1件\n"));
     }
-    Ok(())
 }
@@ -0,0 +1,228 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+
+use crate::logging;
+use crate::scan::package_key;
+use crate::types::{DependencyRecord, Provenance};
+
+/// 手動で確認したライセンス・ホームページの上書き設定。
+/// キーは`manager:name`または`manager:name@version`です。
+#[derive(Debug, Default, Deserialize)]
+#[serde(try_from = "BTreeMap<String, OverrideValue>")]
+pub struct Overrides {
+    entries: Vec<Override>,
+}
+
+#[derive(Debug, PartialEq)]
+struct Override {
+    manager: String,
+    name: String,
+    version: Option<String>,
+    license: Option<String>,
+    homepage: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct OverrideValue {
+    license: Option<String>,
+    homepage: Option<String>,
+}
+
+impl Overrides {
+    /// 拡張子が`.json`ならJSON、それ以外はTOMLとして読み込みます。
+    pub fn load(path: &Path) -> Result<Self> {
+        load_json_or_toml(path, "上書き設定ファイル")
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// 一致する上書き設定を適用し、適用した依存の件数を返します。
+    /// バージョン指定のある設定が名前のみの設定より優先されます。
+    pub fn apply(&self, records: &mut [DependencyRecord]) -> usize {
+        let mut applied = 0;
+        for record in records.iter_mut() {
+            let Some(entry) = self.find(record) else {
+                continue;
+            };
+            if let Some(license) = &entry.license {
+                record.license = license.clone();
+                record.provenance = Some(Provenance::Override);
+            }
+            if let Some(homepage) = &entry.homepage {
+                record.homepage = Some(homepage.clone());
+            }
+            logging::verbose(format!(
+                "上書き設定を適用: {}({} {})",
+                record.name,
+                record.manager,
+                record.version.as_deref().unwrap_or("-")
+            ));
+            applied += 1;
+        }
+        applied
+    }
+
+    fn find(&self, record: &DependencyRecord) -> Option<&Override> {
+        let name = package_key(&record.manager, &record.name);
+        let matches = |entry: &&Override| {
+            entry.manager.eq_ignore_ascii_case(&record.manager) && entry.name == name
+        };
+        self.entries
+            .iter()
+            .filter(matches)
+            .find(|entry| entry.version.is_some() && entry.version == record.version)
+            .or_else(|| {
+                self.entries
+                    .iter()
+                    .filter(matches)
+                    .find(|entry| entry.version.is_none())
+            })
+    }
+}
+
+impl TryFrom<BTreeMap<String, OverrideValue>> for Overrides {
+    type Error = anyhow::Error;
+
+    fn try_from(values: BTreeMap<String, OverrideValue>) -> Result<Self> {
+        let entries = values
+            .into_iter()
+            .map(|(key, value)| {
+                let (manager, name, version) = parse_key(&key)?;
+                if value.license.is_none() && value.homepage.is_none() {
+                    bail!("licenseまたはhomepageを指定してください: {key}");
+                }
+                Ok(Override {
+                    manager,
+                    name,
+                    version,
+                    license: value.license,
+                    homepage: value.homepage,
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { entries })
+    }
+}
+
+/// 拡張子が`.json`ならJSON、それ以外はTOMLとしてファイルを読み込みます。
+/// `label`はエラーメッセージに使うファイルの種類（「上書き設定ファイル」など）です。
+pub(crate) fn load_json_or_toml<T: DeserializeOwned>(path: &Path, label: &str) -> Result<T> {
+    let text = fs::read_to_string(path)
+        .with_context(|| format!("{label}を読み込めませんでした: {}", path.display()))?;
+    let is_json = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+    let parsed = if is_json {
+        serde_json::from_str(&text).map_err(anyhow::Error::from)
+    } else {
+        toml::from_str(&text).map_err(anyhow::Error::from)
+    };
+    parsed.with_context(|| format!("{label}の形式が不正です: {}", path.display()))
+}
+
+fn parse_key(key: &str) -> Result<(String, String, Option<String>)> {
+    let Some((manager, package)) = key.split_once(':') else {
+        bail!("manager:name 形式で指定してください: {key}");
+    };
+    let (name, version) = match package.rfind('@') {
+        Some(idx) if idx > 0 => (&package[..idx], Some(package[idx + 1..].trim())),
+        _ => (package, None),
+    };
+    let manager = manager.trim();
+    let name = name.trim();
+    if manager.is_empty() || name.is_empty() || version.is_some_and(str::is_empty) {
+        bail!("manager:name 形式で指定してください: {key}");
+    }
+    Ok((
+        manager.to_ascii_lowercase(),
+        package_key(manager, name),
+        version.map(str::to_string),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(manager: &str, name: &str, version: &str) -> DependencyRecord {
+        DependencyRecord {
+            manager: manager.to_string(),
+            name: name.to_string(),
+            version: Some(version.to_string()),
+            license: "Unknown".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn keys_accept_versions_and_scoped_npm_names() {
+        assert_eq!(
+            parse_key("npm:@types/node@20.1.0").unwrap(),
+            (
+                "npm".to_string(),
+                "@types/node".to_string(),
+                Some("20.1.0".to_string())
+            )
+        );
+        assert_eq!(
+            parse_key("pip:Flask_SQLAlchemy").unwrap(),
+            ("pip".to_string(), "flask-sqlalchemy".to_string(), None)
+        );
+        assert!(parse_key("requests").is_err());
+        assert!(parse_key("pip:requests@").is_err());
+    }
+
+    #[test]
+    fn version_specific_overrides_win() {
+        let overrides = toml::from_str::<Overrides>(
+            r#"
+["pip:mystery"]
+license = "BSD-3-Clause"
+homepage = "https://example.com/mystery"
+
+["pip:mystery@2.0.0"]
+license = "MIT"
+"#,
+        )
+        .unwrap();
+        let mut records = vec![
+            record("pip", "mystery", "1.0.0"),
+            record("pip", "mystery", "2.0.0"),
+            record("npm", "mystery", "1.0.0"),
+        ];
+
+        assert_eq!(overrides.apply(&mut records), 2);
+        assert_eq!(records[0].license, "BSD-3-Clause");
+        assert_eq!(records[0].provenance, Some(Provenance::Override));
+        assert_eq!(
+            records[0].homepage.as_deref(),
+            Some("https://example.com/mystery")
+        );
+        assert_eq!(records[1].license, "MIT");
+        assert_eq!(records[1].homepage, None);
+        assert_eq!(records[2].license, "Unknown");
+    }
+
+    #[test]
+    fn json_overrides_are_supported() {
+        let overrides =
+            serde_json::from_str::<Overrides>(r#"{"npm:left-pad": {"license": "WTFPL"}}"#).unwrap();
+        assert_eq!(overrides.len(), 1);
+        assert!(serde_json::from_str::<Overrides>(r#"{"npm:left-pad": {}}"#).is_err());
+        assert!(
+            serde_json::from_str::<Overrides>(r#"{"npm:left-pad": {"licence": "MIT"}}"#).is_err()
+        );
+    }
+}
@@ -0,0 +1,631 @@
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::{Context, Result, bail};
+use glob::Pattern;
+use serde::{Deserialize, Serialize};
+
+use crate::ignore::{matches_package_pattern, parse_package_pattern};
+use crate::scan::package_key;
+use crate::spdx::{Category, LicenseExpr, license_category, normalize_spdx};
+use crate::types::DependencyRecord;
+use crate::version::SemVer;
+
+#[derive(Debug, Default)]
+pub struct Policy {
+    pub deny: Vec<String>,
+    pub allow_only: Vec<String>,
+    pub deny_unknown: bool,
+    pub severities: Vec<SeverityRule>,
+    pub exceptions: Vec<PolicyException>,
+    pub min_versions: Vec<MinVersionRule>,
+}
+
+/// ライセンスごとの重大度。`forbidden`のみポリシー違反として扱います。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Warn,
+    Review,
+    Forbidden,
+}
+
+impl Severity {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Warn => "warn",
+            Self::Review => "review",
+            Self::Forbidden => "forbidden",
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+impl FromStr for Severity {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        [Self::Warn, Self::Review, Self::Forbidden]
+            .into_iter()
+            .find(|severity| severity.label().eq_ignore_ascii_case(value.trim()))
+            .ok_or_else(|| format!("重大度はforbidden / warn / reviewのいずれかです: {value}"))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SeverityTarget {
+    Category(Category),
+    License(String),
+}
+
+/// `GPL-3.0=forbidden`や`weak-copyleft=review`のような重大度の割り当て。
+/// 同じライセンスに一致する場合、ライセンスIDの指定が区分の指定より優先されます。
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeverityRule {
+    pub target: SeverityTarget,
+    pub severity: Severity,
+}
+
+impl SeverityRule {
+    pub fn new(target: &str, severity: &str) -> Result<Self, String> {
+        let target = target.trim();
+        if target.is_empty() {
+            return Err("ライセンスまたは区分を指定してください".to_string());
+        }
+        let target = match Category::from_name(target) {
+            Some(category) => SeverityTarget::Category(category),
+            None => SeverityTarget::License(target.to_string()),
+        };
+        Ok(Self {
+            target,
+            severity: severity.parse()?,
+        })
+    }
+}
+
+impl FromStr for SeverityRule {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (target, severity) = value
+            .split_once('=')
+            .ok_or_else(|| format!("LICENSE=SEVERITY 形式で指定してください: {value}"))?;
+        Self::new(target, severity)
+    }
+}
+
+/// 確認済みとしてポリシー違反から外すパッケージ（`npm/left-pad@1.3.0 理由`）。
+/// バージョンごとの指定のため、別のバージョンになると再び判定の対象になります。
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolicyException {
+    manager: String,
+    name: String,
+    version: String,
+    reason: String,
+}
+
+impl PolicyException {
+    pub fn load(path: &Path) -> Result<Vec<Self>> {
+        let text = fs::read_to_string(path).with_context(|| {
+            format!("例外設定ファイルを読み込めませんでした: {}", path.display())
+        })?;
+        Self::parse_list(&text).with_context(|| format!("{}の解析に失敗しました", path.display()))
+    }
+
+    /// 1行に1件。`#`で始まる行と空行は無視します。
+    pub fn parse_list(text: &str) -> Result<Vec<Self>> {
+        let mut exceptions = Vec::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            match line.parse() {
+                Ok(exception) => exceptions.push(exception),
+                Err(err) => bail!("{}行目: {err}", index + 1),
+            }
+        }
+        Ok(exceptions)
+    }
+
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+
+    fn matches(&self, record: &DependencyRecord) -> bool {
+        self.manager.eq_ignore_ascii_case(&record.manager)
+            && self.name == package_key(&record.manager, &record.name)
+            && record.version.as_deref() == Some(self.version.as_str())
+    }
+}
+
+impl FromStr for PolicyException {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("manager/name@version 理由 の形式で指定してください: {value}");
+        let (package, reason) = value
+            .trim()
+            .split_once(char::is_whitespace)
+            .ok_or_else(invalid)?;
+        let (manager, rest) = package.split_once('/').ok_or_else(invalid)?;
+        let (name, version) = rest.rsplit_once('@').ok_or_else(invalid)?;
+        let reason = reason.trim();
+        if manager.is_empty() || name.is_empty() || version.is_empty() || reason.is_empty() {
+            return Err(invalid());
+        }
+        let manager = manager.to_ascii_lowercase();
+        Ok(Self {
+            name: package_key(&manager, name),
+            manager,
+            version: version.to_string(),
+            reason: reason.to_string(),
+        })
+    }
+}
+
+/// `npm:lodash=4.17.21`のような、パッケージごとの最低バージョン。名前にはglobも使えます。
+#[derive(Debug, Clone, PartialEq)]
+pub struct MinVersionRule {
+    manager: String,
+    pattern: Pattern,
+    minimum: SemVer,
+    text: String,
+}
+
+impl MinVersionRule {
+    pub fn new(package: &str, version: &str) -> Result<Self, String> {
+        let (manager, pattern) = parse_package_pattern(package).map_err(|err| err.to_string())?;
+        let minimum = SemVer::parse(version)
+            .ok_or_else(|| format!("バージョンとして解釈できません: {version}"))?;
+        Ok(Self {
+            text: format!("{manager}:{}>={}", pattern.as_str(), version.trim()),
+            manager,
+            pattern,
+            minimum,
+        })
+    }
+
+    /// 一致する依存のバージョンが下限未満なら`Some`。バージョンが無い・解釈できない依存も、
+    /// 下限を満たすか確認できないため違反とします。
+    fn check(&self, record: &DependencyRecord) -> Option<String> {
+        if !matches_package_pattern(&self.manager, &self.pattern, record) {
+            return None;
+        }
+        match record.version.as_deref().and_then(SemVer::parse) {
+            Some(version) if version >= self.minimum => None,
+            Some(_) => Some(format!("min-version: {}", self.text)),
+            None => Some(format!(
+                "min-version: {}（バージョンを確認できません）",
+                self.text
+            )),
+        }
+    }
+}
+
+impl FromStr for MinVersionRule {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (package, version) = value
+            .split_once('=')
+            .ok_or_else(|| format!("MANAGER:NAME=VERSION 形式で指定してください: {value}"))?;
+        Self::new(package, version)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Violation {
+    pub name: String,
+    pub manager: String,
+    pub version: Option<String>,
+    pub license: String,
+    pub rule: String,
+    /// `--exceptions-file`で承認済みの場合はその理由。違反として扱いません。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exception: Option<String>,
+}
+
+impl Violation {
+    pub fn is_approved(&self) -> bool {
+        self.exception.is_some()
+    }
+}
+
+impl Policy {
+    pub fn evaluate(&self, records: &[DependencyRecord]) -> Vec<Violation> {
+        records
+            .iter()
+            .filter_map(|record| {
+                let rule = self
+                    .check_record(record)
+                    .err()
+                    .or_else(|| {
+                        (self.severity(record) == Some(Severity::Forbidden))
+                            .then(|| "severity: forbidden".to_string())
+                    })
+                    .or_else(|| self.min_versions.iter().find_map(|rule| rule.check(record)))?;
+                Some(Violation {
+                    manager: record.manager.clone(),
+                    name: record.name.clone(),
+                    version: record.version.clone(),
+                    license: record.license.clone(),
+                    rule,
+                    exception: self
+                        .exceptions
+                        .iter()
+                        .find(|exception| exception.matches(record))
+                        .map(|exception| exception.reason.clone()),
+                })
+            })
+            .collect()
+    }
+
+    /// 重大度の割り当てに従って各依存の`severity`を設定します。
+    pub fn annotate(&self, records: &mut [DependencyRecord]) {
+        for record in records.iter_mut() {
+            record.severity = self.severity(record);
+        }
+    }
+
+    /// ORは最も軽い選択肢、ANDは最も重い構成要素の重大度になります。割り当てがなければ`None`。
+    pub fn severity(&self, record: &DependencyRecord) -> Option<Severity> {
+        if self.severities.is_empty() {
+            return None;
+        }
+        if record.has_unknown_license() {
+            return self.term_severity("Unknown");
+        }
+        match LicenseExpr::parse(&record.license) {
+            Some(expr) => self.expr_severity(&expr),
+            None => self.term_severity(record.license.replace(['(', ')'], " ").trim()),
+        }
+    }
+
+    fn expr_severity(&self, expr: &LicenseExpr) -> Option<Severity> {
+        match expr {
+            LicenseExpr::License(term) => self.term_severity(term),
+            LicenseExpr::And(operands) => operands
+                .iter()
+                .map(|operand| self.expr_severity(operand))
+                .max()
+                .flatten(),
+            LicenseExpr::Or(operands) => operands
+                .iter()
+                .map(|operand| self.expr_severity(operand))
+                .min()
+                .flatten(),
+        }
+    }
+
+    fn term_severity(&self, term: &str) -> Option<Severity> {
+        let by_license = self.severities.iter().find(|rule| {
+            matches!(&rule.target, SeverityTarget::License(license) if license_matches(term, license))
+        });
+        let category = license_category(term);
+        by_license
+            .or_else(|| {
+                self.severities
+                    .iter()
+                    .find(|rule| rule.target == SeverityTarget::Category(category))
+            })
+            .map(|rule| rule.severity)
+    }
+
+    fn check_record(&self, record: &DependencyRecord) -> Result<(), String> {
+        if record.has_unknown_license() {
+            return if self.deny_unknown {
+                Err("deny-unknown".to_string())
+            } else {
+                Ok(())
+            };
+        }
+        self.check_expression(&record.license)
+    }
+
+    fn check_expression(&self, expression: &str) -> Result<(), String> {
+        match LicenseExpr::parse(expression) {
+            Some(expr) => self.check_expr(&expr),
+            None if normalize_spdx(expression).is_some() => self.check_term(expression),
+            None => self.check_term(expression.replace(['(', ')'], " ").trim()),
+        }
+    }
+
+    fn check_expr(&self, expr: &LicenseExpr) -> Result<(), String> {
+        match expr {
+            LicenseExpr::License(term) => self.check_term(term),
+            LicenseExpr::And(operands) => operands
+                .iter()
+                .try_for_each(|operand| self.check_expr(operand)),
+            LicenseExpr::Or(operands) => {
+                let mut first_error = None;
+                for operand in operands {
+                    match self.check_expr(operand) {
+                        Ok(()) => return Ok(()),
+                        Err(err) => {
+                            first_error.get_or_insert(err);
+                        }
+                    }
+                }
+                first_error.map_or(Ok(()), Err)
+            }
+        }
+    }
+
+    fn check_term(&self, term: &str) -> Result<(), String> {
+        if let Some(denied) = self.deny.iter().find(|rule| license_matches(term, rule)) {
+            return Err(format!("deny: {denied}"));
+        }
+        if !self.allow_only.is_empty()
+            && !self
+                .allow_only
+                .iter()
+                .any(|rule| license_matches(term, rule))
+        {
+            return Err(format!("allow-only対象外: {term}"));
+        }
+        Ok(())
+    }
+}
+
+fn license_matches(term: &str, rule: &str) -> bool {
+    let term = canonical_family(term);
+    let rule = canonical_family(rule);
+    term.eq_ignore_ascii_case(&rule)
+}
+
+fn canonical_family(license: &str) -> String {
+    let normalized = normalize_spdx(license).unwrap_or_else(|| license.trim().to_string());
+    let base = normalized
+        .strip_suffix("-only")
+        .or_else(|| normalized.strip_suffix("-or-later"))
+        .or_else(|| normalized.strip_suffix('+'))
+        .unwrap_or(&normalized);
+    base.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(license: &str) -> DependencyRecord {
+        DependencyRecord {
+            manager: "npm".to_string(),
+            name: "pkg".to_string(),
+            license: license.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn deny(licenses: &[&str]) -> Policy {
+        Policy {
+            deny: licenses.iter().map(|l| l.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn deny_matching_is_case_insensitive_and_family_aware() {
+        let policy = deny(&["gpl-3.0"]);
+        assert_eq!(policy.evaluate(&[record("GPL-3.0-only")]).len(), 1);
+        assert_eq!(policy.evaluate(&[record("GPL-3.0-or-later")]).len(), 1);
+        assert!(policy.evaluate(&[record("LGPL-3.0-only")]).is_empty());
+    }
+
+    fn severities(rules: &[&str]) -> Policy {
+        Policy {
+            severities: rules.iter().map(|rule| rule.parse().unwrap()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn severity_rules_prefer_licenses_over_categories() {
+        let policy = severities(&[
+            "strong-copyleft=forbidden",
+            "weak-copyleft=review",
+            "LGPL-2.1=warn",
+            "unknown=review",
+        ]);
+        assert_eq!(
+            policy.severity(&record("GPL-3.0-only")),
+            Some(Severity::Forbidden)
+        );
+        assert_eq!(policy.severity(&record("MPL-2.0")), Some(Severity::Review));
+        assert_eq!(
+            policy.severity(&record("LGPL-2.1-only")),
+            Some(Severity::Warn)
+        );
+        assert_eq!(policy.severity(&record("Unknown")), Some(Severity::Review));
+        assert_eq!(policy.severity(&record("MIT")), None);
+        assert_eq!(policy.severity(&record("MIT OR GPL-3.0")), None);
+        assert_eq!(
+            policy.severity(&record("MPL-2.0 AND GPL-2.0")),
+            Some(Severity::Forbidden)
+        );
+        assert!("GPL-3.0=blocked".parse::<SeverityRule>().is_err());
+        assert!("GPL-3.0".parse::<SeverityRule>().is_err());
+    }
+
+    #[test]
+    fn only_forbidden_severity_is_a_violation() {
+        let policy = severities(&["GPL-3.0=forbidden", "MPL-2.0=review"]);
+        let mut records = vec![record("GPL-3.0"), record("MPL-2.0"), record("MIT")];
+        let violations = policy.evaluate(&records);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].rule, "severity: forbidden");
+
+        policy.annotate(&mut records);
+        let annotated: Vec<Option<Severity>> = records.iter().map(|r| r.severity).collect();
+        assert_eq!(
+            annotated,
+            vec![Some(Severity::Forbidden), Some(Severity::Review), None]
+        );
+    }
+
+    #[test]
+    fn violations_serialize_for_the_policy_report() {
+        let violations = deny(&["GPL-3.0"]).evaluate(&[record("GPL-3.0-only")]);
+        assert_eq!(
+            serde_json::to_value(&violations).unwrap(),
+            serde_json::json!([{
+                "name": "pkg",
+                "manager": "npm",
+                "version": null,
+                "license": "GPL-3.0-only",
+                "rule": "deny: GPL-3.0"
+            }])
+        );
+    }
+
+    #[test]
+    fn or_expressions_pass_when_any_arm_is_allowed() {
+        let policy = deny(&["GPL-3.0"]);
+        assert!(policy.evaluate(&[record("(MIT OR GPL-3.0)")]).is_empty());
+        assert!(policy.evaluate(&[record("MIT, GPL-3.0")]).is_empty());
+        assert_eq!(policy.evaluate(&[record("GPL-3.0 OR GPL-3.0+")]).len(), 1);
+    }
+
+    #[test]
+    fn and_expressions_require_every_operand() {
+        let policy = deny(&["GPL-3.0"]);
+        let violations = policy.evaluate(&[record("(MIT AND GPL-3.0)")]);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].rule, "deny: GPL-3.0");
+    }
+
+    #[test]
+    fn parenthesized_expressions_are_evaluated_as_a_whole() {
+        let policy = deny(&["GPL-3.0"]);
+        assert_eq!(
+            policy
+                .evaluate(&[record("(MIT OR Apache-2.0) AND GPL-3.0")])
+                .len(),
+            1
+        );
+        assert!(
+            policy
+                .evaluate(&[record("(MIT AND ISC) OR GPL-3.0")])
+                .is_empty()
+        );
+        assert_eq!(
+            deny(&["GPL-3.0", "AGPL-3.0"])
+                .evaluate(&[record("(GPL-3.0 OR AGPL-3.0) AND MIT")])
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn malformed_expressions_fall_back_to_the_raw_string() {
+        let policy = deny(&["GPL-3.0"]);
+        assert_eq!(policy.evaluate(&[record("(GPL-3.0")]).len(), 1);
+        assert!(
+            policy
+                .evaluate(&[record("Mozilla Public License 2.0 (MPL 2.0)")])
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn allow_only_and_unknown_handling() {
+        let policy = Policy {
+            allow_only: vec!["MIT".to_string(), "Apache-2.0".to_string()],
+            ..Default::default()
+        };
+        assert!(policy.evaluate(&[record("MIT OR ISC")]).is_empty());
+        assert_eq!(policy.evaluate(&[record("ISC")]).len(), 1);
+        assert!(policy.evaluate(&[record("Unknown")]).is_empty());
+
+        let strict = Policy {
+            deny_unknown: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            strict.evaluate(&[record("Unknown")])[0].rule,
+            "deny-unknown"
+        );
+    }
+
+    #[test]
+    fn exceptions_approve_only_the_listed_version() {
+        let policy = Policy {
+            exceptions: PolicyException::parse_list(
+                "# 法務確認済み\nnpm/pkg@1.2.0 LEGAL-42で承認\npip/Flask_SQLAlchemy@3.0.0  社内利用のみ\n",
+            )
+            .unwrap(),
+            ..deny(&["GPL-3.0"])
+        };
+        let versioned = |manager: &str, name: &str, version: &str| DependencyRecord {
+            manager: manager.to_string(),
+            name: name.to_string(),
+            version: Some(version.to_string()),
+            ..record("GPL-3.0-only")
+        };
+
+        let violations = policy.evaluate(&[
+            versioned("npm", "pkg", "1.2.0"),
+            versioned("pip", "flask-sqlalchemy", "3.0.0"),
+        ]);
+        assert_eq!(violations.len(), 2);
+        assert!(violations.iter().all(Violation::is_approved));
+        assert_eq!(violations[0].exception.as_deref(), Some("LEGAL-42で承認"));
+
+        let violations = policy.evaluate(&[versioned("npm", "pkg", "1.3.0")]);
+        assert!(!violations[0].is_approved());
+        assert!(policy.evaluate(&[record("MIT")]).is_empty());
+    }
+
+    #[test]
+    fn malformed_exceptions_are_rejected_with_the_line_number() {
+        let scoped: PolicyException = "npm/@scope/pkg@2.0.0 確認済み".parse().unwrap();
+        assert_eq!(scoped.name, "@scope/pkg");
+        assert_eq!(scoped.version, "2.0.0");
+
+        for line in [
+            "npm/pkg@1.0.0",
+            "npm/pkg 理由",
+            "pkg@1.0.0 理由",
+            "npm/@1.0.0 理由",
+        ] {
+            assert!(line.parse::<PolicyException>().is_err(), "{line}");
+        }
+        let err = PolicyException::parse_list("npm/pkg@1.0.0 ok\nnpm:pkg=1.0.0\n").unwrap_err();
+        assert!(err.to_string().starts_with("2行目"), "{err}");
+    }
+
+    #[test]
+    fn min_version_flags_records_below_the_floor() {
+        let policy = Policy {
+            min_versions: vec!["npm:lodash=4.17.21".parse().unwrap()],
+            ..Default::default()
+        };
+        let lodash = |version: Option<&str>| DependencyRecord {
+            name: "lodash".to_string(),
+            version: version.map(str::to_string),
+            ..record("MIT")
+        };
+        let violations = policy.evaluate(&[lodash(Some("4.9.0"))]);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].rule, "min-version: npm:lodash>=4.17.21");
+        assert!(policy.evaluate(&[lodash(Some("4.17.21"))]).is_empty());
+        assert!(policy.evaluate(&[lodash(Some("4.100.0"))]).is_empty());
+        for version in [None, Some("git+https://github.com/lodash/lodash")] {
+            let violations = policy.evaluate(&[lodash(version)]);
+            assert_eq!(
+                violations[0].rule,
+                "min-version: npm:lodash>=4.17.21（バージョンを確認できません）"
+            );
+        }
+        assert!(policy.evaluate(&[record("MIT")]).is_empty());
+        assert!("npm:lodash".parse::<MinVersionRule>().is_err());
+        assert!("npm:lodash=latest".parse::<MinVersionRule>().is_err());
+    }
+}
@@ -1,13 +1,29 @@
+use std::io::{self, IsTerminal};
 use std::time::Duration;
 
 use anyhow::Result;
+use clap::ValueEnum;
 use indicatif::{ProgressBar, ProgressStyle};
+use license_scout::logging::{self, Verbosity};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum ProgressFormat {
+    /// 端末にスピナー・プログレスバーを表示
+    #[default]
+    Human,
+    /// 標準エラー出力に1行1件のJSONイベントを出力
+    Json,
+}
 
 pub fn with_spinner<T, F>(message: &str, action: F) -> Result<T>
 where
     F: FnOnce(&ProgressBar) -> Result<T>,
 {
-    let spinner = ProgressBar::new_spinner();
+    let spinner = if progress_enabled() {
+        ProgressBar::new_spinner()
+    } else {
+        ProgressBar::hidden()
+    };
     spinner.enable_steady_tick(Duration::from_millis(80));
     spinner.set_style(
         ProgressStyle::with_template("{spinner:.green} {msg}")
@@ -23,3 +39,34 @@ where
     }
     result
 }
+
+pub fn with_progress_bar<T, F>(message: &str, action: F) -> Result<T>
+where
+    F: FnOnce(&ProgressBar) -> Result<T>,
+{
+    let bar = if progress_enabled() {
+        ProgressBar::new(0)
+    } else {
+        ProgressBar::hidden()
+    };
+    bar.enable_steady_tick(Duration::from_millis(200));
+    bar.set_style(
+        ProgressStyle::with_template("{msg} [{bar:30.green}] {pos}/{len} ({eta})")
+            .unwrap()
+            .progress_chars("=> "),
+    );
+    bar.set_message(message.to_string());
+
+    let result = action(&bar);
+    match &result {
+        Ok(_) => bar.finish_with_message(format!("{message} 完了")),
+        Err(_) => bar.finish_and_clear(),
+    }
+    result
+}
+
+fn progress_enabled() -> bool {
+    logging::verbosity() > Verbosity::Quiet
+        && io::stdout().is_terminal()
+        && !logging::progress_events_enabled()
+}
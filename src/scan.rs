@@ -1,39 +1,568 @@
-use std::fs;
-use std::path::Path;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
 
 use anyhow::{Context, Result, bail};
+use glob::{MatchOptions, Pattern};
+use regex::Regex;
+use serde::de::{Deserializer, IgnoredAny, MapAccess, Visitor};
+use serde::{Deserialize, Serialize, Serializer};
 use serde_json::Value;
 use walkdir::WalkDir;
 
-use crate::types::DependencyRecord;
+use crate::logging;
+use crate::scan_cache::ScanCache;
+use crate::types::{DependencyRecord, Provenance};
+use crate::version;
 
-pub fn collect_records(root: &Path) -> Result<Vec<DependencyRecord>> {
-    if !root.exists() {
-        bail!("指定されたパスが存在しません: {}", root.display());
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ManifestKind {
+    Requirements,
+    PyprojectToml,
+    PoetryLock,
+    PipfileLock,
+    PackageLock,
+    YarnLock,
+    PnpmLock,
+    CargoLock,
+    ComposerLock,
+    GemfileLock,
+    GoMod,
+    GoVendorModules,
+    PomXml,
+    GradleLockfile,
+    CondaEnvironment,
+    UvLock,
+    SetupPy,
+    SetupCfg,
+}
+
+impl ManifestKind {
+    pub const ALL: [Self; 18] = [
+        Self::Requirements,
+        Self::PyprojectToml,
+        Self::PoetryLock,
+        Self::PipfileLock,
+        Self::PackageLock,
+        Self::YarnLock,
+        Self::PnpmLock,
+        Self::CargoLock,
+        Self::ComposerLock,
+        Self::GemfileLock,
+        Self::GoMod,
+        Self::GoVendorModules,
+        Self::PomXml,
+        Self::GradleLockfile,
+        Self::CondaEnvironment,
+        Self::UvLock,
+        Self::SetupPy,
+        Self::SetupCfg,
+    ];
+
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let file_name = path.file_name()?.to_string_lossy();
+        if file_name == "modules.txt" {
+            let parent = path.parent()?.file_name()?;
+            return (parent == "vendor").then_some(Self::GoVendorModules);
+        }
+        Self::from_file_name(&file_name)
     }
 
-    let mut collected = Vec::new();
-    let walker = WalkDir::new(root).into_iter().filter_entry(|entry| {
-        if entry.depth() == 0 {
-            return true;
+    pub fn from_file_name(name: &str) -> Option<Self> {
+        if requirements_group(name).is_some() {
+            return Some(Self::Requirements);
         }
-        let name = entry
-            .file_name()
-            .to_str()
-            .unwrap_or_default()
-            .to_ascii_lowercase();
-        entry.depth() <= 64
-            && !matches!(
-                name.as_str(),
-                "node_modules" | ".git" | "target" | "__pycache__" | "venv" | ".venv"
-            )
+        match name {
+            "requirements.txt" => Some(Self::Requirements),
+            "pyproject.toml" => Some(Self::PyprojectToml),
+            "poetry.lock" => Some(Self::PoetryLock),
+            "Pipfile.lock" => Some(Self::PipfileLock),
+            "package-lock.json" => Some(Self::PackageLock),
+            "yarn.lock" => Some(Self::YarnLock),
+            "pnpm-lock.yaml" => Some(Self::PnpmLock),
+            "Cargo.lock" => Some(Self::CargoLock),
+            "composer.lock" => Some(Self::ComposerLock),
+            "Gemfile.lock" => Some(Self::GemfileLock),
+            "go.mod" => Some(Self::GoMod),
+            "pom.xml" => Some(Self::PomXml),
+            "gradle.lockfile" => Some(Self::GradleLockfile),
+            "environment.yml" | "environment.yaml" => Some(Self::CondaEnvironment),
+            "uv.lock" => Some(Self::UvLock),
+            "setup.py" => Some(Self::SetupPy),
+            "setup.cfg" => Some(Self::SetupCfg),
+            _ => None,
+        }
+    }
+
+    /// `--stdin`で指定する形式名（例: `package-lock`）。ファイル名（`package-lock.json`）でも指定できます。
+    pub fn from_format(name: &str) -> Option<Self> {
+        let name = name.trim();
+        Self::ALL.into_iter().find(|kind| {
+            kind.format_name().eq_ignore_ascii_case(name) || kind.label().eq_ignore_ascii_case(name)
+        })
+    }
+
+    /// エラーメッセージ用の対応形式の一覧（`requirements, pyproject, ...`）。
+    pub fn format_names() -> String {
+        Self::ALL
+            .iter()
+            .map(|kind| kind.format_name())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    pub fn format_name(self) -> &'static str {
+        match self {
+            Self::Requirements => "requirements",
+            Self::PyprojectToml => "pyproject",
+            Self::PoetryLock => "poetry-lock",
+            Self::PipfileLock => "pipfile-lock",
+            Self::PackageLock => "package-lock",
+            Self::YarnLock => "yarn-lock",
+            Self::PnpmLock => "pnpm-lock",
+            Self::CargoLock => "cargo-lock",
+            Self::ComposerLock => "composer-lock",
+            Self::GemfileLock => "gemfile-lock",
+            Self::GoMod => "go-mod",
+            Self::GoVendorModules => "go-vendor",
+            Self::PomXml => "pom",
+            Self::GradleLockfile => "gradle-lock",
+            Self::CondaEnvironment => "conda-env",
+            Self::UvLock => "uv-lock",
+            Self::SetupPy => "setup-py",
+            Self::SetupCfg => "setup-cfg",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Requirements => "requirements.txt",
+            Self::PyprojectToml => "pyproject.toml",
+            Self::PoetryLock => "poetry.lock",
+            Self::PipfileLock => "Pipfile.lock",
+            Self::PackageLock => "package-lock.json",
+            Self::YarnLock => "yarn.lock",
+            Self::PnpmLock => "pnpm-lock.yaml",
+            Self::CargoLock => "Cargo.lock",
+            Self::ComposerLock => "composer.lock",
+            Self::GemfileLock => "Gemfile.lock",
+            Self::GoMod => "go.mod",
+            Self::GoVendorModules => "vendor/modules.txt",
+            Self::PomXml => "pom.xml",
+            Self::GradleLockfile => "gradle.lockfile",
+            Self::CondaEnvironment => "environment.yml",
+            Self::UvLock => "uv.lock",
+            Self::SetupPy => "setup.py",
+            Self::SetupCfg => "setup.cfg",
+        }
+    }
+}
+
+pub fn parse_manifest(
+    kind: ManifestKind,
+    content: &str,
+    source: &Path,
+) -> Result<Vec<DependencyRecord>> {
+    let mut records = match kind {
+        ManifestKind::Requirements => {
+            for include in content.lines().filter_map(requirement_include) {
+                logging::warn(format!(
+                    "{}: ファイル以外から読み込んだため-r {include}は解決できません",
+                    source.display()
+                ));
+            }
+            parse_requirements(content, source)
+        }
+        ManifestKind::PyprojectToml => parse_pyproject_toml(content, source)?,
+        ManifestKind::PoetryLock => parse_poetry_lock(content, source)?,
+        ManifestKind::PipfileLock => parse_pipfile_lock(content, source)?,
+        ManifestKind::PackageLock => parse_package_lock(content, source)?,
+        ManifestKind::YarnLock => parse_yarn_lock(content, source),
+        ManifestKind::PnpmLock => parse_pnpm_lock(content, source),
+        ManifestKind::CargoLock => parse_cargo_lock(content, source)?,
+        ManifestKind::ComposerLock => parse_composer_lock(content, source)?,
+        ManifestKind::GemfileLock => parse_gemfile_lock(content, source),
+        ManifestKind::GoMod => parse_go_mod(content, source),
+        ManifestKind::GoVendorModules => parse_go_vendor_modules(content, source),
+        ManifestKind::PomXml => parse_pom_xml(content, source)?,
+        ManifestKind::GradleLockfile => parse_gradle_lockfile(content, source),
+        ManifestKind::CondaEnvironment => parse_conda_environment(content, source),
+        ManifestKind::UvLock => parse_uv_lock(content, source)?,
+        ManifestKind::SetupPy => parse_setup_py(content, source),
+        ManifestKind::SetupCfg => parse_setup_cfg(content, source),
+    };
+    mark_lockfile_provenance(&mut records);
+    Ok(records)
+}
+
+fn mark_lockfile_provenance(records: &mut [DependencyRecord]) {
+    for record in records.iter_mut() {
+        if !record.has_unknown_license() {
+            record.provenance = Some(Provenance::Lockfile);
+        }
+    }
+}
+
+/// ファイル名（パス）から形式を判定して内容を解析します。ディレクトリの走査とアーカイブの読み込みで共有します。
+/// 対応していないファイル名なら`Ok(None)`を返します。
+pub fn parse_manifest_entry(
+    path: &Path,
+    content: &str,
+    options: &ScanOptions,
+) -> Result<Option<Vec<DependencyRecord>>> {
+    let Some(kind) = options.manifest_kind(path, path) else {
+        return Ok(None);
+    };
+    parse_manifest(kind, content, path)
+        .with_context(|| format!("{}の解析に失敗: {}", kind.label(), path.display()))
+        .map(Some)
+}
+
+/// requirements.txtは`-r`で読み込むファイル、Goのvendorは各モジュールのLICENSEファイルにも
+/// 結果が依存するため、走査キャッシュの対象外です。
+fn parse_manifest_cached(
+    kind: ManifestKind,
+    path: &Path,
+    visited_requirements: &mut HashSet<PathBuf>,
+    options: &ScanOptions,
+) -> Result<Scanned> {
+    let cache = options.scan_cache.as_deref().filter(|_| {
+        !matches!(
+            kind,
+            ManifestKind::Requirements | ManifestKind::GoVendorModules
+        )
     });
+    let Some(cache) = cache else {
+        return parse_manifest_file(kind, path, visited_requirements);
+    };
+    if let Some(records) = cache.get(path, kind) {
+        logging::verbose(format!("走査キャッシュ: {}", path.display()));
+        return Ok(Scanned::single(path.to_path_buf(), kind, records));
+    }
+    let scanned = parse_manifest_file(kind, path, visited_requirements)?;
+    cache.insert(path, kind, &scanned.records);
+    Ok(scanned)
+}
+
+fn parse_manifest_file(
+    kind: ManifestKind,
+    path: &Path,
+    visited_requirements: &mut HashSet<PathBuf>,
+) -> Result<Scanned> {
+    if kind == ManifestKind::Requirements {
+        return parse_requirements_file(path, visited_requirements);
+    }
+    let read_failed = || format!("{}の読み込みに失敗: {}", kind.label(), path.display());
+    let mut records = if kind == ManifestKind::PackageLock {
+        // 数十MBになるロックファイルもあるため、文字列に読み込まず読みながら解析する
+        let file = File::open(path).with_context(read_failed)?;
+        read_package_lock(BufReader::new(file), path)
+    } else {
+        let content = fs::read_to_string(path).with_context(read_failed)?;
+        parse_manifest(kind, &content, path)
+    }
+    .with_context(|| format!("{}の解析に失敗: {}", kind.label(), path.display()))?;
+
+    if kind == ManifestKind::GoVendorModules
+        && let Some(vendor_dir) = path.parent()
+    {
+        for record in records.iter_mut() {
+            if let Some(license) = detect_license_file(&vendor_dir.join(&record.name)) {
+                record.license = license.to_string();
+                record.provenance = Some(Provenance::LicenseFile);
+            }
+        }
+    }
+
+    Ok(Scanned::single(path.to_path_buf(), kind, records))
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ScanOptions {
+    pub exclude: Vec<Pattern>,
+    /// 指定されていれば、いずれかの階層がパターンに一致するファイルだけを解析します（除外が優先）。
+    pub include: Vec<Pattern>,
+    pub respect_gitignore: bool,
+    pub scan_cache: Option<Arc<ScanCache>>,
+    /// 走査するディレクトリの深さの上限。`None`なら64です。
+    pub max_depth: Option<usize>,
+    /// シンボリックリンク先のディレクトリも走査します。同じ実体のディレクトリは1回だけ走査します。
+    pub follow_symlinks: bool,
+    /// 組み込みのファイル名より優先する、ファイル名と形式の対応（`--map`）。
+    pub format_map: Vec<FormatMapping>,
+}
+
+impl ScanOptions {
+    /// `--map`の対応を優先してファイルの形式を判定します。パターンはファイル名か、
+    /// 走査のルートからの相対パス（`relative`）に一致すれば適用します。
+    pub fn manifest_kind(&self, path: &Path, relative: &Path) -> Option<ManifestKind> {
+        let name = path.file_name()?.to_string_lossy();
+        self.format_map
+            .iter()
+            .find(|mapping| {
+                mapping.pattern.matches(&name) || mapping.pattern.matches_path(relative)
+            })
+            .map(|mapping| mapping.kind)
+            .or_else(|| ManifestKind::from_path(path))
+    }
+}
+
+/// `prod-requirements.in=requirements`のような、ファイル名のglobと解析に使う形式の対応。
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormatMapping {
+    pub pattern: Pattern,
+    pub kind: ManifestKind,
+}
+
+impl FromStr for FormatMapping {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let Some((glob, format)) = value.rsplit_once('=') else {
+            return Err(format!("GLOB=FORMAT 形式で指定してください: {value}"));
+        };
+        let pattern = Pattern::new(glob.trim())
+            .map_err(|err| format!("パターンが不正です: {glob}（{err}）"))?;
+        let kind = ManifestKind::from_format(format).ok_or_else(|| {
+            format!(
+                "未対応の形式です: {format}（{}）",
+                ManifestKind::format_names()
+            )
+        })?;
+        Ok(Self { pattern, kind })
+    }
+}
+
+const DEFAULT_MAX_DEPTH: usize = 64;
+
+const DEFAULT_EXCLUDES: &[&str] = &[
+    "node_modules",
+    ".git",
+    "target",
+    "__pycache__",
+    "venv",
+    ".venv",
+];
+
+/// アーカイブ内のパスが既定の除外ディレクトリや`--exclude`のパターンに該当するか判定します。
+pub(crate) fn is_excluded_entry(relative: &Path, options: &ScanOptions) -> bool {
+    let mut prefix = PathBuf::new();
+    relative.components().any(|component| {
+        prefix.push(component);
+        let name = component.as_os_str().to_string_lossy();
+        DEFAULT_EXCLUDES.contains(&name.to_ascii_lowercase().as_str())
+            || options
+                .exclude
+                .iter()
+                .any(|pattern| pattern.matches(&name) || pattern.matches_path(&prefix))
+    })
+}
+
+/// `--include`が指定されていれば、パスのいずれかの階層（名前または相対パス）がパターンに一致するか判定します。
+pub(crate) fn is_included_entry(relative: &Path, options: &ScanOptions) -> bool {
+    if options.include.is_empty() {
+        return true;
+    }
+    let mut prefix = PathBuf::new();
+    relative.components().any(|component| {
+        prefix.push(component);
+        let name = component.as_os_str().to_string_lossy();
+        options
+            .include
+            .iter()
+            .any(|pattern| pattern.matches(&name) || pattern.matches_path(&prefix))
+    })
+}
+
+/// ディレクトリの下に`--include`に一致するパスがありうるか判定します。
+/// `services/api`のような`/`を含むパターンなら、一致しえないディレクトリには降りません。
+fn may_contain_included(relative: &Path, options: &ScanOptions) -> bool {
+    if options.include.is_empty() {
+        return true;
+    }
+    options.include.iter().any(|pattern| {
+        let glob = pattern.as_str();
+        if !glob.contains('/') || glob.contains("**") {
+            return true;
+        }
+        let mut parts = glob
+            .split('/')
+            .filter(|part| !part.is_empty() && *part != ".");
+        relative.components().all(|component| {
+            parts.next().is_none_or(|part| {
+                Pattern::new(part)
+                    .is_ok_and(|part| part.matches(&component.as_os_str().to_string_lossy()))
+            })
+        })
+    })
+}
+
+/// 解析したファイル1つ分の形式と、そこから抽出した依存の件数。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ScannedFile {
+    pub path: PathBuf,
+    #[serde(rename = "parser", serialize_with = "serialize_label")]
+    pub kind: ManifestKind,
+    pub count: usize,
+}
+
+fn serialize_label<S: Serializer>(kind: &ManifestKind, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(kind.label())
+}
+
+/// 抽出した依存と、解析したファイルごとの件数。
+#[derive(Debug, Default)]
+pub struct Scanned {
+    pub records: Vec<DependencyRecord>,
+    pub files: Vec<ScannedFile>,
+}
+
+impl Scanned {
+    pub fn single(path: PathBuf, kind: ManifestKind, records: Vec<DependencyRecord>) -> Self {
+        let file = ScannedFile {
+            path,
+            kind,
+            count: records.len(),
+        };
+        Self {
+            records,
+            files: vec![file],
+        }
+    }
+
+    pub fn extend(&mut self, other: Scanned) {
+        self.records.extend(other.records);
+        self.files.extend(other.files);
+    }
+}
+
+pub fn collect_records(root: &Path, options: &ScanOptions) -> Result<Vec<DependencyRecord>> {
+    scan_records(root, options).map(|scanned| scanned.records)
+}
+
+/// `collect_records`と同様に解析し、解析したファイルごとの形式と件数も返します。
+/// `-r`で取り込んだrequirements.txtも1ファイルとして数え、解析に失敗したファイルは0件とします。
+pub fn scan_records(root: &Path, options: &ScanOptions) -> Result<Scanned> {
+    let mut collected = Scanned::default();
+    stream_records(root, options, |scanned| collected.extend(scanned))?;
+    Ok(collected)
+}
+
+/// マニフェストを1つ解析するたびに、その依存とファイルごとの件数を走査順のまま`on_scanned`へ渡します。
+pub fn stream_records(
+    root: &Path,
+    options: &ScanOptions,
+    mut on_scanned: impl FnMut(Scanned),
+) -> Result<()> {
+    if root.is_file() {
+        let Some(kind) = options.manifest_kind(root, root) else {
+            bail!("対応していないファイルです: {}", root.display());
+        };
+        on_scanned(parse_manifest_cached(
+            kind,
+            root,
+            &mut HashSet::new(),
+            options,
+        )?);
+        return Ok(());
+    }
+
+    let mut visited_requirements = HashSet::new();
+    visit_manifest_files(root, options, |path, kind| {
+        // 壊れたファイルが1つあっても、他のファイルの結果は失わないようにする
+        match parse_manifest_cached(kind, &path, &mut visited_requirements, options) {
+            Ok(scanned) => on_scanned(scanned),
+            Err(err) => {
+                logging::warn(format!("{err:#}（このファイルはスキップします）"));
+                on_scanned(Scanned::single(path, kind, Vec::new()));
+            }
+        }
+    })
+}
+
+/// 解析対象のマニフェスト・ロックファイルを、除外設定に従って走査順に列挙します（内容は読みません）。
+pub fn manifest_files(root: &Path, options: &ScanOptions) -> Result<Vec<(PathBuf, ManifestKind)>> {
+    let mut manifests = Vec::new();
+    visit_manifest_files(root, options, |path, kind| manifests.push((path, kind)))?;
+    Ok(manifests)
+}
+
+/// ディレクトリを走査しながら、見つかったマニフェスト・ロックファイルを順に`visit`へ渡します。
+fn visit_manifest_files(
+    root: &Path,
+    options: &ScanOptions,
+    mut visit: impl FnMut(PathBuf, ManifestKind),
+) -> Result<()> {
+    if !root.exists() {
+        bail!("指定されたパスが存在しません: {}", root.display());
+    }
+    if root.is_file() {
+        let Some(kind) = options.manifest_kind(root, root) else {
+            bail!("対応していないファイルです: {}", root.display());
+        };
+        visit(root.to_path_buf(), kind);
+        return Ok(());
+    }
+
+    let gitignore = if options.respect_gitignore {
+        load_gitignore(root)
+    } else {
+        Vec::new()
+    };
+
+    let max_depth = options.max_depth.unwrap_or(DEFAULT_MAX_DEPTH);
+    // リンクの循環や、同じディレクトリを指す複数のリンクで二重に走査しないよう実体のパスを記録する
+    let mut visited_dirs = HashSet::new();
+    let walker = WalkDir::new(root)
+        .follow_links(options.follow_symlinks)
+        .into_iter()
+        .filter_entry(|entry| {
+            if options.follow_symlinks
+                && entry.file_type().is_dir()
+                && let Ok(canonical) = entry.path().canonicalize()
+                && !visited_dirs.insert(canonical)
+            {
+                logging::verbose(format!(
+                    "走査済みのディレクトリへのリンクをスキップします: {}",
+                    entry.path().display()
+                ));
+                return false;
+            }
+            if entry.depth() == 0 {
+                return true;
+            }
+            let name = entry
+                .file_name()
+                .to_str()
+                .unwrap_or_default()
+                .to_ascii_lowercase();
+            if entry.depth() > max_depth || DEFAULT_EXCLUDES.contains(&name.as_str()) {
+                return false;
+            }
+            let relative = entry.path().strip_prefix(root).unwrap_or(entry.path());
+            let excluded = options.exclude.iter().any(|pattern| {
+                pattern.matches(&entry.file_name().to_string_lossy())
+                    || pattern.matches_path(relative)
+            });
+            let included = if entry.file_type().is_dir() {
+                may_contain_included(relative, options)
+            } else {
+                is_included_entry(relative, options)
+            };
+            included
+                && !excluded
+                && !is_gitignored(&gitignore, relative, entry.file_type().is_dir())
+        });
 
     for entry in walker {
         let entry = match entry {
             Ok(e) => e,
             Err(err) => {
-                eprintln!("警告: ディレクトリの走査に失敗しました: {err}");
+                logging::warn(format!("ディレクトリの走査に失敗しました: {err}"));
                 continue;
             }
         };
@@ -42,48 +571,175 @@ pub fn collect_records(root: &Path) -> Result<Vec<DependencyRecord>> {
             continue;
         }
 
-        match entry.file_name().to_string_lossy().as_ref() {
-            "requirements.txt" => {
-                collected.extend(parse_requirements(entry.path()).with_context(|| {
-                    format!("requirements.txtの解析に失敗: {}", entry.path().display())
-                })?);
-            }
-            "package-lock.json" => {
-                collected.extend(parse_package_lock(entry.path()).with_context(|| {
-                    format!("package-lock.jsonの解析に失敗: {}", entry.path().display())
-                })?);
-            }
-            _ => {}
+        let relative = entry.path().strip_prefix(root).unwrap_or(entry.path());
+        if let Some(kind) = options.manifest_kind(entry.path(), relative) {
+            visit(entry.into_path(), kind);
         }
     }
 
-    Ok(collected)
+    Ok(())
 }
 
-fn parse_requirements(path: &Path) -> Result<Vec<DependencyRecord>> {
-    let content = fs::read_to_string(path)
-        .with_context(|| format!("requirements.txtの読み込みに失敗: {}", path.display()))?;
+/// 開発用として`--no-dev`で除外するrequirementsファイルのグループ。
+const DEV_REQUIREMENT_GROUPS: &[&str] = &["dev", "develop", "development", "test", "tests"];
 
+/// `requirements*.txt`ならファイル名のグループ（`requirements-dev.txt`なら`Some("dev")`、
+/// `requirements.txt`なら`Some("")`）を返し、それ以外のファイル名なら`None`を返します。
+fn requirements_group(file_name: &str) -> Option<&str> {
+    let group = file_name
+        .strip_prefix("requirements")?
+        .strip_suffix(".txt")?;
+    if group.is_empty() {
+        return Some(group);
+    }
+    let group = group.strip_prefix(['-', '_', '.'])?;
+    (!group.is_empty()).then_some(group)
+}
+
+fn parse_requirements(content: &str, path: &Path) -> Vec<DependencyRecord> {
+    let group = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .and_then(requirements_group)
+        .filter(|group| !group.is_empty());
+    let dev = group.is_some_and(|group| {
+        DEV_REQUIREMENT_GROUPS
+            .iter()
+            .any(|dev| dev.eq_ignore_ascii_case(group))
+    });
     let mut records = Vec::new();
-    for line in content.lines() {
-        if let Some((name, version)) = parse_requirement_line(line) {
+    for line in requirement_lines(content) {
+        let (requirement, hashes) = split_requirement_hashes(&line);
+        if let Some((name, version)) = parse_requirement_line(requirement) {
             records.push(DependencyRecord {
                 manager: "pip".to_string(),
                 name,
                 version,
                 license: "Unknown".to_string(),
                 source: path.to_path_buf(),
-                homepage: None,
+                direct: true,
+                dev,
+                group: group.map(str::to_string),
+                marker: requirement_marker(requirement),
+                integrity: (!hashes.is_empty()).then(|| hashes.join(" ")),
+                ..Default::default()
             });
         }
     }
 
-    Ok(records)
+    records
+}
+
+/// 行末の`\`による継続行を1行にまとめます。
+fn requirement_lines(content: &str) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    for line in content.lines() {
+        match line.trim_end().strip_suffix('\\') {
+            Some(continued) => {
+                current.push_str(continued);
+                current.push(' ');
+            }
+            None => {
+                current.push_str(line);
+                lines.push(std::mem::take(&mut current));
+            }
+        }
+    }
+    if !current.is_empty() {
+        lines.push(current);
+    }
+    lines
+}
+
+/// `requests==2.31.0 --hash=sha256:...`を要件部分と`--hash`の値（`sha256:...`）に分けます。
+fn split_requirement_hashes(line: &str) -> (&str, Vec<&str>) {
+    let line = strip_requirement_comment(line);
+    let Some(start) = line.find("--hash") else {
+        return (line, Vec::new());
+    };
+    let mut hashes = Vec::new();
+    let mut tokens = line[start..].split_whitespace();
+    while let Some(token) = tokens.next() {
+        let value = match token.strip_prefix("--hash") {
+            Some("") => tokens.next(),
+            Some(rest) => rest.strip_prefix('='),
+            None => None,
+        };
+        hashes.extend(value.filter(|v| !v.is_empty()));
+    }
+    (&line[..start], hashes)
+}
+
+/// `-r`/`--requirement`で取り込むファイルをたどりながら解析します。
+/// 一度解析したファイルは`visited`に記録し、循環や重複した取り込みを防ぎます。
+fn parse_requirements_file(path: &Path, visited: &mut HashSet<PathBuf>) -> Result<Scanned> {
+    let key = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    if !visited.insert(key) {
+        return Ok(Scanned::default());
+    }
+
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("requirements.txtの読み込みに失敗: {}", path.display()))?;
+    let mut scanned = Scanned::single(
+        path.to_path_buf(),
+        ManifestKind::Requirements,
+        parse_requirements(&content, path),
+    );
+    let base_dir = path.parent().unwrap_or(Path::new(""));
+    for include in content.lines().filter_map(requirement_include) {
+        let included = base_dir.join(include);
+        if !included.is_file() {
+            logging::warn(format!(
+                "{}: -r {include}のファイルが見つかりません",
+                path.display()
+            ));
+            continue;
+        }
+        scanned.extend(parse_requirements_file(&included, visited)?);
+    }
+    Ok(scanned)
+}
+
+/// `-r base.txt` / `--requirement=base.txt`の取り込み先を返します。
+fn requirement_include(line: &str) -> Option<&str> {
+    option_value(
+        strip_requirement_comment(line).trim(),
+        "-r",
+        "--requirement",
+    )
+}
+
+fn option_value<'a>(line: &'a str, short: &str, long: &str) -> Option<&'a str> {
+    let rest = line
+        .strip_prefix(long)
+        .or_else(|| line.strip_prefix(short))?;
+    let value = rest.strip_prefix('=').unwrap_or(rest).trim();
+    (!value.is_empty()).then_some(value)
+}
+
+/// pipと同様、行頭または空白の直後の`#`以降をコメントとして取り除きます（URL中の`#egg=`は残す）。
+fn strip_requirement_comment(line: &str) -> &str {
+    let mut previous = ' ';
+    for (idx, ch) in line.char_indices() {
+        if ch == '#' && previous.is_whitespace() {
+            return &line[..idx];
+        }
+        previous = ch;
+    }
+    line
 }
 
 fn parse_requirement_line(line: &str) -> Option<(String, Option<String>)> {
-    let without_comment = line.split('#').next()?.trim();
-    if without_comment.is_empty() || without_comment.starts_with('-') {
+    let without_comment = strip_requirement_comment(line).trim();
+    if without_comment.is_empty() {
+        return None;
+    }
+    // `-e .`のようなローカルパスの編集可能インストールは依存として扱わない
+    if let Some(target) = option_value(without_comment, "-e", "--editable") {
+        return Some((egg_name(target)?, None));
+    }
+    if without_comment.starts_with('-') {
         return None;
     }
 
@@ -92,6 +748,15 @@ fn parse_requirement_line(line: &str) -> Option<(String, Option<String>)> {
         return None;
     }
 
+    if let Some((name, _url)) = requirement.split_once('@')
+        && is_requirement_name(name)
+    {
+        return Some((normalize_package_name(name)?, None));
+    }
+    if requirement.contains("://") {
+        return Some((egg_name(requirement)?, None));
+    }
+
     let markers: &[&str] = &["===", "==", ">=", "<=", "~=", "!=", ">", "<", "="];
     for marker in markers {
         if let Some(idx) = requirement.find(marker) {
@@ -107,160 +772,1915 @@ fn parse_requirement_line(line: &str) -> Option<(String, Option<String>)> {
     Some((normalize_package_name(requirement)?, None))
 }
 
-fn normalize_package_name(name: &str) -> Option<String> {
-    let trimmed = name.trim();
-    if trimmed.is_empty() {
-        None
-    } else {
-        let base = trimmed
-            .split('[')
-            .next()
-            .unwrap_or(trimmed)
-            .replace('_', "-");
-        Some(base)
+/// `pywin32==306; sys_platform == 'win32'`の`;`以降（環境マーカー）を返します。
+fn requirement_marker(line: &str) -> Option<String> {
+    let requirement = strip_requirement_comment(line).trim();
+    if requirement.starts_with('-') {
+        return None;
     }
+    let (_, marker) = requirement.split_once(';')?;
+    let marker = marker.trim();
+    (!marker.is_empty()).then(|| marker.to_string())
 }
 
-fn parse_package_lock(path: &Path) -> Result<Vec<DependencyRecord>> {
-    let text = fs::read_to_string(path)
-        .with_context(|| format!("package-lock.jsonの読み込みに失敗: {}", path.display()))?;
-    let json: Value = serde_json::from_str(&text)
-        .with_context(|| format!("package-lock.jsonのJSON解析に失敗: {}", path.display()))?;
+fn is_requirement_name(name: &str) -> bool {
+    let name = name.trim();
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_.[], ".contains(c))
+}
 
-    if let Some(packages) = json.get("packages").and_then(|v| v.as_object()) {
-        Ok(packages
-            .iter()
-            .filter_map(|(pkg_path, info)| build_package_lock_record(pkg_path, info, path, &json))
-            .collect())
-    } else if let Some(deps) = json.get("dependencies").and_then(|v| v.as_object()) {
-        let mut records = Vec::new();
-        collect_from_dependencies_map(deps, path, &mut records);
-        Ok(records)
-    } else {
-        Ok(Vec::new())
-    }
+/// `git+https://...#egg=name`のようなURLからパッケージ名を取り出します。
+fn egg_name(url: &str) -> Option<String> {
+    let (_, fragment) = url.split_once("#egg=")?;
+    normalize_package_name(fragment.split('&').next()?)
 }
 
-fn build_package_lock_record(
-    pkg_path: &str,
-    info: &Value,
-    source: &Path,
-    root_json: &Value,
-) -> Option<DependencyRecord> {
-    let version = info
-        .get("version")
-        .and_then(|v| v.as_str())
-        .map(|v| v.to_string());
-    let license = info
-        .get("license")
-        .and_then(extract_license)
-        .unwrap_or_else(|| "Unknown".to_string());
-    let name = info
-        .get("name")
-        .and_then(|v| v.as_str())
-        .map(|s| s.to_string())
-        .or_else(|| package_name_from_path(pkg_path))
-        .or_else(|| {
-            root_json
-                .get("name")
-                .and_then(|v| v.as_str())
-                .map(|s| s.to_string())
-        })?;
+#[derive(Debug, Default, Deserialize)]
+struct PyprojectFile {
+    #[serde(default)]
+    project: PyprojectProject,
+    #[serde(default)]
+    tool: PyprojectTool,
+}
 
-    Some(DependencyRecord {
-        manager: "npm".to_string(),
-        name,
-        version,
-        license,
-        source: source.to_path_buf(),
-        homepage: None,
-    })
+#[derive(Debug, Default, Deserialize)]
+struct PyprojectProject {
+    #[serde(default)]
+    dependencies: Vec<String>,
+    #[serde(default, rename = "optional-dependencies")]
+    optional_dependencies: BTreeMap<String, Vec<String>>,
 }
 
-fn collect_from_dependencies_map(
-    map: &serde_json::Map<String, Value>,
-    source: &Path,
-    acc: &mut Vec<DependencyRecord>,
-) {
-    for (name, value) in map {
-        let version = value
-            .get("version")
-            .and_then(|v| v.as_str())
-            .map(|v| v.to_string());
-        acc.push(DependencyRecord {
-            manager: "npm".to_string(),
-            name: name.clone(),
-            version,
-            license: value
-                .get("license")
-                .and_then(extract_license)
-                .unwrap_or_else(|| "Unknown".to_string()),
-            source: source.to_path_buf(),
-            homepage: None,
-        });
-        if let Some(inner) = value.get("dependencies").and_then(|v| v.as_object()) {
-            collect_from_dependencies_map(inner, source, acc);
-        }
-    }
+#[derive(Debug, Default, Deserialize)]
+struct PyprojectTool {
+    #[serde(default)]
+    poetry: PoetrySection,
 }
 
-fn package_name_from_path(path: &str) -> Option<String> {
-    if path.is_empty() {
-        return None;
-    }
+#[derive(Debug, Default, Deserialize)]
+struct PoetrySection {
+    #[serde(default)]
+    dependencies: BTreeMap<String, toml::Value>,
+    #[serde(default, rename = "dev-dependencies")]
+    dev_dependencies: BTreeMap<String, toml::Value>,
+    #[serde(default)]
+    group: BTreeMap<String, PoetryGroup>,
+}
 
-    let mut segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
-    if segments.is_empty() {
-        return None;
-    }
+#[derive(Debug, Default, Deserialize)]
+struct PoetryGroup {
+    #[serde(default)]
+    dependencies: BTreeMap<String, toml::Value>,
+}
 
-    if let Some(pos) = segments
+fn parse_pyproject_toml(content: &str, path: &Path) -> Result<Vec<DependencyRecord>> {
+    let pyproject: PyprojectFile = toml::from_str(content)
+        .with_context(|| format!("pyproject.tomlのTOML解析に失敗: {}", path.display()))?;
+
+    let record = |name: String, version: Option<String>, dev: bool| DependencyRecord {
+        manager: "pip".to_string(),
+        name,
+        version,
+        license: "Unknown".to_string(),
+        source: path.to_path_buf(),
+        direct: true,
+        dev,
+        ..Default::default()
+    };
+
+    let project = pyproject.project;
+    let mut records: Vec<DependencyRecord> = project
+        .dependencies
         .iter()
-        .rposition(|segment| *segment == "node_modules")
+        .chain(project.optional_dependencies.values().flatten())
+        .filter_map(|requirement| {
+            let (name, version) = parse_requirement_line(&requirement.replace(['(', ')'], " "))?;
+            Some(DependencyRecord {
+                marker: requirement_marker(requirement),
+                ..record(name, version, false)
+            })
+        })
+        .collect();
+
+    let poetry = pyproject.tool.poetry;
+    let groups = poetry
+        .group
+        .into_iter()
+        .map(|(name, group)| (group.dependencies, name != "main"));
+    for (dependencies, dev) in [
+        (poetry.dependencies, false),
+        (poetry.dev_dependencies, true),
+    ]
+    .into_iter()
+    .chain(groups)
     {
-        segments = segments.split_off(pos + 1);
+        for (name, spec) in dependencies {
+            if name == "python" {
+                continue;
+            }
+            if let Some(name) = normalize_package_name(&name) {
+                records.push(record(name, poetry_version(&spec), dev));
+            }
+        }
     }
 
-    if segments.is_empty() {
+    Ok(records)
+}
+
+fn poetry_version(spec: &toml::Value) -> Option<String> {
+    let version = match spec {
+        toml::Value::String(version) => version.as_str(),
+        toml::Value::Table(table) => table.get("version")?.as_str()?,
+        toml::Value::Array(specs) => return specs.first().and_then(poetry_version),
+        _ => return None,
+    };
+    let version = version.trim().trim_start_matches("==").trim();
+    (!version.is_empty() && version != "*").then(|| version.to_string())
+}
+
+fn normalize_package_name(name: &str) -> Option<String> {
+    let base = name.split('[').next().unwrap_or(name).trim();
+    if base.is_empty() {
         None
-    } else if segments[0].starts_with('@') && segments.len() >= 2 {
-        Some(format!("{}/{}", segments[0], segments[1]))
     } else {
-        Some(segments[0].to_string())
+        Some(normalize_pypi_name(base))
     }
 }
 
-pub fn extract_license(value: &Value) -> Option<String> {
-    match value {
-        Value::String(s) => Some(s.to_string()),
-        Value::Array(values) => {
-            let merged: Vec<String> = values.iter().filter_map(extract_license).collect();
-            (!merged.is_empty()).then(|| merged.join(", "))
+/// PEP 503に従ってPyPIのパッケージ名を正規化します（小文字化し、`-` `_` `.`の連続を`-`1文字にまとめる）。
+pub fn normalize_pypi_name(name: &str) -> String {
+    let mut normalized = String::with_capacity(name.len());
+    let mut in_separator = false;
+    for ch in name.trim().chars() {
+        if matches!(ch, '-' | '_' | '.') {
+            in_separator = true;
+            continue;
         }
-        Value::Object(map) => map
-            .get("type")
-            .and_then(|t| t.as_str())
-            .map(|s| s.to_string()),
-        _ => None,
+        if in_separator && !normalized.is_empty() {
+            normalized.push('-');
+        }
+        in_separator = false;
+        normalized.extend(ch.to_lowercase());
+    }
+    normalized
+}
+
+fn setup_requirement_record(requirement: &str, path: &Path) -> Option<DependencyRecord> {
+    let (name, version) = parse_requirement_line(requirement)?;
+    Some(DependencyRecord {
+        manager: "pip".to_string(),
+        name,
+        version,
+        license: "Unknown".to_string(),
+        source: path.to_path_buf(),
+        direct: true,
+        marker: requirement_marker(requirement),
+        ..Default::default()
+    })
+}
+
+/// `setup.cfg`の`[options]`にある`install_requires`（1行に1件、継続行はインデント）を読みます。
+/// `file:`による別ファイルの参照は解決しません。
+fn parse_setup_cfg(content: &str, path: &Path) -> Vec<DependencyRecord> {
+    let mut section = String::new();
+    let mut in_install_requires = false;
+    let mut requirements = Vec::new();
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with(['#', ';']) {
+            continue;
+        }
+        if let Some(name) = trimmed
+            .strip_prefix('[')
+            .and_then(|rest| rest.strip_suffix(']'))
+        {
+            section = name.trim().to_ascii_lowercase();
+            in_install_requires = false;
+            continue;
+        }
+        if line.starts_with(char::is_whitespace) {
+            if in_install_requires {
+                requirements.push(trimmed);
+            }
+            continue;
+        }
+        let Some((key, value)) = trimmed.split_once(['=', ':']) else {
+            in_install_requires = false;
+            continue;
+        };
+        in_install_requires = section == "options" && key.trim() == "install_requires";
+        if in_install_requires && !value.trim().is_empty() {
+            requirements.push(value.trim());
+        }
+    }
+    requirements
+        .into_iter()
+        .filter(|requirement| !requirement.starts_with("file:"))
+        .filter_map(|requirement| setup_requirement_record(requirement, path))
+        .collect()
+}
+
+/// `setup.py`の`install_requires=[...]`（またはモジュール内で`NAME = [...]`と定義した変数）に並ぶ
+/// 文字列リテラルを読みます。実行はしないため、リストを動的に組み立てている場合は読み取れません。
+fn parse_setup_py(content: &str, path: &Path) -> Vec<DependencyRecord> {
+    let Some(list) = setup_py_install_requires(content) else {
+        return Vec::new();
+    };
+    python_string_literals(list)
+        .iter()
+        .filter_map(|requirement| setup_requirement_record(requirement, path))
+        .collect()
+}
+
+/// `install_requires`に渡しているリストの`[`以降を返します。
+fn setup_py_install_requires(content: &str) -> Option<&str> {
+    let argument = Regex::new(
+        r#"(?:\binstall_requires\s*=|["']install_requires["']\s*:)\s*(\[|[A-Za-z_]\w*)"#,
+    )
+    .ok()?;
+    let value = argument.captures(content)?.get(1)?;
+    if value.as_str() == "[" {
+        return Some(&content[value.start()..]);
+    }
+    let assignment = Regex::new(&format!(
+        r"(?m)^\s*{}\s*=\s*\[",
+        regex::escape(value.as_str())
+    ))
+    .ok()?;
+    let found = assignment.find(content)?;
+    Some(&content[found.end() - 1..])
+}
+
+/// `[`で始まるリストから、対応する`]`までの文字列リテラルを取り出します（`#`以降のコメントは無視）。
+fn python_string_literals(list: &str) -> Vec<String> {
+    let mut literals = Vec::new();
+    let mut depth = 0;
+    let mut chars = list.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '[' | '(' => depth += 1,
+            ']' | ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    break;
+                }
+            }
+            '#' => {
+                chars.by_ref().find(|&c| c == '\n');
+            }
+            '"' | '\'' => {
+                let mut literal = String::new();
+                while let Some(next) = chars.next() {
+                    match next {
+                        '\\' => literal.extend(chars.next()),
+                        _ if next == c => break,
+                        _ => literal.push(next),
+                    }
+                }
+                literals.push(literal);
+            }
+            _ => {}
+        }
+    }
+    literals
+}
+
+/// パッケージ名を比較するためのキー。pipはPEP 503に従って正規化し、それ以外は小文字にします。
+pub(crate) fn package_key(manager: &str, name: &str) -> String {
+    if manager.eq_ignore_ascii_case("pip") {
+        normalize_pypi_name(name)
+    } else {
+        name.to_ascii_lowercase()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct PoetryLockFile {
+    #[serde(default)]
+    package: Vec<PoetryLockPackage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PoetryLockPackage {
+    name: String,
+    version: Option<String>,
+    category: Option<String>,
+}
+
+fn parse_poetry_lock(content: &str, path: &Path) -> Result<Vec<DependencyRecord>> {
+    let lock: PoetryLockFile = toml::from_str(content)
+        .with_context(|| format!("poetry.lockのTOML解析に失敗: {}", path.display()))?;
+
+    Ok(lock
+        .package
+        .into_iter()
+        .filter_map(|package| {
+            Some(DependencyRecord {
+                manager: "pip".to_string(),
+                name: normalize_package_name(&package.name)?,
+                version: package.version,
+                license: "Unknown".to_string(),
+                source: path.to_path_buf(),
+                dev: package.category.as_deref() == Some("dev"),
+                ..Default::default()
+            })
+        })
+        .collect())
+}
+
+#[derive(Debug, Deserialize)]
+struct UvLockFile {
+    #[serde(default)]
+    package: Vec<UvLockPackage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct UvLockPackage {
+    name: String,
+    version: Option<String>,
+    #[serde(default)]
+    source: BTreeMap<String, toml::Value>,
+}
+
+/// uvの`uv.lock`。`source`がgit・URLの依存は`origin`に取得元を記録し、
+/// ローカルのパス（`editable`・`virtual`のプロジェクト自身を含む）はプロジェクト自身として扱います。
+fn parse_uv_lock(content: &str, path: &Path) -> Result<Vec<DependencyRecord>> {
+    let lock: UvLockFile = toml::from_str(content)
+        .with_context(|| format!("uv.lockのTOML解析に失敗: {}", path.display()))?;
+
+    Ok(lock
+        .package
+        .into_iter()
+        .filter_map(|package| {
+            let source = |key: &str| package.source.get(key).and_then(|v| v.as_str());
+            let local = ["editable", "virtual", "directory", "path"]
+                .into_iter()
+                .find_map(source);
+            let origin = if let Some(url) = source("git") {
+                Some(format!("git+{url}"))
+            } else if let Some(url) = source("url") {
+                Some(url.to_string())
+            } else {
+                local.map(|local| format!("path:{local}"))
+            };
+            Some(DependencyRecord {
+                manager: "pip".to_string(),
+                name: normalize_package_name(&package.name)?,
+                version: package.version,
+                license: "Unknown".to_string(),
+                source: path.to_path_buf(),
+                is_self: local.is_some(),
+                origin,
+                ..Default::default()
+            })
+        })
+        .collect())
+}
+
+fn parse_pipfile_lock(content: &str, path: &Path) -> Result<Vec<DependencyRecord>> {
+    let json: Value = serde_json::from_str(content)
+        .with_context(|| format!("Pipfile.lockのJSON解析に失敗: {}", path.display()))?;
+
+    let mut records = Vec::new();
+    for section in ["default", "develop"] {
+        let Some(packages) = json.get(section).and_then(|v| v.as_object()) else {
+            continue;
+        };
+        for (name, info) in packages {
+            let Some(name) = normalize_package_name(name) else {
+                continue;
+            };
+            let version = info
+                .get("version")
+                .and_then(|v| v.as_str())
+                .map(|v| v.trim_start_matches("==").trim().to_string())
+                .filter(|v| !v.is_empty());
+            records.push(DependencyRecord {
+                manager: "pip".to_string(),
+                name,
+                version,
+                license: "Unknown".to_string(),
+                source: path.to_path_buf(),
+                dev: section == "develop",
+                ..Default::default()
+            });
+        }
+    }
+
+    Ok(records)
+}
+
+/// package-lock.jsonのうち使用するフィールドだけを読みます。数十MBになるロックファイルもあるため、
+/// `Value`に全体を展開せず、それ以外の値（`requires`など）は読み飛ばします。
+#[derive(Debug, Default, Deserialize)]
+struct PackageLock {
+    name: Option<String>,
+    packages: Option<LockPackages>,
+    dependencies: Option<BTreeMap<String, LockDependency>>,
+}
+
+/// `packages`の各エントリ。ルート（キーが`""`）だけは直接依存の判定のため依存名も読みます。
+#[derive(Debug, Default)]
+struct LockPackages {
+    entries: BTreeMap<String, LockPackage>,
+    direct_names: HashSet<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LockPackage {
+    name: Option<String>,
+    version: Option<String>,
+    license: Option<Value>,
+    resolved: Option<String>,
+    integrity: Option<String>,
+    #[serde(default)]
+    link: bool,
+    #[serde(default)]
+    dev: bool,
+    #[serde(default)]
+    optional: bool,
+    #[serde(default)]
+    dev_optional: bool,
+    #[serde(default)]
+    peer: bool,
+    #[serde(default)]
+    in_bundle: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LockRootPackage {
+    #[serde(flatten)]
+    package: LockPackage,
+    #[serde(default)]
+    dependencies: BTreeMap<String, IgnoredAny>,
+    #[serde(default)]
+    dev_dependencies: BTreeMap<String, IgnoredAny>,
+    #[serde(default)]
+    optional_dependencies: BTreeMap<String, IgnoredAny>,
+    #[serde(default)]
+    peer_dependencies: BTreeMap<String, IgnoredAny>,
+}
+
+impl<'de> Deserialize<'de> for LockPackages {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct PackagesVisitor;
+
+        impl<'de> Visitor<'de> for PackagesVisitor {
+            type Value = LockPackages;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a map of package paths")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<LockPackages, A::Error> {
+                let mut packages = LockPackages::default();
+                while let Some(pkg_path) = map.next_key::<String>()? {
+                    let package = if pkg_path.is_empty() {
+                        let root: LockRootPackage = map.next_value()?;
+                        packages.direct_names = [
+                            root.dependencies,
+                            root.dev_dependencies,
+                            root.optional_dependencies,
+                            root.peer_dependencies,
+                        ]
+                        .into_iter()
+                        .flat_map(BTreeMap::into_keys)
+                        .collect();
+                        root.package
+                    } else {
+                        map.next_value()?
+                    };
+                    packages.entries.insert(pkg_path, package);
+                }
+                Ok(packages)
+            }
+        }
+
+        deserializer.deserialize_map(PackagesVisitor)
+    }
+}
+
+/// v1形式（およびv2の互換用）の`dependencies`のエントリ。
+#[derive(Debug, Default, Deserialize)]
+struct LockDependency {
+    version: Option<String>,
+    license: Option<Value>,
+    integrity: Option<String>,
+    #[serde(default)]
+    dev: bool,
+    #[serde(default)]
+    optional: bool,
+    #[serde(default)]
+    bundled: bool,
+    #[serde(default)]
+    dependencies: BTreeMap<String, LockDependency>,
+}
+
+fn parse_package_lock(text: &str, path: &Path) -> Result<Vec<DependencyRecord>> {
+    let lock: PackageLock = serde_json::from_str(text)
+        .with_context(|| format!("package-lock.jsonのJSON解析に失敗: {}", path.display()))?;
+
+    Ok(records_from_package_lock(lock, path))
+}
+
+fn read_package_lock(reader: impl Read, path: &Path) -> Result<Vec<DependencyRecord>> {
+    let lock: PackageLock = serde_json::from_reader(reader)
+        .with_context(|| format!("package-lock.jsonのJSON解析に失敗: {}", path.display()))?;
+    let mut records = records_from_package_lock(lock, path);
+    mark_lockfile_provenance(&mut records);
+    Ok(records)
+}
+
+fn records_from_package_lock(lock: PackageLock, path: &Path) -> Vec<DependencyRecord> {
+    let PackageLock {
+        name: root_name,
+        packages,
+        dependencies,
+    } = lock;
+
+    if let Some(packages) = packages {
+        let direct_names = packages.direct_names;
+        let mut records: Vec<(String, DependencyRecord)> = packages
+            .entries
+            .into_iter()
+            .filter_map(|(pkg_path, info)| {
+                let mut record =
+                    build_package_lock_record(&pkg_path, info, path, root_name.as_deref())?;
+                record.direct = pkg_path.strip_prefix("node_modules/") == Some(&record.name)
+                    && direct_names.contains(&record.name);
+                Some((pkg_path, record))
+            })
+            .collect();
+        if let Some(deps) = &dependencies {
+            backfill_versions_from_dependencies(&mut records, deps);
+        }
+        merge_npm_duplicates(records.into_iter().map(|(_, record)| record).collect())
+    } else if let Some(deps) = dependencies {
+        let mut records = Vec::new();
+        collect_from_dependencies_map(deps, path, &mut records);
+        merge_npm_duplicates(records)
+    } else {
+        Vec::new()
+    }
+}
+
+/// `packages`にバージョンが無いエントリを、同じパスにある`dependencies`のエントリで補います。
+/// パスをたどれない場合は名前だけで探します。
+fn backfill_versions_from_dependencies(
+    records: &mut [(String, DependencyRecord)],
+    deps: &BTreeMap<String, LockDependency>,
+) {
+    if records.iter().all(|(_, record)| record.version.is_some()) {
+        return;
+    }
+
+    let mut versions = HashMap::new();
+    collect_dependency_versions(deps, &mut versions);
+    for (pkg_path, record) in records.iter_mut().filter(|(_, r)| r.version.is_none()) {
+        record.version = nested_dependency_version(deps, pkg_path)
+            .or_else(|| versions.get(record.name.as_str()).cloned());
+    }
+}
+
+/// `node_modules/a/node_modules/b`のようなパスを`dependencies`の入れ子に沿ってたどります。
+fn nested_dependency_version(
+    deps: &BTreeMap<String, LockDependency>,
+    pkg_path: &str,
+) -> Option<String> {
+    let mut names = pkg_path
+        .strip_prefix("node_modules/")?
+        .split("/node_modules/");
+    let mut dependency = deps.get(names.next()?)?;
+    for name in names {
+        dependency = dependency.dependencies.get(name)?;
+    }
+    dependency.version.as_deref().and_then(npm_lock_version)
+}
+
+fn collect_dependency_versions<'a>(
+    map: &'a BTreeMap<String, LockDependency>,
+    acc: &mut HashMap<&'a str, String>,
+) {
+    for (name, value) in map {
+        if let Some(version) = value.version.as_deref().and_then(npm_lock_version) {
+            acc.entry(name.as_str()).or_insert(version);
+        }
+    }
+    for value in map.values() {
+        collect_dependency_versions(&value.dependencies, acc);
+    }
+}
+
+fn build_package_lock_record(
+    pkg_path: &str,
+    info: LockPackage,
+    source: &Path,
+    root_name: Option<&str>,
+) -> Option<DependencyRecord> {
+    // ワークスペースへのリンクはリンク先のエントリ（`packages/foo`など）と重複するため読まない
+    if info.link {
+        return None;
+    }
+    let license = info
+        .license
+        .as_ref()
+        .and_then(extract_license)
+        .unwrap_or_else(|| "Unknown".to_string());
+    let name = info
+        .name
+        .or_else(|| package_name_from_path(pkg_path))
+        .or_else(|| root_name.map(|s| s.to_string()))?;
+
+    Some(DependencyRecord {
+        manager: "npm".to_string(),
+        name,
+        version: info.version,
+        license,
+        source: source.to_path_buf(),
+        dev: info.dev,
+        optional: info.optional || info.dev_optional,
+        peer: info.peer,
+        bundled: info.in_bundle,
+        is_self: is_local_npm_package(pkg_path, info.resolved.as_deref()),
+        integrity: npm_integrity(info.integrity),
+        ..Default::default()
+    })
+}
+
+/// ネストした`node_modules`などで同じ`(name, version)`が複数回現れる場合に1件にまとめます。
+/// 直接依存・同梱はどれか1つでも該当すれば、開発用・optional・peerはすべてが該当する場合だけ立てます
+/// （通常の依存としても使われるパッケージは`--no-peer`などで除外しないため）。
+fn merge_npm_duplicates(records: Vec<DependencyRecord>) -> Vec<DependencyRecord> {
+    let mut index: HashMap<(String, Option<String>), usize> = HashMap::new();
+    let mut unique: Vec<DependencyRecord> = Vec::new();
+    for record in records {
+        let key = (record.name.clone(), record.version.clone());
+        match index.get(&key) {
+            Some(&idx) => {
+                let merged = &mut unique[idx];
+                merged.direct |= record.direct;
+                merged.bundled |= record.bundled;
+                merged.is_self |= record.is_self;
+                merged.dev &= record.dev;
+                merged.optional &= record.optional;
+                merged.peer &= record.peer;
+                if merged.has_unknown_license() && !record.has_unknown_license() {
+                    merged.license = record.license;
+                }
+                if merged.integrity.is_none() {
+                    merged.integrity = record.integrity;
+                }
+            }
+            None => {
+                index.insert(key, unique.len());
+                unique.push(record);
+            }
+        }
+    }
+    unique
+}
+
+/// ルートパッケージ、`node_modules`の外にあるワークスペースのパッケージ、`file:`で参照する
+/// ローカルのパッケージは第三者の依存ではないため、プロジェクト自身として扱います。
+fn is_local_npm_package(pkg_path: &str, resolved: Option<&str>) -> bool {
+    pkg_path.is_empty()
+        || !pkg_path.split('/').any(|segment| segment == "node_modules")
+        || resolved.is_some_and(|resolved| resolved.starts_with("file:"))
+}
+
+fn npm_integrity(integrity: Option<String>) -> Option<String> {
+    integrity
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+fn collect_from_dependencies_map(
+    map: BTreeMap<String, LockDependency>,
+    source: &Path,
+    acc: &mut Vec<DependencyRecord>,
+) {
+    for (name, value) in map {
+        let version = value.version.as_deref().and_then(npm_lock_version);
+        acc.push(DependencyRecord {
+            manager: "npm".to_string(),
+            name,
+            is_self: version.as_deref().is_some_and(|v| v.starts_with("file:")),
+            version,
+            license: value
+                .license
+                .as_ref()
+                .and_then(extract_license)
+                .unwrap_or_else(|| "Unknown".to_string()),
+            source: source.to_path_buf(),
+            dev: value.dev,
+            optional: value.optional,
+            bundled: value.bundled,
+            integrity: npm_integrity(value.integrity),
+            ..Default::default()
+        });
+        collect_from_dependencies_map(value.dependencies, source, acc);
+    }
+}
+
+/// v1形式の`dependencies`の`version`から解決済みのバージョンだけを取り出します。
+/// `^1.2.3`・`=1.2.3`のような単一バージョンの範囲は`1.2.3`にし、`~1.0`・`>=1 <2`・`*`のように
+/// バージョンが定まらない範囲は`None`にします。`file:`・`git+https://...`などの指定はそのまま残します。
+fn npm_lock_version(raw: &str) -> Option<String> {
+    let raw = raw.trim();
+    let first = raw.chars().next()?;
+    if !(first.is_ascii_digit() || "^~=<>*vxX".contains(first)) {
+        return Some(raw.to_string());
+    }
+    let base = raw.strip_prefix(['^', '~', '=']).unwrap_or(raw);
+    let base = base.strip_prefix('v').unwrap_or(base);
+    version::is_exact(base).then(|| base.to_string())
+}
+
+fn package_name_from_path(path: &str) -> Option<String> {
+    if path.is_empty() {
+        return None;
+    }
+
+    let mut segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    if segments.is_empty() {
+        return None;
+    }
+
+    if let Some(pos) = segments
+        .iter()
+        .rposition(|segment| *segment == "node_modules")
+    {
+        segments = segments.split_off(pos + 1);
+    }
+
+    if segments.is_empty() {
+        None
+    } else if segments[0].starts_with('@') && segments.len() >= 2 {
+        Some(format!("{}/{}", segments[0], segments[1]))
+    } else {
+        Some(segments[0].to_string())
+    }
+}
+
+fn parse_yarn_lock(content: &str, path: &Path) -> Vec<DependencyRecord> {
+    let mut records = Vec::new();
+    let mut seen = HashSet::new();
+    let mut current_name: Option<String> = None;
+
+    for line in content.lines() {
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+
+        if !line.starts_with(' ') {
+            current_name = line
+                .strip_suffix(':')
+                .and_then(package_name_from_yarn_header);
+            continue;
+        }
+
+        let Some(field) = line.strip_prefix("  ").filter(|f| !f.starts_with(' ')) else {
+            continue;
+        };
+        let Some(version) = field
+            .strip_prefix("version:")
+            .or_else(|| field.strip_prefix("version "))
+        else {
+            continue;
+        };
+        let Some(name) = current_name.take() else {
+            continue;
+        };
+
+        let version = version.trim().trim_matches('"').to_string();
+        if seen.insert((name.clone(), version.clone())) {
+            records.push(DependencyRecord {
+                manager: "npm".to_string(),
+                name,
+                version: (!version.is_empty()).then_some(version),
+                license: "Unknown".to_string(),
+                source: path.to_path_buf(),
+                ..Default::default()
+            });
+        }
+    }
+
+    records
+}
+
+fn package_name_from_yarn_header(header: &str) -> Option<String> {
+    let spec = header.split(',').next()?.trim().trim_matches('"');
+    if spec.is_empty() || spec == "__metadata" || spec.contains("@workspace:") {
+        return None;
+    }
+
+    let name = match spec.get(1..).and_then(|rest| rest.find('@')) {
+        Some(idx) => &spec[..idx + 1],
+        None => spec,
+    };
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+fn parse_pnpm_lock(content: &str, path: &Path) -> Vec<DependencyRecord> {
+    let legacy_keys = content
+        .lines()
+        .find_map(|line| line.strip_prefix("lockfileVersion:"))
+        .and_then(|value| {
+            value
+                .trim()
+                .trim_matches(|c| c == '\'' || c == '"')
+                .split('.')
+                .next()
+                .and_then(|major| major.parse::<u32>().ok())
+        })
+        .is_some_and(|major| major < 6);
+
+    let mut records: Vec<DependencyRecord> = Vec::new();
+    let mut seen = HashSet::new();
+    let mut in_packages = false;
+    let mut current: Option<usize> = None;
+
+    for line in content.lines() {
+        if !line.starts_with(' ') && !line.trim().is_empty() {
+            in_packages = line.trim_end() == "packages:";
+            current = None;
+            continue;
+        }
+        if !in_packages {
+            continue;
+        }
+        if line.starts_with("    ") && line.trim() == "dev: true" {
+            if let Some(idx) = current {
+                records[idx].dev = true;
+            }
+            continue;
+        }
+
+        let Some(key) = line
+            .strip_prefix("  ")
+            .filter(|rest| !rest.starts_with(' '))
+            .and_then(|rest| rest.trim_end().strip_suffix(':'))
+        else {
+            continue;
+        };
+        let key = key.trim_matches(|c| c == '\'' || c == '"');
+        let parsed = if legacy_keys {
+            parse_pnpm_v5_key(key)
+        } else {
+            parse_pnpm_key(key)
+        };
+        current = None;
+        let Some((name, version)) = parsed else {
+            continue;
+        };
+
+        if seen.insert((name.clone(), version.clone())) {
+            current = Some(records.len());
+            records.push(DependencyRecord {
+                manager: "npm".to_string(),
+                name,
+                version: Some(version),
+                license: "Unknown".to_string(),
+                source: path.to_path_buf(),
+                ..Default::default()
+            });
+        }
+    }
+
+    records
+}
+
+fn parse_pnpm_key(key: &str) -> Option<(String, String)> {
+    let key = key.strip_prefix('/').unwrap_or(key);
+    let key = key.split('(').next()?;
+    let idx = key.get(1..)?.find('@')? + 1;
+    let (name, version) = (&key[..idx], &key[idx + 1..]);
+    (!name.is_empty() && !version.is_empty()).then(|| (name.to_string(), version.to_string()))
+}
+
+fn parse_pnpm_v5_key(key: &str) -> Option<(String, String)> {
+    let key = key.strip_prefix('/')?;
+    let segments: Vec<&str> = key.split('/').collect();
+    let name_len = if key.starts_with('@') { 2 } else { 1 };
+    if segments.len() <= name_len {
+        return None;
+    }
+
+    let name = segments[..name_len].join("/");
+    let version = segments[name_len..].join("/");
+    let version = version.split('_').next()?.to_string();
+    (!version.is_empty()).then_some((name, version))
+}
+
+#[derive(Debug, Deserialize)]
+struct CargoLockFile {
+    #[serde(default)]
+    package: Vec<CargoLockPackage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CargoLockPackage {
+    name: String,
+    version: Option<String>,
+}
+
+fn parse_cargo_lock(content: &str, path: &Path) -> Result<Vec<DependencyRecord>> {
+    let lock: CargoLockFile = toml::from_str(content)
+        .with_context(|| format!("Cargo.lockのTOML解析に失敗: {}", path.display()))?;
+
+    Ok(lock
+        .package
+        .into_iter()
+        .map(|package| DependencyRecord {
+            manager: "cargo".to_string(),
+            name: package.name,
+            version: package.version,
+            license: "Unknown".to_string(),
+            source: path.to_path_buf(),
+            ..Default::default()
+        })
+        .collect())
+}
+
+#[derive(Debug, Deserialize)]
+struct ComposerLockFile {
+    #[serde(default)]
+    packages: Vec<ComposerPackage>,
+    #[serde(default, rename = "packages-dev")]
+    packages_dev: Vec<ComposerPackage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ComposerPackage {
+    name: String,
+    version: Option<String>,
+    #[serde(default)]
+    license: Vec<String>,
+    homepage: Option<String>,
+}
+
+fn parse_composer_lock(content: &str, path: &Path) -> Result<Vec<DependencyRecord>> {
+    let lock: ComposerLockFile = serde_json::from_str(content)
+        .with_context(|| format!("composer.lockのJSON解析に失敗: {}", path.display()))?;
+
+    let production = lock.packages.into_iter().map(|package| (package, false));
+    let development = lock.packages_dev.into_iter().map(|package| (package, true));
+    Ok(production
+        .chain(development)
+        .map(|(package, dev)| {
+            let license = if package.license.is_empty() {
+                "Unknown".to_string()
+            } else {
+                package.license.join(" OR ")
+            };
+            DependencyRecord {
+                manager: "composer".to_string(),
+                name: package.name,
+                version: package.version,
+                license,
+                source: path.to_path_buf(),
+                homepage: package.homepage.filter(|url| !url.trim().is_empty()),
+                dev,
+                ..Default::default()
+            }
+        })
+        .collect())
+}
+
+fn parse_gemfile_lock(content: &str, path: &Path) -> Vec<DependencyRecord> {
+    let mut records = Vec::new();
+    let mut in_gem_section = false;
+    let mut in_specs = false;
+    for line in content.lines() {
+        if !line.starts_with(' ') {
+            in_gem_section = matches!(line.trim(), "GEM" | "GIT" | "PATH");
+            in_specs = false;
+            continue;
+        }
+        if !in_gem_section {
+            continue;
+        }
+        if line.trim() == "specs:" {
+            in_specs = true;
+            continue;
+        }
+        let Some(spec) = line.strip_prefix("    ") else {
+            continue;
+        };
+        if !in_specs || spec.starts_with(' ') {
+            continue;
+        }
+        let Some((name, rest)) = spec.split_once(" (") else {
+            continue;
+        };
+        let version = rest.trim_end_matches(')').trim();
+        records.push(DependencyRecord {
+            manager: "gem".to_string(),
+            name: name.trim().to_string(),
+            version: (!version.is_empty()).then(|| version.to_string()),
+            license: "Unknown".to_string(),
+            source: path.to_path_buf(),
+            ..Default::default()
+        });
+    }
+    records
+}
+
+fn parse_pom_xml(content: &str, path: &Path) -> Result<Vec<DependencyRecord>> {
+    let document = roxmltree::Document::parse(content)
+        .with_context(|| format!("pom.xmlのXML解析に失敗: {}", path.display()))?;
+    let Some(dependencies) = child_element(document.root_element(), "dependencies") else {
+        return Ok(Vec::new());
+    };
+
+    Ok(dependencies
+        .children()
+        .filter(|node| node.has_tag_name("dependency"))
+        .filter_map(|dependency| {
+            let group_id = child_text(dependency, "groupId")?;
+            let artifact_id = child_text(dependency, "artifactId")?;
+            Some(DependencyRecord {
+                manager: "maven".to_string(),
+                name: format!("{group_id}:{artifact_id}"),
+                version: child_text(dependency, "version"),
+                license: "Unknown".to_string(),
+                source: path.to_path_buf(),
+                direct: true,
+                dev: child_text(dependency, "scope").as_deref() == Some("test"),
+                ..Default::default()
+            })
+        })
+        .collect())
+}
+
+/// `group:artifact:version=configuration,...`の各行をMavenの座標として読み込みます。
+/// テスト用の構成（`testCompileClasspath`など）でのみ使われる依存はdevとして扱います。
+fn parse_gradle_lockfile(content: &str, path: &Path) -> Vec<DependencyRecord> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let (coordinate, configurations) = line.split_once('=').unwrap_or((line, ""));
+            let mut parts = coordinate.split(':');
+            let (Some(group), Some(artifact), Some(version)) =
+                (parts.next(), parts.next(), parts.next())
+            else {
+                return None;
+            };
+            let configurations: Vec<&str> = configurations
+                .split(',')
+                .map(str::trim)
+                .filter(|c| !c.is_empty())
+                .collect();
+            Some(DependencyRecord {
+                manager: "maven".to_string(),
+                name: format!("{group}:{artifact}"),
+                version: Some(version.to_string()),
+                license: "Unknown".to_string(),
+                source: path.to_path_buf(),
+                dev: !configurations.is_empty()
+                    && configurations.iter().all(|c| c.starts_with("test")),
+                ..Default::default()
+            })
+        })
+        .collect()
+}
+
+/// condaの`environment.yml`の`dependencies:`を読み込みます。`numpy=1.26`などはcondaのパッケージ、
+/// `- pip:`の下の一覧はrequirements.txtと同じ書式のpipの依存として扱います。
+fn parse_conda_environment(content: &str, path: &Path) -> Vec<DependencyRecord> {
+    let mut records = Vec::new();
+    let mut in_dependencies = false;
+    let mut pip_indent: Option<usize> = None;
+
+    for line in content.lines() {
+        let text = strip_requirement_comment(line).trim_end();
+        if text.trim().is_empty() {
+            continue;
+        }
+        let indent = text.len() - text.trim_start().len();
+        if indent == 0 && !text.starts_with('-') {
+            in_dependencies = text == "dependencies:";
+            pip_indent = None;
+            continue;
+        }
+        let Some(item) = in_dependencies
+            .then(|| text.trim_start().strip_prefix('-'))
+            .flatten()
+        else {
+            continue;
+        };
+        let item = item.trim().trim_matches(|c| c == '\'' || c == '"');
+        if pip_indent.is_some_and(|pip| indent <= pip) {
+            pip_indent = None;
+        }
+
+        if pip_indent.is_some() {
+            if let Some((name, version)) = parse_requirement_line(item) {
+                records.push(DependencyRecord {
+                    manager: "pip".to_string(),
+                    name,
+                    version,
+                    license: "Unknown".to_string(),
+                    source: path.to_path_buf(),
+                    direct: true,
+                    marker: requirement_marker(item),
+                    ..Default::default()
+                });
+            }
+        } else if item == "pip:" {
+            pip_indent = Some(indent);
+        } else if let Some((name, version)) = parse_conda_spec(item) {
+            records.push(DependencyRecord {
+                manager: "conda".to_string(),
+                name,
+                version,
+                license: "Unknown".to_string(),
+                source: path.to_path_buf(),
+                direct: true,
+                ..Default::default()
+            });
+        }
+    }
+
+    records
+}
+
+/// `conda-forge::numpy=1.26.0=py311_0`のようなcondaのパッケージ指定から、
+/// チャンネルとビルド文字列を除いた名前とバージョンを取り出します。
+fn parse_conda_spec(spec: &str) -> Option<(String, Option<String>)> {
+    let spec = spec.rsplit_once("::").map_or(spec, |(_, rest)| rest).trim();
+    let idx = spec
+        .find(|c: char| matches!(c, '=' | '<' | '>' | '!' | '~') || c.is_whitespace())
+        .unwrap_or(spec.len());
+    let name = spec[..idx].trim();
+    if name.is_empty() || name.ends_with(':') {
+        return None;
+    }
+    let version = spec[idx..].trim_start_matches(|c: char| {
+        matches!(c, '=' | '<' | '>' | '!' | '~') || c.is_whitespace()
+    });
+    let version = version
+        .split(|c: char| c == '=' || c.is_whitespace())
+        .next()
+        .filter(|v| !v.is_empty());
+    Some((name.to_string(), version.map(str::to_string)))
+}
+
+pub fn child_element<'a, 'input>(
+    node: roxmltree::Node<'a, 'input>,
+    name: &str,
+) -> Option<roxmltree::Node<'a, 'input>> {
+    node.children().find(|child| child.has_tag_name(name))
+}
+
+pub fn child_text(node: roxmltree::Node, name: &str) -> Option<String> {
+    child_element(node, name)
+        .and_then(|child| child.text())
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+        .map(str::to_string)
+}
+
+fn parse_go_mod(content: &str, path: &Path) -> Vec<DependencyRecord> {
+    let mut records = Vec::new();
+    let mut block: Option<String> = None;
+    for raw in content.lines() {
+        let indirect = raw
+            .split_once("//")
+            .is_some_and(|(_, comment)| comment.trim() == "indirect");
+        let line = raw.split("//").next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let spec = match block.as_deref() {
+            Some(directive) => {
+                if line == ")" {
+                    block = None;
+                    continue;
+                }
+                (directive == "require").then_some(line)
+            }
+            None => {
+                let (directive, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+                let rest = rest.trim();
+                if rest == "(" {
+                    block = Some(directive.to_string());
+                    continue;
+                }
+                (directive == "require").then_some(rest)
+            }
+        };
+
+        let mut parts = spec.unwrap_or_default().split_whitespace();
+        if let (Some(name), Some(version)) = (parts.next(), parts.next()) {
+            records.push(DependencyRecord {
+                manager: "go".to_string(),
+                name: name.to_string(),
+                version: Some(version.to_string()),
+                license: "Unknown".to_string(),
+                source: path.to_path_buf(),
+                direct: !indirect,
+                ..Default::default()
+            });
+        }
+    }
+    records
+}
+
+fn parse_go_vendor_modules(content: &str, path: &Path) -> Vec<DependencyRecord> {
+    let mut records = Vec::new();
+    for line in content.lines() {
+        let Some(spec) = line.strip_prefix("# ") else {
+            continue;
+        };
+        let module = spec.split("=>").next().unwrap_or(spec);
+        let mut parts = module.split_whitespace();
+        let Some(name) = parts.next() else {
+            continue;
+        };
+        records.push(DependencyRecord {
+            manager: "go".to_string(),
+            name: name.to_string(),
+            version: parts.next().map(|v| v.to_string()),
+            license: "Unknown".to_string(),
+            source: path.to_path_buf(),
+            ..Default::default()
+        });
+    }
+    records
+}
+
+struct IgnoreRule {
+    pattern: Pattern,
+    anchored: bool,
+    dir_only: bool,
+}
+
+fn load_gitignore(root: &Path) -> Vec<IgnoreRule> {
+    let Ok(content) = fs::read_to_string(root.join(".gitignore")) else {
+        return Vec::new();
+    };
+    content.lines().filter_map(parse_gitignore_line).collect()
+}
+
+fn parse_gitignore_line(line: &str) -> Option<IgnoreRule> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') || line.starts_with('!') {
+        return None;
+    }
+    let dir_only = line.ends_with('/');
+    let line = line.trim_end_matches('/');
+    let anchored = line.contains('/');
+    let pattern = Pattern::new(line.trim_start_matches('/')).ok()?;
+    Some(IgnoreRule {
+        pattern,
+        anchored,
+        dir_only,
+    })
+}
+
+fn is_gitignored(rules: &[IgnoreRule], relative: &Path, is_dir: bool) -> bool {
+    let options = MatchOptions {
+        require_literal_separator: true,
+        ..MatchOptions::new()
+    };
+    rules.iter().any(|rule| {
+        if rule.dir_only && !is_dir {
+            return false;
+        }
+        if rule.anchored {
+            rule.pattern.matches_path_with(relative, options)
+        } else {
+            relative
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| rule.pattern.matches_with(name, options))
+        }
+    })
+}
+
+pub fn fill_licenses_from_local_files(records: &mut [DependencyRecord]) -> usize {
+    let mut filled = 0;
+    for record in records.iter_mut() {
+        if !record.has_unknown_license() {
+            continue;
+        }
+        let Some(manifest_dir) = record.source.parent() else {
+            continue;
+        };
+        if let Some(license) = local_package_dirs(record, manifest_dir)
+            .iter()
+            .find_map(|dir| detect_license_file(dir))
+        {
+            record.license = license.to_string();
+            filled += 1;
+        }
+    }
+    filled
+}
+
+fn local_package_dirs(record: &DependencyRecord, manifest_dir: &Path) -> Vec<PathBuf> {
+    match record.manager.as_str() {
+        "npm" => vec![manifest_dir.join("node_modules").join(&record.name)],
+        "pip" => {
+            let module = record.name.replace('-', "_");
+            let mut dirs = Vec::new();
+            for vendor in ["vendor", "_vendor"] {
+                dirs.push(manifest_dir.join(vendor).join(&record.name));
+                if module != record.name {
+                    dirs.push(manifest_dir.join(vendor).join(&module));
+                }
+            }
+            dirs
+        }
+        "cargo" => vec![manifest_dir.join("vendor").join(&record.name)],
+        _ => Vec::new(),
+    }
+}
+
+fn detect_license_file(dir: &Path) -> Option<&'static str> {
+    let entries = fs::read_dir(dir).ok()?;
+    let mut candidates: Vec<_> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            let name = entry.file_name().to_string_lossy().to_ascii_lowercase();
+            name.starts_with("license")
+                || name.starts_with("licence")
+                || name.starts_with("copying")
+        })
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .collect();
+    candidates.sort();
+
+    candidates
+        .iter()
+        .filter_map(|path| fs::read_to_string(path).ok())
+        .find_map(|text| guess_license_from_text(&text))
+}
+
+fn guess_license_from_text(text: &str) -> Option<&'static str> {
+    let normalized = text
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_ascii_uppercase();
+    let has = |needle: &str| normalized.contains(needle);
+
+    if has("GNU AFFERO GENERAL PUBLIC LICENSE") {
+        Some("AGPL-3.0")
+    } else if has("GNU LESSER GENERAL PUBLIC LICENSE") {
+        if has("VERSION 2.1") {
+            Some("LGPL-2.1")
+        } else {
+            Some("LGPL-3.0")
+        }
+    } else if has("GNU GENERAL PUBLIC LICENSE") {
+        if has("VERSION 2,") || has("VERSION 2 ") {
+            Some("GPL-2.0")
+        } else {
+            Some("GPL-3.0")
+        }
+    } else if has("MOZILLA PUBLIC LICENSE VERSION 2.0")
+        || has("MOZILLA PUBLIC LICENSE, VERSION 2.0")
+    {
+        Some("MPL-2.0")
+    } else if has("APACHE LICENSE") && has("VERSION 2.0") {
+        Some("Apache-2.0")
+    } else if has("PERMISSION IS HEREBY GRANTED, FREE OF CHARGE") {
+        Some("MIT")
+    } else if has("PERMISSION TO USE, COPY, MODIFY, AND/OR DISTRIBUTE THIS SOFTWARE") {
+        Some("ISC")
+    } else if has("REDISTRIBUTION AND USE IN SOURCE AND BINARY FORMS") {
+        if has("NEITHER THE NAME") || has("THE NAMES OF ITS CONTRIBUTORS MAY NOT BE USED") {
+            Some("BSD-3-Clause")
+        } else {
+            Some("BSD-2-Clause")
+        }
+    } else if has("THIS IS FREE AND UNENCUMBERED SOFTWARE RELEASED INTO THE PUBLIC DOMAIN") {
+        Some("Unlicense")
+    } else {
+        None
+    }
+}
+
+/// npmの`license`/`licenses`フィールドをSPDX式にします。配列はデュアルライセンスを意味するため、
+/// 重複を除いて`OR`で結合します。旧形式の`{"type": ...}`・`{"license": ...}`にも対応します。
+pub fn extract_license(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => {
+            let trimmed = s.trim();
+            (!trimmed.is_empty()).then(|| trimmed.to_string())
+        }
+        Value::Array(values) => {
+            let mut merged: Vec<String> = Vec::new();
+            for license in values.iter().filter_map(extract_license) {
+                if !merged
+                    .iter()
+                    .any(|seen| seen.eq_ignore_ascii_case(&license))
+                {
+                    merged.push(license);
+                }
+            }
+            match merged.len() {
+                0 => None,
+                1 => merged.pop(),
+                _ => Some(
+                    merged
+                        .iter()
+                        .map(|license| {
+                            if license.contains(' ') {
+                                format!("({license})")
+                            } else {
+                                license.clone()
+                            }
+                        })
+                        .collect::<Vec<_>>()
+                        .join(" OR "),
+                ),
+            }
+        }
+        Value::Object(map) => map
+            .get("type")
+            .or_else(|| map.get("license"))
+            .and_then(extract_license),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn license_arrays_become_or_expressions() {
+        let extract = |json: &str| extract_license(&serde_json::from_str(json).unwrap());
+        assert_eq!(extract(r#"["MIT", "ISC"]"#), Some("MIT OR ISC".to_string()));
+        assert_eq!(extract(r#"["MIT", "mit", "MIT"]"#), Some("MIT".to_string()));
+        assert_eq!(
+            extract(r#"[{"type": "MIT", "url": "https://x"}, {"type": "Apache-2.0"}]"#),
+            Some("MIT OR Apache-2.0".to_string())
+        );
+        assert_eq!(
+            extract(r#"["BSD-3-Clause", "Apache-2.0 AND MIT"]"#),
+            Some("BSD-3-Clause OR (Apache-2.0 AND MIT)".to_string())
+        );
+        assert_eq!(
+            extract(r#"{"license": {"type": "ISC"}}"#),
+            Some("ISC".to_string())
+        );
+        assert_eq!(
+            extract(r#"[["MIT"], {"license": "GPL-2.0"}]"#),
+            Some("MIT OR GPL-2.0".to_string())
+        );
+        assert_eq!(extract(r#"[" ", {}]"#), None);
+    }
+
+    #[test]
+    fn requirement_line_parsing() {
+        assert_eq!(
+            parse_requirement_line("requests==2.32.0"),
+            Some(("requests".to_string(), Some("2.32.0".to_string())))
+        );
+        assert_eq!(
+            parse_requirement_line("uvicorn[standard]>=0.27"),
+            Some(("uvicorn".to_string(), Some("0.27".to_string())))
+        );
+        assert_eq!(parse_requirement_line("# comment line"), None);
+        assert_eq!(parse_requirement_line(""), None);
+    }
+
+    #[test]
+    fn environment_markers_are_kept() {
+        let records = parse_requirements(
+            "pywin32==306; sys_platform == 'win32'  # windows only\nrequests==2.32.0\n",
+            Path::new("requirements.txt"),
+        );
+        assert_eq!(records[0].name, "pywin32");
+        assert_eq!(
+            records[0].marker.as_deref(),
+            Some("sys_platform == 'win32'")
+        );
+        assert_eq!(records[1].marker, None);
+
+        let pyproject = parse_pyproject_toml(
+            "[project]\ndependencies = [\"tomli>=2.0; python_version < '3.11'\"]\n",
+            Path::new("pyproject.toml"),
+        )
+        .unwrap();
+        assert_eq!(
+            pyproject[0].marker.as_deref(),
+            Some("python_version < '3.11'")
+        );
+    }
+
+    #[test]
+    fn integrity_hashes_are_read_from_lockfiles() {
+        let records = parse_requirements(
+            "requests==2.31.0 \\\n    --hash=sha256:aaa \\\n    --hash sha256:bbb  # pinned\n\
+             pywin32==306; sys_platform == 'win32' --hash=sha256:ccc\nflask\n",
+            Path::new("requirements.txt"),
+        );
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0].version.as_deref(), Some("2.31.0"));
+        assert_eq!(
+            records[0].integrity.as_deref(),
+            Some("sha256:aaa sha256:bbb")
+        );
+        assert_eq!(
+            records[1].marker.as_deref(),
+            Some("sys_platform == 'win32'")
+        );
+        assert_eq!(records[1].integrity.as_deref(), Some("sha256:ccc"));
+        assert_eq!(records[2].integrity, None);
+
+        let records = parse_package_lock(
+            r#"{"packages": {"node_modules/react": {"version": "18.2.0", "integrity": "sha512-AAAA"}},
+                "dependencies": {}}"#,
+            Path::new("package-lock.json"),
+        )
+        .unwrap();
+        assert_eq!(records[0].integrity.as_deref(), Some("sha512-AAAA"));
+    }
+
+    #[test]
+    fn requirement_lines_with_urls_and_editables() {
+        assert_eq!(
+            parse_requirement_line("mylib @ git+https://github.com/org/mylib.git@v1.2 # pinned"),
+            Some(("mylib".to_string(), None))
+        );
+        assert_eq!(
+            parse_requirement_line("-e git+https://github.com/org/tool.git#egg=My_Tool"),
+            Some(("my-tool".to_string(), None))
+        );
+        assert_eq!(
+            parse_requirement_line("git+https://user@host/repo.git#egg=repo&subdirectory=pkg"),
+            Some(("repo".to_string(), None))
+        );
+        assert_eq!(parse_requirement_line("-e ."), None);
+        assert_eq!(
+            parse_requirement_line("--index-url https://pypi.example.com"),
+            None
+        );
+        assert_eq!(requirement_include("-r base.txt"), Some("base.txt"));
+        assert_eq!(
+            requirement_include("--requirement=dev.txt # dev"),
+            Some("dev.txt")
+        );
+        assert_eq!(requirement_include("-rcommon.txt"), Some("common.txt"));
+        assert_eq!(requirement_include("requests"), None);
+    }
+
+    #[test]
+    fn a_single_manifest_file_can_be_scanned() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("nested")).unwrap();
+        fs::write(root.join("requirements.txt"), "flask==3.0.0\n").unwrap();
+        fs::write(root.join("nested/requirements.txt"), "requests\n").unwrap();
+        fs::write(root.join("notes.txt"), "hello\n").unwrap();
+
+        let records =
+            collect_records(&root.join("requirements.txt"), &ScanOptions::default()).unwrap();
+        let unsupported = collect_records(&root.join("notes.txt"), &ScanOptions::default());
+
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].name, "flask");
+        assert!(unsupported.is_err());
+    }
+
+    #[test]
+    fn setup_cfg_reads_install_requires() {
+        let records = parse_setup_cfg(
+            "[metadata]\nname = demo\ninstall_requires = ignored\n\n[options]\npython_requires = >=3.8\ninstall_requires =\n    requests>=2.28\n    # comment\n    pywin32==306; sys_platform == 'win32'\n    file: requirements.txt\npackages = find:\n",
+            Path::new("setup.cfg"),
+        );
+        let names: Vec<_> = records.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["requests", "pywin32"]);
+        assert_eq!(records[1].version.as_deref(), Some("306"));
+        assert_eq!(
+            records[1].marker.as_deref(),
+            Some("sys_platform == 'win32'")
+        );
+        assert!(records.iter().all(|r| r.manager == "pip" && r.direct));
+    }
+
+    #[test]
+    fn setup_py_reads_static_install_requires() {
+        let inline = parse_setup_py(
+            r#"
+from setuptools import setup
+
+setup(
+    name="demo",
+    install_requires=[
+        "click==8.1.7",  # CLI
+        'rich>=13',
+        "colorama; platform_system == \"Windows\"",
+    ],
+    extras_require={"dev": ["pytest"]},
+)
+"#,
+            Path::new("setup.py"),
+        );
+        let names: Vec<_> = inline.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["click", "rich", "colorama"]);
+        assert_eq!(inline[0].version.as_deref(), Some("8.1.7"));
+
+        let variable = parse_setup_py(
+            "REQUIRES = [\n    'flask==3.0.0',\n]\n\nsetup(name='demo', install_requires=REQUIRES)\n",
+            Path::new("setup.py"),
+        );
+        assert_eq!(variable.len(), 1);
+        assert_eq!(variable[0].name, "flask");
+
+        let dynamic = parse_setup_py(
+            "setup(install_requires=open('requirements.txt').read().splitlines())\n",
+            Path::new("setup.py"),
+        );
+        assert!(dynamic.is_empty());
+    }
+
+    #[test]
+    fn format_map_parses_custom_file_names() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("web")).unwrap();
+        fs::write(root.join("prod-requirements.in"), "flask==3.0.0\n").unwrap();
+        fs::write(
+            root.join("web/locked.json"),
+            r#"{"packages": {"node_modules/lodash": {"version": "4.17.21"}}}"#,
+        )
+        .unwrap();
+
+        let options = ScanOptions {
+            format_map: vec![
+                "*-requirements.in=requirements".parse().unwrap(),
+                "web/locked.json=package-lock".parse().unwrap(),
+            ],
+            ..Default::default()
+        };
+        let mut records = collect_records(root, &options).unwrap();
+        let unmapped = collect_records(root, &ScanOptions::default()).unwrap();
+
+        records.sort_by(|a, b| a.name.cmp(&b.name));
+        let names: Vec<_> = records
+            .iter()
+            .map(|r| (r.manager.as_str(), r.name.as_str()))
+            .collect();
+        assert_eq!(names, vec![("pip", "flask"), ("npm", "lodash")]);
+        assert!(unmapped.is_empty());
+        assert!("locked.json=unknown".parse::<FormatMapping>().is_err());
+        assert!("locked.json".parse::<FormatMapping>().is_err());
+    }
+
+    #[test]
+    fn requirements_files_carry_their_group() {
+        assert_eq!(requirements_group("requirements.txt"), Some(""));
+        assert_eq!(requirements_group("requirements-dev.txt"), Some("dev"));
+        assert_eq!(requirements_group("requirements_test.txt"), Some("test"));
+        assert_eq!(requirements_group("requirements.docs.txt"), Some("docs"));
+        assert_eq!(requirements_group("requirementsdev.txt"), None);
+        assert_eq!(requirements_group("requirements-.txt"), None);
+        assert_eq!(requirements_group("dev-requirements.txt"), None);
+
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::write(root.join("requirements.txt"), "flask==3.0.0\n").unwrap();
+        fs::write(
+            root.join("requirements-dev.txt"),
+            "-r requirements.txt\npytest==8.0.0\n",
+        )
+        .unwrap();
+        fs::write(root.join("requirements-docs.txt"), "sphinx==7.2.6\n").unwrap();
+
+        let mut records = collect_records(root, &ScanOptions::default()).unwrap();
+        records.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let groups: Vec<(&str, Option<&str>, bool)> = records
+            .iter()
+            .map(|r| (r.name.as_str(), r.group.as_deref(), r.dev))
+            .collect();
+        assert_eq!(
+            groups,
+            vec![
+                ("flask", None, false),
+                ("pytest", Some("dev"), true),
+                ("sphinx", Some("docs"), false),
+            ]
+        );
+    }
+
+    #[test]
+    fn requirement_includes_are_followed_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("reqs")).unwrap();
+        fs::write(
+            root.join("requirements.txt"),
+            "-r reqs/base.txt\nflask==3.0.0\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("reqs/base.txt"),
+            "requests==2.32.0\n-r ../requirements.txt\n-r missing.txt\n",
+        )
+        .unwrap();
+
+        let records = collect_records(root, &ScanOptions::default()).unwrap();
+
+        let mut names: Vec<&str> = records.iter().map(|r| r.name.as_str()).collect();
+        names.sort();
+        assert_eq!(names, vec!["flask", "requests"]);
+        let requests = records.iter().find(|r| r.name == "requests").unwrap();
+        assert!(requests.source.ends_with("reqs/base.txt"));
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn malformed_manifests_are_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("web")).unwrap();
+        fs::write(root.join("requirements.txt"), "flask==3.0.0\n").unwrap();
+        fs::write(root.join("web/package-lock.json"), "{not json").unwrap();
+
+        let records = collect_records(root, &ScanOptions::default()).unwrap();
+        assert_eq!(name_versions(&records), vec![("flask", Some("3.0.0"))]);
+        let mut files = manifest_files(root, &ScanOptions::default()).unwrap();
+        files.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            files,
+            vec![
+                (root.join("requirements.txt"), ManifestKind::Requirements),
+                (
+                    root.join("web/package-lock.json"),
+                    ManifestKind::PackageLock
+                ),
+            ]
+        );
+        assert!(
+            collect_records(&root.join("web/package-lock.json"), &ScanOptions::default()).is_err()
+        );
+    }
 
     #[test]
-    fn requirement_line_parsing() {
+    fn include_patterns_limit_the_walk() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        for dir in ["services/api/dist", "services/web", "tools"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        fs::write(root.join("requirements.txt"), "flask==3.0.0\n").unwrap();
+        fs::write(
+            root.join("services/api/requirements.txt"),
+            "requests==2.32.0\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("services/api/dist/requirements.txt"),
+            "six==1.16.0\n",
+        )
+        .unwrap();
+        fs::write(root.join("services/web/requirements.txt"), "django==5.0\n").unwrap();
+        fs::write(root.join("tools/requirements.txt"), "black==24.1.0\n").unwrap();
+
+        let names = |include: &[&str]| {
+            let options = ScanOptions {
+                include: include.iter().map(|p| Pattern::new(p).unwrap()).collect(),
+                exclude: vec![Pattern::new("dist").unwrap()],
+                ..Default::default()
+            };
+            let mut names: Vec<String> = collect_records(root, &options)
+                .unwrap()
+                .into_iter()
+                .map(|r| r.name)
+                .collect();
+            names.sort();
+            names
+        };
+        assert_eq!(names(&["services/api"]), vec!["requests"]);
+        assert_eq!(names(&["services/*"]), vec!["django", "requests"]);
+        assert_eq!(names(&["tools", "web"]), vec!["black", "django"]);
+        assert_eq!(names(&[]).len(), 4);
+
+        let options = ScanOptions {
+            include: vec![Pattern::new("services/api").unwrap()],
+            ..Default::default()
+        };
+        assert!(may_contain_included(Path::new("services"), &options));
+        assert!(!may_contain_included(Path::new("tools"), &options));
+    }
+
+    #[test]
+    fn max_depth_limits_the_walk() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("app/vendor/lib")).unwrap();
+        fs::write(root.join("requirements.txt"), "flask==3.0.0\n").unwrap();
+        fs::write(root.join("app/requirements.txt"), "requests==2.32.0\n").unwrap();
+        fs::write(
+            root.join("app/vendor/lib/requirements.txt"),
+            "six==1.16.0\n",
+        )
+        .unwrap();
+
+        let names = |max_depth| {
+            let options = ScanOptions {
+                max_depth,
+                ..Default::default()
+            };
+            let mut names: Vec<String> = collect_records(root, &options)
+                .unwrap()
+                .into_iter()
+                .map(|r| r.name)
+                .collect();
+            names.sort();
+            names
+        };
+        assert_eq!(names(Some(1)), vec!["flask"]);
+        assert_eq!(names(Some(2)), vec!["flask", "requests"]);
+        assert_eq!(names(None), vec!["flask", "requests", "six"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn follow_symlinks_walks_linked_directories_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let shared = root.join("shared");
+        let project = root.join("project");
+        fs::create_dir_all(&shared).unwrap();
+        fs::create_dir_all(&project).unwrap();
+        fs::write(shared.join("requirements.txt"), "flask==3.0.0\n").unwrap();
+        fs::write(project.join("requirements.txt"), "requests==2.32.0\n").unwrap();
+        std::os::unix::fs::symlink(&shared, project.join("shared")).unwrap();
+        std::os::unix::fs::symlink(&shared, project.join("shared-again")).unwrap();
+        std::os::unix::fs::symlink(&project, project.join("loop")).unwrap();
+
+        let names = |follow_symlinks| {
+            let options = ScanOptions {
+                follow_symlinks,
+                ..Default::default()
+            };
+            let mut names: Vec<String> = collect_records(&project, &options)
+                .unwrap()
+                .into_iter()
+                .map(|r| r.name)
+                .collect();
+            names.sort();
+            names
+        };
+        assert_eq!(names(false), vec!["requests"]);
+        assert_eq!(names(true), vec!["flask", "requests"]);
+    }
+
+    #[test]
+    fn manifest_formats_resolve_by_name_or_file_name() {
         assert_eq!(
-            parse_requirement_line("requests==2.32.0"),
-            Some(("requests".to_string(), Some("2.32.0".to_string())))
+            ManifestKind::from_format("package-lock"),
+            Some(ManifestKind::PackageLock)
         );
         assert_eq!(
-            parse_requirement_line("uvicorn[standard]>=0.27"),
-            Some(("uvicorn".to_string(), Some("0.27".to_string())))
+            ManifestKind::from_format("requirements.txt"),
+            Some(ManifestKind::Requirements)
         );
-        assert_eq!(parse_requirement_line("# comment line"), None);
-        assert_eq!(parse_requirement_line(""), None);
+        assert_eq!(
+            ManifestKind::from_format("Cargo-Lock"),
+            Some(ManifestKind::CargoLock)
+        );
+        assert_eq!(
+            ManifestKind::from_format("setup.py"),
+            Some(ManifestKind::SetupPy)
+        );
+        assert_eq!(ManifestKind::from_format("build.gradle"), None);
+        for kind in ManifestKind::ALL {
+            assert_eq!(ManifestKind::from_format(kind.format_name()), Some(kind));
+        }
+    }
+
+    #[test]
+    fn pypi_names_are_normalized_per_pep_503() {
+        assert_eq!(normalize_pypi_name("Flask-SQLAlchemy"), "flask-sqlalchemy");
+        assert_eq!(normalize_pypi_name("flask_sqlalchemy"), "flask-sqlalchemy");
+        assert_eq!(normalize_pypi_name("zope.interface"), "zope-interface");
+        assert_eq!(normalize_pypi_name("Some__Weird.-_Name"), "some-weird-name");
+        assert_eq!(
+            parse_requirement_line("Flask_SQLAlchemy [asyncio] >= 3.1"),
+            Some(("flask-sqlalchemy".to_string(), Some("3.1".to_string())))
+        );
+
+        let records = parse_requirements(
+            "Flask-SQLAlchemy==3.1.1\nflask_sqlalchemy==3.1.1\n",
+            Path::new("requirements.txt"),
+        );
+        assert!(records.iter().all(|r| r.name == "flask-sqlalchemy"));
     }
 
     #[test]
@@ -274,4 +2694,983 @@ mod tests {
             Some("lodash".to_string())
         );
     }
+
+    #[test]
+    fn workspace_links_and_local_packages_are_first_party() {
+        let json: PackageLock = serde_json::from_str(
+            r#"{
+                "name": "monorepo",
+                "lockfileVersion": 3,
+                "packages": {
+                    "": {"name": "monorepo", "workspaces": ["packages/*"]},
+                    "node_modules/@acme/ui": {"resolved": "packages/ui", "link": true},
+                    "packages/ui": {"name": "@acme/ui", "version": "1.0.0", "license": "GPL-3.0"},
+                    "packages/ui/node_modules/react": {"version": "18.2.0", "license": "MIT"},
+                    "node_modules/local-tool": {"version": "0.1.0", "resolved": "file:tools/local-tool"},
+                    "node_modules/lodash": {"version": "4.17.21", "license": "MIT"}
+                }
+            }"#,
+        )
+        .unwrap();
+        let records = records_from_package_lock(json, Path::new("package-lock.json"));
+        let mut names: Vec<(&str, bool)> = records
+            .iter()
+            .map(|r| (r.name.as_str(), r.is_self))
+            .collect();
+        names.sort();
+        assert_eq!(
+            names,
+            [
+                ("@acme/ui", true),
+                ("local-tool", true),
+                ("lodash", false),
+                ("monorepo", true),
+                ("react", false),
+            ]
+        );
+
+        let legacy: PackageLock = serde_json::from_str(
+            r#"{"dependencies": {"shared": {"version": "file:../shared"}, "react": {"version": "18.2.0"}}}"#,
+        )
+        .unwrap();
+        let records = records_from_package_lock(legacy, Path::new("package-lock.json"));
+        assert!(records.iter().any(|r| r.name == "shared" && r.is_self));
+        assert!(records.iter().any(|r| r.name == "react" && !r.is_self));
+    }
+
+    #[test]
+    fn package_lock_files_are_read_without_buffering_the_whole_text() {
+        let text = r#"{"lockfileVersion": 3, "packages": {
+            "node_modules/lodash": {"version": "4.17.21", "license": "MIT"},
+            "node_modules/mystery": {"version": "1.0.0"}
+        }}"#;
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("package-lock.json");
+        fs::write(&path, text).unwrap();
+
+        let scanned =
+            parse_manifest_file(ManifestKind::PackageLock, &path, &mut HashSet::new()).unwrap();
+        let expected = parse_manifest(ManifestKind::PackageLock, text, &path).unwrap();
+        assert_eq!(
+            serde_json::to_value(&scanned.records).unwrap(),
+            serde_json::to_value(&expected).unwrap()
+        );
+        assert_eq!(scanned.records[0].provenance, Some(Provenance::Lockfile));
+        assert_eq!(scanned.records[1].provenance, None);
+    }
+
+    #[test]
+    fn hybrid_lockfile_backfills_missing_versions() {
+        let json: PackageLock = serde_json::from_str(
+            r#"{
+                "name": "app",
+                "lockfileVersion": 2,
+                "packages": {
+                    "node_modules/react": { "version": "18.2.0" },
+                    "node_modules/loose-envify": {},
+                    "node_modules/react/node_modules/js-tokens": {},
+                    "node_modules/ms": {},
+                    "node_modules/a": { "version": "1.0.0" },
+                    "node_modules/a/node_modules/ms": {}
+                },
+                "dependencies": {
+                    "react": {
+                        "version": "18.2.0",
+                        "dependencies": {
+                            "js-tokens": { "version": "4.0.0" }
+                        }
+                    },
+                    "loose-envify": { "version": "1.4.0" },
+                    "ms": { "version": "2.0.0" },
+                    "a": {
+                        "version": "1.0.0",
+                        "dependencies": {
+                            "ms": { "version": "2.1.3" }
+                        }
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let records = records_from_package_lock(json, Path::new("package-lock.json"));
+        let version_of = |name: &str| {
+            records
+                .iter()
+                .find(|r| r.name == name)
+                .and_then(|r| r.version.clone())
+        };
+        assert_eq!(version_of("react"), Some("18.2.0".to_string()));
+        assert_eq!(version_of("loose-envify"), Some("1.4.0".to_string()));
+        assert_eq!(version_of("js-tokens"), Some("4.0.0".to_string()));
+        // node_modules/a/node_modules/msはトップレベルの2.0.0ではなくa配下の2.1.3
+        let mut ms_versions: Vec<Option<&str>> = records
+            .iter()
+            .filter(|r| r.name == "ms")
+            .map(|r| r.version.as_deref())
+            .collect();
+        ms_versions.sort();
+        assert_eq!(ms_versions, vec![Some("2.0.0"), Some("2.1.3")]);
+    }
+
+    #[test]
+    fn package_lock_v3_marks_root_dependencies_as_direct() {
+        let json: PackageLock = serde_json::from_str(
+            r#"{
+                "name": "app",
+                "lockfileVersion": 3,
+                "packages": {
+                    "": {
+                        "name": "app",
+                        "dependencies": { "react": "^18.2.0" },
+                        "devDependencies": { "typescript": "^5.0.0" }
+                    },
+                    "node_modules/react": { "version": "18.2.0" },
+                    "node_modules/typescript": { "version": "5.4.0", "dev": true },
+                    "node_modules/loose-envify": { "version": "1.4.0" },
+                    "node_modules/other/node_modules/react": { "version": "17.0.0" }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let records = records_from_package_lock(json, Path::new("package-lock.json"));
+        let direct: Vec<(&str, Option<&str>)> = records
+            .iter()
+            .filter(|r| r.direct)
+            .map(|r| (r.name.as_str(), r.version.as_deref()))
+            .collect();
+        assert_eq!(
+            direct,
+            vec![("react", Some("18.2.0")), ("typescript", Some("5.4.0"))]
+        );
+        let dev: Vec<&str> = records
+            .iter()
+            .filter(|r| r.dev)
+            .map(|r| r.name.as_str())
+            .collect();
+        assert_eq!(dev, vec!["typescript"]);
+        let own: Vec<&str> = records
+            .iter()
+            .filter(|r| r.is_self)
+            .map(|r| r.name.as_str())
+            .collect();
+        assert_eq!(own, vec!["app"]);
+    }
+
+    #[test]
+    fn package_lock_merges_peer_and_regular_occurrences() {
+        let json: PackageLock = serde_json::from_str(
+            r#"{
+                "lockfileVersion": 3,
+                "packages": {
+                    "": { "name": "app", "dependencies": { "react": "^18.2.0" } },
+                    "node_modules/react": { "version": "18.2.0", "license": "MIT" },
+                    "node_modules/ui-kit/node_modules/react": { "version": "18.2.0", "peer": true },
+                    "node_modules/jest/node_modules/react": { "version": "18.2.0", "dev": true },
+                    "node_modules/legacy/node_modules/react": { "version": "17.0.2", "peer": true }
+                }
+            }"#,
+        )
+        .unwrap();
+        let records = records_from_package_lock(json, Path::new("package-lock.json"));
+        let react: Vec<(Option<&str>, bool, bool, bool, &str)> = records
+            .iter()
+            .filter(|r| r.name == "react")
+            .map(|r| {
+                (
+                    r.version.as_deref(),
+                    r.direct,
+                    r.peer,
+                    r.dev,
+                    r.license.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            react,
+            vec![
+                (Some("18.2.0"), true, false, false, "MIT"),
+                (Some("17.0.2"), false, true, false, "Unknown"),
+            ]
+        );
+    }
+
+    #[test]
+    fn package_lock_records_optional_peer_and_bundled_flags() {
+        let json: PackageLock = serde_json::from_str(
+            r#"{
+                "lockfileVersion": 3,
+                "packages": {
+                    "node_modules/fsevents": { "version": "2.3.3", "optional": true },
+                    "node_modules/chokidar-dev": { "version": "1.0.0", "devOptional": true },
+                    "node_modules/react-dom": { "version": "18.2.0", "peer": true },
+                    "node_modules/vendored": { "version": "0.1.0", "inBundle": true },
+                    "node_modules/react": { "version": "18.2.0" }
+                }
+            }"#,
+        )
+        .unwrap();
+        let records = records_from_package_lock(json, Path::new("package-lock.json"));
+        let flags: Vec<(&str, bool, bool, bool)> = records
+            .iter()
+            .map(|r| (r.name.as_str(), r.optional, r.peer, r.bundled))
+            .collect();
+        assert_eq!(
+            flags,
+            vec![
+                ("chokidar-dev", true, false, false),
+                ("fsevents", true, false, false),
+                ("react", false, false, false),
+                ("react-dom", false, true, false),
+                ("vendored", false, false, true),
+            ]
+        );
+
+        let v1: PackageLock = serde_json::from_str(
+            r#"{"lockfileVersion": 1, "dependencies": {
+                "fsevents": { "version": "2.3.3", "optional": true },
+                "vendored": { "version": "0.1.0", "bundled": true }
+            }}"#,
+        )
+        .unwrap();
+        let records = records_from_package_lock(v1, Path::new("package-lock.json"));
+        assert!(records[0].optional && !records[0].bundled);
+        assert!(records[1].bundled && !records[1].optional);
+    }
+
+    #[test]
+    fn package_lock_v1_dedups_nested_duplicates() {
+        let json: PackageLock = serde_json::from_str(
+            r#"{
+                "name": "app",
+                "lockfileVersion": 1,
+                "dependencies": {
+                    "a": {
+                        "version": "1.0.0",
+                        "dependencies": {
+                            "ms": { "version": "2.1.3" }
+                        }
+                    },
+                    "b": {
+                        "version": "1.0.0",
+                        "dependencies": {
+                            "ms": { "version": "2.1.3", "license": "MIT" },
+                            "debug": { "version": "4.3.4" }
+                        }
+                    },
+                    "ms": { "version": "2.0.0" }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let records = records_from_package_lock(json, Path::new("package-lock.json"));
+        let mut keys: Vec<(&str, Option<&str>, &str)> = records
+            .iter()
+            .map(|r| (r.name.as_str(), r.version.as_deref(), r.license.as_str()))
+            .collect();
+        keys.sort();
+        assert_eq!(
+            keys,
+            vec![
+                ("a", Some("1.0.0"), "Unknown"),
+                ("b", Some("1.0.0"), "Unknown"),
+                ("debug", Some("4.3.4"), "Unknown"),
+                ("ms", Some("2.0.0"), "Unknown"),
+                ("ms", Some("2.1.3"), "MIT"),
+            ]
+        );
+    }
+
+    #[test]
+    fn npm_lock_version_strips_ranges() {
+        assert_eq!(npm_lock_version("1.2.3"), Some("1.2.3".to_string()));
+        assert_eq!(npm_lock_version("^1.2.3"), Some("1.2.3".to_string()));
+        assert_eq!(
+            npm_lock_version("=v2.0.0-beta.1"),
+            Some("2.0.0-beta.1".to_string())
+        );
+        assert_eq!(npm_lock_version("~1.0"), None);
+        assert_eq!(npm_lock_version(">=1.0.0 <2.0.0"), None);
+        assert_eq!(npm_lock_version("1.x"), None);
+        assert_eq!(npm_lock_version("*"), None);
+        assert_eq!(npm_lock_version(""), None);
+        assert_eq!(
+            npm_lock_version("file:../shared"),
+            Some("file:../shared".to_string())
+        );
+
+        let json: PackageLock = serde_json::from_str(
+            r#"{
+                "lockfileVersion": 1,
+                "dependencies": {
+                    "a": { "version": "^1.2.3" },
+                    "b": { "version": "~1.0" }
+                }
+            }"#,
+        )
+        .unwrap();
+        let records = records_from_package_lock(json, Path::new("package-lock.json"));
+        let version_of = |name: &str| {
+            records
+                .iter()
+                .find(|r| r.name == name)
+                .and_then(|r| r.version.as_deref())
+        };
+        assert_eq!(version_of("a"), Some("1.2.3"));
+        assert_eq!(version_of("b"), None);
+    }
+
+    #[test]
+    fn yarn_v1_lock_parsing() {
+        let content = r#"# THIS IS AN AUTOGENERATED FILE. DO NOT EDIT THIS FILE DIRECTLY.
+# yarn lockfile v1
+
+
+"@babel/core@^7.0.0", "@babel/core@^7.12.3":
+  version "7.20.0"
+  resolved "https://registry.yarnpkg.com/@babel/core/-/core-7.20.0.tgz"
+  dependencies:
+    "@babel/code-frame" "^7.18.6"
+
+lodash@^4.17.20, lodash@^4.17.21:
+  version "4.17.21"
+
+lodash@4.17.21:
+  version "4.17.21"
+"#;
+        let records = parse_yarn_lock(content, Path::new("yarn.lock"));
+        assert_eq!(
+            name_versions(&records),
+            vec![("@babel/core", Some("7.20.0")), ("lodash", Some("4.17.21")),]
+        );
+        assert!(records.iter().all(|r| r.manager == "npm"));
+    }
+
+    #[test]
+    fn yarn_berry_lock_parsing() {
+        let content = r#"# This file is generated by running "yarn install" inside your project.
+
+__metadata:
+  version: 6
+  cacheKey: 8
+
+"@babel/core@npm:^7.0.0, @babel/core@npm:^7.12.3":
+  version: 7.20.0
+  resolution: "@babel/core@npm:7.20.0"
+  dependencies:
+    "@babel/code-frame": ^7.18.6
+
+"my-app@workspace:.":
+  version: 0.0.0-use.local
+  resolution: "my-app@workspace:."
+
+"react@npm:^18.2.0":
+  version: 18.2.0
+  resolution: "react@npm:18.2.0"
+"#;
+        let records = parse_yarn_lock(content, Path::new("yarn.lock"));
+        assert_eq!(
+            name_versions(&records),
+            vec![("@babel/core", Some("7.20.0")), ("react", Some("18.2.0"))]
+        );
+    }
+
+    fn name_versions(records: &[DependencyRecord]) -> Vec<(&str, Option<&str>)> {
+        records
+            .iter()
+            .map(|r| (r.name.as_str(), r.version.as_deref()))
+            .collect()
+    }
+
+    #[test]
+    fn pnpm_v5_lock_parsing() {
+        let content = "\
+lockfileVersion: 5.4
+
+specifiers:
+  react: ^18.2.0
+
+packages:
+
+  /@babel/runtime/7.20.0:
+    resolution: {integrity: sha512-abc}
+    dev: false
+
+  /react-dom/18.2.0_react@18.2.0:
+    resolution: {integrity: sha512-def}
+
+  /react-dom/18.2.0_react@18.1.0:
+    resolution: {integrity: sha512-def}
+";
+        let records = parse_pnpm_lock(content, Path::new("pnpm-lock.yaml"));
+        assert_eq!(
+            name_versions(&records),
+            vec![
+                ("@babel/runtime", Some("7.20.0")),
+                ("react-dom", Some("18.2.0")),
+            ]
+        );
+    }
+
+    #[test]
+    fn pnpm_v6_and_v9_lock_parsing() {
+        let v6 = "\
+lockfileVersion: '6.0'
+
+packages:
+
+  /@babel/runtime@7.20.0:
+    resolution: {integrity: sha512-abc}
+    dev: true
+
+  /react-dom@18.2.0(react@18.2.0):
+    resolution: {integrity: sha512-def}
+    dev: false
+";
+        let v9 = "\
+lockfileVersion: '9.0'
+
+packages:
+
+  '@babel/runtime@7.20.0':
+    resolution: {integrity: sha512-abc}
+
+  react-dom@18.2.0:
+    resolution: {integrity: sha512-def}
+
+snapshots:
+
+  react-dom@18.2.0(react@18.2.0):
+    dependencies:
+      react: 18.2.0
+";
+        let expected = vec![
+            ("@babel/runtime", Some("7.20.0")),
+            ("react-dom", Some("18.2.0")),
+        ];
+        let v6_records = parse_pnpm_lock(v6, Path::new("pnpm-lock.yaml"));
+        assert_eq!(name_versions(&v6_records), expected);
+        assert!(v6_records[0].dev && !v6_records[1].dev);
+        assert_eq!(
+            name_versions(&parse_pnpm_lock(v9, Path::new("pnpm-lock.yaml"))),
+            expected
+        );
+    }
+
+    #[test]
+    fn poetry_lock_parsing() {
+        let content = r#"
+[[package]]
+name = "requests"
+version = "2.32.0"
+description = "Python HTTP for Humans."
+category = "main"
+optional = false
+
+[[package]]
+name = "pytest"
+version = "8.0.0"
+category = "dev"
+
+[metadata]
+lock-version = "1.1"
+"#;
+        let records = parse_poetry_lock(content, Path::new("poetry.lock")).unwrap();
+        assert_eq!(
+            name_versions(&records),
+            vec![("requests", Some("2.32.0")), ("pytest", Some("8.0.0"))]
+        );
+        assert!(records.iter().all(|r| r.manager == "pip"));
+    }
+
+    #[test]
+    fn pipfile_lock_parsing() {
+        let content = r#"{
+            "_meta": { "hash": { "sha256": "abc" } },
+            "default": {
+                "requests": { "hashes": [], "version": "==2.32.0" },
+                "some_pkg": { "version": "==1.0" }
+            },
+            "develop": {
+                "pytest": { "version": "==8.0.0" },
+                "editable-pkg": { "editable": true, "path": "." }
+            }
+        }"#;
+        let records = parse_pipfile_lock(content, Path::new("Pipfile.lock")).unwrap();
+        assert_eq!(
+            name_versions(&records),
+            vec![
+                ("requests", Some("2.32.0")),
+                ("some-pkg", Some("1.0")),
+                ("editable-pkg", None),
+                ("pytest", Some("8.0.0")),
+            ]
+        );
+        let dev: Vec<bool> = records.iter().map(|r| r.dev).collect();
+        assert_eq!(dev, vec![false, false, true, true]);
+    }
+
+    #[test]
+    fn cargo_lock_parsing() {
+        let content = r#"
+version = 3
+
+[[package]]
+name = "anyhow"
+version = "1.0.100"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "license-scout"
+version = "0.1.0"
+dependencies = ["anyhow"]
+"#;
+        let records = parse_cargo_lock(content, Path::new("Cargo.lock")).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].manager, "cargo");
+        assert_eq!(records[0].name, "anyhow");
+        assert_eq!(records[0].version.as_deref(), Some("1.0.100"));
+        assert_eq!(records[0].license, "Unknown");
+    }
+
+    #[test]
+    fn composer_lock_parsing() {
+        let content = r#"{
+            "packages": [
+                {"name": "symfony/console", "version": "v6.4.1", "license": ["MIT"], "homepage": "https://symfony.com"},
+                {"name": "acme/dual", "version": "1.0.0", "license": ["LGPL-2.1-only", "GPL-3.0-or-later"]}
+            ],
+            "packages-dev": [
+                {"name": "phpunit/phpunit", "version": "10.5.0", "license": ["BSD-3-Clause"]},
+                {"name": "acme/internal", "version": "dev-main"}
+            ]
+        }"#;
+        let records = parse_composer_lock(content, Path::new("composer.lock")).unwrap();
+        assert_eq!(
+            name_versions(&records),
+            vec![
+                ("symfony/console", Some("v6.4.1")),
+                ("acme/dual", Some("1.0.0")),
+                ("phpunit/phpunit", Some("10.5.0")),
+                ("acme/internal", Some("dev-main")),
+            ]
+        );
+        assert_eq!(records[0].license, "MIT");
+        assert_eq!(records[0].homepage.as_deref(), Some("https://symfony.com"));
+        assert_eq!(records[1].license, "LGPL-2.1-only OR GPL-3.0-or-later");
+        assert_eq!(records[3].license, "Unknown");
+        assert!(records.iter().all(|r| r.manager == "composer"));
+    }
+
+    #[test]
+    fn gemfile_lock_parsing() {
+        let content = "GEM\n  remote: https://rubygems.org/\n  specs:\n    actionpack (7.0.4)\n      rack (~> 2.0)\n    nokogiri (1.15.4-x86_64-linux)\n    rack (2.2.8)\n\nPLATFORMS\n  x86_64-linux\n\nDEPENDENCIES\n  rails (~> 7.0)\n\nBUNDLED WITH\n   2.4.10\n";
+        let records = parse_gemfile_lock(content, Path::new("Gemfile.lock"));
+        assert_eq!(
+            name_versions(&records),
+            vec![
+                ("actionpack", Some("7.0.4")),
+                ("nokogiri", Some("1.15.4-x86_64-linux")),
+                ("rack", Some("2.2.8")),
+            ]
+        );
+        assert!(records.iter().all(|r| r.manager == "gem"));
+    }
+
+    #[test]
+    fn pyproject_toml_parsing() {
+        let content = r#"
+[project]
+name = "app"
+dependencies = ["requests>=2.31", "httpx[http2] (==0.27.0)", "rich"]
+
+[project.optional-dependencies]
+cli = ["click==8.1.7"]
+
+[tool.poetry.dependencies]
+python = "^3.11"
+Django = "4.2.7"
+celery = { version = "^5.3", extras = ["redis"] }
+anything = "*"
+
+[tool.poetry.group.test.dependencies]
+pytest = "^8.0"
+"#;
+        let records = parse_pyproject_toml(content, Path::new("pyproject.toml")).unwrap();
+        assert_eq!(
+            name_versions(&records),
+            vec![
+                ("requests", Some("2.31")),
+                ("httpx", Some("0.27.0")),
+                ("rich", None),
+                ("click", Some("8.1.7")),
+                ("django", Some("4.2.7")),
+                ("anything", None),
+                ("celery", Some("^5.3")),
+                ("pytest", Some("^8.0")),
+            ]
+        );
+        assert!(records.iter().all(|r| r.manager == "pip" && r.direct));
+        let dev: Vec<&str> = records
+            .iter()
+            .filter(|r| r.dev)
+            .map(|r| r.name.as_str())
+            .collect();
+        assert_eq!(dev, vec!["pytest"]);
+    }
+
+    #[test]
+    fn pom_xml_parsing() {
+        let content = r#"<?xml version="1.0" encoding="UTF-8"?>
+<project xmlns="http://maven.apache.org/POM/4.0.0">
+  <modelVersion>4.0.0</modelVersion>
+  <dependencyManagement>
+    <dependencies>
+      <dependency><groupId>org.managed</groupId><artifactId>bom</artifactId><version>1.0</version></dependency>
+    </dependencies>
+  </dependencyManagement>
+  <dependencies>
+    <dependency>
+      <groupId>com.google.guava</groupId>
+      <artifactId>guava</artifactId>
+      <version>32.1.3-jre</version>
+    </dependency>
+    <dependency>
+      <groupId>org.example</groupId>
+      <artifactId>core</artifactId>
+      <version>${project.version}</version>
+    </dependency>
+    <dependency>
+      <groupId>junit</groupId>
+      <artifactId>junit</artifactId>
+      <scope>test</scope>
+    </dependency>
+  </dependencies>
+</project>"#;
+        let records = parse_pom_xml(content, Path::new("pom.xml")).unwrap();
+        assert_eq!(
+            name_versions(&records),
+            vec![
+                ("com.google.guava:guava", Some("32.1.3-jre")),
+                ("org.example:core", Some("${project.version}")),
+                ("junit:junit", None),
+            ]
+        );
+        assert!(records.iter().all(|r| r.manager == "maven" && r.direct));
+        let dev: Vec<bool> = records.iter().map(|r| r.dev).collect();
+        assert_eq!(dev, vec![false, false, true]);
+    }
+
+    #[test]
+    fn gradle_lockfile_parsing() {
+        let content = "# This is a Gradle generated file for dependency locking.\n\
+            # Manual edits can break the build and are not advised.\n\
+            com.google.guava:guava:32.1.3-jre=compileClasspath,runtimeClasspath\n\
+            junit:junit:4.13.2=testCompileClasspath,testRuntimeClasspath\n\
+            empty=annotationProcessor\n";
+        let records = parse_manifest(
+            ManifestKind::from_file_name("gradle.lockfile").unwrap(),
+            content,
+            Path::new("gradle.lockfile"),
+        )
+        .unwrap();
+        assert_eq!(
+            name_versions(&records),
+            vec![
+                ("com.google.guava:guava", Some("32.1.3-jre")),
+                ("junit:junit", Some("4.13.2")),
+            ]
+        );
+        assert!(records.iter().all(|r| r.manager == "maven"));
+        let dev: Vec<bool> = records.iter().map(|r| r.dev).collect();
+        assert_eq!(dev, vec![false, true]);
+    }
+
+    #[test]
+    fn uv_lock_distinguishes_registry_git_and_local_sources() {
+        let content = r#"
+version = 1
+requires-python = ">=3.12"
+
+[[package]]
+name = "myapp"
+version = "0.1.0"
+source = { editable = "." }
+
+[[package]]
+name = "Requests"
+version = "2.32.3"
+source = { registry = "https://pypi.org/simple" }
+
+[[package]]
+name = "internal-lib"
+version = "1.2.0"
+source = { git = "https://github.com/acme/internal-lib?rev=v1.2.0#0123abcd" }
+
+[[package]]
+name = "shared"
+version = "0.3.0"
+source = { directory = "../shared" }
+"#;
+        let records = parse_uv_lock(content, Path::new("uv.lock")).unwrap();
+        let summary: Vec<(&str, Option<&str>, bool, Option<&str>)> = records
+            .iter()
+            .map(|r| {
+                (
+                    r.name.as_str(),
+                    r.version.as_deref(),
+                    r.is_self,
+                    r.origin.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("myapp", Some("0.1.0"), true, Some("path:.")),
+                ("requests", Some("2.32.3"), false, None),
+                (
+                    "internal-lib",
+                    Some("1.2.0"),
+                    false,
+                    Some("git+https://github.com/acme/internal-lib?rev=v1.2.0#0123abcd")
+                ),
+                ("shared", Some("0.3.0"), true, Some("path:../shared")),
+            ]
+        );
+        assert!(records.iter().all(|r| r.manager == "pip"));
+        assert_eq!(
+            ManifestKind::from_path(Path::new("svc/uv.lock")),
+            Some(ManifestKind::UvLock)
+        );
+    }
+
+    #[test]
+    fn conda_environment_parsing() {
+        let content = "name: ml\n\
+            channels:\n  - conda-forge\n\
+            dependencies:\n\
+            \x20 - python=3.11\n\
+            \x20 - conda-forge::numpy=1.26.0=py311_0\n\
+            \x20 - scipy>=1.11  # 最低バージョン\n\
+            \x20 - pandas\n\
+            \x20 - pip\n\
+            \x20 - pip:\n\
+            \x20   - requests==2.31.0\n\
+            \x20   - -e .\n\
+            \x20 - matplotlib 3.8.*\n\
+            prefix: /opt/conda/envs/ml\n";
+        let records = parse_manifest(
+            ManifestKind::from_file_name("environment.yml").unwrap(),
+            content,
+            Path::new("environment.yml"),
+        )
+        .unwrap();
+        let managers: Vec<(&str, &str, Option<&str>)> = records
+            .iter()
+            .map(|r| (r.manager.as_str(), r.name.as_str(), r.version.as_deref()))
+            .collect();
+        assert_eq!(
+            managers,
+            vec![
+                ("conda", "python", Some("3.11")),
+                ("conda", "numpy", Some("1.26.0")),
+                ("conda", "scipy", Some("1.11")),
+                ("conda", "pandas", None),
+                ("conda", "pip", None),
+                ("pip", "requests", Some("2.31.0")),
+                ("conda", "matplotlib", Some("3.8.*")),
+            ]
+        );
+        assert!(records.iter().all(|r| r.direct));
+    }
+
+    #[test]
+    fn go_mod_parsing() {
+        let content = "module example.com/app\n\ngo 1.22\n\nrequire github.com/pkg/errors v0.9.1\n\nrequire (\n\tgolang.org/x/text v0.14.0 // indirect\n\t// comment\n\tgithub.com/spf13/cobra v1.8.0\n)\n\nreplace (\n\texample.com/old v1.0.0 => ../old\n)\n";
+        let records = parse_go_mod(content, Path::new("go.mod"));
+        assert_eq!(
+            name_versions(&records),
+            vec![
+                ("github.com/pkg/errors", Some("v0.9.1")),
+                ("golang.org/x/text", Some("v0.14.0")),
+                ("github.com/spf13/cobra", Some("v1.8.0")),
+            ]
+        );
+        assert!(records.iter().all(|r| r.manager == "go"));
+        let direct: Vec<bool> = records.iter().map(|r| r.direct).collect();
+        assert_eq!(direct, vec![true, false, true]);
+    }
+
+    #[test]
+    fn go_vendor_modules_parsing() {
+        let content = "\
+# github.com/pkg/errors v0.9.1
+## explicit
+github.com/pkg/errors
+# golang.org/x/sys v0.15.0 => golang.org/x/sys v0.16.0
+## explicit; go 1.18
+golang.org/x/sys/unix
+# example.com/local => ../local
+example.com/local
+";
+        let records = parse_go_vendor_modules(content, Path::new("vendor/modules.txt"));
+        assert_eq!(
+            name_versions(&records),
+            vec![
+                ("github.com/pkg/errors", Some("v0.9.1")),
+                ("golang.org/x/sys", Some("v0.15.0")),
+                ("example.com/local", None),
+            ]
+        );
+        assert!(records.iter().all(|r| r.manager == "go"));
+    }
+
+    #[test]
+    fn local_license_files_fill_unknown_records() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let package_dir = root.join("node_modules").join("@scope").join("internal");
+        fs::create_dir_all(&package_dir).unwrap();
+        fs::write(
+            package_dir.join("LICENSE.md"),
+            "MIT License\n\nPermission is hereby granted, free of charge",
+        )
+        .unwrap();
+
+        let mut records = vec![
+            DependencyRecord {
+                manager: "npm".to_string(),
+                name: "@scope/internal".to_string(),
+                license: "Unknown".to_string(),
+                source: root.join("package-lock.json"),
+                ..Default::default()
+            },
+            DependencyRecord {
+                manager: "npm".to_string(),
+                name: "missing".to_string(),
+                license: "Unknown".to_string(),
+                source: root.join("package-lock.json"),
+                ..Default::default()
+            },
+        ];
+        assert_eq!(fill_licenses_from_local_files(&mut records), 1);
+        assert_eq!(records[0].license, "MIT");
+        assert_eq!(records[1].license, "Unknown");
+    }
+
+    #[test]
+    fn gitignore_rules_match_names_and_anchored_paths() {
+        let rules: Vec<IgnoreRule> = [
+            "# comment",
+            "dist/",
+            "/build",
+            "*.tmp",
+            "docs/generated",
+            "!keep",
+        ]
+        .iter()
+        .filter_map(|line| parse_gitignore_line(line))
+        .collect();
+        assert_eq!(rules.len(), 4);
+
+        assert!(is_gitignored(&rules, Path::new("web/dist"), true));
+        assert!(!is_gitignored(&rules, Path::new("web/dist"), false));
+        assert!(is_gitignored(&rules, Path::new("build"), true));
+        assert!(!is_gitignored(&rules, Path::new("web/build"), true));
+        assert!(is_gitignored(&rules, Path::new("a/b/cache.tmp"), false));
+        assert!(is_gitignored(&rules, Path::new("docs/generated"), true));
+        assert!(!is_gitignored(&rules, Path::new("src"), true));
+    }
+
+    #[test]
+    fn scanned_files_count_records_per_parsed_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("web")).unwrap();
+        fs::create_dir_all(root.join("broken")).unwrap();
+        fs::write(root.join("requirements.txt"), "-r base.txt\nflask==3.0.0\n").unwrap();
+        fs::write(root.join("base.txt"), "requests==2.32.0\nidna==3.7\n").unwrap();
+        fs::write(
+            root.join("web/package-lock.json"),
+            r#"{"lockfileVersion": 3, "packages": {}}"#,
+        )
+        .unwrap();
+        fs::write(root.join("broken/package-lock.json"), "{not json").unwrap();
+
+        let scanned = scan_records(root, &ScanOptions::default()).unwrap();
+
+        let mut files: Vec<(PathBuf, &str, usize)> = scanned
+            .files
+            .into_iter()
+            .map(|file| {
+                let path = file.path.strip_prefix(root).unwrap().to_path_buf();
+                (path, file.kind.label(), file.count)
+            })
+            .collect();
+        files.sort();
+        assert_eq!(
+            files,
+            vec![
+                // -rで取り込んだファイルは取り込み元とは別に数える
+                (PathBuf::from("base.txt"), "requirements.txt", 2),
+                // 解析に失敗したファイルも0件として残す
+                (
+                    PathBuf::from("broken/package-lock.json"),
+                    "package-lock.json",
+                    0
+                ),
+                (PathBuf::from("requirements.txt"), "requirements.txt", 1),
+                (
+                    PathBuf::from("web/package-lock.json"),
+                    "package-lock.json",
+                    0
+                ),
+            ]
+        );
+        assert_eq!(scanned.records.len(), 3);
+    }
+
+    #[test]
+    fn license_text_heuristics() {
+        assert_eq!(
+            guess_license_from_text(
+                "MIT License\n\nPermission is hereby granted, free of charge, to any person"
+            ),
+            Some("MIT")
+        );
+        assert_eq!(
+            guess_license_from_text(
+                "                                 Apache License\n                           Version 2.0, January 2004"
+            ),
+            Some("Apache-2.0")
+        );
+        assert_eq!(
+            guess_license_from_text(
+                "                    GNU GENERAL PUBLIC LICENSE\n                       Version 3, 29 June 2007"
+            ),
+            Some("GPL-3.0")
+        );
+        assert_eq!(
+            guess_license_from_text(
+                "                    GNU GENERAL PUBLIC LICENSE\n                       Version 2, June 1991"
+            ),
+            Some("GPL-2.0")
+        );
+        assert_eq!(
+            guess_license_from_text(
+                "                   GNU LESSER GENERAL PUBLIC LICENSE\n                       Version 3, 29 June 2007"
+            ),
+            Some("LGPL-3.0")
+        );
+        assert_eq!(
+            guess_license_from_text(
+                "Redistribution and use in source and binary forms, with or without modification, are permitted.\nNeither the name of Google Inc. nor the names"
+            ),
+            Some("BSD-3-Clause")
+        );
+        assert_eq!(guess_license_from_text("All rights reserved."), None);
+    }
 }
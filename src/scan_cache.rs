@@ -0,0 +1,232 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::UNIX_EPOCH;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::cache;
+use crate::scan::ManifestKind;
+use crate::types::DependencyRecord;
+
+const SCAN_CACHE_FILE: &str = "scan-cache.json";
+/// 解析処理の世代。パーサーの出力が変わる変更では番号を上げ、古い解析結果を使わないようにします。
+const PARSER_VERSION: u32 = 5;
+
+/// マニフェストごとの解析結果のキャッシュ。更新日時とサイズが変わっていないファイルは再解析しません。
+#[derive(Debug)]
+pub struct ScanCache {
+    path: PathBuf,
+    data: Mutex<ScanCacheData>,
+    dirty: AtomicBool,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ScanCacheData {
+    /// 解析処理はバージョンごとに変わりうるため、異なるバージョンのキャッシュは破棄します。
+    version: String,
+    #[serde(default)]
+    parser_version: u32,
+    entries: HashMap<PathBuf, ScanCacheEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct FileStamp {
+    modified_secs: u64,
+    modified_nanos: u32,
+    size: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ScanCacheEntry {
+    #[serde(flatten)]
+    stamp: FileStamp,
+    /// 解析したときの形式（`package-lock`など）。別の形式として解析する場合は使いません。
+    format: String,
+    records: Vec<DependencyRecord>,
+}
+
+impl ScanCache {
+    /// ライセンスキャッシュと同じディレクトリの`scan-cache.json`を読み込みます。
+    pub fn load_near(license_cache_path: Option<&Path>) -> Result<Self> {
+        Self::load(&scan_cache_path(license_cache_path))
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let data = match fs::read_to_string(path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_default(),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => ScanCacheData::default(),
+            Err(err) => {
+                return Err(err).with_context(|| {
+                    format!("走査キャッシュファイルの読み込みに失敗: {}", path.display())
+                });
+            }
+        };
+        let data =
+            if data.version == env!("CARGO_PKG_VERSION") && data.parser_version == PARSER_VERSION {
+                data
+            } else {
+                ScanCacheData {
+                    version: env!("CARGO_PKG_VERSION").to_string(),
+                    parser_version: PARSER_VERSION,
+                    entries: HashMap::new(),
+                }
+            };
+        Ok(Self {
+            path: path.to_path_buf(),
+            data: Mutex::new(data),
+            dirty: AtomicBool::new(false),
+        })
+    }
+
+    pub fn clear(license_cache_path: Option<&Path>) -> Result<PathBuf> {
+        let path = scan_cache_path(license_cache_path);
+        match fs::remove_file(&path) {
+            Ok(()) => Ok(path),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(path),
+            Err(err) => Err(err)
+                .with_context(|| format!("走査キャッシュファイルの削除に失敗: {}", path.display())),
+        }
+    }
+
+    /// ファイルが前回の解析時から変わっておらず同じ形式で解析していれば、前回の解析結果を返します。
+    pub fn get(&self, path: &Path, kind: ManifestKind) -> Option<Vec<DependencyRecord>> {
+        let stamp = FileStamp::read(path)?;
+        let data = self.data.lock().ok()?;
+        let entry = data.entries.get(path)?;
+        (entry.stamp == stamp && entry.format == kind.format_name()).then(|| entry.records.clone())
+    }
+
+    pub fn insert(&self, path: &Path, kind: ManifestKind, records: &[DependencyRecord]) {
+        let Some(stamp) = FileStamp::read(path) else {
+            return;
+        };
+        if let Ok(mut data) = self.data.lock() {
+            data.entries.insert(
+                path.to_path_buf(),
+                ScanCacheEntry {
+                    stamp,
+                    format: kind.format_name().to_string(),
+                    records: records.to_vec(),
+                },
+            );
+            self.dirty.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// 削除されたファイルのエントリを取り除いて書き出します。
+    pub fn save(&self) -> Result<()> {
+        if !self.dirty.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+        let mut data = self
+            .data
+            .lock()
+            .map_err(|_| anyhow::anyhow!("走査キャッシュのロックに失敗しました"))?;
+        data.entries.retain(|path, _| path.is_file());
+        if let Some(parent) = self.path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            fs::create_dir_all(parent).with_context(|| {
+                format!("キャッシュディレクトリの作成に失敗: {}", parent.display())
+            })?;
+        }
+        let json = serde_json::to_string(&*data).context("走査キャッシュのJSON化に失敗しました")?;
+        fs::write(&self.path, json).with_context(|| {
+            format!(
+                "走査キャッシュファイルの書き込みに失敗: {}",
+                self.path.display()
+            )
+        })?;
+        self.dirty.store(false, Ordering::Relaxed);
+        Ok(())
+    }
+}
+
+impl FileStamp {
+    fn read(path: &Path) -> Option<Self> {
+        let metadata = fs::metadata(path).ok()?;
+        let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+        Some(Self {
+            modified_secs: modified.as_secs(),
+            modified_nanos: modified.subsec_nanos(),
+            size: metadata.len(),
+        })
+    }
+}
+
+fn scan_cache_path(license_cache_path: Option<&Path>) -> PathBuf {
+    license_cache_path
+        .map_or_else(cache::default_cache_path, Path::to_path_buf)
+        .with_file_name(SCAN_CACHE_FILE)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::scan::{ScanOptions, collect_records};
+
+    #[test]
+    fn unchanged_manifests_are_reused_and_changed_ones_reparsed() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let manifest = root.join("package-lock.json");
+        fs::write(
+            &manifest,
+            r#"{"packages": {"node_modules/react": {"version": "18.2.0"}}}"#,
+        )
+        .unwrap();
+        let cache_path = root.join("cache").join(SCAN_CACHE_FILE);
+
+        let cache = Arc::new(ScanCache::load(&cache_path).unwrap());
+        let options = ScanOptions {
+            scan_cache: Some(Arc::clone(&cache)),
+            ..Default::default()
+        };
+        let first = collect_records(root, &options).unwrap();
+        assert_eq!(first.len(), 1);
+        cache.save().unwrap();
+
+        let reloaded = ScanCache::load(&cache_path).unwrap();
+        let cached = reloaded.get(&manifest, ManifestKind::PackageLock).unwrap();
+        assert_eq!(cached[0].name, "react");
+        assert!(reloaded.get(&manifest, ManifestKind::YarnLock).is_none());
+
+        fs::write(
+            &manifest,
+            r#"{"packages": {"node_modules/react": {"version": "18.2.0"}, "node_modules/vue": {"version": "3.4.0"}}}"#,
+        )
+        .unwrap();
+        assert!(reloaded.get(&manifest, ManifestKind::PackageLock).is_none());
+        let options = ScanOptions {
+            scan_cache: Some(Arc::new(reloaded)),
+            ..Default::default()
+        };
+        assert_eq!(collect_records(root, &options).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn caches_from_another_parser_version_are_discarded() {
+        let dir = tempfile::tempdir().unwrap();
+        let manifest = dir.path().join("package-lock.json");
+        fs::write(&manifest, "{}").unwrap();
+        let cache_path = dir.path().join(SCAN_CACHE_FILE);
+        let cache = ScanCache::load(&cache_path).unwrap();
+        cache.insert(&manifest, ManifestKind::PackageLock, &[]);
+        cache.save().unwrap();
+
+        let mut data: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&cache_path).unwrap()).unwrap();
+        data["parser_version"] = serde_json::json!(PARSER_VERSION - 1);
+        fs::write(&cache_path, data.to_string()).unwrap();
+
+        let reloaded = ScanCache::load(&cache_path).unwrap();
+        assert!(reloaded.get(&manifest, ManifestKind::PackageLock).is_none());
+    }
+}
@@ -0,0 +1,781 @@
+const SPDX_IDS: &[&str] = &[
+    "0BSD",
+    "AFL-3.0",
+    "AGPL-3.0",
+    "AGPL-3.0-only",
+    "AGPL-3.0-or-later",
+    "Apache-1.1",
+    "Apache-2.0",
+    "Artistic-2.0",
+    "BlueOak-1.0.0",
+    "BSD-1-Clause",
+    "BSD-2-Clause",
+    "BSD-3-Clause",
+    "BSD-4-Clause",
+    "BSL-1.0",
+    "CC-BY-3.0",
+    "CC-BY-4.0",
+    "CC-BY-SA-4.0",
+    "CC0-1.0",
+    "CDDL-1.0",
+    "CDDL-1.1",
+    "EPL-1.0",
+    "EPL-2.0",
+    "EUPL-1.2",
+    "GPL-2.0",
+    "GPL-2.0-only",
+    "GPL-2.0-or-later",
+    "GPL-3.0",
+    "GPL-3.0-only",
+    "GPL-3.0-or-later",
+    "HPND",
+    "ISC",
+    "LGPL-2.0-only",
+    "LGPL-2.0-or-later",
+    "LGPL-2.1",
+    "LGPL-2.1-only",
+    "LGPL-2.1-or-later",
+    "LGPL-3.0",
+    "LGPL-3.0-only",
+    "LGPL-3.0-or-later",
+    "MIT",
+    "MIT-0",
+    "MPL-1.1",
+    "MPL-2.0",
+    "MS-PL",
+    "OFL-1.1",
+    "PostgreSQL",
+    "PSF-2.0",
+    "Python-2.0",
+    "Unicode-3.0",
+    "Unicode-DFS-2016",
+    "Unlicense",
+    "UPL-1.0",
+    "WTFPL",
+    "X11",
+    "Zlib",
+];
+
+const ALIASES: &[(&str, &str)] = &[
+    ("mit license", "MIT"),
+    ("the mit license", "MIT"),
+    ("mit licence", "MIT"),
+    ("expat", "MIT"),
+    ("expat license", "MIT"),
+    ("mit no attribution license", "MIT-0"),
+    ("apache", "Apache-2.0"),
+    ("apache 2", "Apache-2.0"),
+    ("apache 2.0", "Apache-2.0"),
+    ("apache-2", "Apache-2.0"),
+    ("apache2", "Apache-2.0"),
+    ("apache license", "Apache-2.0"),
+    ("apache license 2.0", "Apache-2.0"),
+    ("apache license, version 2.0", "Apache-2.0"),
+    ("apache license version 2.0", "Apache-2.0"),
+    ("the apache license, version 2.0", "Apache-2.0"),
+    ("the apache software license, version 2.0", "Apache-2.0"),
+    ("apache software license", "Apache-2.0"),
+    ("apache software license 2.0", "Apache-2.0"),
+    ("asl 2.0", "Apache-2.0"),
+    ("bsd-3", "BSD-3-Clause"),
+    ("bsd 3-clause", "BSD-3-Clause"),
+    ("bsd 3-clause license", "BSD-3-Clause"),
+    ("3-clause bsd", "BSD-3-Clause"),
+    ("3-clause bsd license", "BSD-3-Clause"),
+    ("new bsd", "BSD-3-Clause"),
+    ("new bsd license", "BSD-3-Clause"),
+    ("modified bsd license", "BSD-3-Clause"),
+    ("revised bsd license", "BSD-3-Clause"),
+    ("bsd-2", "BSD-2-Clause"),
+    ("bsd 2-clause", "BSD-2-Clause"),
+    ("bsd 2-clause license", "BSD-2-Clause"),
+    ("2-clause bsd license", "BSD-2-Clause"),
+    ("simplified bsd", "BSD-2-Clause"),
+    ("simplified bsd license", "BSD-2-Clause"),
+    ("freebsd", "BSD-2-Clause"),
+    ("isc license", "ISC"),
+    ("isc license (iscl)", "ISC"),
+    ("iscl", "ISC"),
+    ("gplv2", "GPL-2.0-only"),
+    ("gpl v2", "GPL-2.0-only"),
+    ("gpl-2", "GPL-2.0-only"),
+    ("gpl2", "GPL-2.0-only"),
+    ("gplv2+", "GPL-2.0-or-later"),
+    ("gplv3", "GPL-3.0-only"),
+    ("gpl v3", "GPL-3.0-only"),
+    ("gpl-3", "GPL-3.0-only"),
+    ("gpl3", "GPL-3.0-only"),
+    ("gplv3+", "GPL-3.0-or-later"),
+    ("gnu general public license v2 (gplv2)", "GPL-2.0-only"),
+    (
+        "gnu general public license v2 or later (gplv2+)",
+        "GPL-2.0-or-later",
+    ),
+    ("gnu general public license v3 (gplv3)", "GPL-3.0-only"),
+    (
+        "gnu general public license v3 or later (gplv3+)",
+        "GPL-3.0-or-later",
+    ),
+    ("lgplv2.1", "LGPL-2.1-only"),
+    ("lgpl-2.1", "LGPL-2.1"),
+    ("lgplv3", "LGPL-3.0-only"),
+    ("lgpl-3", "LGPL-3.0-only"),
+    ("lgplv3+", "LGPL-3.0-or-later"),
+    (
+        "gnu lesser general public license v2 (lgplv2)",
+        "LGPL-2.0-only",
+    ),
+    (
+        "gnu lesser general public license v2 or later (lgplv2+)",
+        "LGPL-2.0-or-later",
+    ),
+    (
+        "gnu lesser general public license v3 (lgplv3)",
+        "LGPL-3.0-only",
+    ),
+    (
+        "gnu lesser general public license v3 or later (lgplv3+)",
+        "LGPL-3.0-or-later",
+    ),
+    ("agplv3", "AGPL-3.0-only"),
+    ("agplv3+", "AGPL-3.0-or-later"),
+    ("gnu affero general public license v3", "AGPL-3.0-only"),
+    (
+        "gnu affero general public license v3 or later (agplv3+)",
+        "AGPL-3.0-or-later",
+    ),
+    ("mpl 2.0", "MPL-2.0"),
+    ("mpl-2", "MPL-2.0"),
+    ("mpl2", "MPL-2.0"),
+    ("mozilla public license 2.0", "MPL-2.0"),
+    ("mozilla public license 2.0 (mpl 2.0)", "MPL-2.0"),
+    ("mozilla public license 1.1 (mpl 1.1)", "MPL-1.1"),
+    ("eclipse public license 1.0", "EPL-1.0"),
+    ("eclipse public license 2.0", "EPL-2.0"),
+    ("eclipse public license - v 1.0", "EPL-1.0"),
+    ("eclipse public license - v 2.0", "EPL-2.0"),
+    ("european union public licence 1.2 (eupl 1.2)", "EUPL-1.2"),
+    ("boost", "BSL-1.0"),
+    ("boost software license", "BSL-1.0"),
+    ("boost software license 1.0", "BSL-1.0"),
+    ("cc0", "CC0-1.0"),
+    (
+        "cc0 1.0 universal (cc0 1.0) public domain dedication",
+        "CC0-1.0",
+    ),
+    ("psf", "PSF-2.0"),
+    ("psfl", "PSF-2.0"),
+    ("psf license", "PSF-2.0"),
+    ("python software foundation license", "PSF-2.0"),
+    ("the unlicense", "Unlicense"),
+    ("the unlicense (unlicense)", "Unlicense"),
+    ("zlib license", "Zlib"),
+    ("zlib/libpng license", "Zlib"),
+    ("universal permissive license (upl)", "UPL-1.0"),
+    ("historical permission notice and disclaimer (hpnd)", "HPND"),
+    ("sil open font license 1.1 (ofl-1.1)", "OFL-1.1"),
+    ("postgresql license", "PostgreSQL"),
+    ("microsoft public license", "MS-PL"),
+];
+
+/// ライセンスのリスク区分。`Ord`は制約の強さの順（`Unknown`が最も強い）です。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Category {
+    Permissive,
+    WeakCopyleft,
+    StrongCopyleft,
+    Unknown,
+}
+
+impl Category {
+    pub const ALL: [Self; 4] = [
+        Self::Permissive,
+        Self::WeakCopyleft,
+        Self::StrongCopyleft,
+        Self::Unknown,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Permissive => "permissive",
+            Self::WeakCopyleft => "weak-copyleft",
+            Self::StrongCopyleft => "strong-copyleft",
+            Self::Unknown => "unknown",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|category| category.name().eq_ignore_ascii_case(name.trim()))
+    }
+}
+
+const WEAK_COPYLEFT: &[&str] = &[
+    "Artistic-2.0",
+    "CC-BY-SA-4.0",
+    "CDDL-1.0",
+    "CDDL-1.1",
+    "EPL-1.0",
+    "EPL-2.0",
+    "LGPL-2.0-only",
+    "LGPL-2.0-or-later",
+    "LGPL-2.1",
+    "LGPL-2.1-only",
+    "LGPL-2.1-or-later",
+    "LGPL-3.0",
+    "LGPL-3.0-only",
+    "LGPL-3.0-or-later",
+    "MPL-1.1",
+    "MPL-2.0",
+    "MS-PL",
+    "OFL-1.1",
+];
+
+const STRONG_COPYLEFT: &[&str] = &[
+    "AGPL-3.0",
+    "AGPL-3.0-only",
+    "AGPL-3.0-or-later",
+    "EUPL-1.2",
+    "GPL-2.0",
+    "GPL-2.0-only",
+    "GPL-2.0-or-later",
+    "GPL-3.0",
+    "GPL-3.0-only",
+    "GPL-3.0-or-later",
+];
+
+/// ライセンスに従って頒布する際の主な義務（`--obligations`）。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Obligation {
+    /// 著作権表示とライセンス文を同梱する
+    Attribution,
+    /// NOTICEファイルの内容を同梱する
+    NoticeFile,
+    /// 変更したファイルに変更した旨を明示する
+    StateChanges,
+    /// ソースコードを開示する
+    DiscloseSource,
+    /// 同じライセンスで頒布する
+    SameLicense,
+    /// ネットワーク越しに利用させる場合もソースコードを提供する
+    NetworkUse,
+    /// ライセンスが不明・複合ライセンスのため個別に確認する
+    ManualReview,
+}
+
+impl Obligation {
+    pub const ALL: [Self; 7] = [
+        Self::Attribution,
+        Self::NoticeFile,
+        Self::StateChanges,
+        Self::DiscloseSource,
+        Self::SameLicense,
+        Self::NetworkUse,
+        Self::ManualReview,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Attribution => "attribution",
+            Self::NoticeFile => "notice-file",
+            Self::StateChanges => "state-changes",
+            Self::DiscloseSource => "disclose-source",
+            Self::SameLicense => "same-license",
+            Self::NetworkUse => "network-use",
+            Self::ManualReview => "manual-review",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::Attribution => "著作権表示・ライセンス文の同梱",
+            Self::NoticeFile => "NOTICEファイルの同梱",
+            Self::StateChanges => "変更箇所の明示",
+            Self::DiscloseSource => "ソースコードの開示",
+            Self::SameLicense => "同一ライセンスでの頒布",
+            Self::NetworkUse => "ネットワーク経由の利用者へのソース提供",
+            Self::ManualReview => "ライセンス不明・複合ライセンスのため要確認",
+        }
+    }
+}
+
+const ATTRIBUTION: &[Obligation] = &[Obligation::Attribution];
+const APACHE: &[Obligation] = &[
+    Obligation::Attribution,
+    Obligation::NoticeFile,
+    Obligation::StateChanges,
+];
+const FILE_COPYLEFT: &[Obligation] = &[
+    Obligation::Attribution,
+    Obligation::DiscloseSource,
+    Obligation::SameLicense,
+];
+const COPYLEFT: &[Obligation] = &[
+    Obligation::Attribution,
+    Obligation::StateChanges,
+    Obligation::DiscloseSource,
+    Obligation::SameLicense,
+];
+const NETWORK_COPYLEFT: &[Obligation] = &[
+    Obligation::Attribution,
+    Obligation::StateChanges,
+    Obligation::DiscloseSource,
+    Obligation::SameLicense,
+    Obligation::NetworkUse,
+];
+
+/// ライセンスの主な義務を返します。義務の無いライセンスは空、不明なライセンスや
+/// ライセンス式（`MIT OR GPL-3.0`など）は選択・組み合わせ次第のため`ManualReview`になります。
+pub fn license_obligations(license: &str) -> &'static [Obligation] {
+    const MANUAL_REVIEW: &[Obligation] = &[Obligation::ManualReview];
+    if LicenseExpr::parse(license).is_some_and(|expr| expr.is_compound()) {
+        return MANUAL_REVIEW;
+    }
+    let Some(id) = normalize_spdx(license) else {
+        return MANUAL_REVIEW;
+    };
+    match id.as_str() {
+        "0BSD" | "CC0-1.0" | "MIT-0" | "Unlicense" | "WTFPL" => &[],
+        "Apache-2.0" => APACHE,
+        "Artistic-2.0" | "CC-BY-3.0" | "CC-BY-4.0" => {
+            &[Obligation::Attribution, Obligation::StateChanges]
+        }
+        "CC-BY-SA-4.0" => &[
+            Obligation::Attribution,
+            Obligation::StateChanges,
+            Obligation::SameLicense,
+        ],
+        "MS-PL" | "OFL-1.1" => &[Obligation::Attribution, Obligation::SameLicense],
+        "CDDL-1.0" | "CDDL-1.1" | "EPL-1.0" | "EPL-2.0" | "MPL-1.1" | "MPL-2.0" => FILE_COPYLEFT,
+        "AGPL-3.0" | "AGPL-3.0-only" | "AGPL-3.0-or-later" => NETWORK_COPYLEFT,
+        id if STRONG_COPYLEFT.contains(&id) || WEAK_COPYLEFT.contains(&id) => COPYLEFT,
+        _ => ATTRIBUTION,
+    }
+}
+
+/// ライセンス（SPDX ID・表記ゆれ・ライセンス式）をリスク区分に分類します。
+/// 式の場合、ORは最も緩い選択肢、ANDは最も厳しい構成要素の区分になります。
+pub fn license_category(spdx: &str) -> Category {
+    match LicenseExpr::parse(spdx) {
+        Some(expr) if expr.is_compound() => expr_category(&expr),
+        _ => term_category(spdx),
+    }
+}
+
+fn expr_category(expr: &LicenseExpr) -> Category {
+    match expr {
+        LicenseExpr::License(term) => term_category(term),
+        LicenseExpr::And(operands) => operands
+            .iter()
+            .map(expr_category)
+            .max()
+            .unwrap_or(Category::Unknown),
+        LicenseExpr::Or(operands) => operands
+            .iter()
+            .map(expr_category)
+            .min()
+            .unwrap_or(Category::Unknown),
+    }
+}
+
+fn term_category(term: &str) -> Category {
+    let Some(id) = normalize_spdx(term) else {
+        return Category::Unknown;
+    };
+    if STRONG_COPYLEFT.contains(&id.as_str()) {
+        Category::StrongCopyleft
+    } else if WEAK_COPYLEFT.contains(&id.as_str()) {
+        Category::WeakCopyleft
+    } else {
+        Category::Permissive
+    }
+}
+
+pub fn normalize_spdx(raw: &str) -> Option<String> {
+    let trimmed = raw.trim().trim_matches('"');
+    if trimmed.is_empty() {
+        return None;
+    }
+
+    if let Some(id) = canonical_id(trimmed) {
+        return Some(id.to_string());
+    }
+
+    let key = trimmed
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_ascii_lowercase();
+    if let Some((_, id)) = ALIASES.iter().find(|(alias, _)| *alias == key) {
+        return Some(id.to_string());
+    }
+
+    if let Some(inner) = key
+        .strip_suffix(')')
+        .and_then(|rest| rest.rsplit_once('('))
+        .map(|(_, inner)| inner.trim())
+        && let Some(id) = canonical_id(inner)
+    {
+        return Some(id.to_string());
+    }
+
+    None
+}
+
+pub fn canonical_id(value: &str) -> Option<&'static str> {
+    SPDX_IDS
+        .iter()
+        .copied()
+        .find(|id| id.eq_ignore_ascii_case(value))
+}
+
+/// 表記の違い（`MIT License`と`MIT`、式の括弧など）を除いて同じライセンスか判定します。
+pub fn same_license(left: &str, right: &str) -> bool {
+    fn canonical(license: &str) -> String {
+        let leaf = |term: &str| normalize_spdx(term).unwrap_or_else(|| term.to_ascii_lowercase());
+        match LicenseExpr::parse(license) {
+            Some(expr) => expr.render(&leaf),
+            None => license.trim().to_ascii_lowercase(),
+        }
+    }
+    canonical(left) == canonical(right)
+}
+
+/// 正規化してもSPDX IDにならないライセンス（式の場合はその構成要素）を返します。
+/// `LicenseRef-`で始まる独自の識別子、`+`・`WITH`による例外付きの指定はSPDXの表記として認めます。
+/// 式として解釈できない場合は全体を返します。
+pub fn non_spdx_terms(license: &str) -> Vec<String> {
+    fn collect(expr: &LicenseExpr, acc: &mut Vec<String>) {
+        match expr {
+            LicenseExpr::License(term) if !is_spdx_term(term) => acc.push(term.clone()),
+            LicenseExpr::License(_) => {}
+            LicenseExpr::And(operands) | LicenseExpr::Or(operands) => {
+                operands.iter().for_each(|operand| collect(operand, acc))
+            }
+        }
+    }
+    let mut terms = Vec::new();
+    match LicenseExpr::parse(license) {
+        Some(expr) => collect(&expr, &mut terms),
+        None => terms.push(license.trim().to_string()),
+    }
+    terms
+}
+
+fn is_spdx_term(term: &str) -> bool {
+    let lower = term.to_ascii_lowercase();
+    let base = match lower.find(" with ") {
+        Some(index) if !term[index + 6..].trim().is_empty() => &term[..index],
+        Some(_) => return false,
+        None => term,
+    };
+    let base = base.trim();
+    const LICENSE_REF: &str = "LicenseRef-";
+    let reference = base.rsplit(':').next().unwrap_or(base);
+    if reference.len() > LICENSE_REF.len()
+        && reference
+            .get(..LICENSE_REF.len())
+            .is_some_and(|prefix| prefix.eq_ignore_ascii_case(LICENSE_REF))
+    {
+        return true;
+    }
+    normalize_spdx(base).is_some()
+        || base
+            .strip_suffix('+')
+            .is_some_and(|base| canonical_id(base).is_some())
+}
+
+/// `MIT OR (Apache-2.0 AND BSD-3-Clause)`のようなSPDXライセンス式。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LicenseExpr {
+    License(String),
+    And(Vec<LicenseExpr>),
+    Or(Vec<LicenseExpr>),
+}
+
+impl LicenseExpr {
+    /// ライセンス式を解析します。`,`は`OR`として扱います。
+    /// 括弧の対応が取れないなど式として解釈できない場合は`None`を返します。
+    pub fn parse(expression: &str) -> Option<Self> {
+        let tokens = tokenize(expression);
+        let mut position = 0;
+        let expr = parse_or(&tokens, &mut position)?;
+        (position == tokens.len()).then_some(expr)
+    }
+
+    pub fn is_compound(&self) -> bool {
+        !matches!(self, Self::License(_))
+    }
+
+    /// 各ライセンスを`leaf`で変換しながら式を文字列に戻します。
+    pub fn render(&self, leaf: &dyn Fn(&str) -> String) -> String {
+        match self {
+            Self::License(name) => leaf(name),
+            Self::And(operands) => join_operands(operands, " AND ", leaf),
+            Self::Or(operands) => join_operands(operands, " OR ", leaf),
+        }
+    }
+}
+
+fn join_operands(
+    operands: &[LicenseExpr],
+    operator: &str,
+    leaf: &dyn Fn(&str) -> String,
+) -> String {
+    operands
+        .iter()
+        .map(|operand| match operand {
+            LicenseExpr::License(_) => operand.render(leaf),
+            _ => format!("({})", operand.render(leaf)),
+        })
+        .collect::<Vec<_>>()
+        .join(operator)
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Open,
+    Close,
+    And,
+    Or,
+    Word(String),
+}
+
+fn tokenize(expression: &str) -> Vec<Token> {
+    let spaced = expression
+        .replace('(', " ( ")
+        .replace(')', " ) ")
+        .replace(',', " , ");
+    spaced
+        .split_whitespace()
+        .map(|word| match word {
+            "(" => Token::Open,
+            ")" => Token::Close,
+            "," => Token::Or,
+            _ if word.eq_ignore_ascii_case("and") => Token::And,
+            _ if word.eq_ignore_ascii_case("or") => Token::Or,
+            _ => Token::Word(word.to_string()),
+        })
+        .collect()
+}
+
+fn parse_or(tokens: &[Token], position: &mut usize) -> Option<LicenseExpr> {
+    let mut operands = vec![parse_and(tokens, position)?];
+    while tokens.get(*position) == Some(&Token::Or) {
+        *position += 1;
+        operands.push(parse_and(tokens, position)?);
+    }
+    Some(flatten(operands, LicenseExpr::Or))
+}
+
+fn parse_and(tokens: &[Token], position: &mut usize) -> Option<LicenseExpr> {
+    let mut operands = vec![parse_primary(tokens, position)?];
+    while tokens.get(*position) == Some(&Token::And) {
+        *position += 1;
+        operands.push(parse_primary(tokens, position)?);
+    }
+    Some(flatten(operands, LicenseExpr::And))
+}
+
+fn parse_primary(tokens: &[Token], position: &mut usize) -> Option<LicenseExpr> {
+    if tokens.get(*position) == Some(&Token::Open) {
+        *position += 1;
+        let expr = parse_or(tokens, position)?;
+        if tokens.get(*position) != Some(&Token::Close) {
+            return None;
+        }
+        *position += 1;
+        return Some(expr);
+    }
+
+    // "GPL-2.0 WITH Classpath-exception-2.0"や"BSD License"のような複数語は1つのライセンスとして扱う
+    let mut words = Vec::new();
+    while let Some(Token::Word(word)) = tokens.get(*position) {
+        words.push(word.as_str());
+        *position += 1;
+    }
+    (!words.is_empty()).then(|| LicenseExpr::License(words.join(" ")))
+}
+
+fn flatten(
+    mut operands: Vec<LicenseExpr>,
+    combine: fn(Vec<LicenseExpr>) -> LicenseExpr,
+) -> LicenseExpr {
+    if operands.len() == 1 {
+        operands.remove(0)
+    } else {
+        combine(operands)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn canonical_ids_are_case_normalized() {
+        assert_eq!(normalize_spdx("mit"), Some("MIT".to_string()));
+        assert_eq!(normalize_spdx("apache-2.0"), Some("Apache-2.0".to_string()));
+        assert_eq!(
+            normalize_spdx(" bsd-3-clause "),
+            Some("BSD-3-Clause".to_string())
+        );
+    }
+
+    #[test]
+    fn obligations_follow_the_bundled_table() {
+        assert_eq!(license_obligations("MIT"), [Obligation::Attribution]);
+        assert!(license_obligations("CC0-1.0").is_empty());
+        assert!(license_obligations("Apache License 2.0").contains(&Obligation::NoticeFile));
+        assert!(license_obligations("LGPL-2.1-only").contains(&Obligation::DiscloseSource));
+        assert_eq!(
+            license_obligations("AGPL-3.0").last(),
+            Some(&Obligation::NetworkUse)
+        );
+        assert_eq!(license_obligations("Unknown"), [Obligation::ManualReview]);
+        assert_eq!(
+            license_obligations("MIT OR GPL-3.0"),
+            [Obligation::ManualReview]
+        );
+    }
+
+    #[test]
+    fn licenses_are_classified_by_risk() {
+        assert_eq!(license_category("MIT"), Category::Permissive);
+        assert_eq!(license_category("Apache License 2.0"), Category::Permissive);
+        assert_eq!(license_category("LGPL-2.1-only"), Category::WeakCopyleft);
+        assert_eq!(license_category("MPL-2.0"), Category::WeakCopyleft);
+        assert_eq!(
+            license_category("gpl-3.0-or-later"),
+            Category::StrongCopyleft
+        );
+        assert_eq!(license_category("AGPL-3.0"), Category::StrongCopyleft);
+        assert_eq!(license_category("Unknown"), Category::Unknown);
+        assert_eq!(license_category("Proprietary"), Category::Unknown);
+
+        assert_eq!(license_category("MIT OR GPL-3.0"), Category::Permissive);
+        assert_eq!(license_category("MIT AND LGPL-3.0"), Category::WeakCopyleft);
+        assert_eq!(
+            license_category("(MIT OR Apache-2.0) AND GPL-2.0"),
+            Category::StrongCopyleft
+        );
+    }
+
+    #[test]
+    fn free_text_aliases() {
+        assert_eq!(normalize_spdx("MIT License"), Some("MIT".to_string()));
+        assert_eq!(
+            normalize_spdx("Apache License, Version 2.0"),
+            Some("Apache-2.0".to_string())
+        );
+        assert_eq!(
+            normalize_spdx("New BSD License"),
+            Some("BSD-3-Clause".to_string())
+        );
+        assert_eq!(
+            normalize_spdx("Simplified BSD"),
+            Some("BSD-2-Clause".to_string())
+        );
+        assert_eq!(
+            normalize_spdx("GPLv3+"),
+            Some("GPL-3.0-or-later".to_string())
+        );
+    }
+
+    #[test]
+    fn pypi_classifier_tails() {
+        assert_eq!(
+            normalize_spdx("Apache Software License"),
+            Some("Apache-2.0".to_string())
+        );
+        assert_eq!(
+            normalize_spdx("GNU Lesser General Public License v2 or later (LGPLv2+)"),
+            Some("LGPL-2.0-or-later".to_string())
+        );
+        assert_eq!(
+            normalize_spdx("Mozilla Public License 2.0 (MPL 2.0)"),
+            Some("MPL-2.0".to_string())
+        );
+        assert_eq!(
+            normalize_spdx("Eclipse Public License 2.0 (EPL-2.0)"),
+            Some("EPL-2.0".to_string())
+        );
+        assert_eq!(
+            normalize_spdx("Python Software Foundation License"),
+            Some("PSF-2.0".to_string())
+        );
+    }
+
+    #[test]
+    fn unknown_strings_are_not_mapped() {
+        assert_eq!(normalize_spdx("BSD License"), None);
+        assert_eq!(normalize_spdx("Proprietary"), None);
+        assert_eq!(normalize_spdx(""), None);
+    }
+
+    #[test]
+    fn license_expressions_respect_precedence_and_parentheses() {
+        let license = |name: &str| LicenseExpr::License(name.to_string());
+        assert_eq!(LicenseExpr::parse("MIT"), Some(license("MIT")));
+        assert_eq!(
+            LicenseExpr::parse("MIT OR Apache-2.0 AND BSD-3-Clause"),
+            Some(LicenseExpr::Or(vec![
+                license("MIT"),
+                LicenseExpr::And(vec![license("Apache-2.0"), license("BSD-3-Clause")]),
+            ]))
+        );
+        let grouped =
+            LicenseExpr::parse("(MIT or ISC) and GPL-2.0 WITH Classpath-exception-2.0").unwrap();
+        assert_eq!(
+            grouped,
+            LicenseExpr::And(vec![
+                LicenseExpr::Or(vec![license("MIT"), license("ISC")]),
+                license("GPL-2.0 WITH Classpath-exception-2.0"),
+            ])
+        );
+        assert_eq!(
+            grouped.render(&|name| name.to_string()),
+            "(MIT OR ISC) AND GPL-2.0 WITH Classpath-exception-2.0"
+        );
+        assert_eq!(
+            LicenseExpr::parse("MIT, GPL-3.0"),
+            Some(LicenseExpr::Or(vec![license("MIT"), license("GPL-3.0")]))
+        );
+    }
+
+    #[test]
+    fn malformed_license_expressions_are_rejected() {
+        assert_eq!(LicenseExpr::parse("(MIT OR Apache-2.0"), None);
+        assert_eq!(LicenseExpr::parse("MIT OR"), None);
+        assert_eq!(
+            LicenseExpr::parse("Mozilla Public License 2.0 (MPL 2.0)"),
+            None
+        );
+        assert_eq!(LicenseExpr::parse(""), None);
+    }
+
+    #[test]
+    fn non_spdx_terms_lists_unrecognized_parts() {
+        assert!(non_spdx_terms("MIT").is_empty());
+        assert!(non_spdx_terms("Apache License 2.0").is_empty());
+        assert!(non_spdx_terms("(MIT OR Apache-2.0) AND BSD-3-Clause").is_empty());
+        assert!(non_spdx_terms("GPL-2.0-only WITH Classpath-exception-2.0").is_empty());
+        assert!(non_spdx_terms("LicenseRef-Proprietary").is_empty());
+        assert!(non_spdx_terms("GPL-2.0+").is_empty());
+        assert_eq!(non_spdx_terms("MIT OR Apache-2.O"), vec!["Apache-2.O"]);
+        assert_eq!(non_spdx_terms("Acme Commercial"), vec!["Acme Commercial"]);
+        assert_eq!(non_spdx_terms("(MIT"), vec!["(MIT"]);
+    }
+
+    #[test]
+    fn same_license_ignores_spelling_differences() {
+        assert!(same_license("MIT License", "mit"));
+        assert!(same_license(
+            "(MIT OR Apache-2.0)",
+            "MIT OR Apache License 2.0"
+        ));
+        assert!(!same_license("ISC", "MIT"));
+        assert!(!same_license("MIT OR Apache-2.0", "MIT AND Apache-2.0"));
+    }
+}
@@ -0,0 +1,338 @@
+use std::io::{self, IsTerminal};
+
+use anyhow::{Result, bail};
+use license_scout::DependencyRecord;
+use license_scout::spdx::{Category, license_category};
+use ratatui::DefaultTerminal;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, Cell, Paragraph, Row, Table, TableState};
+
+use crate::output::{self, SortField};
+
+const SORT_FIELDS: [SortField; 5] = [
+    SortField::Manager,
+    SortField::Name,
+    SortField::Version,
+    SortField::License,
+    SortField::Source,
+];
+const HELP: &str =
+    "↑↓/jk:移動 PgUp/PgDn:ページ /:検索 s:並べ替え r:逆順 l:ライセンス m:マネージャ c:解除 q:終了";
+
+/// 依存の一覧を全画面で閲覧します。検索・並べ替え・ライセンス/マネージャでの絞り込みはキー操作で行います。
+pub fn browse(records: &[DependencyRecord]) -> Result<()> {
+    if !io::stdout().is_terminal() {
+        bail!("--tuiは端末でのみ使用できます");
+    }
+    let mut terminal = ratatui::init();
+    let result = run(&mut terminal, Browser::new(records.to_vec()));
+    ratatui::restore();
+    result
+}
+
+fn run(terminal: &mut DefaultTerminal, mut browser: Browser) -> Result<()> {
+    let mut state = TableState::default().with_selected(Some(0));
+    loop {
+        terminal.draw(|frame| {
+            let [body, status] =
+                Layout::vertical([Constraint::Min(3), Constraint::Length(2)]).areas(frame.area());
+            browser.page_size = usize::from(body.height.saturating_sub(3)).max(1);
+            frame.render_stateful_widget(browser.table(), body, &mut state);
+            frame.render_widget(browser.status(), status);
+        })?;
+
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        if browser.searching {
+            match key.code {
+                KeyCode::Esc => {
+                    browser.searching = false;
+                    browser.set_query(String::new());
+                }
+                KeyCode::Enter => browser.searching = false,
+                KeyCode::Backspace => {
+                    let mut query = browser.query.clone();
+                    query.pop();
+                    browser.set_query(query);
+                }
+                KeyCode::Char(c) => browser.set_query(format!("{}{c}", browser.query)),
+                _ => continue,
+            }
+            state.select(Some(0));
+            continue;
+        }
+        let selected = state.selected().unwrap_or(0);
+        let last = browser.visible.len().saturating_sub(1);
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+            KeyCode::Down | KeyCode::Char('j') => state.select(Some((selected + 1).min(last))),
+            KeyCode::Up | KeyCode::Char('k') => state.select(Some(selected.saturating_sub(1))),
+            KeyCode::PageDown => state.select(Some((selected + browser.page_size).min(last))),
+            KeyCode::PageUp => state.select(Some(selected.saturating_sub(browser.page_size))),
+            KeyCode::Home | KeyCode::Char('g') => state.select(Some(0)),
+            KeyCode::End | KeyCode::Char('G') => state.select(Some(last)),
+            KeyCode::Char('/') => browser.searching = true,
+            KeyCode::Char(c @ ('s' | 'r' | 'l' | 'm' | 'c')) => {
+                match c {
+                    's' => browser.cycle_sort(),
+                    'r' => browser.toggle_reverse(),
+                    'l' => browser.cycle_license(),
+                    'm' => browser.cycle_manager(),
+                    _ => browser.clear_filters(),
+                }
+                state.select(Some(0));
+            }
+            _ => {}
+        }
+    }
+}
+
+struct Browser {
+    /// 渡された順の依存。並べ替えのたびにここから並べ直します。
+    original: Vec<DependencyRecord>,
+    records: Vec<DependencyRecord>,
+    /// 絞り込み・並べ替え後に表示する`records`の添字。
+    visible: Vec<usize>,
+    query: String,
+    searching: bool,
+    sort: Option<SortField>,
+    reverse: bool,
+    license: Option<String>,
+    manager: Option<String>,
+    page_size: usize,
+}
+
+impl Browser {
+    fn new(records: Vec<DependencyRecord>) -> Self {
+        let mut browser = Self {
+            original: records.clone(),
+            records,
+            visible: Vec::new(),
+            query: String::new(),
+            searching: false,
+            sort: None,
+            reverse: false,
+            license: None,
+            manager: None,
+            page_size: 1,
+        };
+        browser.refresh();
+        browser
+    }
+
+    fn resort(&mut self) {
+        self.records = self.original.clone();
+        output::sort_records_by(&mut self.records, self.sort, self.reverse);
+        self.refresh();
+    }
+
+    fn refresh(&mut self) {
+        let search = crate::SearchQuery::substring(&self.query);
+        self.visible = self
+            .records
+            .iter()
+            .enumerate()
+            .filter(|(_, record)| {
+                (self.query.is_empty() || search.matches(record))
+                    && self.license.as_ref().is_none_or(|l| *l == record.license)
+                    && self.manager.as_ref().is_none_or(|m| *m == record.manager)
+            })
+            .map(|(idx, _)| idx)
+            .collect();
+    }
+
+    fn set_query(&mut self, query: String) {
+        self.query = query;
+        self.refresh();
+    }
+
+    fn cycle_sort(&mut self) {
+        self.sort = match self.sort {
+            None => Some(SORT_FIELDS[0]),
+            Some(current) => SORT_FIELDS
+                .iter()
+                .position(|field| *field == current)
+                .and_then(|idx| SORT_FIELDS.get(idx + 1))
+                .copied(),
+        };
+        self.resort();
+    }
+
+    fn toggle_reverse(&mut self) {
+        self.reverse = !self.reverse;
+        self.resort();
+    }
+
+    fn cycle_license(&mut self) {
+        let licenses = distinct(self.records.iter().map(|r| r.license.as_str()));
+        self.license = next_value(&licenses, self.license.as_deref());
+        self.refresh();
+    }
+
+    fn cycle_manager(&mut self) {
+        let managers = distinct(self.records.iter().map(|r| r.manager.as_str()));
+        self.manager = next_value(&managers, self.manager.as_deref());
+        self.refresh();
+    }
+
+    fn clear_filters(&mut self) {
+        self.query.clear();
+        self.license = None;
+        self.manager = None;
+        self.refresh();
+    }
+
+    fn table(&self) -> Table<'_> {
+        let header = Row::new([
+            "Manager", "Name", "Version", "License", "Homepage", "Source",
+        ])
+        .style(Style::new().add_modifier(Modifier::BOLD));
+        let rows = self.visible.iter().map(|&idx| {
+            let record = &self.records[idx];
+            Row::new([
+                Cell::from(record.manager.as_str()),
+                Cell::from(record.name.as_str()),
+                Cell::from(record.version.as_deref().unwrap_or("-")),
+                Cell::from(record.license.as_str()).style(license_style(&record.license)),
+                Cell::from(record.homepage.as_deref().unwrap_or("-")),
+                Cell::from(record.source.display().to_string()),
+            ])
+        });
+        let widths = [
+            Constraint::Length(8),
+            Constraint::Percentage(20),
+            Constraint::Length(12),
+            Constraint::Percentage(20),
+            Constraint::Percentage(25),
+            Constraint::Fill(1),
+        ];
+        Table::new(rows, widths)
+            .header(header)
+            .block(Block::new().borders(Borders::ALL).title(format!(
+                " license-scout: {}/{}件 ",
+                self.visible.len(),
+                self.records.len()
+            )))
+            .row_highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+    }
+
+    fn status(&self) -> Paragraph<'_> {
+        let mut conditions = Vec::new();
+        if self.searching || !self.query.is_empty() {
+            let cursor = if self.searching { "_" } else { "" };
+            conditions.push(format!("検索: {}{cursor}", self.query));
+        }
+        if let Some(field) = self.sort {
+            let order = if self.reverse { "降順" } else { "昇順" };
+            conditions.push(format!("並べ替え: {field:?}（{order}）"));
+        }
+        if let Some(license) = &self.license {
+            conditions.push(format!("ライセンス: {license}"));
+        }
+        if let Some(manager) = &self.manager {
+            conditions.push(format!("マネージャ: {manager}"));
+        }
+        Paragraph::new(vec![Line::from(conditions.join("  ")), Line::from(HELP)])
+    }
+}
+
+fn distinct<'a>(values: impl Iterator<Item = &'a str>) -> Vec<String> {
+    let mut values: Vec<String> = values.map(str::to_string).collect();
+    values.sort();
+    values.dedup();
+    values
+}
+
+/// 未選択→1件目→…→最後→未選択の順に切り替えます。
+fn next_value(values: &[String], current: Option<&str>) -> Option<String> {
+    match current {
+        None => values.first().cloned(),
+        Some(current) => values
+            .iter()
+            .position(|value| value == current)
+            .and_then(|idx| values.get(idx + 1))
+            .cloned(),
+    }
+}
+
+fn license_style(license: &str) -> Style {
+    match license_category(license) {
+        Category::Permissive => Style::new().fg(Color::Green),
+        Category::WeakCopyleft => Style::new().fg(Color::Yellow),
+        Category::StrongCopyleft => Style::new().fg(Color::Red),
+        Category::Unknown => Style::new().fg(Color::DarkGray),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(manager: &str, name: &str, license: &str) -> DependencyRecord {
+        DependencyRecord {
+            manager: manager.to_string(),
+            name: name.to_string(),
+            license: license.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn visible_names(browser: &Browser) -> Vec<&str> {
+        browser
+            .visible
+            .iter()
+            .map(|&idx| browser.records[idx].name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn search_and_filters_narrow_the_visible_rows() {
+        let mut browser = Browser::new(vec![
+            record("npm", "react", "MIT"),
+            record("npm", "left-pad", "WTFPL"),
+            record("pip", "requests", "Apache-2.0"),
+            record("pip", "flask", "BSD-3-Clause"),
+        ]);
+        assert_eq!(visible_names(&browser).len(), 4);
+
+        browser.set_query("re".to_string());
+        assert_eq!(visible_names(&browser), vec!["react", "requests"]);
+
+        browser.cycle_manager();
+        assert_eq!(browser.manager.as_deref(), Some("npm"));
+        assert_eq!(visible_names(&browser), vec!["react"]);
+
+        browser.clear_filters();
+        browser.cycle_license();
+        assert_eq!(browser.license.as_deref(), Some("Apache-2.0"));
+        assert_eq!(visible_names(&browser), vec!["requests"]);
+    }
+
+    #[test]
+    fn sort_and_filters_cycle_back_to_default() {
+        let mut browser = Browser::new(vec![record("pip", "b", "MIT"), record("npm", "a", "MIT")]);
+        for _ in 0..SORT_FIELDS.len() {
+            browser.cycle_sort();
+        }
+        assert_eq!(browser.sort, Some(SortField::Source));
+        browser.cycle_sort();
+        assert_eq!(browser.sort, None);
+
+        browser.cycle_sort();
+        browser.cycle_sort();
+        browser.toggle_reverse();
+        assert_eq!(visible_names(&browser), vec!["b", "a"]);
+
+        browser.cycle_manager();
+        browser.cycle_manager();
+        browser.cycle_manager();
+        assert_eq!(browser.manager, None);
+    }
+}
@@ -1,7 +1,14 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
-#[derive(Debug, Serialize, Clone)]
+use urlencoding::encode;
+
+use crate::compat::Compatibility;
+use crate::policy::Severity;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
 pub struct DependencyRecord {
     pub manager: String,
     pub name: String,
@@ -9,10 +16,190 @@ pub struct DependencyRecord {
     pub license: String,
     pub source: PathBuf,
     pub homepage: Option<String>,
+    /// `--check-homepages`でホームページに到達できたかどうか。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub homepage_reachable: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub outdated: Option<String>,
+    /// レジストリで非推奨（npmの`deprecated`）・取り下げ済み（PyPIのyank）とされている場合の理由。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deprecated: Option<String>,
+    pub direct: bool,
+    pub dev: bool,
+    /// npmのoptionalDependencies（package-lock.jsonの`optional`・`devOptional`）。
+    #[serde(skip_serializing_if = "is_false")]
+    pub optional: bool,
+    /// npmのpeerDependencies（package-lock.jsonの`peer`）。
+    #[serde(skip_serializing_if = "is_false")]
+    pub peer: bool,
+    /// npmのbundleDependencies（package-lock.jsonの`inBundle`・`bundled`）。
+    #[serde(skip_serializing_if = "is_false")]
+    pub bundled: bool,
+    /// 解析対象のプロジェクト自身（package-lock.jsonの`packages[""]`、ワークスペースや`file:`のローカルパッケージ）。
+    #[serde(skip_serializing_if = "is_false")]
+    pub is_self: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// requirements.txtなどの環境マーカー（例: `sys_platform == 'win32'`）。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub marker: Option<String>,
+    /// 依存のグループ。`requirements-dev.txt`のようなファイル名から得た`dev`など。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    /// ポリシーで割り当てた重大度（forbidden / warn / review）。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub severity: Option<Severity>,
+    /// `--project-license`で判定したプロジェクトのライセンスとの互換性。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compatibility: Option<Compatibility>,
+    /// ライセンス情報の取得元。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
+    /// `--reconcile`で、ロックファイルのライセンスと異なる値をレジストリが返した場合のその値。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub registry_license: Option<String>,
+    /// レジストリ以外から取得した依存の取得元（uv.lockの`git+https://...`・`path:../lib`など）。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub origin: Option<String>,
+    /// ロックファイルに記録されたハッシュ（npmの`integrity`、pipの`--hash`）。複数ある場合は空白区切り。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub integrity: Option<String>,
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+impl DependencyRecord {
+    pub fn has_unknown_license(&self) -> bool {
+        self.license.trim().is_empty() || self.license.eq_ignore_ascii_case("unknown")
+    }
+
+    /// Package URL（`pkg:npm/%40types/node@20.1.0`など）。マネージャはpurlのtypeに対応付けます。
+    pub fn purl(&self) -> String {
+        let purl_type = match self.manager.as_str() {
+            "pip" => "pypi",
+            "go" => "golang",
+            other => other,
+        };
+        let name = match purl_type {
+            "pypi" => self.name.to_ascii_lowercase().replace('_', "-"),
+            "maven" => self.name.replacen(':', "/", 1),
+            _ => self.name.clone(),
+        };
+        let path = name
+            .split('/')
+            .map(|segment| encode(segment).into_owned())
+            .collect::<Vec<_>>()
+            .join("/");
+
+        match &self.version {
+            Some(version) => format!("pkg:{purl_type}/{path}@{}", encode(version)),
+            None => format!("pkg:{purl_type}/{path}"),
+        }
+    }
+}
+
+/// ライセンス情報をどこから得たか。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Provenance {
+    /// ロックファイル・マニフェストに記載されていた
+    Lockfile,
+    /// PyPIのTrove分類子（`License :: ...`）
+    PypiClassifier,
+    /// PyPIの`license`フィールド
+    PypiLicense,
+    NpmRegistry,
+    CratesIo,
+    Rubygems,
+    MavenCentral,
+    /// ローカルのLICENSEファイル（Goのvendorディレクトリなど）
+    LicenseFile,
+    /// `--overrides`による手動上書き
+    Override,
+    /// `--guess-licenses`の対応表による推定
+    Guessed,
+}
+
+impl Provenance {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Lockfile => "lockfile",
+            Self::PypiClassifier => "pypi-classifier",
+            Self::PypiLicense => "pypi-license",
+            Self::NpmRegistry => "npm-registry",
+            Self::CratesIo => "crates-io",
+            Self::Rubygems => "rubygems",
+            Self::MavenCentral => "maven-central",
+            Self::LicenseFile => "license-file",
+            Self::Override => "override",
+            Self::Guessed => "guessed",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PackageMetadata {
     pub license: Option<String>,
     pub homepage: Option<String>,
+    #[serde(default)]
+    pub latest_version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
+    /// 非推奨・取り下げを確認したバージョンごとの理由（該当しなければ`None`）。
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub deprecations: BTreeMap<String, Option<String>>,
+}
+
+impl PackageMetadata {
+    /// そのバージョンが非推奨・取り下げ済みなら理由。確認していないバージョンも`None`です。
+    pub fn deprecation(&self, version: &str) -> Option<&str> {
+        self.deprecations.get(version)?.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(manager: &str, name: &str, version: Option<&str>) -> DependencyRecord {
+        DependencyRecord {
+            manager: manager.to_string(),
+            name: name.to_string(),
+            version: version.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn purl_maps_managers_and_encodes_names() {
+        assert_eq!(
+            record("npm", "@types/node", Some("20.1.0")).purl(),
+            "pkg:npm/%40types/node@20.1.0"
+        );
+        assert_eq!(record("npm", "react", None).purl(), "pkg:npm/react");
+        assert_eq!(
+            record("pip", "Flask_SQLAlchemy", Some("3.1.1")).purl(),
+            "pkg:pypi/flask-sqlalchemy@3.1.1"
+        );
+        assert_eq!(
+            record("pip", "zope.interface", None).purl(),
+            "pkg:pypi/zope.interface"
+        );
+        assert_eq!(
+            record("maven", "org.slf4j:slf4j-api", Some("2.0.9")).purl(),
+            "pkg:maven/org.slf4j/slf4j-api@2.0.9"
+        );
+        assert_eq!(
+            record("go", "github.com/pkg/errors", Some("v0.9.1")).purl(),
+            "pkg:golang/github.com/pkg/errors@v0.9.1"
+        );
+        assert_eq!(
+            record("cargo", "serde", Some("1.0.0+build.1")).purl(),
+            "pkg:cargo/serde@1.0.0%2Bbuild.1"
+        );
+    }
 }
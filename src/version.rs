@@ -0,0 +1,160 @@
+use std::cmp::Ordering;
+
+/// semverの順序で比較するバージョン。`2.32`のような省略形は足りない部分を0とみなします。
+#[derive(Debug, Clone)]
+pub struct SemVer {
+    release: Vec<u64>,
+    pre: Vec<PreRelease>,
+}
+
+/// プレリリースの識別子。数値は英字を含むものより前になります。
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum PreRelease {
+    Numeric(u64),
+    Alpha(String),
+}
+
+impl SemVer {
+    /// 先頭の`=` `v`とビルドメタデータ（`+`以降）は無視します。数字以外を含む場合は`None`です。
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim().trim_start_matches(['=', 'v']);
+        let text = text.split('+').next()?;
+        let (core, pre) = match text.split_once('-') {
+            Some((core, pre)) => (core, Some(pre)),
+            None => (text, None),
+        };
+        let release = core
+            .split('.')
+            .map(|part| part.parse().ok())
+            .collect::<Option<Vec<u64>>>()?;
+        let pre = match pre {
+            Some(pre) => pre
+                .split('.')
+                .map(|id| match id.parse() {
+                    Ok(number) => Some(PreRelease::Numeric(number)),
+                    Err(_) if !id.is_empty() => Some(PreRelease::Alpha(id.to_string())),
+                    Err(_) => None,
+                })
+                .collect::<Option<Vec<_>>>()?,
+            None => Vec::new(),
+        };
+        Some(Self { release, pre })
+    }
+}
+
+impl Ord for SemVer {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let len = self.release.len().max(other.release.len());
+        let part = |version: &Self, index: usize| version.release.get(index).copied().unwrap_or(0);
+        (0..len)
+            .map(|index| part(self, index).cmp(&part(other, index)))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or_else(|| match (self.pre.is_empty(), other.pre.is_empty()) {
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                (false, false) => self.pre.cmp(&other.pre),
+            })
+    }
+}
+
+impl PartialEq for SemVer {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl Eq for SemVer {}
+
+impl PartialOrd for SemVer {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// 範囲指定やタグではなく、`1.2.3`・`1.0.0-beta.1+build`のように1つのバージョンを指しているか。
+pub fn is_exact(version: &str) -> bool {
+    version.starts_with(|c: char| c.is_ascii_digit())
+        && !version.contains(char::is_whitespace)
+        && SemVer::parse(version).is_some_and(|version| version.release.len() == 3)
+}
+
+/// 2つのバージョン文字列を比較します。どちらもsemverとして読めればその順序（`1.9.0-beta` < `1.9.0` < `1.10.0`）、
+/// 読めない場合は数字の並びを数値として比較します。
+pub fn compare_versions(left: &str, right: &str) -> Ordering {
+    if let (Some(a), Some(b)) = (SemVer::parse(left), SemVer::parse(right)) {
+        return a.cmp(&b);
+    }
+    let mut left = version_chunks(left).into_iter();
+    let mut right = version_chunks(right).into_iter();
+    loop {
+        let ordering = match (left.next(), right.next()) {
+            (Some(a), Some(b)) => match (a.parse::<u64>(), b.parse::<u64>()) {
+                (Ok(a), Ok(b)) => a.cmp(&b),
+                _ => a.cmp(b),
+            },
+            (Some(_), None) => Ordering::Greater,
+            (None, Some(_)) => Ordering::Less,
+            (None, None) => return Ordering::Equal,
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+}
+
+fn version_chunks(version: &str) -> Vec<&str> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    let mut previous_digit = None;
+    for (index, ch) in version.char_indices() {
+        let is_digit = ch.is_ascii_digit();
+        if previous_digit.is_some_and(|previous| previous != is_digit) {
+            chunks.push(&version[start..index]);
+            start = index;
+        }
+        previous_digit = Some(is_digit);
+    }
+    if start < version.len() {
+        chunks.push(&version[start..]);
+    }
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn semver_ordering_is_numeric_and_handles_prereleases() {
+        let v = |text: &str| SemVer::parse(text).unwrap();
+        assert!(v("1.10.0") > v("1.9.0"));
+        assert!(v("v2.32") == v("2.32.0"));
+        assert!(v("1.0.0-rc.1") < v("1.0.0"));
+        assert!(v("1.0.0-alpha") < v("1.0.0-alpha.1"));
+        assert!(v("1.0.0-alpha.2") < v("1.0.0-alpha.10"));
+        assert!(v("1.0.0-2") < v("1.0.0-beta"));
+        assert!(v("1.0.0+build.5") == v("1.0.0"));
+        assert!(SemVer::parse("file:../local").is_none());
+    }
+
+    #[test]
+    fn exact_versions_have_three_numeric_parts() {
+        assert!(is_exact("1.3.0"));
+        assert!(is_exact("1.0.0-beta.1+build"));
+        assert!(!is_exact("^1.3.0"));
+        assert!(!is_exact("latest"));
+        assert!(!is_exact("1.0"));
+        assert!(!is_exact("1.2.3 || 2.0.0"));
+    }
+
+    #[test]
+    fn non_semver_versions_compare_digit_runs_numerically() {
+        assert_eq!(compare_versions("1.9.0-beta", "1.9.0"), Ordering::Less);
+        assert_eq!(compare_versions("2024.10a", "2024.9a"), Ordering::Greater);
+        assert_eq!(
+            compare_versions("git+https://x/a", "git+https://x/b"),
+            Ordering::Less
+        );
+    }
+}
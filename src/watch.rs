@@ -0,0 +1,105 @@
+use std::collections::{BTreeSet, HashSet};
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+
+use license_scout::logging;
+
+/// 連続した保存をまとめて1回の再スキャンにするための待ち時間。
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// `run`を実行し、返されたマニフェスト・ロックファイルが変更されるたびに再実行します。
+/// `run`が失敗した場合はエラーを表示し、直前の監視対象のまま監視を続けます。
+pub fn watch(mut run: impl FnMut() -> Result<Vec<PathBuf>>) -> Result<()> {
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher =
+        notify::recommended_watcher(sender).context("ファイル監視の初期化に失敗しました")?;
+    let mut watched_dirs = BTreeSet::new();
+    let mut sources = HashSet::new();
+
+    loop {
+        match run() {
+            Ok(found) => sources = found.into_iter().collect(),
+            Err(err) => eprintln!("エラー: {err:#}"),
+        }
+        if sources.is_empty() {
+            bail!("監視対象のマニフェスト・ロックファイルが見つかりませんでした");
+        }
+
+        // エディタの保存はファイルの置き換えになることが多いため、親ディレクトリを監視する
+        let dirs: BTreeSet<PathBuf> = sources
+            .iter()
+            .filter_map(|source| source.parent().map(Path::to_path_buf))
+            .collect();
+        for dir in watched_dirs.difference(&dirs) {
+            let _ = watcher.unwatch(dir);
+        }
+        for dir in dirs.difference(&watched_dirs) {
+            watcher
+                .watch(dir, RecursiveMode::NonRecursive)
+                .with_context(|| {
+                    format!("ディレクトリを監視できませんでした: {}", dir.display())
+                })?;
+        }
+        watched_dirs = dirs;
+
+        logging::status(format!(
+            "> {}個のファイルの変更を監視中... (Ctrl+Cで終了)",
+            sources.len()
+        ));
+        wait_for_change(&receiver, &sources)?;
+
+        if io::stdout().is_terminal() {
+            print!("\x1b[2J\x1b[H");
+            io::stdout().flush()?;
+        }
+    }
+}
+
+fn wait_for_change(
+    receiver: &Receiver<notify::Result<Event>>,
+    sources: &HashSet<PathBuf>,
+) -> Result<()> {
+    loop {
+        let event = receiver
+            .recv()
+            .context("ファイル監視が停止しました")?
+            .context("ファイル監視でエラーが発生しました")?;
+        if touches_sources(&event, sources) {
+            break;
+        }
+    }
+    while receiver.recv_timeout(DEBOUNCE).is_ok() {}
+    Ok(())
+}
+
+fn touches_sources(event: &Event, sources: &HashSet<PathBuf>) -> bool {
+    !matches!(event.kind, EventKind::Access(_))
+        && event.paths.iter().any(|path| sources.contains(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{AccessKind, ModifyKind};
+
+    #[test]
+    fn only_changes_to_watched_sources_trigger_a_rescan() {
+        let lockfile = PathBuf::from("/repo/package-lock.json");
+        let sources = HashSet::from([lockfile.clone()]);
+
+        let modified = Event::new(EventKind::Modify(ModifyKind::Any)).add_path(lockfile.clone());
+        assert!(touches_sources(&modified, &sources));
+
+        let read = Event::new(EventKind::Access(AccessKind::Any)).add_path(lockfile);
+        assert!(!touches_sources(&read, &sources));
+
+        let other = Event::new(EventKind::Modify(ModifyKind::Any))
+            .add_path(PathBuf::from("/repo/README.md"));
+        assert!(!touches_sources(&other, &sources));
+    }
+}